@@ -0,0 +1,1074 @@
+//! Remote monitoring agent
+//!
+//! In agent mode the updater's snapshots are served to other task manager
+//! instances (GUI, TUI or CLI) so they can watch and control this machine:
+//! - Framing: 4-byte big-endian length, then one JSON message
+//! - Handshake: the first request is `Hello` carrying the protocol version
+//!   and a shared token; the token decides the session's `AgentScope`
+//! - Requests: latest snapshot, system history, and (with `Control` scope)
+//!   terminate / priority / affinity commands
+//!
+//! The server only listens on 127.0.0.1 (and optionally a Unix socket on
+//! Unix platforms); remote machines reach it through an SSH tunnel.
+//! `AgentClient` is the matching client and implements `ProcessSource` and
+//! `ProcessActuator`, so any frontend can use a remote machine unchanged.
+
+use std::collections::BTreeMap;
+use std::io::{self, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::app::config::{AgentConfig, AgentScope, AgentToken};
+use crate::app::policies::ProcessActuator;
+use crate::app::report::{HistoryRecorder, ReportSample};
+use crate::app::updater::{ProcessSource, SnapshotSink};
+use crate::core::metrics::SystemMetrics;
+use crate::windows::monitor::{nt_query, ProcessSnapshot};
+use crate::windows::process::control::{PriorityClass, ProcessError};
+
+/// Protocol version spoken by this build
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Largest accepted frame (a snapshot of a busy machine is well below this)
+const MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
+
+/// Largest accepted frame before the client has authenticated
+const HELLO_FRAME_SIZE: usize = 4 * 1024;
+
+/// Time a client has to send its hello before the connection is dropped
+const HELLO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Accept loop poll interval while waiting for connections
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Server-side read timeout; idle connections re-check for shutdown this often
+const SERVER_READ_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// Client-side read/write timeout
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Process entry as sent over the wire
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WireProcess {
+    pub pid: u32,
+    pub parent_pid: u32,
+    pub name: String,
+    pub thread_count: u32,
+    pub handle_count: u32,
+    pub cpu_time_user: u64,
+    pub cpu_time_kernel: u64,
+    pub memory_working_set: u64,
+    pub memory_pagefile: u64,
+    pub memory_private: u64,
+    /// Missing from older agents; their processes all report session 0
+    #[serde(default)]
+    pub session_id: u32,
+    /// Missing from older agents; 0 means unknown
+    #[serde(default)]
+    pub create_time: u64,
+    /// Missing from older agents; their processes report no I/O
+    #[serde(default)]
+    pub io_read_bytes: u64,
+    #[serde(default)]
+    pub io_write_bytes: u64,
+}
+
+/// System metrics as sent over the wire
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WireSystemMetrics {
+    pub cpu_total: f32,
+    pub cpu_cores: Vec<f32>,
+    pub memory_total: u64,
+    pub memory_available: u64,
+    pub memory_load_percent: u32,
+    pub disk_read_bps: u64,
+    pub disk_write_bps: u64,
+    pub network_receive_bps: u64,
+    pub network_transmit_bps: u64,
+    /// Collector values (`collector.metric`)
+    #[serde(default)]
+    pub custom: BTreeMap<String, f64>,
+}
+
+/// Snapshot as sent over the wire; `Instant`s become Unix milliseconds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WireSnapshot {
+    pub unix_ms: u64,
+    pub processes: Vec<WireProcess>,
+    pub system: WireSystemMetrics,
+}
+
+impl WireSnapshot {
+    pub fn from_snapshot(snapshot: &ProcessSnapshot, unix_ms: u64) -> Self {
+        let metrics = &snapshot.system_metrics;
+        Self {
+            unix_ms,
+            processes: snapshot
+                .processes
+                .iter()
+                .map(|p| WireProcess {
+                    pid: p.pid,
+                    parent_pid: p.parent_pid,
+                    name: p.name.clone(),
+                    thread_count: p.thread_count,
+                    handle_count: p.handle_count,
+                    cpu_time_user: p.cpu_time_user,
+                    cpu_time_kernel: p.cpu_time_kernel,
+                    memory_working_set: p.memory_working_set,
+                    memory_pagefile: p.memory_pagefile,
+                    memory_private: p.memory_private,
+                    session_id: p.session_id,
+                    create_time: p.create_time,
+                    io_read_bytes: p.io_read_bytes,
+                    io_write_bytes: p.io_write_bytes,
+                })
+                .collect(),
+            system: WireSystemMetrics {
+                cpu_total: metrics.cpu_total,
+                cpu_cores: metrics.cpu_cores.clone(),
+                memory_total: metrics.memory_total,
+                memory_available: metrics.memory_available,
+                memory_load_percent: metrics.memory_load_percent,
+                disk_read_bps: metrics.disk_read_bps,
+                disk_write_bps: metrics.disk_write_bps,
+                network_receive_bps: metrics.network_receive_bps,
+                network_transmit_bps: metrics.network_transmit_bps,
+                custom: metrics.custom.clone(),
+            },
+        }
+    }
+
+    /// Local snapshot stamped with `timestamp`
+    pub fn into_snapshot(self, timestamp: Instant) -> ProcessSnapshot {
+        let mut system_metrics = SystemMetrics::new();
+        system_metrics.timestamp = timestamp;
+        system_metrics.cpu_total = self.system.cpu_total;
+        system_metrics.cpu_cores = self.system.cpu_cores;
+        system_metrics.memory_total = self.system.memory_total;
+        system_metrics.memory_available = self.system.memory_available;
+        system_metrics.memory_load_percent = self.system.memory_load_percent;
+        system_metrics.disk_read_bps = self.system.disk_read_bps;
+        system_metrics.disk_write_bps = self.system.disk_write_bps;
+        system_metrics.network_receive_bps = self.system.network_receive_bps;
+        system_metrics.network_transmit_bps = self.system.network_transmit_bps;
+        system_metrics.custom = self.system.custom;
+
+        ProcessSnapshot {
+            timestamp,
+            processes: self
+                .processes
+                .into_iter()
+                .map(|p| nt_query::ProcessInfo {
+                    pid: p.pid,
+                    parent_pid: p.parent_pid,
+                    name: p.name,
+                    thread_count: p.thread_count,
+                    handle_count: p.handle_count,
+                    cpu_time_user: p.cpu_time_user,
+                    cpu_time_kernel: p.cpu_time_kernel,
+                    memory_working_set: p.memory_working_set,
+                    memory_pagefile: p.memory_pagefile,
+                    memory_private: p.memory_private,
+                    session_id: p.session_id,
+                    create_time: p.create_time,
+                    io_read_bytes: p.io_read_bytes,
+                    io_write_bytes: p.io_write_bytes,
+                })
+                .collect(),
+            system_metrics,
+        }
+    }
+}
+
+/// Client-to-agent message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Request {
+    /// Must be the first request on a connection
+    Hello {
+        version: u32,
+        token: String,
+    },
+    /// Latest snapshot
+    Snapshot,
+    /// System samples from the last `seconds`
+    History {
+        seconds: u64,
+    },
+    Terminate {
+        pid: u32,
+    },
+    SetPriority {
+        pid: u32,
+        class: PriorityClass,
+    },
+    SetAffinity {
+        pid: u32,
+        mask: usize,
+    },
+}
+
+impl Request {
+    /// Scope needed to run this request
+    fn required_scope(&self) -> AgentScope {
+        match self {
+            Request::Terminate { .. }
+            | Request::SetPriority { .. }
+            | Request::SetAffinity { .. } => AgentScope::Control,
+            _ => AgentScope::ReadOnly,
+        }
+    }
+}
+
+/// Reason a request failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentErrorCode {
+    /// Protocol version not supported by the agent
+    UnsupportedVersion,
+    /// Missing or unknown token
+    Unauthorized,
+    /// Token scope does not allow the request
+    Forbidden,
+    /// Malformed or out-of-order request
+    BadRequest,
+    /// No snapshot collected yet
+    NoData,
+    NotFound,
+    AccessDenied,
+    Failed,
+}
+
+/// Agent-to-client message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Response {
+    Welcome {
+        version: u32,
+        scope: AgentScope,
+    },
+    Snapshot {
+        snapshot: WireSnapshot,
+    },
+    History {
+        samples: Vec<ReportSample>,
+    },
+    Done,
+    Error {
+        code: AgentErrorCode,
+        message: String,
+    },
+}
+
+impl Response {
+    fn error(code: AgentErrorCode, message: impl Into<String>) -> Self {
+        Response::Error {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// Write one length-prefixed JSON frame
+pub fn write_frame<T: Serialize>(writer: &mut impl Write, message: &T) -> io::Result<()> {
+    let payload = serde_json::to_vec(message).map_err(io::Error::other)?;
+    if payload.len() > MAX_FRAME_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "frame too large",
+        ));
+    }
+    writer.write_all(&(payload.len() as u32).to_be_bytes())?;
+    writer.write_all(&payload)?;
+    writer.flush()
+}
+
+/// Read one length-prefixed JSON frame
+///
+/// Read timeouts are retried until `stop` is set, so a server thread can
+/// block on an idle connection and still notice shutdown. Returns
+/// `UnexpectedEof` when the peer closes between frames.
+pub fn read_frame<T: DeserializeOwned>(reader: &mut impl Read, stop: &AtomicBool) -> io::Result<T> {
+    read_frame_limited(reader, stop, MAX_FRAME_SIZE, None)
+}
+
+/// Read one frame, rejecting length prefixes above `max_size`
+///
+/// With a `deadline`, read timeouts after it fail with `TimedOut` instead of
+/// being retried.
+fn read_frame_limited<T: DeserializeOwned>(
+    reader: &mut impl Read,
+    stop: &AtomicBool,
+    max_size: usize,
+    deadline: Option<Instant>,
+) -> io::Result<T> {
+    let mut header = [0u8; 4];
+    read_full(reader, &mut header, stop, deadline)?;
+    let len = u32::from_be_bytes(header) as usize;
+    if len > max_size {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "frame too large",
+        ));
+    }
+    let mut payload = vec![0u8; len];
+    read_full(reader, &mut payload, stop, deadline)?;
+    serde_json::from_slice(&payload).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn read_full(
+    reader: &mut impl Read,
+    buffer: &mut [u8],
+    stop: &AtomicBool,
+    deadline: Option<Instant>,
+) -> io::Result<()> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(n) => filled += n,
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                if stop.load(Ordering::SeqCst) {
+                    return Err(e);
+                }
+                if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                    return Err(io::ErrorKind::TimedOut.into());
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+/// Where an agent listens or a client connects
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AgentAddress {
+    Tcp(String),
+    /// Unix domain socket path (Unix platforms only)
+    Unix(String),
+}
+
+impl AgentAddress {
+    /// Parse `host:port`, `tcp://host:port` or `unix:/path`
+    pub fn parse(address: &str) -> Result<Self, String> {
+        if let Some(path) = address.strip_prefix("unix:") {
+            if path.is_empty() {
+                return Err("Unix socket path is empty".to_string());
+            }
+            return Ok(AgentAddress::Unix(path.to_string()));
+        }
+        let host_port = address.strip_prefix("tcp://").unwrap_or(address);
+        match host_port.rsplit_once(':') {
+            Some((host, port)) if !host.is_empty() && port.parse::<u16>().is_ok() => {
+                Ok(AgentAddress::Tcp(host_port.to_string()))
+            }
+            _ => Err(format!(
+                "Invalid agent address '{}' (expected host:port)",
+                address
+            )),
+        }
+    }
+}
+
+/// Bidirectional byte stream to an agent
+trait AgentStream: Read + Write + Send {}
+
+impl<T: Read + Write + Send> AgentStream for T {}
+
+enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(std::os::unix::net::UnixListener),
+}
+
+impl Listener {
+    fn accept(&self) -> io::Result<Box<dyn AgentStream>> {
+        match self {
+            Listener::Tcp(listener) => {
+                let (stream, _) = listener.accept()?;
+                stream.set_nonblocking(false)?;
+                stream.set_nodelay(true)?;
+                stream.set_read_timeout(Some(SERVER_READ_TIMEOUT))?;
+                Ok(Box::new(stream))
+            }
+            #[cfg(unix)]
+            Listener::Unix(listener) => {
+                let (stream, _) = listener.accept()?;
+                stream.set_nonblocking(false)?;
+                stream.set_read_timeout(Some(SERVER_READ_TIMEOUT))?;
+                Ok(Box::new(stream))
+            }
+        }
+    }
+}
+
+/// State shared by the sink, the listener and connection threads
+struct AgentState {
+    latest: RwLock<Option<WireSnapshot>>,
+    history: HistoryRecorder,
+    tokens: Vec<AgentToken>,
+    actuator: Mutex<Box<dyn ProcessActuator>>,
+    connections: AtomicUsize,
+    max_connections: usize,
+    shutdown: AtomicBool,
+}
+
+/// Snapshot sink publishing into an `AgentServer`
+pub struct AgentSink {
+    state: Arc<AgentState>,
+    history: HistoryRecorder,
+}
+
+impl SnapshotSink for AgentSink {
+    fn on_snapshot(&mut self, snapshot: &ProcessSnapshot) {
+        self.history.on_snapshot(snapshot);
+        let wire = WireSnapshot::from_snapshot(snapshot, unix_ms());
+        *self.state.latest.write().unwrap() = Some(wire);
+    }
+}
+
+/// Agent listener serving snapshots and commands
+///
+/// # Threading Model
+///
+/// Accepts connections on a dedicated background thread and serves each
+/// client on its own thread, since clients keep their connection open and
+/// poll once per refresh. Requests read the latest published snapshot under
+/// a read lock; commands are serialized through the actuator mutex.
+pub struct AgentServer {
+    state: Arc<AgentState>,
+    local_addr: Option<SocketAddr>,
+    thread_handle: Option<thread::JoinHandle<()>>,
+}
+
+impl AgentServer {
+    /// Bind the configured listeners and start serving
+    ///
+    /// TCP binds `127.0.0.1:port`; port 0 binds an ephemeral port (see
+    /// `local_addr()`) unless a Unix socket is configured, in which case it
+    /// disables TCP.
+    pub fn start(config: &AgentConfig, actuator: Box<dyn ProcessActuator>) -> io::Result<Self> {
+        if config.tokens.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "agent requires at least one token",
+            ));
+        }
+
+        let mut listeners = Vec::new();
+        let mut local_addr = None;
+        if config.port != 0 || config.unix_socket.is_none() {
+            let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, config.port))?;
+            listener.set_nonblocking(true)?;
+            local_addr = Some(listener.local_addr()?);
+            listeners.push(Listener::Tcp(listener));
+        }
+        if let Some(path) = &config.unix_socket {
+            listeners.push(bind_unix(path)?);
+        }
+
+        let state = Arc::new(AgentState {
+            latest: RwLock::new(None),
+            history: HistoryRecorder::new(config.history_capacity.max(1)),
+            tokens: config.tokens.clone(),
+            actuator: Mutex::new(actuator),
+            connections: AtomicUsize::new(0),
+            max_connections: config.max_connections.max(1),
+            shutdown: AtomicBool::new(false),
+        });
+
+        let thread_state = Arc::clone(&state);
+        let thread_handle = thread::spawn(move || run_accept_loop(listeners, thread_state));
+
+        Ok(Self {
+            state,
+            local_addr,
+            thread_handle: Some(thread_handle),
+        })
+    }
+
+    /// Start the agent only if enabled in configuration
+    pub fn start_if_enabled(
+        config: &AgentConfig,
+        actuator: Box<dyn ProcessActuator>,
+    ) -> Option<io::Result<Self>> {
+        if config.enabled {
+            Some(Self::start(config, actuator))
+        } else {
+            None
+        }
+    }
+
+    /// Create a sink that publishes updater snapshots to this agent
+    pub fn sink(&self) -> AgentSink {
+        AgentSink {
+            state: Arc::clone(&self.state),
+            history: self.state.history.clone(),
+        }
+    }
+
+    /// TCP address the listener is bound to, if TCP is enabled
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.local_addr
+    }
+
+    /// Stop the listener; connected clients are dropped within a read timeout
+    pub fn shutdown(&mut self) {
+        self.state.shutdown.store(true, Ordering::SeqCst);
+
+        if let Some(handle) = self.thread_handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for AgentServer {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+#[cfg(unix)]
+fn bind_unix(path: &str) -> io::Result<Listener> {
+    // A stale socket file from a previous run would make bind fail
+    let _ = std::fs::remove_file(path);
+    let listener = std::os::unix::net::UnixListener::bind(path)?;
+    listener.set_nonblocking(true)?;
+    Ok(Listener::Unix(listener))
+}
+
+#[cfg(not(unix))]
+fn bind_unix(_path: &str) -> io::Result<Listener> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Unix sockets are not supported on this platform",
+    ))
+}
+
+/// Accept loop running on the listener thread
+fn run_accept_loop(listeners: Vec<Listener>, state: Arc<AgentState>) {
+    while !state.shutdown.load(Ordering::SeqCst) {
+        let mut accepted = false;
+        for listener in &listeners {
+            match listener.accept() {
+                Ok(mut stream) => {
+                    accepted = true;
+                    if state.connections.load(Ordering::SeqCst) >= state.max_connections {
+                        let busy = Response::error(AgentErrorCode::Failed, "too many connections");
+                        let _ = write_frame(&mut stream, &busy);
+                        continue;
+                    }
+                    state.connections.fetch_add(1, Ordering::SeqCst);
+                    let connection_state = Arc::clone(&state);
+                    thread::spawn(move || {
+                        if let Err(e) = handle_connection(stream, &connection_state) {
+                            if e.kind() != io::ErrorKind::UnexpectedEof {
+                                eprintln!("Agent: connection failed: {}", e);
+                            }
+                        }
+                        connection_state.connections.fetch_sub(1, Ordering::SeqCst);
+                    });
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                Err(e) => eprintln!("Agent: accept failed: {}", e),
+            }
+        }
+        if !accepted {
+            thread::sleep(ACCEPT_POLL_INTERVAL);
+        }
+    }
+}
+
+/// Serve one client until it disconnects or the agent shuts down
+fn handle_connection(mut stream: Box<dyn AgentStream>, state: &AgentState) -> io::Result<()> {
+    // Unauthenticated peers only get to send a small hello, and only briefly,
+    // so idle sockets cannot hold the connection slots
+    let deadline = Instant::now() + HELLO_TIMEOUT;
+    let hello = read_frame_limited::<Request>(
+        &mut stream,
+        &state.shutdown,
+        HELLO_FRAME_SIZE,
+        Some(deadline),
+    )?;
+    let scope = match hello {
+        Request::Hello { version, .. } if version != PROTOCOL_VERSION => {
+            let message = format!("agent speaks protocol version {}", PROTOCOL_VERSION);
+            return write_frame(
+                &mut stream,
+                &Response::error(AgentErrorCode::UnsupportedVersion, message),
+            );
+        }
+        Request::Hello { token, .. } => match authenticate(&state.tokens, &token) {
+            Some(scope) => scope,
+            None => {
+                return write_frame(
+                    &mut stream,
+                    &Response::error(AgentErrorCode::Unauthorized, "invalid token"),
+                );
+            }
+        },
+        _ => {
+            return write_frame(
+                &mut stream,
+                &Response::error(AgentErrorCode::BadRequest, "expected hello"),
+            );
+        }
+    };
+    write_frame(
+        &mut stream,
+        &Response::Welcome {
+            version: PROTOCOL_VERSION,
+            scope,
+        },
+    )?;
+
+    loop {
+        let request = read_frame::<Request>(&mut stream, &state.shutdown)?;
+        let response = handle_request(request, scope, state);
+        write_frame(&mut stream, &response)?;
+    }
+}
+
+/// Scope of the token matching `token`, compared in constant time
+fn authenticate(tokens: &[AgentToken], token: &str) -> Option<AgentScope> {
+    let mut scope = None;
+    for candidate in tokens {
+        if constant_time_eq(candidate.token.as_bytes(), token.as_bytes()) {
+            scope = scope.max(Some(candidate.scope));
+        }
+    }
+    scope
+}
+
+/// Byte comparison whose running time does not depend on where they differ
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+fn handle_request(request: Request, scope: AgentScope, state: &AgentState) -> Response {
+    if request.required_scope() > scope {
+        return Response::error(AgentErrorCode::Forbidden, "token is read-only");
+    }
+
+    let result = match request {
+        Request::Hello { .. } => {
+            return Response::error(AgentErrorCode::BadRequest, "already authenticated")
+        }
+        Request::Snapshot => {
+            return match state.latest.read().unwrap().clone() {
+                Some(snapshot) => Response::Snapshot { snapshot },
+                None => Response::error(AgentErrorCode::NoData, "no snapshot collected yet"),
+            };
+        }
+        Request::History { seconds } => {
+            let history = state.history.history();
+            let end = history.samples().last().map_or(0.0, |s| s.elapsed_secs);
+            let start = end - seconds as f64;
+            let samples = history
+                .samples()
+                .filter(|s| s.elapsed_secs >= start)
+                .copied()
+                .collect();
+            return Response::History { samples };
+        }
+        Request::Terminate { pid } => state.actuator.lock().unwrap().terminate(pid),
+        Request::SetPriority { pid, class } => {
+            state.actuator.lock().unwrap().set_priority(pid, class)
+        }
+        Request::SetAffinity { pid, mask } => {
+            state.actuator.lock().unwrap().set_affinity(pid, mask)
+        }
+    };
+
+    match result {
+        Ok(()) => Response::Done,
+        Err(error) => {
+            let code = match error {
+                ProcessError::NotFound(_) => AgentErrorCode::NotFound,
+                ProcessError::AccessDenied(_) => AgentErrorCode::AccessDenied,
+                _ => AgentErrorCode::Failed,
+            };
+            Response::error(code, error.short_description())
+        }
+    }
+}
+
+/// Connection to a remote agent
+pub struct AgentClient {
+    stream: Box<dyn AgentStream>,
+    scope: AgentScope,
+    /// Agent clock (Unix ms) and local `Instant` of the first snapshot
+    clock_base: Option<(u64, Instant)>,
+    /// Never set; client reads rely on the socket timeout instead
+    stop: AtomicBool,
+}
+
+impl AgentClient {
+    /// Connect and authenticate with `token`
+    pub fn connect(address: &str, token: &str) -> io::Result<Self> {
+        let address = AgentAddress::parse(address)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let stream: Box<dyn AgentStream> = match address {
+            AgentAddress::Tcp(host_port) => {
+                let stream = TcpStream::connect(host_port)?;
+                stream.set_nodelay(true)?;
+                stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+                stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+                Box::new(stream)
+            }
+            AgentAddress::Unix(path) => connect_unix(&path)?,
+        };
+
+        let mut client = Self {
+            stream,
+            scope: AgentScope::ReadOnly,
+            clock_base: None,
+            stop: AtomicBool::new(true),
+        };
+        let hello = Request::Hello {
+            version: PROTOCOL_VERSION,
+            token: token.to_string(),
+        };
+        match client.request(&hello)? {
+            Response::Welcome { scope, .. } => client.scope = scope,
+            other => return Err(unexpected(other)),
+        }
+        Ok(client)
+    }
+
+    /// Scope granted by the agent for this session
+    pub fn scope(&self) -> AgentScope {
+        self.scope
+    }
+
+    /// Latest snapshot, stamped on the local clock
+    ///
+    /// Timestamps keep the agent's spacing between snapshots, so CPU rates
+    /// computed from consecutive snapshots are not skewed by network delay.
+    pub fn snapshot(&mut self) -> io::Result<ProcessSnapshot> {
+        match self.request(&Request::Snapshot)? {
+            Response::Snapshot { snapshot } => {
+                let (base_ms, base_instant) = *self
+                    .clock_base
+                    .get_or_insert((snapshot.unix_ms, Instant::now()));
+                let offset = Duration::from_millis(snapshot.unix_ms.saturating_sub(base_ms));
+                Ok(snapshot.into_snapshot(base_instant + offset))
+            }
+            other => Err(unexpected(other)),
+        }
+    }
+
+    /// System samples recorded by the agent during the last `seconds`
+    pub fn history(&mut self, seconds: u64) -> io::Result<Vec<ReportSample>> {
+        match self.request(&Request::History { seconds })? {
+            Response::History { samples } => Ok(samples),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    /// Send one request and read its response
+    ///
+    /// `Error` responses are returned as-is; only transport failures are `Err`.
+    pub fn request(&mut self, request: &Request) -> io::Result<Response> {
+        write_frame(&mut self.stream, request)?;
+        read_frame(&mut self.stream, &self.stop)
+    }
+
+    fn command(&mut self, pid: u32, request: Request) -> Result<(), ProcessError> {
+        match self.request(&request) {
+            Ok(Response::Done) => Ok(()),
+            Ok(Response::Error { code, message }) => Err(match code {
+                AgentErrorCode::NotFound => ProcessError::NotFound(pid),
+                AgentErrorCode::AccessDenied | AgentErrorCode::Forbidden => {
+                    ProcessError::AccessDenied(message)
+                }
+                _ => ProcessError::InvalidOperation(message),
+            }),
+            Ok(other) => Err(ProcessError::InvalidOperation(
+                unexpected(other).to_string(),
+            )),
+            Err(e) => Err(ProcessError::InvalidOperation(format!(
+                "agent connection failed: {}",
+                e
+            ))),
+        }
+    }
+}
+
+impl ProcessSource for AgentClient {
+    fn collect(&mut self) -> Result<ProcessSnapshot, String> {
+        self.snapshot().map_err(|e| format!("Agent: {}", e))
+    }
+}
+
+impl ProcessActuator for AgentClient {
+    fn set_priority(&mut self, pid: u32, class: PriorityClass) -> Result<(), ProcessError> {
+        self.command(pid, Request::SetPriority { pid, class })
+    }
+
+    fn set_affinity(&mut self, pid: u32, mask: usize) -> Result<(), ProcessError> {
+        self.command(pid, Request::SetAffinity { pid, mask })
+    }
+
+    fn terminate(&mut self, pid: u32) -> Result<(), ProcessError> {
+        self.command(pid, Request::Terminate { pid })
+    }
+}
+
+#[cfg(unix)]
+fn connect_unix(path: &str) -> io::Result<Box<dyn AgentStream>> {
+    let stream = std::os::unix::net::UnixStream::connect(path)?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+    Ok(Box::new(stream))
+}
+
+#[cfg(not(unix))]
+fn connect_unix(_path: &str) -> io::Result<Box<dyn AgentStream>> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Unix sockets are not supported on this platform",
+    ))
+}
+
+/// Error for a response that does not answer the request
+fn unexpected(response: Response) -> io::Error {
+    let message = match response {
+        Response::Error { code, message } => {
+            format!("agent refused request ({:?}): {}", code, message)
+        }
+        other => format!("unexpected agent response: {:?}", other),
+    };
+    io::Error::other(message)
+}
+
+fn unix_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{ProcessBuilder, SnapshotBuilder};
+
+    struct RecordingActuator(Arc<Mutex<Vec<String>>>);
+
+    impl ProcessActuator for RecordingActuator {
+        fn set_priority(&mut self, pid: u32, class: PriorityClass) -> Result<(), ProcessError> {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("priority {} {:?}", pid, class));
+            Ok(())
+        }
+
+        fn set_affinity(&mut self, pid: u32, mask: usize) -> Result<(), ProcessError> {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("affinity {} {:X}", pid, mask));
+            Ok(())
+        }
+
+        fn terminate(&mut self, pid: u32) -> Result<(), ProcessError> {
+            Err(ProcessError::NotFound(pid))
+        }
+    }
+
+    fn start_server(calls: &Arc<Mutex<Vec<String>>>) -> AgentServer {
+        let config = AgentConfig {
+            enabled: true,
+            port: 0,
+            tokens: vec![
+                AgentToken {
+                    token: "viewer".to_string(),
+                    scope: AgentScope::ReadOnly,
+                },
+                AgentToken {
+                    token: "admin".to_string(),
+                    scope: AgentScope::Control,
+                },
+            ],
+            ..AgentConfig::default()
+        };
+        AgentServer::start(&config, Box::new(RecordingActuator(Arc::clone(calls)))).unwrap()
+    }
+
+    #[test]
+    fn test_frame_round_trip() {
+        let request = Request::SetPriority {
+            pid: 7,
+            class: PriorityClass::High,
+        };
+        let mut buffer = Vec::new();
+        write_frame(&mut buffer, &request).unwrap();
+        assert_eq!(&buffer[..4], &((buffer.len() - 4) as u32).to_be_bytes());
+        assert!(String::from_utf8_lossy(&buffer[4..]).contains("\"type\":\"set_priority\""));
+
+        let stop = AtomicBool::new(false);
+        let decoded: Request = read_frame(&mut &buffer[..], &stop).unwrap();
+        assert_eq!(decoded, request);
+
+        // Oversized length prefix is rejected before allocating
+        let huge = (MAX_FRAME_SIZE as u32 + 1).to_be_bytes();
+        let error = read_frame::<Request>(&mut &huge[..], &stop).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        // The hello limit is much tighter
+        let mut large = ((HELLO_FRAME_SIZE + 1) as u32).to_be_bytes().to_vec();
+        large.extend(vec![b' '; HELLO_FRAME_SIZE + 1]);
+        let error =
+            read_frame_limited::<Request>(&mut &large[..], &stop, HELLO_FRAME_SIZE, None)
+                .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        // A silent peer is given up on once the deadline passed
+        let deadline = Some(Instant::now());
+        let error =
+            read_frame_limited::<Request>(&mut SilentPeer, &stop, HELLO_FRAME_SIZE, deadline)
+                .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+    }
+
+    /// Connection that never sends anything
+    struct SilentPeer;
+
+    impl Read for SilentPeer {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::WouldBlock.into())
+        }
+    }
+
+    #[test]
+    fn test_parse_address() {
+        assert_eq!(
+            AgentAddress::parse("tcp://127.0.0.1:9183"),
+            Ok(AgentAddress::Tcp("127.0.0.1:9183".to_string()))
+        );
+        assert_eq!(
+            AgentAddress::parse("unix:/run/tm.sock"),
+            Ok(AgentAddress::Unix("/run/tm.sock".to_string()))
+        );
+        assert!(AgentAddress::parse("localhost").is_err());
+        assert!(AgentAddress::parse("localhost:http").is_err());
+    }
+
+    #[test]
+    fn test_client_reads_snapshots_and_history() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut server = start_server(&calls);
+        let address = server.local_addr().unwrap().to_string();
+
+        let mut client = AgentClient::connect(&address, "viewer").unwrap();
+        assert_eq!(client.scope(), AgentScope::ReadOnly);
+        assert!(client.snapshot().is_err(), "no snapshot published yet");
+
+        let mut sink = server.sink();
+        let start = Instant::now();
+        sink.on_snapshot(
+            &SnapshotBuilder::new()
+                .with_timestamp(start)
+                .with_processes(vec![ProcessBuilder::new(42, "remote.exe").build()])
+                .with_cpu(12.5, vec![10.0, 15.0])
+                .build(),
+        );
+        let first = client.collect().unwrap();
+        assert_eq!(first.processes[0].name, "remote.exe");
+        assert_eq!(first.system_metrics.cpu_cores, vec![10.0, 15.0]);
+
+        thread::sleep(Duration::from_millis(20));
+        sink.on_snapshot(
+            &SnapshotBuilder::new()
+                .with_timestamp(start + Duration::from_secs(1))
+                .with_processes(vec![ProcessBuilder::new(42, "remote.exe")
+                    .with_cpu_time(5_000_000)
+                    .build()])
+                .with_cpu(12.5, vec![10.0, 15.0])
+                .build(),
+        );
+        let second = client.collect().unwrap();
+        assert!(second.timestamp > first.timestamp);
+        assert_eq!(second.processes[0].cpu_time_user, 5_000_000);
+
+        let history = client.history(60).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].cpu_percent, 12.5);
+
+        // Read-only tokens cannot control processes
+        assert!(matches!(
+            client.terminate(42),
+            Err(ProcessError::AccessDenied(_))
+        ));
+        assert!(calls.lock().unwrap().is_empty());
+
+        server.shutdown();
+    }
+
+    #[test]
+    fn test_control_scope_and_authentication() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut server = start_server(&calls);
+        let address = server.local_addr().unwrap().to_string();
+
+        let error = AgentClient::connect(&address, "wrong").err().unwrap();
+        assert!(error.to_string().contains("Unauthorized"));
+
+        let mut admin = AgentClient::connect(&address, "admin").unwrap();
+        assert_eq!(admin.scope(), AgentScope::Control);
+        admin.set_priority(42, PriorityClass::BelowNormal).unwrap();
+        admin.set_affinity(42, 0x3).unwrap();
+        assert!(matches!(
+            admin.terminate(42),
+            Err(ProcessError::NotFound(42))
+        ));
+        assert_eq!(
+            calls.lock().unwrap().as_slice(),
+            ["priority 42 BelowNormal", "affinity 42 3"]
+        );
+
+        // Version mismatch is refused during the handshake
+        let mut stream = TcpStream::connect(&address).unwrap();
+        let hello = Request::Hello {
+            version: PROTOCOL_VERSION + 1,
+            token: "admin".to_string(),
+        };
+        write_frame(&mut stream, &hello).unwrap();
+        let response: Response = read_frame(&mut stream, &AtomicBool::new(true)).unwrap();
+        assert!(matches!(
+            response,
+            Response::Error {
+                code: AgentErrorCode::UnsupportedVersion,
+                ..
+            }
+        ));
+
+        // An oversized frame before hello drops the connection unanswered
+        let mut stream = TcpStream::connect(&address).unwrap();
+        stream
+            .write_all(&((HELLO_FRAME_SIZE + 1) as u32).to_be_bytes())
+            .unwrap();
+        let error = read_frame::<Response>(&mut stream, &AtomicBool::new(true)).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+
+        server.shutdown();
+    }
+}
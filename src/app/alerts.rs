@@ -0,0 +1,698 @@
+//! Threshold alerting engine
+//!
+//! Evaluates `AlertRule`s from `AppConfig` against every snapshot:
+//! - Sustained-duration conditions ("CPU > 90% for 30s")
+//! - Hysteresis so a value hovering at the threshold does not flap
+//! - Per-rule cooldowns between repeated firings
+//! - Per-process rules tracked separately for each matching PID
+//!
+//! Fired and cleared events are dispatched to `AlertHandler`s: the UI thread
+//! (tray notifications) through a channel, the event log, and a JSON-lines
+//! export file. `AlertSink` wires the engine into `Updater::start_with_sinks`.
+
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::sync::mpsc::Sender;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::app::config::{
+    AlertComparison, AlertMetric, AlertRule, AlertSeverity, AlertsConfig, AppConfig, ConfigSection,
+};
+use crate::app::updater::SnapshotSink;
+use crate::util::logging::EventLogger;
+use crate::windows::monitor::ProcessSnapshot;
+
+/// Whether an alert started or ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertEventKind {
+    Fired,
+    Cleared,
+}
+
+/// Alert state change produced by `AlertEngine::evaluate`
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertEvent {
+    pub rule: String,
+    pub severity: AlertSeverity,
+    pub kind: AlertEventKind,
+    /// Value that triggered the change
+    pub value: f64,
+    pub threshold: f64,
+    /// Process the event refers to (process rules only)
+    pub pid: Option<u32>,
+    pub process_name: Option<String>,
+    /// Snapshot time of the change
+    pub timestamp: Instant,
+    /// Wall-clock time of the change (milliseconds since the Unix epoch)
+    pub unix_ms: u64,
+}
+
+impl AlertEvent {
+    /// One-line human-readable description
+    pub fn message(&self) -> String {
+        let subject = match (&self.process_name, self.pid) {
+            (Some(name), Some(pid)) => format!(" ({} pid {})", name, pid),
+            _ => String::new(),
+        };
+        let state = match self.kind {
+            AlertEventKind::Fired => "fired",
+            AlertEventKind::Cleared => "cleared",
+        };
+        format!(
+            "{}{} {}: value {:.1}, threshold {:.1}",
+            self.rule, subject, state, self.value, self.threshold
+        )
+    }
+
+    /// JSON object used by the export file
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "unix_ms": self.unix_ms,
+            "rule": self.rule,
+            "severity": self.severity,
+            "kind": match self.kind {
+                AlertEventKind::Fired => "fired",
+                AlertEventKind::Cleared => "cleared",
+            },
+            "value": self.value,
+            "threshold": self.threshold,
+            "pid": self.pid,
+            "process_name": self.process_name,
+        })
+    }
+}
+
+/// Receiver of alert events
+///
+/// Handlers run on the updater thread; anything slow should hand the event
+/// off to another thread (see the `Sender<AlertEvent>` implementation).
+pub trait AlertHandler: Send {
+    fn on_alert(&mut self, event: &AlertEvent);
+}
+
+/// Forwards events to another thread, e.g. the UI thread driving the tray
+impl AlertHandler for Sender<AlertEvent> {
+    fn on_alert(&mut self, event: &AlertEvent) {
+        let _ = self.send(event.clone());
+    }
+}
+
+/// Writes events to the application event log
+impl AlertHandler for EventLogger {
+    fn on_alert(&mut self, event: &AlertEvent) {
+        let message = event.message();
+        match (event.kind, event.severity) {
+            (AlertEventKind::Cleared, _) | (_, AlertSeverity::Info) => self.log_info(&message),
+            (_, AlertSeverity::Warning) => self.log_warning(&message),
+            (_, AlertSeverity::Critical) => self.log_error(&message),
+        }
+    }
+}
+
+/// Appends events as JSON lines for export
+pub struct JsonLinesAlertHandler {
+    file: File,
+}
+
+impl JsonLinesAlertHandler {
+    /// Open (or create) the export file in append mode
+    pub fn open(path: &str) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self { file })
+    }
+}
+
+impl AlertHandler for JsonLinesAlertHandler {
+    fn on_alert(&mut self, event: &AlertEvent) {
+        let _ = writeln!(self.file, "{}", event.to_json());
+    }
+}
+
+/// Per rule and subject evaluation state
+#[derive(Debug, Default)]
+struct RuleState {
+    /// When the current breach started (None while not breached)
+    breach_since: Option<Instant>,
+    active: bool,
+    last_fired: Option<Instant>,
+    last_value: f64,
+    process_name: Option<String>,
+}
+
+/// Rule evaluator keeping sustain, hysteresis and cooldown state
+pub struct AlertEngine {
+    rules: Vec<AlertRule>,
+    /// Keyed by (rule index, pid) - pid is None for system rules
+    states: HashMap<(usize, Option<u32>), RuleState>,
+}
+
+impl AlertEngine {
+    /// Create an engine for the enabled rules in `config`
+    pub fn new(config: &AlertsConfig) -> Self {
+        Self::with_rules(enabled_rules(config))
+    }
+
+    pub fn with_rules(rules: Vec<AlertRule>) -> Self {
+        Self {
+            rules,
+            states: HashMap::new(),
+        }
+    }
+
+    pub fn rules(&self) -> &[AlertRule] {
+        &self.rules
+    }
+
+    /// Replace the rules, keeping the state of rules that did not change
+    ///
+    /// An unchanged rule that is firing stays active instead of firing again.
+    pub fn set_rules(&mut self, rules: Vec<AlertRule>) {
+        let old_rules = std::mem::replace(&mut self.rules, rules);
+        let mut states = HashMap::new();
+        for ((index, pid), state) in self.states.drain() {
+            let kept = self.rules.iter().position(|rule| *rule == old_rules[index]);
+            if let Some(new_index) = kept {
+                states.insert((new_index, pid), state);
+            }
+        }
+        self.states = states;
+    }
+
+    /// Number of (rule, subject) pairs currently firing
+    pub fn active_count(&self) -> usize {
+        self.states.values().filter(|s| s.active).count()
+    }
+
+    /// Evaluate all rules against a snapshot and return state changes
+    pub fn evaluate(&mut self, snapshot: &ProcessSnapshot) -> Vec<AlertEvent> {
+        let now = snapshot.timestamp;
+        let mut events = Vec::new();
+
+        for index in 0..self.rules.len() {
+            let samples = sample_metric(&self.rules[index].metric, snapshot);
+            let is_process_rule = is_process_metric(&self.rules[index].metric);
+
+            for (pid, name, value) in &samples {
+                let state = self.states.entry((index, *pid)).or_default();
+                state.process_name = name.clone();
+                if let Some(kind) = step(&self.rules[index], state, *value, now) {
+                    events.push(make_event(&self.rules[index], state, *pid, kind, now));
+                }
+            }
+
+            // Processes that exited clear their alerts and drop their state
+            if is_process_rule {
+                let gone: Vec<_> = self
+                    .states
+                    .keys()
+                    .filter(|(rule, pid)| {
+                        *rule == index && !samples.iter().any(|(p, _, _)| p == pid)
+                    })
+                    .copied()
+                    .collect();
+                for key in gone {
+                    if let Some(state) = self.states.remove(&key) {
+                        if state.active {
+                            let rule = &self.rules[index];
+                            events.push(make_event(
+                                rule,
+                                &state,
+                                key.1,
+                                AlertEventKind::Cleared,
+                                now,
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+
+        events
+    }
+}
+
+fn enabled_rules(config: &AlertsConfig) -> Vec<AlertRule> {
+    config.rules.iter().filter(|r| r.enabled).cloned().collect()
+}
+
+/// Advance one (rule, subject) state with a new value
+fn step(
+    rule: &AlertRule,
+    state: &mut RuleState,
+    value: f64,
+    now: Instant,
+) -> Option<AlertEventKind> {
+    state.last_value = value;
+
+    let breached = match rule.comparison {
+        AlertComparison::Above => value > rule.threshold,
+        AlertComparison::Below => value < rule.threshold,
+    };
+
+    if state.active {
+        let recovered = match rule.comparison {
+            AlertComparison::Above => value < rule.threshold - rule.hysteresis,
+            AlertComparison::Below => value > rule.threshold + rule.hysteresis,
+        };
+        if recovered {
+            state.active = false;
+            state.breach_since = None;
+            return Some(AlertEventKind::Cleared);
+        }
+        return None;
+    }
+
+    if !breached {
+        state.breach_since = None;
+        return None;
+    }
+
+    let since = *state.breach_since.get_or_insert(now);
+    let sustained = now.saturating_duration_since(since) >= Duration::from_secs(rule.sustain_secs);
+    let cooled_down = match state.last_fired {
+        Some(last) => {
+            now.saturating_duration_since(last) >= Duration::from_secs(rule.cooldown_secs)
+        }
+        None => true,
+    };
+
+    if sustained && cooled_down {
+        state.active = true;
+        state.last_fired = Some(now);
+        return Some(AlertEventKind::Fired);
+    }
+    None
+}
+
+fn make_event(
+    rule: &AlertRule,
+    state: &RuleState,
+    pid: Option<u32>,
+    kind: AlertEventKind,
+    timestamp: Instant,
+) -> AlertEvent {
+    AlertEvent {
+        rule: rule.name.clone(),
+        severity: rule.severity,
+        kind,
+        value: state.last_value,
+        threshold: rule.threshold,
+        pid,
+        process_name: state.process_name.clone(),
+        timestamp,
+        unix_ms: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0),
+    }
+}
+
+fn is_process_metric(metric: &AlertMetric) -> bool {
+    matches!(
+        metric,
+        AlertMetric::ProcessPrivateBytes(_)
+            | AlertMetric::ProcessWorkingSet(_)
+            | AlertMetric::ProcessHandleCount(_)
+    )
+}
+
+/// Current value(s) of a metric as (pid, process name, value)
+fn sample_metric(
+    metric: &AlertMetric,
+    snapshot: &ProcessSnapshot,
+) -> Vec<(Option<u32>, Option<String>, f64)> {
+    let system = &snapshot.system_metrics;
+    let system_value = match metric {
+        AlertMetric::SystemCpuPercent => Some(system.cpu_total as f64),
+        AlertMetric::MemoryLoadPercent => Some(system.memory_load_percent as f64),
+        AlertMetric::MemoryAvailableBytes => Some(system.memory_available as f64),
+        AlertMetric::DiskReadBps => Some(system.disk_read_bps as f64),
+        AlertMetric::DiskWriteBps => Some(system.disk_write_bps as f64),
+        AlertMetric::NetworkReceiveBps => Some(system.network_receive_bps as f64),
+        AlertMetric::NetworkTransmitBps => Some(system.network_transmit_bps as f64),
+        AlertMetric::Custom(key) => system.custom.get(key).copied(),
+        _ => None,
+    };
+    if let Some(value) = system_value {
+        return vec![(None, None, value)];
+    }
+
+    let (name, extract): (
+        &str,
+        fn(&crate::windows::monitor::nt_query::ProcessInfo) -> f64,
+    ) = match metric {
+        AlertMetric::ProcessPrivateBytes(name) => (name, |p| p.memory_private as f64),
+        AlertMetric::ProcessWorkingSet(name) => (name, |p| p.memory_working_set as f64),
+        AlertMetric::ProcessHandleCount(name) => (name, |p| p.handle_count as f64),
+        _ => return Vec::new(),
+    };
+
+    snapshot
+        .processes
+        .iter()
+        .filter(|p| p.name.eq_ignore_ascii_case(name))
+        .map(|p| (Some(p.pid), Some(p.name.clone()), extract(p)))
+        .collect()
+}
+
+/// Snapshot sink running the alert engine on the updater thread
+pub struct AlertSink {
+    engine: AlertEngine,
+    handlers: Vec<Box<dyn AlertHandler>>,
+}
+
+impl AlertSink {
+    pub fn new(engine: AlertEngine) -> Self {
+        Self {
+            engine,
+            handlers: Vec::new(),
+        }
+    }
+
+    /// Engine for `config` with the event log and, if configured, the export file attached
+    pub fn from_config(config: &AlertsConfig) -> io::Result<Self> {
+        let mut sink = Self::new(AlertEngine::new(config))
+            .with_handler(Box::new(EventLogger::new("TaskManager")));
+        if let Some(path) = &config.export_path {
+            sink = sink.with_handler(Box::new(JsonLinesAlertHandler::open(path)?));
+        }
+        Ok(sink)
+    }
+
+    pub fn with_handler(mut self, handler: Box<dyn AlertHandler>) -> Self {
+        self.handlers.push(handler);
+        self
+    }
+}
+
+impl SnapshotSink for AlertSink {
+    fn on_snapshot(&mut self, snapshot: &ProcessSnapshot) {
+        for event in self.engine.evaluate(snapshot) {
+            for handler in &mut self.handlers {
+                handler.on_alert(&event);
+            }
+        }
+    }
+
+    /// Picks up rule changes; a new `export_path` applies after a restart
+    fn on_config(&mut self, config: &AppConfig, changed: &[ConfigSection]) {
+        if changed.contains(&ConfigSection::Alerts) {
+            self.engine.set_rules(enabled_rules(&config.alerts));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{ProcessBuilder, SnapshotBuilder};
+    use std::sync::mpsc::channel;
+
+    fn cpu_rule() -> AlertRule {
+        AlertRule::new(
+            "cpu",
+            AlertMetric::SystemCpuPercent,
+            AlertComparison::Above,
+            90.0,
+        )
+    }
+
+    fn kinds(events: &[AlertEvent]) -> Vec<AlertEventKind> {
+        events.iter().map(|e| e.kind).collect()
+    }
+
+    #[test]
+    fn test_sustained_duration() {
+        let mut engine = AlertEngine::with_rules(vec![cpu_rule().with_sustain_secs(30)]);
+        let start = Instant::now();
+
+        assert!(engine
+            .evaluate(
+                &SnapshotBuilder::new()
+                    .with_timestamp(start)
+                    .with_cpu(95.0, Vec::new())
+                    .build()
+            )
+            .is_empty());
+        assert!(engine
+            .evaluate(
+                &SnapshotBuilder::new()
+                    .with_timestamp(start + Duration::from_secs(29))
+                    .with_cpu(95.0, Vec::new())
+                    .build()
+            )
+            .is_empty());
+        let events = engine.evaluate(
+            &SnapshotBuilder::new()
+                .with_timestamp(start + Duration::from_secs(30))
+                .with_cpu(95.0, Vec::new())
+                .build(),
+        );
+        assert_eq!(kinds(&events), vec![AlertEventKind::Fired]);
+        assert_eq!(engine.active_count(), 1);
+    }
+
+    #[test]
+    fn test_breach_interrupted_resets_timer() {
+        let mut engine = AlertEngine::with_rules(vec![cpu_rule().with_sustain_secs(10)]);
+        let start = Instant::now();
+
+        engine.evaluate(
+            &SnapshotBuilder::new()
+                .with_timestamp(start)
+                .with_cpu(95.0, Vec::new())
+                .build(),
+        );
+        engine.evaluate(
+            &SnapshotBuilder::new()
+                .with_timestamp(start + Duration::from_secs(5))
+                .with_cpu(50.0, Vec::new())
+                .build(),
+        );
+        let events = engine.evaluate(
+            &SnapshotBuilder::new()
+                .with_timestamp(start + Duration::from_secs(10))
+                .with_cpu(95.0, Vec::new())
+                .build(),
+        );
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_hysteresis() {
+        let mut engine = AlertEngine::with_rules(vec![cpu_rule().with_hysteresis(10.0)]);
+        let start = Instant::now();
+
+        assert_eq!(
+            kinds(
+                &engine.evaluate(
+                    &SnapshotBuilder::new()
+                        .with_timestamp(start)
+                        .with_cpu(91.0, Vec::new())
+                        .build()
+                )
+            ),
+            vec![AlertEventKind::Fired]
+        );
+        // Dipping just under the threshold does not clear
+        assert!(engine
+            .evaluate(
+                &SnapshotBuilder::new()
+                    .with_timestamp(start)
+                    .with_cpu(85.0, Vec::new())
+                    .build()
+            )
+            .is_empty());
+        assert_eq!(
+            kinds(
+                &engine.evaluate(
+                    &SnapshotBuilder::new()
+                        .with_timestamp(start)
+                        .with_cpu(79.0, Vec::new())
+                        .build()
+                )
+            ),
+            vec![AlertEventKind::Cleared]
+        );
+    }
+
+    #[test]
+    fn test_cooldown() {
+        let mut engine = AlertEngine::with_rules(vec![cpu_rule().with_cooldown_secs(60)]);
+        let start = Instant::now();
+
+        assert_eq!(
+            engine
+                .evaluate(
+                    &SnapshotBuilder::new()
+                        .with_timestamp(start)
+                        .with_cpu(95.0, Vec::new())
+                        .build()
+                )
+                .len(),
+            1
+        );
+        assert_eq!(
+            engine
+                .evaluate(
+                    &SnapshotBuilder::new()
+                        .with_timestamp(start + Duration::from_secs(1))
+                        .with_cpu(50.0, Vec::new())
+                        .build()
+                )
+                .len(),
+            1
+        );
+        // Breached again inside the cooldown: stays quiet
+        assert!(engine
+            .evaluate(
+                &SnapshotBuilder::new()
+                    .with_timestamp(start + Duration::from_secs(2))
+                    .with_cpu(95.0, Vec::new())
+                    .build()
+            )
+            .is_empty());
+        let events = engine.evaluate(
+            &SnapshotBuilder::new()
+                .with_timestamp(start + Duration::from_secs(60))
+                .with_cpu(95.0, Vec::new())
+                .build(),
+        );
+        assert_eq!(kinds(&events), vec![AlertEventKind::Fired]);
+    }
+
+    #[test]
+    fn test_process_rule_per_pid() {
+        let rule = AlertRule::new(
+            "big",
+            AlertMetric::ProcessPrivateBytes("App.exe".to_string()),
+            AlertComparison::Above,
+            2e9,
+        )
+        .with_severity(AlertSeverity::Critical);
+        let mut engine = AlertEngine::with_rules(vec![rule]);
+        let start = Instant::now();
+
+        let events = engine.evaluate(
+            &SnapshotBuilder::new()
+                .with_timestamp(start)
+                .with_processes(vec![
+                    ProcessBuilder::new(1, "app.exe")
+                        .with_memory(3_000_000_000)
+                        .build(),
+                    ProcessBuilder::new(2, "app.exe").with_memory(10).build(),
+                ])
+                .with_cpu(0.0, Vec::new())
+                .build(),
+        );
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].pid, Some(1));
+        assert_eq!(events[0].severity, AlertSeverity::Critical);
+        assert!(events[0].message().contains("app.exe pid 1"));
+
+        // Process exits: alert clears
+        let events = engine.evaluate(
+            &SnapshotBuilder::new()
+                .with_timestamp(start)
+                .with_processes(vec![ProcessBuilder::new(2, "app.exe")
+                    .with_memory(10)
+                    .build()])
+                .with_cpu(0.0, Vec::new())
+                .build(),
+        );
+        assert_eq!(kinds(&events), vec![AlertEventKind::Cleared]);
+        assert_eq!(engine.active_count(), 0);
+    }
+
+    #[test]
+    fn test_custom_metric_rule() {
+        let rule = AlertRule::new(
+            "backlog",
+            AlertMetric::Custom("queue.depth".to_string()),
+            AlertComparison::Above,
+            100.0,
+        );
+        let mut engine = AlertEngine::with_rules(vec![rule]);
+        let start = Instant::now();
+
+        // No value yet (collector not sampled): nothing to evaluate
+        assert!(engine
+            .evaluate(
+                &SnapshotBuilder::new()
+                    .with_timestamp(start)
+                    .with_cpu(0.0, Vec::new())
+                    .build()
+            )
+            .is_empty());
+
+        let mut snapshot = SnapshotBuilder::new()
+            .with_timestamp(start + Duration::from_secs(1))
+            .with_cpu(0.0, Vec::new())
+            .build();
+        snapshot
+            .system_metrics
+            .custom
+            .insert("queue.depth".to_string(), 250.0);
+        let events = engine.evaluate(&snapshot);
+        assert_eq!(kinds(&events), vec![AlertEventKind::Fired]);
+        assert_eq!(events[0].value, 250.0);
+    }
+
+    #[test]
+    fn test_set_rules_keeps_unchanged_state() {
+        let mut engine = AlertEngine::with_rules(vec![cpu_rule()]);
+        let start = Instant::now();
+        let events = engine.evaluate(
+            &SnapshotBuilder::new()
+                .with_timestamp(start)
+                .with_cpu(95.0, Vec::new())
+                .build(),
+        );
+        assert_eq!(kinds(&events), vec![AlertEventKind::Fired]);
+
+        // New rule in front; the CPU rule keeps firing without a new event
+        let memory = AlertRule::new(
+            "memory",
+            AlertMetric::MemoryLoadPercent,
+            AlertComparison::Above,
+            99.0,
+        );
+        engine.set_rules(vec![memory, cpu_rule()]);
+        assert_eq!(engine.active_count(), 1);
+        let later = start + Duration::from_secs(1);
+        assert!(engine
+            .evaluate(
+                &SnapshotBuilder::new()
+                    .with_timestamp(later)
+                    .with_cpu(95.0, Vec::new())
+                    .build()
+            )
+            .is_empty());
+
+        // Changed threshold starts over
+        engine.set_rules(vec![cpu_rule().with_hysteresis(1.0)]);
+        assert_eq!(engine.active_count(), 0);
+    }
+
+    #[test]
+    fn test_disabled_rules_skipped() {
+        let mut config = AlertsConfig::default();
+        config.rules[0].enabled = false;
+        let engine = AlertEngine::new(&config);
+        assert_eq!(engine.rules().len(), config.rules.len() - 1);
+    }
+
+    #[test]
+    fn test_sink_dispatches_to_handlers() {
+        let (tx, rx) = channel();
+        let mut sink =
+            AlertSink::new(AlertEngine::with_rules(vec![cpu_rule()])).with_handler(Box::new(tx));
+
+        sink.on_snapshot(&SnapshotBuilder::new().with_cpu(99.0, Vec::new()).build());
+
+        let event = rx.try_recv().unwrap();
+        assert_eq!(event.kind, AlertEventKind::Fired);
+        assert_eq!(event.to_json()["rule"], "cpu");
+    }
+}
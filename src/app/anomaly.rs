@@ -0,0 +1,320 @@
+//! CPU and I/O spike detection
+//!
+//! `AnomalySink` runs the `core::metrics` anomaly detectors over every
+//! snapshot: system CPU, disk and network rates, and per-process CPU for the
+//! busiest processes. Each flagged sample becomes an `AnomalyEvent`, which is
+//! written to the event log ("Unusual CPU spike in build.exe at 14:03") and
+//! sent to the UI thread so the matching graph sample can be highlighted.
+
+use std::collections::HashMap;
+use std::sync::mpsc::Sender;
+use std::time::Instant;
+
+use crate::app::config::AnomaliesConfig;
+use crate::app::updater::SnapshotSink;
+use crate::core::metrics::{Anomaly, AnomalyConfig, AnomalyMonitor};
+use crate::util::format::NumberFormat;
+use crate::util::logging::EventLogger;
+use crate::windows::monitor::ProcessSnapshot;
+
+/// Minimum rise for CPU series (percentage points)
+const CPU_MIN_DELTA: f32 = 10.0;
+
+/// Minimum rise for I/O series (bytes per second)
+const IO_MIN_DELTA: f32 = 1024.0 * 1024.0;
+
+/// Series an anomaly was detected in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AnomalySeries {
+    SystemCpu,
+    DiskRead,
+    DiskWrite,
+    NetworkReceive,
+    NetworkTransmit,
+    /// CPU usage of a process (PID)
+    ProcessCpu(u32),
+}
+
+impl AnomalySeries {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::SystemCpu | Self::ProcessCpu(_) => "CPU",
+            Self::DiskRead => "disk read",
+            Self::DiskWrite => "disk write",
+            Self::NetworkReceive => "network receive",
+            Self::NetworkTransmit => "network transmit",
+        }
+    }
+
+    fn is_cpu(&self) -> bool {
+        matches!(self, Self::SystemCpu | Self::ProcessCpu(_))
+    }
+}
+
+/// Anomalous sample reported by `AnomalySink`
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnomalyEvent {
+    pub series: AnomalySeries,
+    /// Image name for process series
+    pub process_name: Option<String>,
+    pub anomaly: Anomaly,
+    /// Snapshot time of the sample
+    pub timestamp: Instant,
+    /// Local wall-clock time of the sample (HH:MM)
+    pub local_time: String,
+}
+
+impl AnomalyEvent {
+    /// One-line human-readable description, values formatted with `numbers`
+    pub fn message(&self, numbers: &NumberFormat) -> String {
+        let subject = match &self.process_name {
+            Some(name) => format!(" in {}", name),
+            None => String::new(),
+        };
+        format!(
+            "Unusual {} spike{} at {} ({}, expected {})",
+            self.series.label(),
+            subject,
+            self.local_time,
+            self.format_value(self.anomaly.value, numbers),
+            self.format_value(self.anomaly.expected, numbers)
+        )
+    }
+
+    fn format_value(&self, value: f32, numbers: &NumberFormat) -> String {
+        if self.series.is_cpu() {
+            numbers.percent(value as f64)
+        } else {
+            numbers.rate(value.max(0.0) as f64)
+        }
+    }
+}
+
+/// Snapshot sink running the anomaly detectors
+pub struct AnomalySink {
+    cpu: AnomalyMonitor<AnomalySeries>,
+    io: AnomalyMonitor<AnomalySeries>,
+    top_processes: usize,
+    /// Previous total CPU time per PID (name, 100ns units)
+    prev_cpu_times: HashMap<u32, (String, u64)>,
+    prev_timestamp: Option<Instant>,
+    logger: Option<EventLogger>,
+    /// Formats values in logged messages
+    numbers: NumberFormat,
+    senders: Vec<Sender<AnomalyEvent>>,
+}
+
+impl AnomalySink {
+    pub fn new(config: &AnomaliesConfig) -> Self {
+        let mut base = AnomalyConfig::new(config.method);
+        if let Some(threshold) = config.threshold {
+            base = base.with_threshold(threshold);
+        }
+        Self {
+            cpu: AnomalyMonitor::new(base.clone().with_min_delta(CPU_MIN_DELTA)),
+            io: AnomalyMonitor::new(base.with_min_delta(IO_MIN_DELTA)),
+            top_processes: config.top_processes,
+            prev_cpu_times: HashMap::new(),
+            prev_timestamp: None,
+            logger: None,
+            numbers: NumberFormat::new(),
+            senders: Vec::new(),
+        }
+    }
+
+    /// Sink for `config` reporting to the event log
+    pub fn from_config(config: &AnomaliesConfig) -> Self {
+        Self::new(config).with_logger(EventLogger::new("TaskManager"))
+    }
+
+    pub fn with_logger(mut self, logger: EventLogger) -> Self {
+        self.logger = Some(logger);
+        self
+    }
+
+    /// Format logged values with `numbers` (`DisplayConfig::number_format`)
+    pub fn with_number_format(mut self, numbers: NumberFormat) -> Self {
+        self.numbers = numbers;
+        self
+    }
+
+    /// Also deliver events over a channel (e.g. to the UI thread)
+    pub fn with_sender(mut self, sender: Sender<AnomalyEvent>) -> Self {
+        self.senders.push(sender);
+        self
+    }
+
+    /// Run all detectors over a snapshot and return the anomalies found
+    pub fn evaluate(&mut self, snapshot: &ProcessSnapshot) -> Vec<AnomalyEvent> {
+        let metrics = &snapshot.system_metrics;
+        let mut found: Vec<(AnomalySeries, Option<String>, Anomaly)> = Vec::new();
+
+        if let Some(anomaly) = self
+            .cpu
+            .observe(AnomalySeries::SystemCpu, metrics.cpu_total)
+        {
+            found.push((AnomalySeries::SystemCpu, None, anomaly));
+        }
+        let io_series = [
+            (AnomalySeries::DiskRead, metrics.disk_read_bps),
+            (AnomalySeries::DiskWrite, metrics.disk_write_bps),
+            (AnomalySeries::NetworkReceive, metrics.network_receive_bps),
+            (AnomalySeries::NetworkTransmit, metrics.network_transmit_bps),
+        ];
+        for (series, value) in io_series {
+            if let Some(anomaly) = self.io.observe(series, value as f32) {
+                found.push((series, None, anomaly));
+            }
+        }
+
+        // Per-process CPU % of the whole machine since the previous snapshot
+        let elapsed = self
+            .prev_timestamp
+            .map(|prev| {
+                snapshot
+                    .timestamp
+                    .saturating_duration_since(prev)
+                    .as_secs_f64()
+            })
+            .unwrap_or(0.0);
+        let cpus = metrics.cpu_cores.len().max(1) as f64;
+        let mut usage = Vec::with_capacity(snapshot.processes.len());
+        let mut cpu_times = HashMap::with_capacity(snapshot.processes.len());
+        for process in &snapshot.processes {
+            let total = process.cpu_time_user + process.cpu_time_kernel;
+            if let Some((name, prev)) = self.prev_cpu_times.get(&process.pid) {
+                if *name == process.name && elapsed > 0.0 {
+                    let delta = total.saturating_sub(*prev) as f64;
+                    let percent = delta / (elapsed * 10_000_000.0 * cpus) * 100.0;
+                    usage.push((process.pid, &process.name, percent.min(100.0) as f32));
+                }
+            }
+            cpu_times.insert(process.pid, (process.name.clone(), total));
+        }
+
+        // Every process keeps a baseline; only the busiest are reported
+        usage.sort_by(|a, b| b.2.total_cmp(&a.2).then(a.0.cmp(&b.0)));
+        for (rank, (pid, name, percent)) in usage.iter().enumerate() {
+            let series = AnomalySeries::ProcessCpu(*pid);
+            if let Some(anomaly) = self.cpu.observe(series, *percent) {
+                if rank < self.top_processes {
+                    found.push((series, Some(name.to_string()), anomaly));
+                }
+            }
+        }
+
+        self.cpu.retain(|series| match series {
+            AnomalySeries::ProcessCpu(pid) => cpu_times.contains_key(pid),
+            _ => true,
+        });
+        self.prev_cpu_times = cpu_times;
+        self.prev_timestamp = Some(snapshot.timestamp);
+
+        let local_time = chrono::Local::now().format("%H:%M").to_string();
+        found
+            .into_iter()
+            .map(|(series, process_name, anomaly)| AnomalyEvent {
+                series,
+                process_name,
+                anomaly,
+                timestamp: snapshot.timestamp,
+                local_time: local_time.clone(),
+            })
+            .collect()
+    }
+}
+
+impl SnapshotSink for AnomalySink {
+    fn on_snapshot(&mut self, snapshot: &ProcessSnapshot) {
+        for event in self.evaluate(snapshot) {
+            if let Some(logger) = &self.logger {
+                logger.log_info(&event.message(&self.numbers));
+            }
+            self.senders
+                .retain(|sender| sender.send(event.clone()).is_ok());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::metrics::SystemMetrics;
+    use crate::test_support::{ProcessBuilder, SnapshotBuilder};
+    use std::sync::mpsc::channel;
+    use std::time::Duration;
+
+    /// Replays a one-core trace; `build_busy(i)` is build.exe CPU % in second i
+    fn replay(
+        sink: &mut AnomalySink,
+        seconds: usize,
+        build_busy: impl Fn(usize) -> f32,
+    ) -> Vec<(usize, AnomalyEvent)> {
+        let start = Instant::now();
+        let mut build_time = 0u64;
+        let mut events = Vec::new();
+        for i in 0..seconds {
+            build_time += (build_busy(i) / 100.0 * 10_000_000.0) as u64;
+
+            let snapshot = SnapshotBuilder::new()
+                .with_timestamp(start + Duration::from_secs(i as u64))
+                .with_processes(vec![
+                    ProcessBuilder::new(10, "build.exe")
+                        .with_cpu_time(build_time)
+                        .build(),
+                    ProcessBuilder::new(20, "idle.exe").build(),
+                ])
+                .with_cpu(5.0 + (i % 3) as f32, vec![0.0])
+                .build();
+            events.extend(sink.evaluate(&snapshot).into_iter().map(|e| (i, e)));
+        }
+        events
+    }
+
+    #[test]
+    fn test_process_cpu_spike_reported() {
+        let mut sink = AnomalySink::new(&AnomaliesConfig::default());
+        let busy = |i: usize| if i == 60 { 80.0 } else { 2.0 + (i % 4) as f32 };
+        let events = replay(&mut sink, 90, busy);
+
+        assert_eq!(events.len(), 1);
+        let (second, event) = &events[0];
+        assert_eq!(*second, 60);
+        assert_eq!(event.series, AnomalySeries::ProcessCpu(10));
+        let message = event.message(&NumberFormat::new());
+        assert!(message.starts_with("Unusual CPU spike in build.exe at "));
+        assert!(message.contains("(80.0%, expected"));
+    }
+
+    #[test]
+    fn test_top_processes_limit() {
+        let config = AnomaliesConfig {
+            top_processes: 0,
+            ..AnomaliesConfig::default()
+        };
+        let mut sink = AnomalySink::new(&config);
+        let events = replay(&mut sink, 90, |i| if i == 60 { 80.0 } else { 2.0 });
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_events_sent_to_channel() {
+        let (tx, rx) = channel();
+        let mut sink = AnomalySink::new(&AnomaliesConfig::default()).with_sender(tx);
+        let start = Instant::now();
+        for i in 0..40u64 {
+            let mut system_metrics = SystemMetrics::new();
+            system_metrics.disk_write_bps = if i == 35 { 500 << 20 } else { 1 << 20 };
+            sink.on_snapshot(&ProcessSnapshot {
+                timestamp: start + Duration::from_secs(i),
+                processes: Vec::new(),
+                system_metrics,
+            });
+        }
+
+        let event = rx.try_recv().unwrap();
+        assert_eq!(event.series, AnomalySeries::DiskWrite);
+        assert!(event.message(&NumberFormat::new()).contains("(500 MB/s, expected 1.0 MB/s)"));
+        assert!(rx.try_recv().is_err());
+    }
+}
@@ -0,0 +1,1772 @@
+//! Headless command-line interface
+//!
+//! Any command-line arguments select this mode instead of the window:
+//! - `list` prints one filtered, sorted process listing (table, CSV or JSON)
+//! - `kill` terminates a process, optionally with all its descendants
+//! - `watch` repeats the listing at a fixed interval
+//! - `export` records system metrics for a while and writes them to a file
+//! - `tui` starts the full-screen terminal interface (`ui::tui`)
+//! - `agent` serves this machine to remote clients (`app::agent`)
+//! - `profile` lists, switches, exports and imports configuration profiles
+//! - `commands` searches the command registry (`app::commands`)
+//!
+//! With `--remote ADDR` before the command, `list`, `watch`, `kill`, `export`
+//! and `tui` run against an agent instead of the local machine. `list` and
+//! `watch` start from the filters of the active profile (or `--profile`) and
+//! add the configured computed columns to every row.
+//!
+//! Exit codes are stable so scripts can branch on them (see `EXIT_*`).
+
+use std::collections::HashSet;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::app::agent::{AgentClient, AgentServer};
+use crate::app::commands::CommandRegistry;
+use crate::app::config::{AgentScope, AgentToken, AppConfig, ConfigManager};
+use crate::app::export::{DataExporter, ExportFormat};
+use crate::app::leaks::{apply_leak_rates, LeakMonitor};
+use crate::app::policies::{ProcessActuator, WindowsActuator};
+use crate::app::report::{ReportHistory, ReportOptions};
+use crate::app::services::Services;
+use crate::app::updater::{ProcessSource, SnapshotSink, UpdateMessage, Updater};
+use crate::core::expression::{check_column_name, ComputedColumns, CustomColumn};
+use crate::core::filter::{
+    parse_filter, parse_sort, ProcessFilter, ProcessInfo, ProcessSorter, SortColumn, SortDirection,
+};
+use crate::core::leak::LeakDetectorConfig;
+use crate::ui::dialogs::confirm::DialogPreferences;
+use crate::ui::keymap::install_keymap;
+use crate::util::format::NumberFormat;
+use crate::windows::monitor::{ProcessSnapshot, SystemMonitor};
+use crate::windows::process::control::{termination, ProcessError};
+
+/// Command completed successfully
+pub const EXIT_SUCCESS: i32 = 0;
+/// Runtime failure (data collection, I/O, termination error)
+pub const EXIT_FAILURE: i32 = 1;
+/// Invalid command line
+pub const EXIT_USAGE: i32 = 2;
+/// Target process does not exist
+pub const EXIT_NOT_FOUND: i32 = 3;
+/// Access denied (e.g. killing an elevated process)
+pub const EXIT_ACCESS_DENIED: i32 = 4;
+
+const USAGE: &str = "\
+Usage: task-manager [--remote ADDR [--token TOKEN]] <command> [options]
+
+Commands:
+  list    [--sort cpu|mem|pid|name|handles] [--asc] [--filter EXPR]...
+          [--format table|csv|json] [--top N] [--sample DURATION] [--profile NAME]
+  kill    [--tree] [--force] [--timeout DURATION] PID
+  watch   [list options] [--interval DURATION] [--count N]
+  export  [--duration DURATION] [--interval DURATION]
+          [--format csv|json|html] [--output PATH]
+  tui     [--interval DURATION]
+  agent   [--port N] [--socket PATH] [--token TOKEN]... [--control-token TOKEN]...
+          [--interval DURATION]
+  profile list | use NAME | use --base | export NAME PATH | import PATH
+  commands [QUERY]
+
+Remote: ADDR is host:port or unix:PATH of an agent; the token defaults to
+the TASK_MANAGER_TOKEN environment variable.
+
+Filters: cpu>5, mem>500MB, name=chrome, session=0 (services), session>0,
+or EXPR>VALUE / EXPR>=VALUE / EXPR=VALUE over fields and computed columns
+(threads>50, memory_private/threads>1MB); repeat --filter to combine. They
+are added to the filters of the active profile or --profile NAME
+--sort also accepts the name of a computed column
+Durations: 500ms, 1s, 5m, 1h (plain numbers are seconds)
+
+Exit codes: 0 success, 1 failure, 2 usage, 3 not found, 4 access denied";
+
+/// Error reported to the user with the process exit code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CliError {
+    pub code: i32,
+    pub message: String,
+}
+
+impl CliError {
+    pub fn usage(message: impl Into<String>) -> Self {
+        Self {
+            code: EXIT_USAGE,
+            message: message.into(),
+        }
+    }
+
+    pub fn failure(message: impl Into<String>) -> Self {
+        Self {
+            code: EXIT_FAILURE,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<ProcessError> for CliError {
+    fn from(error: ProcessError) -> Self {
+        let code = match error {
+            ProcessError::NotFound(_) => EXIT_NOT_FOUND,
+            ProcessError::AccessDenied(_) => EXIT_ACCESS_DENIED,
+            _ => EXIT_FAILURE,
+        };
+        Self {
+            code,
+            message: error.short_description(),
+        }
+    }
+}
+
+impl From<io::Error> for CliError {
+    fn from(error: io::Error) -> Self {
+        Self::failure(error.to_string())
+    }
+}
+
+/// Output format for `list` and `watch`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Table,
+    Csv,
+    /// Pretty JSON array for `list`, one JSON object per line for `watch`
+    Json,
+}
+
+/// Options shared by `list` and `watch`
+#[derive(Debug, Clone)]
+pub struct ListOptions {
+    pub sort: SortColumn,
+    pub direction: SortDirection,
+    pub filter: ProcessFilter,
+    pub format: OutputFormat,
+    /// Only print the first N rows
+    pub top: Option<usize>,
+    /// Interval between the two samples used for CPU %
+    pub sample: Duration,
+    /// Profile whose filters apply (the active profile when unset)
+    pub profile: Option<String>,
+    /// Computed column to sort by instead of `sort`, resolved by `with_custom_columns`
+    pub custom_sort: Option<String>,
+    /// Computed columns added to every row
+    pub columns: ComputedColumns,
+    /// Separators and byte units of table output (`AppConfig::display`)
+    pub numbers: NumberFormat,
+    /// Leak detector settings; `watch` fills `leak_rate` when set
+    pub leak_detection: Option<LeakDetectorConfig>,
+}
+
+impl Default for ListOptions {
+    fn default() -> Self {
+        Self {
+            sort: SortColumn::Cpu,
+            direction: SortDirection::Descending,
+            filter: ProcessFilter::new(),
+            format: OutputFormat::Table,
+            top: None,
+            sample: Duration::from_millis(500),
+            profile: None,
+            custom_sort: None,
+            columns: ComputedColumns::default(),
+            numbers: NumberFormat::new(),
+            leak_detection: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KillOptions {
+    pub pid: u32,
+    /// Also terminate all descendants (children first)
+    pub tree: bool,
+    /// Skip the graceful WM_CLOSE attempt
+    pub force: bool,
+    /// How long to wait for a graceful exit
+    pub timeout: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct WatchOptions {
+    pub list: ListOptions,
+    pub interval: Duration,
+    /// Stop after N iterations (runs until interrupted when unset)
+    pub count: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportOptions {
+    pub duration: Duration,
+    pub interval: Duration,
+    pub format: ExportFormat,
+    pub output: PathBuf,
+}
+
+/// Options for `agent`; unset values come from the saved configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgentOptions {
+    pub port: Option<u16>,
+    pub unix_socket: Option<String>,
+    /// Tokens accepted in addition to the configured ones
+    pub tokens: Vec<AgentToken>,
+    pub interval: Duration,
+}
+
+/// `profile` subcommands
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProfileCommand {
+    List,
+    /// Activate a profile; None goes back to the base configuration
+    Use(Option<String>),
+    Export {
+        name: String,
+        path: PathBuf,
+    },
+    Import(PathBuf),
+}
+
+/// Agent targeted by `--remote`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteOptions {
+    pub address: String,
+    pub token: String,
+}
+
+impl RemoteOptions {
+    fn connect(&self) -> Result<AgentClient, CliError> {
+        AgentClient::connect(&self.address, &self.token).map_err(|e| {
+            CliError::failure(format!(
+                "Cannot connect to agent at {}: {}",
+                self.address, e
+            ))
+        })
+    }
+}
+
+/// Parsed command line
+#[derive(Debug, Clone)]
+pub enum Command {
+    List(ListOptions),
+    Kill(KillOptions),
+    Watch(WatchOptions),
+    Export(ExportOptions),
+    /// Terminal UI refreshing at the given interval
+    Tui(Duration),
+    Agent(AgentOptions),
+    Profile(ProfileCommand),
+    /// Registered commands matching the query, best first
+    Commands(String),
+    Help,
+}
+
+/// Run the CLI and return the process exit code
+pub fn run(args: &[String]) -> i32 {
+    let parsed = split_remote(args).and_then(|(remote, args)| {
+        let command = parse_args(args)?;
+        match command {
+            Some(Command::Agent(_)) if remote.is_some() => {
+                return Err(CliError::usage("--remote cannot be used with agent"))
+            }
+            Some(Command::Profile(_)) if remote.is_some() => {
+                return Err(CliError::usage("--remote cannot be used with profile"))
+            }
+            Some(Command::Commands(_)) if remote.is_some() => {
+                return Err(CliError::usage("--remote cannot be used with commands"))
+            }
+            _ => {}
+        }
+        Ok((remote, command))
+    });
+    let (remote, command) = match parsed {
+        Ok((remote, Some(command))) => (remote, command),
+        Ok((_, None)) => return EXIT_SUCCESS,
+        Err(error) => {
+            eprintln!("task-manager: {}", error.message);
+            if error.code == EXIT_USAGE {
+                eprintln!("Run 'task-manager help' for usage.");
+            }
+            return error.code;
+        }
+    };
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let result = match command {
+        Command::List(options) => apply_profile(options).and_then(|options| {
+            let mut target = Target::open(remote.as_ref())?;
+            run_list(&options, &mut target, &mut out)
+        }),
+        Command::Kill(options) => Target::open(remote.as_ref())
+            .and_then(|mut target| run_kill(&options, &mut target, &mut out)),
+        Command::Watch(mut options) => apply_profile(options.list.clone()).and_then(|list| {
+            options.list = list;
+            let mut target = Target::open(remote.as_ref())?;
+            run_watch(&options, &mut target, &mut out)
+        }),
+        Command::Export(options) => Target::open(remote.as_ref())
+            .and_then(|mut target| run_export(&options, &mut target, &mut out)),
+        Command::Tui(interval) => run_tui(interval, remote.as_ref()),
+        Command::Agent(options) => run_agent(&options, &mut out),
+        Command::Profile(command) => run_profile(&command, &mut out),
+        Command::Commands(query) => run_commands(&query, &mut out),
+        Command::Help => writeln!(out, "{}", USAGE).map_err(CliError::from),
+    };
+
+    match result {
+        Ok(()) => EXIT_SUCCESS,
+        Err(error) => {
+            eprintln!("task-manager: {}", error.message);
+            error.code
+        }
+    }
+}
+
+/// Split leading `--remote ADDR` / `--token TOKEN` options from the command
+pub fn split_remote(args: &[String]) -> Result<(Option<RemoteOptions>, &[String]), CliError> {
+    let mut address = None;
+    let mut token = None;
+    let mut rest = args;
+
+    while let Some(first) = rest.first().filter(|arg| arg.starts_with("--")) {
+        let (flag, inline) = match first.split_once('=') {
+            Some((flag, value)) => (flag, Some(value.to_string())),
+            None => (first.as_str(), None),
+        };
+        if flag != "--remote" && flag != "--token" {
+            break;
+        }
+        let value = match inline {
+            Some(value) => {
+                rest = &rest[1..];
+                value
+            }
+            None => {
+                let value = rest
+                    .get(1)
+                    .cloned()
+                    .ok_or_else(|| CliError::usage(format!("{} requires a value", flag)))?;
+                rest = &rest[2..];
+                value
+            }
+        };
+        if flag == "--remote" {
+            address = Some(value);
+        } else {
+            token = Some(value);
+        }
+    }
+
+    let Some(address) = address else {
+        if token.is_some() {
+            return Err(CliError::usage("--token requires --remote"));
+        }
+        return Ok((None, rest));
+    };
+    let token = token
+        .or_else(|| std::env::var("TASK_MANAGER_TOKEN").ok())
+        .ok_or_else(|| CliError::usage("--remote requires --token or TASK_MANAGER_TOKEN"))?;
+    Ok((Some(RemoteOptions { address, token }), rest))
+}
+
+/// Parse arguments (without the program name); `None` if there are none
+pub fn parse_args(args: &[String]) -> Result<Option<Command>, CliError> {
+    let Some((command, rest)) = args.split_first() else {
+        return Ok(None);
+    };
+    let mut args = ArgIter::new(rest);
+
+    let command = match command.as_str() {
+        "list" => {
+            let mut list = ListOptions::default();
+            while let Some(flag) = args.next_flag()? {
+                if !parse_list_flag(&flag, &mut args, &mut list)? {
+                    return Err(unknown_flag(&flag));
+                }
+            }
+            Command::List(list)
+        }
+        "kill" => {
+            let mut pid = None;
+            let mut options = KillOptions {
+                pid: 0,
+                tree: false,
+                force: false,
+                timeout: Duration::from_secs(5),
+            };
+            while let Some(arg) = args.next_arg() {
+                match arg {
+                    Arg::Flag(flag) => match flag.as_str() {
+                        "--tree" => options.tree = true,
+                        "--force" => options.force = true,
+                        "--timeout" => options.timeout = parse_duration(&args.value(&flag)?)?,
+                        _ => return Err(unknown_flag(&flag)),
+                    },
+                    Arg::Positional(value) if pid.is_none() => {
+                        let parsed = value
+                            .parse::<u32>()
+                            .map_err(|_| CliError::usage(format!("Invalid PID '{}'", value)))?;
+                        pid = Some(parsed);
+                    }
+                    Arg::Positional(value) => return Err(unexpected(&value)),
+                }
+            }
+            options.pid = pid.ok_or_else(|| CliError::usage("kill requires a PID"))?;
+            Command::Kill(options)
+        }
+        "watch" => {
+            let mut watch = WatchOptions {
+                list: ListOptions::default(),
+                interval: Duration::from_secs(1),
+                count: None,
+            };
+            while let Some(flag) = args.next_flag()? {
+                if parse_list_flag(&flag, &mut args, &mut watch.list)? {
+                    continue;
+                }
+                match flag.as_str() {
+                    "--interval" => watch.interval = parse_duration(&args.value(&flag)?)?,
+                    "--count" => watch.count = Some(parse_count(&flag, &args.value(&flag)?)?),
+                    _ => return Err(unknown_flag(&flag)),
+                }
+            }
+            if watch.interval.is_zero() {
+                return Err(CliError::usage("--interval must be greater than zero"));
+            }
+            Command::Watch(watch)
+        }
+        "export" => {
+            let mut format = ExportFormat::Csv;
+            let mut output = None;
+            let mut duration = Duration::from_secs(60);
+            let mut interval = Duration::from_secs(1);
+            while let Some(flag) = args.next_flag()? {
+                let value = args.value(&flag)?;
+                match flag.as_str() {
+                    "--duration" => duration = parse_duration(&value)?,
+                    "--interval" => interval = parse_duration(&value)?,
+                    "--output" | "-o" => output = Some(PathBuf::from(value)),
+                    "--format" => {
+                        format = match value.as_str() {
+                            "csv" => ExportFormat::Csv,
+                            "json" => ExportFormat::Json,
+                            "html" => ExportFormat::HtmlReport,
+                            _ => return Err(invalid_value(&flag, &value)),
+                        }
+                    }
+                    _ => return Err(unknown_flag(&flag)),
+                }
+            }
+            if interval.is_zero() {
+                return Err(CliError::usage("--interval must be greater than zero"));
+            }
+            let output = output.unwrap_or_else(|| {
+                PathBuf::from(format!("task-manager-export.{}", format.extension()))
+            });
+            Command::Export(ExportOptions {
+                duration,
+                interval,
+                format,
+                output,
+            })
+        }
+        "tui" => {
+            let mut interval = Duration::from_secs(1);
+            while let Some(flag) = args.next_flag()? {
+                match flag.as_str() {
+                    "--interval" => interval = parse_duration(&args.value(&flag)?)?,
+                    _ => return Err(unknown_flag(&flag)),
+                }
+            }
+            if interval.is_zero() {
+                return Err(CliError::usage("--interval must be greater than zero"));
+            }
+            Command::Tui(interval)
+        }
+        "agent" => {
+            let mut options = AgentOptions {
+                port: None,
+                unix_socket: None,
+                tokens: Vec::new(),
+                interval: Duration::from_secs(1),
+            };
+            while let Some(flag) = args.next_flag()? {
+                let value = args.value(&flag)?;
+                match flag.as_str() {
+                    "--port" => {
+                        let port = value.parse().map_err(|_| invalid_value(&flag, &value))?;
+                        options.port = Some(port);
+                    }
+                    "--socket" => options.unix_socket = Some(value),
+                    "--token" | "--control-token" => {
+                        let scope = if flag == "--token" {
+                            AgentScope::ReadOnly
+                        } else {
+                            AgentScope::Control
+                        };
+                        options.tokens.push(AgentToken {
+                            token: value,
+                            scope,
+                        });
+                    }
+                    "--interval" => options.interval = parse_duration(&value)?,
+                    _ => return Err(unknown_flag(&flag)),
+                }
+            }
+            if options.interval.is_zero() {
+                return Err(CliError::usage("--interval must be greater than zero"));
+            }
+            Command::Agent(options)
+        }
+        "profile" => {
+            let mut positional = Vec::new();
+            let mut base = false;
+            while let Some(arg) = args.next_arg() {
+                match arg {
+                    Arg::Flag(flag) if flag == "--base" => base = true,
+                    Arg::Flag(flag) => return Err(unknown_flag(&flag)),
+                    Arg::Positional(value) => positional.push(value),
+                }
+            }
+            let command = match (positional.as_slice(), base) {
+                ([action], false) if action == "list" => ProfileCommand::List,
+                ([action], true) if action == "use" => ProfileCommand::Use(None),
+                ([action, name], false) if action == "use" => {
+                    ProfileCommand::Use(Some(name.clone()))
+                }
+                ([action, name, path], false) if action == "export" => ProfileCommand::Export {
+                    name: name.clone(),
+                    path: PathBuf::from(path),
+                },
+                ([action, path], false) if action == "import" => {
+                    ProfileCommand::Import(PathBuf::from(path))
+                }
+                _ => {
+                    return Err(CliError::usage(
+                        "profile requires list, use NAME, use --base, export NAME PATH \
+                         or import PATH",
+                    ))
+                }
+            };
+            Command::Profile(command)
+        }
+        "commands" => {
+            let mut words = Vec::new();
+            while let Some(arg) = args.next_arg() {
+                match arg {
+                    Arg::Flag(flag) => return Err(unknown_flag(&flag)),
+                    Arg::Positional(word) => words.push(word),
+                }
+            }
+            Command::Commands(words.join(" "))
+        }
+        "help" | "--help" | "-h" => Command::Help,
+        other => return Err(CliError::usage(format!("Unknown command '{}'", other))),
+    };
+
+    Ok(Some(command))
+}
+
+/// Apply a `list` flag; returns false if `flag` is not a list option
+fn parse_list_flag(
+    flag: &str,
+    args: &mut ArgIter<'_>,
+    list: &mut ListOptions,
+) -> Result<bool, CliError> {
+    match flag {
+        "--asc" => list.direction = SortDirection::Ascending,
+        "--desc" => list.direction = SortDirection::Descending,
+        "--sort" => {
+            let value = args.value(flag)?;
+            match parse_sort(&value) {
+                Some(column) => list.sort = column,
+                // Computed columns are only known once the configuration is loaded
+                None if check_column_name(&value).is_ok() => list.custom_sort = Some(value),
+                None => return Err(invalid_value(flag, &value)),
+            }
+        }
+        "--filter" => {
+            let value = args.value(flag)?;
+            list.filter = parse_filter(&value, list.filter.clone()).map_err(CliError::usage)?;
+        }
+        "--format" => {
+            let value = args.value(flag)?;
+            list.format = match value.as_str() {
+                "table" => OutputFormat::Table,
+                "csv" => OutputFormat::Csv,
+                "json" => OutputFormat::Json,
+                _ => return Err(invalid_value(flag, &value)),
+            };
+        }
+        "--top" => list.top = Some(parse_count(flag, &args.value(flag)?)?),
+        "--sample" => list.sample = parse_duration(&args.value(flag)?)?,
+        "--profile" => list.profile = Some(args.value(flag)?),
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+/// Filter of profile `name` (or the active profile), without command-line criteria
+pub fn profile_filter(config: &AppConfig, name: Option<&str>) -> Result<ProcessFilter, CliError> {
+    let effective = match name {
+        Some(name) => config.with_profile(name).map_err(CliError::usage)?,
+        None => config.effective(),
+    };
+    let mut filter = ProcessFilter::new();
+    for expr in &effective.filters {
+        filter = parse_filter(expr, filter)
+            .map_err(|e| CliError::failure(format!("Configured {}", e)))?;
+    }
+    Ok(filter)
+}
+
+/// `base` with every criterion set in `overrides` replaced
+pub fn merge_filters(base: ProcessFilter, overrides: &ProcessFilter) -> ProcessFilter {
+    ProcessFilter {
+        name: overrides.name.clone().or(base.name),
+        cpu_threshold: overrides.cpu_threshold.or(base.cpu_threshold),
+        memory_threshold: overrides.memory_threshold.or(base.memory_threshold),
+        owned_only: base.owned_only || overrides.owned_only,
+        use_regex: base.use_regex || overrides.use_regex,
+        session: overrides.session.or(base.session),
+        conditions: base
+            .conditions
+            .into_iter()
+            .chain(overrides.conditions.iter().cloned())
+            .collect(),
+    }
+}
+
+/// Put the profile's filters under the command-line ones and use the
+/// configured computed columns and number format
+fn apply_profile(mut list: ListOptions) -> Result<ListOptions, CliError> {
+    let manager = ConfigManager::new();
+    // No saved configuration just means defaults
+    let _ = manager.load();
+    let config = manager.get();
+    let base = profile_filter(&config, list.profile.as_deref())?;
+    list.filter = merge_filters(base, &list.filter);
+    list.numbers = config.display.number_format();
+    list.leak_detection = Some(config.leak_detection.clone()).filter(|c| c.enabled);
+    with_custom_columns(list, &config.custom_columns)
+}
+
+/// Add `columns` to `list` and resolve the computed columns its sort and filter name
+pub fn with_custom_columns(
+    mut list: ListOptions,
+    columns: &[CustomColumn],
+) -> Result<ListOptions, CliError> {
+    list.columns = ComputedColumns::new(columns)
+        .map_err(|e| CliError::failure(format!("Configured {}", e)))?;
+    if let Some(name) = list.custom_sort.take() {
+        let index = list
+            .columns
+            .position(&name)
+            .ok_or_else(|| invalid_value("--sort", &name))?;
+        list.sort = SortColumn::Custom(index);
+    }
+    for condition in &list.filter.conditions {
+        let names = condition.expression.columns();
+        if let Some(name) = names
+            .iter()
+            .find(|name| list.columns.position(name).is_none())
+        {
+            return Err(CliError::usage(format!(
+                "Unknown column '{}' in filter '{}'",
+                name,
+                condition.expression.source()
+            )));
+        }
+    }
+    Ok(list)
+}
+
+/// Parse a duration such as `500ms`, `1s`, `5m` or `1h` (plain numbers are seconds)
+pub fn parse_duration(text: &str) -> Result<Duration, CliError> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let invalid = || CliError::usage(format!("Invalid duration '{}'", text));
+
+    let number: f64 = number.parse().map_err(|_| invalid())?;
+    let seconds = match unit.trim() {
+        "ms" => number / 1000.0,
+        "" | "s" => number,
+        "m" => number * 60.0,
+        "h" => number * 3600.0,
+        _ => return Err(invalid()),
+    };
+    // Rejects negative, non-finite and overflowing values
+    Duration::try_from_secs_f64(seconds).map_err(|_| invalid())
+}
+
+fn parse_count(flag: &str, value: &str) -> Result<usize, CliError> {
+    value.parse().map_err(|_| invalid_value(flag, value))
+}
+
+fn unknown_flag(flag: &str) -> CliError {
+    CliError::usage(format!("Unknown option '{}'", flag))
+}
+
+fn unexpected(arg: &str) -> CliError {
+    CliError::usage(format!("Unexpected argument '{}'", arg))
+}
+
+fn invalid_value(flag: &str, value: &str) -> CliError {
+    CliError::usage(format!("Invalid value '{}' for {}", value, flag))
+}
+
+/// Command-line argument as seen by `ArgIter`
+enum Arg {
+    /// Flag name, without any `=value` suffix
+    Flag(String),
+    Positional(String),
+}
+
+/// Argument cursor supporting `--flag value` and `--flag=value`
+struct ArgIter<'a> {
+    args: std::slice::Iter<'a, String>,
+    /// Value split off the last `--flag=value` argument
+    pending: Option<String>,
+}
+
+impl<'a> ArgIter<'a> {
+    fn new(args: &'a [String]) -> Self {
+        Self {
+            args: args.iter(),
+            pending: None,
+        }
+    }
+
+    fn next_arg(&mut self) -> Option<Arg> {
+        self.pending = None;
+        let arg = self.args.next()?;
+        if !arg.starts_with('-') || arg.len() == 1 {
+            return Some(Arg::Positional(arg.clone()));
+        }
+        match arg.split_once('=') {
+            Some((name, value)) => {
+                self.pending = Some(value.to_string());
+                Some(Arg::Flag(name.to_string()))
+            }
+            None => Some(Arg::Flag(arg.clone())),
+        }
+    }
+
+    /// Next flag name; errors on positional arguments
+    fn next_flag(&mut self) -> Result<Option<String>, CliError> {
+        match self.next_arg() {
+            Some(Arg::Flag(flag)) => Ok(Some(flag)),
+            Some(Arg::Positional(value)) => Err(unexpected(&value)),
+            None => Ok(None),
+        }
+    }
+
+    /// Value for `flag`, from `--flag=value` or the next argument
+    fn value(&mut self, flag: &str) -> Result<String, CliError> {
+        if let Some(value) = self.pending.take() {
+            return Ok(value);
+        }
+        self.args
+            .next()
+            .cloned()
+            .ok_or_else(|| CliError::usage(format!("{} requires a value", flag)))
+    }
+}
+
+/// Machine a command runs against
+enum Target {
+    Local(SystemMonitor),
+    Remote(AgentClient),
+}
+
+impl Target {
+    fn open(remote: Option<&RemoteOptions>) -> Result<Self, CliError> {
+        match remote {
+            Some(remote) => Ok(Target::Remote(remote.connect()?)),
+            None => Ok(Target::Local(SystemMonitor::new())),
+        }
+    }
+
+    fn collect(&mut self) -> Result<ProcessSnapshot, CliError> {
+        let result = match self {
+            Target::Local(monitor) => monitor.collect_all(),
+            Target::Remote(client) => client.collect(),
+        };
+        result.map_err(|e| CliError::failure(format!("Failed to collect process data: {}", e)))
+    }
+
+    /// Terminate `pid`; an agent applies its own termination strategy
+    fn terminate(&mut self, pid: u32, options: &KillOptions) -> Result<(), ProcessError> {
+        match self {
+            Target::Local(_) if options.force => termination::terminate_force(pid),
+            Target::Local(_) => termination::terminate_with_timeout(pid, options.timeout),
+            Target::Remote(client) => client.terminate(pid),
+        }
+    }
+}
+
+/// Process rows with CPU % of the whole machine and I/O rates between two
+/// snapshots
+pub fn process_rows(
+    previous: &ProcessSnapshot,
+    current: &ProcessSnapshot,
+    cpus: usize,
+) -> Vec<ProcessInfo> {
+    let elapsed = current
+        .timestamp
+        .saturating_duration_since(previous.timestamp)
+        .as_secs_f64();
+
+    current
+        .processes
+        .iter()
+        .map(|process| {
+            let prev = previous
+                .processes
+                .iter()
+                .find(|p| p.pid == process.pid && p.name == process.name)
+                .filter(|_| elapsed > 0.0);
+            let cpu_usage = prev
+                .map(|prev| {
+                    let before = prev.cpu_time_user + prev.cpu_time_kernel;
+                    let after = process.cpu_time_user + process.cpu_time_kernel;
+                    let delta = after.saturating_sub(before) as f64;
+                    (delta / (elapsed * 10_000_000.0 * cpus.max(1) as f64) * 100.0).min(100.0)
+                })
+                .unwrap_or(0.0);
+            let rate = |before: u64, after: u64| after.saturating_sub(before) as f64 / elapsed;
+            let (io_read_bps, io_write_bps) = prev
+                .map(|prev| {
+                    (
+                        rate(prev.io_read_bytes, process.io_read_bytes),
+                        rate(prev.io_write_bytes, process.io_write_bytes),
+                    )
+                })
+                .unwrap_or((0.0, 0.0));
+
+            ProcessInfo {
+                pid: process.pid,
+                parent_pid: process.parent_pid,
+                name: process.name.clone(),
+                cpu_usage,
+                memory_private: process.memory_private,
+                memory_working_set: process.memory_working_set,
+                io_read_bytes: process.io_read_bytes,
+                io_write_bytes: process.io_write_bytes,
+                io_read_bps,
+                io_write_bps,
+                thread_count: process.thread_count,
+                handle_count: process.handle_count,
+                leak_rate: None,
+                session_id: process.session_id,
+                custom: Vec::new(),
+            }
+        })
+        .collect()
+}
+
+/// Filter, sort and truncate rows according to `options`
+fn select_rows<'a>(rows: &'a [ProcessInfo], options: &ListOptions) -> Vec<&'a ProcessInfo> {
+    let mut selected = options.filter.apply(rows);
+    ProcessSorter::new(options.sort, options.direction).sort_refs(&mut selected);
+    if let Some(top) = options.top {
+        selected.truncate(top);
+    }
+    selected
+}
+
+fn run_list(
+    options: &ListOptions,
+    target: &mut Target,
+    out: &mut dyn Write,
+) -> Result<(), CliError> {
+    let previous = target.collect()?;
+    thread::sleep(options.sample);
+    let current = target.collect()?;
+
+    let mut rows = process_rows(&previous, &current, num_cpus::get());
+    options.columns.apply(&mut rows);
+    write_rows(
+        out,
+        &select_rows(&rows, options),
+        options.format,
+        &options.numbers,
+    )?;
+    Ok(())
+}
+
+fn run_watch(
+    options: &WatchOptions,
+    target: &mut Target,
+    out: &mut dyn Write,
+) -> Result<(), CliError> {
+    let mut previous = target.collect()?;
+    let mut iteration = 0;
+    // Trends need many samples, so only `watch` runs the leak detector
+    let mut leaks = options.list.leak_detection.clone().map(LeakMonitor::new);
+    if let Some(monitor) = &mut leaks {
+        monitor.on_snapshot(&previous);
+    }
+
+    while options.count != Some(iteration) {
+        thread::sleep(options.interval);
+        let current = target.collect()?;
+        let mut rows = process_rows(&previous, &current, num_cpus::get());
+        if let Some(monitor) = &mut leaks {
+            monitor.on_snapshot(&current);
+            apply_leak_rates(&mut rows, &monitor.detector());
+        }
+        options.list.columns.apply(&mut rows);
+        let selected = select_rows(&rows, &options.list);
+
+        match options.list.format {
+            OutputFormat::Json => {
+                let line = serde_json::json!({
+                    "timestamp": unix_secs(),
+                    "processes": selected.iter().map(|p| row_json(p)).collect::<Vec<_>>(),
+                });
+                writeln!(out, "{}", line)?;
+            }
+            format => {
+                if iteration > 0 {
+                    writeln!(out)?;
+                }
+                writeln!(
+                    out,
+                    "# {}",
+                    chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+                )?;
+                write_rows(out, &selected, format, &options.list.numbers)?;
+            }
+        }
+        out.flush()?;
+
+        previous = current;
+        iteration += 1;
+    }
+    Ok(())
+}
+
+fn run_kill(
+    options: &KillOptions,
+    target: &mut Target,
+    out: &mut dyn Write,
+) -> Result<(), CliError> {
+    let snapshot = target.collect()?;
+    let Some(process) = snapshot.processes.iter().find(|p| p.pid == options.pid) else {
+        return Err(ProcessError::NotFound(options.pid).into());
+    };
+
+    let mut victims = Vec::new();
+    if options.tree {
+        victims = descendants(&snapshot, options.pid);
+    }
+    victims.push(options.pid);
+
+    let mut first_error = None;
+    for pid in victims {
+        match target.terminate(pid, options) {
+            Ok(()) => writeln!(out, "Terminated {}", pid)?,
+            // A child may exit on its own once its parent is gone
+            Err(ProcessError::NotFound(_)) if pid != options.pid => {}
+            Err(error) => {
+                eprintln!(
+                    "task-manager: {} (pid {}): {}",
+                    process.name,
+                    pid,
+                    error.short_description()
+                );
+                first_error.get_or_insert(error);
+            }
+        }
+    }
+
+    match first_error {
+        Some(error) => Err(error.into()),
+        None => Ok(()),
+    }
+}
+
+/// All descendants of `pid`, deepest first so children die before parents
+///
+/// Windows reuses PIDs, so parent links can form cycles and a reused parent
+/// PID can adopt unrelated processes. Each PID is visited at most once, and a
+/// child only counts if it was created after its parent (when both creation
+/// times are known).
+pub fn descendants(snapshot: &ProcessSnapshot, pid: u32) -> Vec<u32> {
+    fn create_time(snapshot: &ProcessSnapshot, pid: u32) -> u64 {
+        snapshot
+            .processes
+            .iter()
+            .find(|p| p.pid == pid)
+            .map_or(0, |p| p.create_time)
+    }
+
+    fn visit(
+        snapshot: &ProcessSnapshot,
+        pid: u32,
+        created: u64,
+        seen: &mut HashSet<u32>,
+        out: &mut Vec<u32>,
+    ) {
+        for child in snapshot.processes.iter().filter(|p| {
+            p.parent_pid == pid
+                && p.pid != pid
+                && (created == 0 || p.create_time == 0 || p.create_time > created)
+        }) {
+            if seen.insert(child.pid) {
+                visit(snapshot, child.pid, child.create_time, seen, out);
+                out.push(child.pid);
+            }
+        }
+    }
+
+    let mut seen = HashSet::from([pid]);
+    let mut out = Vec::new();
+    visit(
+        snapshot,
+        pid,
+        create_time(snapshot, pid),
+        &mut seen,
+        &mut out,
+    );
+    out
+}
+
+fn run_export(
+    options: &ExportOptions,
+    target: &mut Target,
+    out: &mut dyn Write,
+) -> Result<(), CliError> {
+    let samples = (options.duration.as_secs_f64() / options.interval.as_secs_f64()).ceil() as usize;
+    let mut history = ReportHistory::new(samples.max(1));
+    let mut exporter = DataExporter::new(options.format);
+
+    let start = Instant::now();
+    loop {
+        let snapshot = target.collect()?;
+        history.record(&snapshot);
+        exporter.add_system_metrics(unix_secs(), &snapshot.system_metrics);
+        if start.elapsed() + options.interval > options.duration {
+            break;
+        }
+        thread::sleep(options.interval);
+    }
+
+    if options.format == ExportFormat::HtmlReport {
+        let manager = ConfigManager::new();
+        // No saved configuration just means defaults
+        let _ = manager.load();
+        let numbers = manager.get().display.number_format();
+        let options = ReportOptions::new().with_number_format(numbers);
+        exporter.set_report_history(history, options);
+    }
+    exporter.export_to_file(&options.output)?;
+    writeln!(out, "Wrote {}", options.output.display())?;
+    Ok(())
+}
+
+fn run_tui(interval: Duration, remote: Option<&RemoteOptions>) -> Result<(), CliError> {
+    // Separate connections so commands never wait behind a snapshot request
+    let (source, actuator): (Box<dyn ProcessSource>, Box<dyn ProcessActuator>) = match remote {
+        Some(remote) => (Box::new(remote.connect()?), Box::new(remote.connect()?)),
+        None => (Box::new(SystemMonitor::new()), Box::new(WindowsActuator)),
+    };
+
+    // Key bindings, presets and columns come from the local configuration,
+    // also for remote targets
+    let manager = ConfigManager::new();
+    let _ = manager.load();
+    for error in install_keymap(&manager.get().keymap) {
+        eprintln!("task-manager: keymap.{}: {}", error.command, error.message);
+    }
+    let services = start_services(&manager, remote.is_none());
+    crate::ui::tui::run(
+        interval.as_millis() as u64,
+        source,
+        actuator,
+        services,
+        Arc::new(manager),
+    )
+    .map_err(CliError::from)
+}
+
+/// Serve this machine until the process is interrupted
+fn run_agent(options: &AgentOptions, out: &mut dyn Write) -> Result<(), CliError> {
+    let manager = ConfigManager::new();
+    // No saved configuration just means defaults
+    let _ = manager.load();
+    let mut config = manager.get().agent;
+    if let Some(port) = options.port {
+        config.port = port;
+    }
+    if let Some(path) = &options.unix_socket {
+        config.unix_socket = Some(path.clone());
+    }
+    config.tokens.extend(options.tokens.iter().cloned());
+    if config.tokens.is_empty() {
+        return Err(CliError::usage(
+            "agent requires --token, --control-token or configured tokens",
+        ));
+    }
+
+    let server = AgentServer::start(&config, Box::new(WindowsActuator))?;
+    if let Some(addr) = server.local_addr() {
+        writeln!(out, "Agent listening on {}", addr)?;
+    }
+    if let Some(path) = &config.unix_socket {
+        writeln!(out, "Agent listening on unix:{}", path)?;
+    }
+    out.flush()?;
+
+    let mut services = start_services(&manager, true);
+    let mut sinks = services.take_sinks();
+    sinks.push(Box::new(server.sink()));
+
+    let refresh_rate_ms = options.interval.as_millis() as u64;
+    let source = Box::new(SystemMonitor::new());
+    let collectors = services.take_collectors();
+    let (_updater, updates) =
+        Updater::start_with_collectors(refresh_rate_ms, source, collectors, sinks);
+    for message in updates {
+        match message {
+            UpdateMessage::Error(error) => eprintln!("task-manager: {}", error),
+            UpdateMessage::Shutdown => break,
+            UpdateMessage::Snapshot(_) => {}
+        }
+    }
+    Ok(())
+}
+
+/// Start the services enabled in the effective configuration, reporting
+/// the ones that fail on stderr
+///
+/// Process policies, the JSON-RPC endpoint and custom collectors only run
+/// when the snapshots come from this machine (`local`); an agent runs its
+/// own.
+fn start_services(manager: &ConfigManager, local: bool) -> Services {
+    let config = manager.effective();
+    let mut services = Services::start(&config);
+    if local {
+        services = services
+            .with_policies(&config.policies)
+            .with_rpc(&config.rpc, Arc::new(DialogPreferences::new()))
+            .with_collectors(&config.collectors);
+    }
+    for error in services.errors() {
+        eprintln!("task-manager: {}", error);
+    }
+    services
+}
+
+fn run_profile(command: &ProfileCommand, out: &mut dyn Write) -> Result<(), CliError> {
+    let manager = ConfigManager::new();
+    manager
+        .load()
+        .map_err(|e| CliError::failure(format!("Cannot load configuration: {}", e)))?;
+    let save = |manager: &ConfigManager| {
+        manager
+            .save()
+            .map_err(|e| CliError::failure(format!("Cannot save {}: {}", manager.location(), e)))
+    };
+
+    match command {
+        ProfileCommand::List => {
+            let config = manager.get();
+            let active = config.profiles.active.as_deref();
+            let marker = |is_active: bool| if is_active { '*' } else { ' ' };
+            writeln!(out, "{} (base)", marker(active.is_none()))?;
+            for profile in &config.profiles.profiles {
+                let is_active = active.is_some_and(|name| name.eq_ignore_ascii_case(&profile.name));
+                match &profile.inherits {
+                    Some(parent) => writeln!(
+                        out,
+                        "{} {} (inherits {})",
+                        marker(is_active),
+                        profile.name,
+                        parent
+                    )?,
+                    None => writeln!(out, "{} {}", marker(is_active), profile.name)?,
+                }
+            }
+        }
+        ProfileCommand::Use(name) => {
+            manager
+                .switch_profile(name.as_deref())
+                .map_err(CliError::usage)?;
+            save(&manager)?;
+            match manager.get().profiles.active {
+                Some(name) => writeln!(out, "Active profile: {}", name)?,
+                None => writeln!(out, "Using the base configuration")?,
+            }
+        }
+        ProfileCommand::Export { name, path } => {
+            manager
+                .export_profile(name, path)
+                .map_err(|e| CliError::failure(e.to_string()))?;
+            writeln!(out, "Exported profile '{}' to {}", name, path.display())?;
+        }
+        ProfileCommand::Import(path) => {
+            let name = manager
+                .import_profile(path)
+                .map_err(|e| CliError::failure(e.to_string()))?;
+            save(&manager)?;
+            writeln!(out, "Imported profile '{}'", name)?;
+        }
+    }
+    Ok(())
+}
+
+fn run_commands(query: &str, out: &mut dyn Write) -> Result<(), CliError> {
+    // Show the user's own key bindings
+    let manager = ConfigManager::new();
+    let _ = manager.load();
+    install_keymap(&manager.get().keymap);
+    write_commands(&CommandRegistry::defaults(), query, out)
+}
+
+/// One line per matching command: id, title and shortcut
+fn write_commands(
+    registry: &CommandRegistry,
+    query: &str,
+    out: &mut dyn Write,
+) -> Result<(), CliError> {
+    let matches = registry.search(query);
+    if matches.is_empty() {
+        return Err(CliError {
+            code: EXIT_NOT_FOUND,
+            message: format!("No command matches '{}'", query),
+        });
+    }
+    for command in matches {
+        let keys = command.shortcut.map(|s| s.to_string()).unwrap_or_default();
+        let line = format!("{:<28} {:<32} {}", command.id, command.title, keys);
+        writeln!(out, "{}", line.trim_end())?;
+    }
+    Ok(())
+}
+
+fn unix_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// JSON object for one row, as printed by `list --format json`
+///
+/// Computed column values go under `columns`, keyed by column name.
+pub fn row_json(process: &ProcessInfo) -> serde_json::Value {
+    let mut row = serde_json::json!({
+        "pid": process.pid,
+        "parent_pid": process.parent_pid,
+        "name": process.name,
+        "cpu_percent": (process.cpu_usage * 10.0).round() / 10.0,
+        "private_bytes": process.memory_private,
+        "working_set_bytes": process.memory_working_set,
+        "handles": process.handle_count,
+    });
+    if let Some(rate) = process.leak_rate {
+        row["leak_bytes_per_hour"] = serde_json::json!(rate.round());
+    }
+    if !process.custom.is_empty() {
+        let columns: serde_json::Map<_, _> = process
+            .custom
+            .iter()
+            .map(|column| (column.name.clone(), serde_json::json!(column.value)))
+            .collect();
+        row["columns"] = serde_json::Value::Object(columns);
+    }
+    row
+}
+
+/// Write rows in the requested format
+///
+/// `numbers` formats the table; CSV and JSON keep raw values.
+pub fn write_rows(
+    out: &mut dyn Write,
+    rows: &[&ProcessInfo],
+    format: OutputFormat,
+    numbers: &NumberFormat,
+) -> io::Result<()> {
+    // Every row carries the same computed columns
+    let custom = rows.first().map_or(&[][..], |row| &row.custom[..]);
+    match format {
+        OutputFormat::Table => {
+            write!(
+                out,
+                "{:>7}  {:<32} {:>6} {:>10} {:>8}",
+                "PID", "NAME", "CPU%", "MEMORY", "HANDLES"
+            )?;
+            for column in custom {
+                write!(out, " {:>10}", column.name.to_uppercase())?;
+            }
+            writeln!(out)?;
+            for process in rows {
+                write!(
+                    out,
+                    "{:>7}  {:<32} {:>6} {:>10} {:>8}",
+                    process.pid,
+                    truncate(&process.name, 32),
+                    numbers.decimal(process.cpu_usage, 1),
+                    numbers.bytes(process.memory_private),
+                    numbers.integer(process.handle_count as i64)
+                )?;
+                for (column, header) in process.custom.iter().zip(custom) {
+                    let width = header.name.chars().count().max(10);
+                    write!(out, " {:>width$}", column.display(numbers), width = width)?;
+                }
+                writeln!(out)?;
+            }
+        }
+        OutputFormat::Csv => {
+            write!(
+                out,
+                "pid,parent_pid,name,cpu_percent,private_bytes,working_set_bytes,handles"
+            )?;
+            for column in custom {
+                write!(out, ",{}", escape_csv(&column.name))?;
+            }
+            writeln!(out)?;
+            for process in rows {
+                write!(
+                    out,
+                    "{},{},{},{:.1},{},{},{}",
+                    process.pid,
+                    process.parent_pid,
+                    escape_csv(&process.name),
+                    process.cpu_usage,
+                    process.memory_private,
+                    process.memory_working_set,
+                    process.handle_count
+                )?;
+                for column in &process.custom {
+                    match column.value {
+                        Some(value) => write!(out, ",{}", value)?,
+                        None => write!(out, ",")?,
+                    }
+                }
+                writeln!(out)?;
+            }
+        }
+        OutputFormat::Json => {
+            let rows: Vec<_> = rows.iter().map(|p| row_json(p)).collect();
+            let json = serde_json::to_string_pretty(&rows).map_err(io::Error::other)?;
+            writeln!(out, "{}", json)?;
+        }
+    }
+    Ok(())
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        text.to_string()
+    } else {
+        let mut truncated: String = text.chars().take(width - 1).collect();
+        truncated.push('…');
+        truncated
+    }
+}
+
+/// Quote a CSV field if it contains a delimiter, quote or newline
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::config::Profile;
+    use crate::core::expression::ColumnFormat;
+    use crate::core::filter::Comparison;
+    use crate::test_support::{ProcessBuilder, SnapshotBuilder};
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_list() {
+        let command = parse_args(&args(
+            "list --sort mem --asc --filter mem>500MB --filter=name=chrome --format json --top 5",
+        ))
+        .unwrap();
+        let Some(Command::List(list)) = command else {
+            panic!("expected list");
+        };
+        assert_eq!(list.sort, SortColumn::Memory);
+        assert_eq!(list.direction, SortDirection::Ascending);
+        assert_eq!(list.filter.memory_threshold, Some(500 * 1024 * 1024));
+        assert_eq!(list.filter.name.as_deref(), Some("chrome"));
+        assert_eq!(list.format, OutputFormat::Json);
+        assert_eq!(list.top, Some(5));
+    }
+
+    #[test]
+    fn test_parse_errors_are_usage() {
+        assert!(parse_args(&[]).unwrap().is_none());
+        for line in [
+            "bogus",
+            "list --sort 5%",
+            "list --filter name>5",
+            "list --filter threads>many",
+            "list --filter mem<5",
+            "kill",
+            "kill abc",
+            "watch --interval 0s",
+        ] {
+            let error = parse_args(&args(line)).unwrap_err();
+            assert_eq!(error.code, EXIT_USAGE, "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_custom_columns_in_list_options() {
+        let Some(Command::List(list)) = parse_args(&args(
+            "list --sort per_thread --filter per_thread>=1MB --filter threads>2",
+        ))
+        .unwrap() else {
+            panic!("expected list");
+        };
+        assert_eq!(list.custom_sort.as_deref(), Some("per_thread"));
+        assert_eq!(list.filter.conditions.len(), 2);
+        assert_eq!(list.filter.conditions[0].value, 1024.0 * 1024.0);
+        assert_eq!(list.filter.conditions[1].comparison, Comparison::Greater);
+
+        let columns = [
+            CustomColumn::new("cpu_share", "cpu / total(cpu) * 100", ColumnFormat::Percent),
+            CustomColumn::new(
+                "per_thread",
+                "memory_private / threads",
+                ColumnFormat::Bytes,
+            ),
+        ];
+        let list = with_custom_columns(list, &columns).unwrap();
+        assert_eq!(list.sort, SortColumn::Custom(1));
+        assert_eq!(list.columns.len(), 2);
+
+        for line in ["list --sort speed", "list --filter speed>1"] {
+            let Some(Command::List(list)) = parse_args(&args(line)).unwrap() else {
+                panic!("expected list");
+            };
+            let error = with_custom_columns(list, &columns).unwrap_err();
+            assert_eq!(error.code, EXIT_USAGE, "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_parse_kill_and_export() {
+        let Some(Command::Kill(kill)) = parse_args(&args("kill --tree --timeout 2s 1234")).unwrap()
+        else {
+            panic!("expected kill");
+        };
+        assert_eq!(
+            kill,
+            KillOptions {
+                pid: 1234,
+                tree: true,
+                force: false,
+                timeout: Duration::from_secs(2),
+            }
+        );
+
+        let Some(Command::Export(export)) =
+            parse_args(&args("export --duration 5m --format html")).unwrap()
+        else {
+            panic!("expected export");
+        };
+        assert_eq!(export.duration, Duration::from_secs(300));
+        assert_eq!(export.format, ExportFormat::HtmlReport);
+        assert_eq!(export.output, PathBuf::from("task-manager-export.html"));
+
+        let Some(Command::Tui(interval)) = parse_args(&args("tui --interval=250ms")).unwrap()
+        else {
+            panic!("expected tui");
+        };
+        assert_eq!(interval, Duration::from_millis(250));
+    }
+
+    #[test]
+    fn test_parse_remote_and_agent() {
+        let line = args("--remote 10.0.0.5:9183 --token=s3cret list --top 5");
+        let (remote, rest) = split_remote(&line).unwrap();
+        assert_eq!(
+            remote,
+            Some(RemoteOptions {
+                address: "10.0.0.5:9183".to_string(),
+                token: "s3cret".to_string(),
+            })
+        );
+        assert_eq!(rest, &line[3..]);
+
+        let line = args("list --remote x");
+        assert_eq!(split_remote(&line).unwrap(), (None, &line[..]));
+        assert_eq!(
+            split_remote(&args("--token t list")).unwrap_err().code,
+            EXIT_USAGE
+        );
+
+        let Some(Command::Agent(agent)) =
+            parse_args(&args("agent --port 0 --token view --control-token admin")).unwrap()
+        else {
+            panic!("expected agent");
+        };
+        assert_eq!(agent.port, Some(0));
+        assert_eq!(
+            agent.tokens.iter().map(|t| t.scope).collect::<Vec<_>>(),
+            vec![AgentScope::ReadOnly, AgentScope::Control]
+        );
+        assert!(parse_args(&args("agent --port 70000")).is_err());
+    }
+
+    #[test]
+    fn test_parse_profile_and_profile_filters() {
+        let parse = |line: &str| match parse_args(&args(line)).unwrap() {
+            Some(Command::Profile(command)) => command,
+            _ => panic!("expected profile"),
+        };
+        assert_eq!(parse("profile list"), ProfileCommand::List);
+        assert_eq!(parse("profile use --base"), ProfileCommand::Use(None));
+        assert_eq!(
+            parse("profile export leak leak.json"),
+            ProfileCommand::Export {
+                name: "leak".to_string(),
+                path: PathBuf::from("leak.json"),
+            }
+        );
+        assert!(parse_args(&args("profile use")).is_err());
+        assert!(parse_args(&args("profile use a --base")).is_err());
+
+        let mut config = AppConfig::default();
+        config.profiles.profiles.push(
+            Profile::new("leak").with_filters(vec!["mem>1GB".to_string(), "name=svc".to_string()]),
+        );
+        let Some(Command::List(list)) =
+            parse_args(&args("list --profile leak --filter name=chrome")).unwrap()
+        else {
+            panic!("expected list");
+        };
+        let base = profile_filter(&config, list.profile.as_deref()).unwrap();
+        let filter = merge_filters(base, &list.filter);
+        assert_eq!(filter.memory_threshold, Some(1024 * 1024 * 1024));
+        assert_eq!(filter.name.as_deref(), Some("chrome"));
+
+        // No active profile: base filters only
+        assert!(profile_filter(&config, None).unwrap().name.is_none());
+        assert_eq!(
+            profile_filter(&config, Some("missing")).unwrap_err().code,
+            EXIT_USAGE
+        );
+    }
+
+    #[test]
+    fn test_parse_and_search_commands() {
+        let parse = |line: &str| match parse_args(&args(line)).unwrap() {
+            Some(Command::Commands(query)) => query,
+            _ => panic!("expected commands"),
+        };
+        assert_eq!(parse("commands end tree"), "end tree");
+        assert_eq!(parse("commands"), "");
+        assert!(parse_args(&args("commands --all")).is_err());
+
+        let registry = CommandRegistry::defaults();
+        let mut out = Vec::new();
+        write_commands(&registry, "end tree", &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let first = text.lines().next().unwrap();
+        assert!(first.starts_with("process.end_tree"));
+        assert!(first.ends_with("End process tree                 Shift+Delete"));
+
+        let error = write_commands(&registry, "zzz", &mut Vec::new()).unwrap_err();
+        assert_eq!(error.code, EXIT_NOT_FOUND);
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("500ms").unwrap(), Duration::from_millis(500));
+        assert_eq!(parse_duration("1.5").unwrap(), Duration::from_millis(1500));
+        assert_eq!(parse_duration("2h").unwrap(), Duration::from_secs(7200));
+        assert!(parse_duration("soon").is_err());
+        assert!(parse_duration("99999999999999999999999").is_err());
+    }
+
+    #[test]
+    fn test_process_rows_cpu_and_descendants() {
+        let start = Instant::now();
+        let previous = SnapshotBuilder::new()
+            .with_timestamp(start)
+            .with_processes(vec![ProcessBuilder::new(10, "build.exe")
+                .with_parent(1)
+                .build()])
+            .build();
+        let current = SnapshotBuilder::new()
+            .with_timestamp(start + Duration::from_secs(1))
+            .with_processes(vec![
+                ProcessBuilder::new(10, "build.exe")
+                    .with_parent(1)
+                    .with_cpu_time(5_000_000)
+                    .build(),
+                ProcessBuilder::new(11, "cc.exe").with_parent(10).build(),
+                ProcessBuilder::new(12, "ld.exe").with_parent(11).build(),
+                ProcessBuilder::new(13, "other.exe").with_parent(1).build(),
+            ])
+            .build();
+
+        let rows = process_rows(&previous, &current, 2);
+        assert!((rows[0].cpu_usage - 25.0).abs() < 1e-9);
+        assert_eq!(rows[1].cpu_usage, 0.0);
+
+        assert_eq!(descendants(&current, 10), vec![12, 11]);
+        assert!(descendants(&current, 13).is_empty());
+    }
+
+    #[test]
+    fn test_descendants_skip_reused_parent_pid() {
+        let process = |pid, parent, created| {
+            ProcessBuilder::new(pid, "app.exe")
+                .with_parent(parent)
+                .with_create_time(created)
+                .build()
+        };
+        // 21 and 22 name 20 as their parent, but 21 was started before 20
+        // existed and belongs to an earlier process that reused the PID
+        let snapshot = SnapshotBuilder::new()
+            .with_processes(vec![
+                process(20, 1, 500),
+                process(21, 20, 100),
+                process(22, 20, 600),
+                process(23, 21, 700),
+                process(24, 22, 0),
+            ])
+            .build();
+
+        assert_eq!(descendants(&snapshot, 20), vec![24, 22]);
+        // The older process keeps its own children
+        assert_eq!(descendants(&snapshot, 21), vec![23]);
+    }
+
+    #[test]
+    fn test_write_rows_formats() {
+        let start = Instant::now();
+        let snapshot = SnapshotBuilder::new()
+            .with_timestamp(start)
+            .with_processes(vec![ProcessBuilder::new(7, "a,b.exe")
+                .with_memory(1024)
+                .build()])
+            .build();
+        let rows = process_rows(&snapshot, &snapshot, 1);
+        let refs: Vec<&ProcessInfo> = rows.iter().collect();
+
+        let mut csv = Vec::new();
+        write_rows(&mut csv, &refs, OutputFormat::Csv, &NumberFormat::new()).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.ends_with("7,0,\"a,b.exe\",0.0,1024,1024,10\n"));
+
+        let mut json = Vec::new();
+        write_rows(&mut json, &refs, OutputFormat::Json, &NumberFormat::new()).unwrap();
+        let parsed: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(parsed[0]["name"], "a,b.exe");
+
+        let mut table = Vec::new();
+        write_rows(&mut table, &refs, OutputFormat::Table, &NumberFormat::new()).unwrap();
+        assert!(String::from_utf8(table).unwrap().contains("1.0 KB"));
+    }
+
+    #[test]
+    fn test_process_rows_io_rates() {
+        let start = Instant::now();
+        let io = |read, write| {
+            ProcessBuilder::new(20, "copy.exe")
+                .with_io(read, write)
+                .build()
+        };
+        let previous = SnapshotBuilder::new()
+            .with_timestamp(start)
+            .with_processes(vec![io(1_000, 0)])
+            .build();
+        let current = SnapshotBuilder::new()
+            .with_timestamp(start + Duration::from_secs(2))
+            .with_processes(vec![
+                io(5_001_000, 1_000_000),
+                ProcessBuilder::new(21, "new.exe").build(),
+            ])
+            .build();
+
+        let mut rows = process_rows(&previous, &current, 1);
+        assert_eq!(rows[0].io_read_bytes, 5_001_000);
+        assert_eq!(rows[0].io_read_bps, 2_500_000.0);
+        assert_eq!(rows[0].io_write_bps, 500_000.0);
+        // No earlier sample, no rate
+        assert_eq!(rows[1].io_read_bps, 0.0);
+
+        ComputedColumns::new(&[CustomColumn::new(
+            "io_total",
+            "io_read_bps + io_write_bps",
+            ColumnFormat::Bytes,
+        )])
+        .unwrap()
+        .apply(&mut rows);
+        assert_eq!(rows[0].custom[0].value, Some(3_000_000.0));
+
+        let filter =
+            parse_filter("io_read_bps + io_write_bps > 1MB", ProcessFilter::new()).unwrap();
+        let matched: Vec<u32> = filter.apply(&rows).iter().map(|p| p.pid).collect();
+        assert_eq!(matched, vec![20]);
+    }
+
+    #[test]
+    fn test_write_rows_with_custom_columns() {
+        let start = Instant::now();
+        let snapshot = SnapshotBuilder::new()
+            .with_timestamp(start)
+            .with_processes(vec![ProcessBuilder::new(7, "a.exe")
+                .with_memory(1024)
+                .build()])
+            .build();
+        let mut rows = process_rows(&snapshot, &snapshot, 1);
+        ComputedColumns::new(&[
+            CustomColumn::new(
+                "per_thread",
+                "memory_private / (threads * 2)",
+                ColumnFormat::Bytes,
+            ),
+            CustomColumn::new("broken", "handles / 0", ColumnFormat::Count),
+        ])
+        .unwrap()
+        .apply(&mut rows);
+        let refs: Vec<&ProcessInfo> = rows.iter().collect();
+
+        let mut csv = Vec::new();
+        write_rows(&mut csv, &refs, OutputFormat::Csv, &NumberFormat::new()).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv
+            .lines()
+            .next()
+            .unwrap()
+            .ends_with(",handles,per_thread,broken"));
+        assert!(csv.ends_with(",10,512,\n"));
+
+        let mut json = Vec::new();
+        write_rows(&mut json, &refs, OutputFormat::Json, &NumberFormat::new()).unwrap();
+        let parsed: serde_json::Value = serde_json::from_slice(&json).unwrap();
+        assert_eq!(parsed[0]["columns"]["per_thread"], 512.0);
+        assert!(parsed[0]["columns"]["broken"].is_null());
+
+        let mut table = Vec::new();
+        write_rows(&mut table, &refs, OutputFormat::Table, &NumberFormat::new()).unwrap();
+        let table = String::from_utf8(table).unwrap();
+        assert!(table
+            .lines()
+            .next()
+            .unwrap()
+            .ends_with("PER_THREAD     BROKEN"));
+        assert!(table.contains("512 B"));
+    }
+}
@@ -0,0 +1,557 @@
+//! Command registry and fuzzy command search
+//!
+//! Every user-facing action is registered once as a `Command` with a stable
+//! id, a title, search keywords and an enablement predicate. Frontends only
+//! translate their input into a command and hand it to
+//! `CommandRegistry::dispatch`:
+//! - Shortcuts through `CommandRegistry::for_shortcut`
+//! - Context menu items and tray entries through their `command_id`
+//! - The TUI command palette through `CommandRegistry::search`
+//! - `task-manager commands` lists the registry
+//!
+//! The registry knows nothing about windows or terminals: a frontend
+//! implements `CommandHandler` to describe its state and run actions, so
+//! dispatching is tested without Win32.
+
+use std::cmp::Reverse;
+
+use crate::app::export::ExportFormat;
+use crate::app::theme::Theme;
+use crate::ui::controls::filter_box::FilterPreset;
+use crate::ui::controls::tabview::TabId;
+use crate::ui::input::{Shortcut, PRESET_SHORTCUTS};
+use crate::windows::process::control::PriorityClass;
+
+/// What running a command does; executed by the frontend
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    SwitchTab(TabId),
+    NextTab,
+    PreviousTab,
+    Refresh,
+    Find,
+    ToggleTreeView,
+    EndProcess,
+    EndProcessForce,
+    /// End the selected processes and all their descendants
+    EndProcessTree,
+    SetPriority(PriorityClass),
+    Suspend,
+    Resume,
+    GoToDetails,
+    OpenFileLocation,
+    Export(ExportFormat),
+    SetTheme(Theme),
+    /// Apply the filter preset at this position in the preset list
+    ApplyFilterPreset(usize),
+    ShowWindow,
+    HideWindow,
+    ToggleWindow,
+    Exit,
+}
+
+/// Frontend state that decides which commands are enabled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CommandContext {
+    /// Number of selected processes
+    pub selected: usize,
+    /// Running with administrator rights
+    pub elevated: bool,
+    /// Main window shown (false while minimized to the tray)
+    pub window_visible: bool,
+}
+
+/// Frontend that runs dispatched commands
+pub trait CommandHandler {
+    /// Current state, checked against each command's predicate
+    fn context(&self) -> CommandContext;
+
+    fn execute(&mut self, action: Action) -> Result<(), String>;
+}
+
+/// Registered command
+#[derive(Debug, Clone)]
+pub struct Command {
+    /// Stable identifier, e.g. `process.end_tree`
+    pub id: String,
+    pub title: String,
+    /// Extra words matched by the palette
+    pub keywords: Vec<String>,
+    pub action: Action,
+    /// Keyboard shortcut running this command, shown next to the title
+    pub shortcut: Option<Shortcut>,
+    enabled: fn(&CommandContext) -> bool,
+}
+
+impl Command {
+    /// Always-enabled command without keywords or shortcut
+    pub fn new(id: impl Into<String>, title: impl Into<String>, action: Action) -> Self {
+        Self {
+            id: id.into(),
+            title: title.into(),
+            keywords: Vec::new(),
+            action,
+            shortcut: None,
+            enabled: |_| true,
+        }
+    }
+
+    pub fn with_keywords(mut self, keywords: &[&str]) -> Self {
+        self.keywords = keywords.iter().map(|k| k.to_string()).collect();
+        self
+    }
+
+    pub fn with_shortcut(mut self, shortcut: Shortcut) -> Self {
+        self.shortcut = Some(shortcut);
+        self
+    }
+
+    pub fn with_enabled(mut self, enabled: fn(&CommandContext) -> bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    pub fn is_enabled(&self, context: &CommandContext) -> bool {
+        (self.enabled)(context)
+    }
+
+    /// Best fuzzy score over title, keywords and id
+    ///
+    /// Keyword and id matches rank slightly below an equal title match.
+    pub fn score(&self, query: &str) -> Option<u32> {
+        let secondary = self
+            .keywords
+            .iter()
+            .map(String::as_str)
+            .chain([self.id.as_str()])
+            .filter_map(|text| fuzzy_score(query, text))
+            .map(|score| score.saturating_sub(1));
+        fuzzy_score(query, &self.title)
+            .into_iter()
+            .chain(secondary)
+            .max()
+    }
+}
+
+fn has_selection(context: &CommandContext) -> bool {
+    context.selected > 0
+}
+
+/// Score of `query` as a case-insensitive subsequence of `text`
+///
+/// Matches at word starts and runs of consecutive characters score higher,
+/// skipped characters lower; the best alignment wins. Whitespace in the query
+/// is ignored. Returns None if some query character is missing.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<u32> {
+    let text: Vec<char> = text.chars().collect();
+    let query: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).collect();
+    let Some((&first, rest)) = query.split_first() else {
+        return Some(0);
+    };
+
+    let matches = |wanted: char, j: usize| text[j].to_lowercase().eq(wanted.to_lowercase());
+    let char_score = |j: usize| {
+        let word_start = j == 0
+            || !text[j - 1].is_alphanumeric()
+            || (text[j - 1].is_lowercase() && text[j].is_uppercase());
+        if word_start {
+            9
+        } else {
+            1
+        }
+    };
+    // Skipped characters cost a little, capped per gap
+    let gap = |skipped: usize| -(skipped.min(3) as i64);
+
+    // best[j]: best score so far with the current query character at text[j]
+    let mut best: Vec<Option<i64>> = (0..text.len())
+        .map(|j| matches(first, j).then(|| char_score(j) + gap(j)))
+        .collect();
+    for &wanted in rest {
+        best = (0..text.len())
+            .map(|j| {
+                if !matches(wanted, j) {
+                    return None;
+                }
+                let previous = (0..j)
+                    .filter_map(|k| {
+                        let link = if k + 1 == j { 5 } else { gap(j - k - 1) };
+                        Some(best[k]? + link)
+                    })
+                    .max()?;
+                Some(previous + char_score(j))
+            })
+            .collect();
+    }
+    best.into_iter()
+        .flatten()
+        .max()
+        .map(|score| score.max(0) as u32)
+}
+
+/// All commands known to the application
+#[derive(Debug, Clone, Default)]
+pub struct CommandRegistry {
+    commands: Vec<Command>,
+}
+
+impl CommandRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Built-in commands, with the built-in filter presets
+    pub fn defaults() -> Self {
+        let mut registry = Self::new();
+        for command in default_commands() {
+            registry
+                .register(command)
+                .expect("built-in command ids are unique");
+        }
+        registry.set_filter_presets(&FilterPreset::defaults());
+        registry
+    }
+
+    /// Replace the preset commands with one per preset, in list order
+    ///
+    /// The first nine presets get the `FilterPreset` shortcuts.
+    pub fn set_filter_presets(&mut self, presets: &[FilterPreset]) {
+        self.retain(|action| !matches!(action, Action::ApplyFilterPreset(_)));
+        for (index, preset) in presets.iter().enumerate() {
+            let mut command = Command::new(
+                format!("filter.preset.{}", index + 1),
+                format!("Filter: {}", preset.name),
+                Action::ApplyFilterPreset(index),
+            )
+            .with_keywords(&["preset", "show"]);
+            if index < PRESET_SHORTCUTS {
+                command = command.with_shortcut(Shortcut::FilterPreset(index as u8));
+            }
+            self.commands.push(command);
+        }
+    }
+
+    /// Add a command; fails if its id is empty or already registered
+    pub fn register(&mut self, command: Command) -> Result<(), String> {
+        if command.id.trim().is_empty() {
+            return Err("Command id cannot be empty".to_string());
+        }
+        if self.get(&command.id).is_some() {
+            return Err(format!("Command '{}' is already registered", command.id));
+        }
+        self.commands.push(command);
+        Ok(())
+    }
+
+    /// Keep only commands whose action the frontend supports
+    pub fn retain(&mut self, mut keep: impl FnMut(&Action) -> bool) {
+        self.commands.retain(|command| keep(&command.action));
+    }
+
+    /// Commands in registration order
+    pub fn commands(&self) -> &[Command] {
+        &self.commands
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Command> {
+        self.commands.iter().find(|command| command.id == id)
+    }
+
+    pub fn for_shortcut(&self, shortcut: Shortcut) -> Option<&Command> {
+        self.commands
+            .iter()
+            .find(|command| command.shortcut == Some(shortcut))
+    }
+
+    /// Commands matching `query`, best first
+    ///
+    /// An empty query lists every command in registration order; ties keep
+    /// registration order too.
+    pub fn search(&self, query: &str) -> Vec<&Command> {
+        let mut matches: Vec<(u32, &Command)> = self
+            .commands
+            .iter()
+            .filter_map(|command| Some((command.score(query)?, command)))
+            .collect();
+        matches.sort_by_key(|(score, _)| Reverse(*score));
+        matches.into_iter().map(|(_, command)| command).collect()
+    }
+
+    /// Action of command `id` if it is enabled in `context`
+    pub fn resolve(&self, id: &str, context: &CommandContext) -> Result<Action, String> {
+        let command = self
+            .get(id)
+            .ok_or_else(|| format!("Unknown command '{}'", id))?;
+        if !command.is_enabled(context) {
+            return Err(format!("{} is not available", command.title));
+        }
+        Ok(command.action)
+    }
+
+    /// Run command `id` on `handler`
+    pub fn dispatch(&self, id: &str, handler: &mut dyn CommandHandler) -> Result<(), String> {
+        let action = self.resolve(id, &handler.context())?;
+        handler.execute(action)
+    }
+}
+
+fn default_commands() -> Vec<Command> {
+    let mut commands = Vec::new();
+
+    for tab in (0..).map_while(TabId::from_u32) {
+        let index = tab as u8;
+        commands.push(
+            Command::new(
+                format!("tab.{}", index + 1),
+                format!("Go to {}", tab.name()),
+                Action::SwitchTab(tab),
+            )
+            .with_keywords(&["tab", "switch", "show"])
+            .with_shortcut(Shortcut::SwitchToTab(index)),
+        );
+    }
+    commands.extend([
+        Command::new("tab.next", "Next tab", Action::NextTab).with_shortcut(Shortcut::NextTab),
+        Command::new("tab.previous", "Previous tab", Action::PreviousTab)
+            .with_shortcut(Shortcut::PreviousTab),
+        Command::new("view.refresh", "Refresh now", Action::Refresh)
+            .with_keywords(&["update", "reload"])
+            .with_shortcut(Shortcut::Refresh),
+        Command::new("view.find", "Find process", Action::Find)
+            .with_keywords(&["filter", "search"])
+            .with_shortcut(Shortcut::Find),
+        Command::new(
+            "view.toggle_tree",
+            "Toggle tree view",
+            Action::ToggleTreeView,
+        )
+        .with_keywords(&["hierarchy", "children", "parent"])
+        .with_shortcut(Shortcut::ToggleTreeView),
+        Command::new("process.end", "End process", Action::EndProcess)
+            .with_keywords(&["kill", "terminate", "stop"])
+            .with_shortcut(Shortcut::Delete)
+            .with_enabled(has_selection),
+        Command::new(
+            "process.end_force",
+            "End process (force)",
+            Action::EndProcessForce,
+        )
+        .with_keywords(&["kill", "terminate"])
+        .with_enabled(has_selection),
+        Command::new(
+            "process.end_tree",
+            "End process tree",
+            Action::EndProcessTree,
+        )
+        .with_keywords(&["kill", "terminate", "children", "descendants"])
+        .with_shortcut(Shortcut::KillTree)
+        .with_enabled(has_selection),
+    ]);
+
+    let priorities = [
+        ("realtime", "Realtime", PriorityClass::Realtime),
+        ("high", "High", PriorityClass::High),
+        ("above_normal", "Above normal", PriorityClass::AboveNormal),
+        ("normal", "Normal", PriorityClass::Normal),
+        ("below_normal", "Below normal", PriorityClass::BelowNormal),
+        ("idle", "Idle", PriorityClass::Idle),
+    ];
+    for (id, label, class) in priorities {
+        let command = Command::new(
+            format!("process.priority.{}", id),
+            format!("Set priority: {}", label),
+            Action::SetPriority(class),
+        )
+        .with_keywords(&["priority", "class"]);
+        commands.push(if class == PriorityClass::Realtime {
+            command.with_enabled(|context| has_selection(context) && context.elevated)
+        } else {
+            command.with_enabled(has_selection)
+        });
+    }
+
+    commands.extend([
+        Command::new("process.suspend", "Suspend process", Action::Suspend)
+            .with_keywords(&["pause", "freeze"])
+            .with_enabled(has_selection),
+        Command::new("process.resume", "Resume process", Action::Resume)
+            .with_keywords(&["continue", "unfreeze"])
+            .with_enabled(has_selection),
+        Command::new("process.details", "Go to details", Action::GoToDetails)
+            .with_keywords(&["properties", "info"])
+            .with_enabled(has_selection),
+        Command::new(
+            "process.open_location",
+            "Open file location",
+            Action::OpenFileLocation,
+        )
+        .with_keywords(&["explorer", "folder", "path"])
+        .with_enabled(has_selection),
+    ]);
+
+    let exports = [
+        ("csv", "CSV", ExportFormat::Csv),
+        ("json", "JSON", ExportFormat::Json),
+        ("html", "HTML report", ExportFormat::HtmlReport),
+    ];
+    for (id, label, format) in exports {
+        commands.push(
+            Command::new(
+                format!("export.{}", id),
+                format!("Export metrics as {}", label),
+                Action::Export(format),
+            )
+            .with_keywords(&["save", "report"]),
+        );
+    }
+
+    let themes = [
+        ("system", "System", Theme::System),
+        ("light", "Light", Theme::Light),
+        ("dark", "Dark", Theme::Dark),
+    ];
+    for (id, label, theme) in themes {
+        commands.push(
+            Command::new(
+                format!("theme.{}", id),
+                format!("Theme: {}", label),
+                Action::SetTheme(theme),
+            )
+            .with_keywords(&["appearance", "color", "mode"]),
+        );
+    }
+
+    commands.extend([
+        Command::new("window.show", "Show window", Action::ShowWindow)
+            .with_keywords(&["restore", "tray"])
+            .with_enabled(|context| !context.window_visible),
+        Command::new("window.hide", "Hide window", Action::HideWindow)
+            .with_keywords(&["minimize", "tray"])
+            .with_enabled(|context| context.window_visible),
+        Command::new("window.toggle", "Show or hide window", Action::ToggleWindow)
+            .with_keywords(&["tray"]),
+        Command::new("app.exit", "Exit", Action::Exit).with_keywords(&["quit", "close"]),
+    ]);
+    commands
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::CommandRecorder;
+
+    #[test]
+    fn test_fuzzy_score_prefers_word_starts_and_runs() {
+        assert_eq!(fuzzy_score("xyz", "End process"), None);
+        assert_eq!(fuzzy_score("", "End process"), Some(0));
+        assert!(fuzzy_score("ep", "End process") > fuzzy_score("ep", "Sleep"));
+        assert!(fuzzy_score("tree", "Toggle tree view") > fuzzy_score("tree", "The reset"));
+        assert!(fuzzy_score("EPT", "end process tree").is_some());
+        assert!(fuzzy_score("hr", "HtmlReport") > fuzzy_score("hr", "Thread"));
+    }
+
+    #[test]
+    fn test_search_ranks_commands() {
+        let registry = CommandRegistry::defaults();
+        assert_eq!(registry.search("").len(), registry.commands().len());
+
+        let ids = |query: &str| -> Vec<String> {
+            registry
+                .search(query)
+                .iter()
+                .take(2)
+                .map(|c| c.id.clone())
+                .collect()
+        };
+        assert_eq!(ids("end tree")[0], "process.end_tree");
+        assert_eq!(ids("kill")[0], "process.end");
+        assert_eq!(ids("dark")[0], "theme.dark");
+        assert_eq!(ids("perf")[0], "tab.2");
+        assert_eq!(ids("exp html")[0], "export.html");
+        assert!(registry.search("qqq").is_empty());
+    }
+
+    #[test]
+    fn test_dispatch_checks_enablement() {
+        let registry = CommandRegistry::defaults();
+        let mut handler = CommandRecorder::new(CommandContext::default());
+
+        assert_eq!(
+            registry.dispatch("process.end", &mut handler),
+            Err("End process is not available".to_string())
+        );
+        assert!(registry.dispatch("nope", &mut handler).is_err());
+        registry.dispatch("view.toggle_tree", &mut handler).unwrap();
+
+        handler.context.selected = 2;
+        registry.dispatch("process.end", &mut handler).unwrap();
+        assert!(registry
+            .dispatch("process.priority.realtime", &mut handler)
+            .is_err());
+        handler.context.elevated = true;
+        registry
+            .dispatch("process.priority.realtime", &mut handler)
+            .unwrap();
+        assert_eq!(
+            handler.executed,
+            vec![
+                Action::ToggleTreeView,
+                Action::EndProcess,
+                Action::SetPriority(PriorityClass::Realtime),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_registry_ids_and_shortcuts() {
+        let mut registry = CommandRegistry::defaults();
+        assert!(registry
+            .register(Command::new("app.exit", "Quit", Action::Exit))
+            .is_err());
+        assert!(registry
+            .register(Command::new(" ", "Blank", Action::Exit))
+            .is_err());
+
+        assert_eq!(
+            registry.for_shortcut(Shortcut::KillTree).map(|c| c.action),
+            Some(Action::EndProcessTree)
+        );
+        assert_eq!(
+            registry.get("tab.7").map(|c| c.action),
+            Some(Action::SwitchTab(TabId::Gpu))
+        );
+        assert!(registry.for_shortcut(Shortcut::Escape).is_none());
+
+        registry.retain(|action| matches!(action, Action::Find | Action::Exit));
+        let ids: Vec<&str> = registry.commands().iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, vec!["view.find", "app.exit"]);
+    }
+
+    #[test]
+    fn test_filter_preset_commands() {
+        let mut registry = CommandRegistry::defaults();
+        let command = registry.for_shortcut(Shortcut::FilterPreset(1)).unwrap();
+        assert_eq!(command.id, "filter.preset.2");
+        assert_eq!(command.title, "Filter: High CPU");
+        assert_eq!(command.action, Action::ApplyFilterPreset(1));
+
+        let presets: Vec<FilterPreset> = (0..10)
+            .map(|n| FilterPreset::new(format!("Preset {}", n)))
+            .collect();
+        registry.set_filter_presets(&presets);
+        let preset_commands: Vec<&Command> = registry
+            .commands()
+            .iter()
+            .filter(|c| matches!(c.action, Action::ApplyFilterPreset(_)))
+            .collect();
+        assert_eq!(preset_commands.len(), 10);
+        assert_eq!(preset_commands[9].title, "Filter: Preset 9");
+        assert_eq!(preset_commands[9].shortcut, None);
+        assert_eq!(
+            registry
+                .for_shortcut(Shortcut::FilterPreset(8))
+                .map(|c| c.action),
+            Some(Action::ApplyFilterPreset(8))
+        );
+    }
+}
@@ -1,42 +1,198 @@
 //! Application configuration persistence with Windows Registry
 //!
 //! Implements T423-T428:
-//! - Registry storage at HKCU\Software\TaskManager
+//! - `ConfigStore` backends: registry storage at HKCU\Software\TaskManager
+//!   or a JSON file (`FileStore`) that can be version-controlled and shared
 //! - Window position and size persistence
 //! - Theme preference storage
 //! - Refresh rate and history length
-//! - Column widths and visibility
+//! - Column layout: order, widths, visibility, pinning, alignment and units
+//!   (`ui::controls::table::ColumnLayout`), saved as one section
 //! - Import/export to JSON
+//! - `schema_version` with a migration chain; unreadable config files are
+//!   quarantined and replaced by defaults
+//! - Per-section dirty tracking so external edits (`app::reload`) merge
+//!   without clobbering unsaved changes
+//! - Validation (`app::validation`): loads clamp out-of-range values,
+//!   imports and monitoring changes are rejected with per-field errors
+//! - Named profiles (`Profile`) overriding refresh rate, history, columns,
+//!   filters and alert rules; profiles inherit from each other and export
+//!   to standalone files
+//! - Key binding overrides (`ui::keymap`)
+//! - Named filter presets (`FilterPreset`), editable and reorderable; the
+//!   built-in presets are only defaults
+//! - Computed columns (`core::expression::CustomColumn`) derived from
+//!   expressions over process fields
+//! - Display locale and byte units (`util::format::NumberFormat`)
 
 use windows::Win32::Foundation::RECT;
 use windows::Win32::System::Registry::*;
 use windows::core::PCWSTR;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
 use serde::{Serialize, Deserialize};
 
 use crate::app::theme::Theme;
+use crate::app::validation::{self, ValidationErrors};
+use crate::util::logging::EventLogger;
+use crate::core::expression::CustomColumn;
+use crate::core::leak::LeakDetectorConfig;
+use crate::core::metrics::AnomalyMethod;
+use crate::windows::process::control::PriorityClass;
+use crate::ui::controls::filter_box::FilterPreset;
+use crate::ui::controls::table::ColumnLayout;
+use crate::ui::i18n::{Locale, StringResources};
+use crate::util::format::{NumberFormat, UnitSystem};
 
 /// Registry key path
-#[allow(dead_code)]
 const REGISTRY_KEY: &str = "Software\\TaskManager";
 
+/// Current `AppConfig::schema_version`
+///
+/// Version 1 is every configuration written before the field existed.
+pub const CONFIG_SCHEMA_VERSION: u32 = 3;
+
+/// Upgrade steps on the raw JSON; entry `i` turns version `i + 1` into `i + 2`
+///
+/// Migrations must carry user data (columns, rules, policies) forward.
+const MIGRATIONS: &[fn(&mut serde_json::Map<String, serde_json::Value>)] =
+    &[migrate_v1_to_v2, migrate_v2_to_v3];
+
+/// v2 only added `schema_version` and field defaults; the data is unchanged
+///
+/// Kept as an explicit step so entry `i` of `MIGRATIONS` stays version `i + 1`.
+fn migrate_v1_to_v2(_config: &mut serde_json::Map<String, serde_json::Value>) {}
+
+/// v2 kept column widths and visibility in two maps keyed by header label;
+/// v3 stores the whole `ColumnLayout`
+///
+/// Names that match no column (e.g. `Threads`) cannot be carried over.
+fn migrate_v2_to_v3(config: &mut serde_json::Map<String, serde_json::Value>) {
+    let old = match config.get("columns").and_then(|columns| columns.as_object()) {
+        Some(old) => old,
+        None => return,
+    };
+    let mut layout = ColumnLayout::default();
+    if let Some(widths) = old.get("widths").and_then(|w| w.as_object()) {
+        for (name, width) in widths {
+            if let Some(width) = width.as_i64() {
+                let width = width.clamp(i32::MIN as i64, i32::MAX as i64) as i32;
+                let _ = layout.set_width(name, width);
+            }
+        }
+    }
+    if let Some(visibility) = old.get("visibility").and_then(|v| v.as_object()) {
+        for (name, visible) in visibility {
+            if let Some(visible) = visible.as_bool() {
+                let _ = layout.set_visible(name, visible);
+            }
+        }
+    }
+    if let Ok(layout) = serde_json::to_value(&layout) {
+        config.insert("columns".to_string(), layout);
+    }
+}
+
+/// Parse a stored configuration of any schema version
+///
+/// Runs the migrations the stored version needs and fills missing fields
+/// with defaults. Returns the configuration and the version it was stored
+/// with. Files from newer builds are read as-is; unknown fields are ignored.
+pub fn parse_config(json: &str) -> Result<(AppConfig, u32), String> {
+    let mut value: serde_json::Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    let map = value
+        .as_object_mut()
+        .ok_or_else(|| "configuration is not a JSON object".to_string())?;
+    let stored = match map.get("schema_version") {
+        None => 1,
+        Some(version) => version
+            .as_u64()
+            .filter(|v| (1..=u32::MAX as u64).contains(v))
+            .ok_or_else(|| format!("invalid schema_version {}", version))? as u32,
+    };
+
+    for migrate in MIGRATIONS.iter().skip(stored as usize - 1) {
+        migrate(map);
+    }
+    if stored < CONFIG_SCHEMA_VERSION {
+        map.insert("schema_version".to_string(), CONFIG_SCHEMA_VERSION.into());
+    }
+
+    let config = serde_json::from_value(value).map_err(|e| e.to_string())?;
+    Ok((config, stored))
+}
+
 /// Configuration data structure
+///
+/// Missing fields take their defaults, so older files keep loading.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AppConfig {
+    /// Layout version of this configuration (`CONFIG_SCHEMA_VERSION`)
+    pub schema_version: u32,
     /// Window position and size
     pub window: WindowConfig,
     /// Theme preference
     pub theme: ThemeConfig,
+    /// Locale and units for numbers, sizes and times
+    pub display: DisplayConfig,
     /// Monitoring settings
     pub monitoring: MonitoringConfig,
-    /// Table column settings
-    pub columns: ColumnConfig,
+    /// Table column layout
+    pub columns: ColumnLayout,
     /// Startup options
     pub startup: StartupConfig,
+    /// Metric exporters (Prometheus endpoint, InfluxDB and StatsD push)
+    #[serde(default)]
+    pub exporters: ExportersConfig,
+    /// Threshold alert rules
+    #[serde(default)]
+    pub alerts: AlertsConfig,
+    /// Automatic priority/affinity/kill policies
+    #[serde(default)]
+    pub policies: PoliciesConfig,
+    /// Per-process memory/handle growth analysis
+    #[serde(default)]
+    pub leak_detection: LeakDetectorConfig,
+    /// CPU and I/O spike detection
+    #[serde(default)]
+    pub anomalies: AnomaliesConfig,
+    /// Remote monitoring agent
+    #[serde(default)]
+    pub agent: AgentConfig,
+    /// Local JSON-RPC scripting endpoint
+    #[serde(default)]
+    pub rpc: RpcConfig,
+    /// Custom metric collectors (`MetricCollector`)
+    #[serde(default)]
+    pub collectors: CollectorsConfig,
+    /// Process filter expressions applied to the process list (`cpu>5`, `name=chrome`)
+    #[serde(default)]
+    pub filters: Vec<String>,
+    /// Named override profiles ("Low overhead", "Presentation", ...)
+    #[serde(default)]
+    pub profiles: ProfilesConfig,
+    /// Key binding overrides by command name (`kill_tree` -> `["Ctrl+K Ctrl+T"]`)
+    ///
+    /// Each entry replaces the command's default bindings (`ui::keymap`); an
+    /// empty list unbinds it.
+    #[serde(default)]
+    pub keymap: BTreeMap<String, Vec<String>>,
+    /// Filter presets in menu order; position N is bound to Alt+N
+    ///
+    /// Files without this field get the built-in presets.
+    pub filter_presets: Vec<FilterPreset>,
+    /// Computed columns in display order (`per_thread` = `memory_private / threads`)
+    #[serde(default)]
+    pub custom_columns: Vec<CustomColumn>,
 }
 
 /// Window position and size (T424)
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct WindowConfig {
     pub x: i32,
     pub y: i32,
@@ -47,12 +203,43 @@ pub struct WindowConfig {
 
 /// Theme configuration (T425)
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ThemeConfig {
     pub preference: Theme,
 }
 
+/// Locale and number formatting
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplayConfig {
+    /// Locale tag (`fr-CA`); the system locale when unset
+    pub locale: Option<String>,
+    /// Byte units: `binary` (1 KB = 1024 B), `iec` (KiB) or `si` (1 kB = 1000 B)
+    pub units: UnitSystem,
+}
+
+impl DisplayConfig {
+    pub fn locale(&self) -> Locale {
+        match &self.locale {
+            Some(code) => Locale::new(code),
+            None => Locale::detect_system_locale(),
+        }
+    }
+
+    /// Strings for the configured locale
+    pub fn strings(&self) -> StringResources {
+        StringResources::new(self.locale())
+    }
+
+    /// Separators of the configured locale with the configured units
+    pub fn number_format(&self) -> NumberFormat {
+        self.strings().number_format().with_units(self.units)
+    }
+}
+
 /// Monitoring settings (T425)
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct MonitoringConfig {
     /// Refresh rate in milliseconds (100, 500, 1000, 2000, 5000, 10000)
     pub refresh_rate_ms: u32,
@@ -62,77 +249,1111 @@ pub struct MonitoringConfig {
     pub graph_type: u32,
 }
 
-/// Column configuration (T426)
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ColumnConfig {
-    /// Column widths (name -> width in pixels)
-    pub widths: std::collections::HashMap<String, i32>,
-    /// Column visibility (name -> visible)
-    pub visibility: std::collections::HashMap<String, bool>,
+/// Startup options (T420)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StartupConfig {
+    /// Run at login
+    pub run_at_login: bool,
+    /// Start minimized
+    pub start_minimized: bool,
+    /// Performance mode (disable animations)
+    pub performance_mode: bool,
+}
+
+/// Metric exporter settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportersConfig {
+    /// Prometheus / OpenMetrics scrape endpoint
+    #[serde(default)]
+    pub prometheus: PrometheusConfig,
+    /// InfluxDB line protocol push sink
+    #[serde(default)]
+    pub influx: InfluxConfig,
+    /// StatsD gauge push sink
+    #[serde(default)]
+    pub statsd: StatsdConfig,
+}
+
+/// Prometheus scrape endpoint settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrometheusConfig {
+    /// Serve metrics on localhost
+    pub enabled: bool,
+    /// TCP port on 127.0.0.1
+    pub port: u16,
+    /// Maximum number of processes exported per scrape (largest working set first)
+    pub max_process_series: usize,
+}
+
+impl Default for PrometheusConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 9182,
+            max_process_series: 200,
+        }
+    }
+}
+
+/// Destination for push exporters
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PushTarget {
+    /// Append to a local file
+    File(String),
+    /// Send datagrams to `host:port`
+    Udp(String),
+    /// Stream to `host:port` (reconnects on failure)
+    Tcp(String),
+}
+
+/// Batching and retry settings shared by push exporters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushBatchConfig {
+    /// Maximum lines per write (UDP datagram, TCP write or file append)
+    pub max_lines: usize,
+    /// Maximum payload bytes per write
+    pub max_bytes: usize,
+    /// Failed batches kept for retry; the oldest are dropped beyond this
+    pub retry_queue_capacity: usize,
+}
+
+impl Default for PushBatchConfig {
+    fn default() -> Self {
+        Self {
+            max_lines: 500,
+            max_bytes: 1432, // Fits a single Ethernet-MTU UDP datagram
+            retry_queue_capacity: 64,
+        }
+    }
+}
+
+/// InfluxDB line protocol exporter settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InfluxConfig {
+    /// Push every update cycle
+    pub enabled: bool,
+    /// Where line protocol is written
+    pub target: PushTarget,
+    /// Tags added to every point (e.g. host, site)
+    pub tags: std::collections::BTreeMap<String, String>,
+    /// Maximum number of per-process points per cycle (0 = system only)
+    pub max_process_series: usize,
+    /// Batching and retry settings
+    pub batch: PushBatchConfig,
+}
+
+impl Default for InfluxConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target: PushTarget::Udp("127.0.0.1:8089".to_string()),
+            tags: Default::default(),
+            max_process_series: 50,
+            batch: PushBatchConfig::default(),
+        }
+    }
+}
+
+/// StatsD gauge exporter settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsdConfig {
+    /// Push every update cycle
+    pub enabled: bool,
+    /// Where gauges are sent (usually UDP)
+    pub target: PushTarget,
+    /// Metric name prefix (e.g. "taskmanager")
+    pub prefix: String,
+    /// DogStatsD-style tags appended to every gauge (empty = plain StatsD)
+    pub tags: std::collections::BTreeMap<String, String>,
+    /// Maximum number of processes reported per cycle (0 = system only)
+    pub max_process_series: usize,
+    /// Batching and retry settings
+    pub batch: PushBatchConfig,
+}
+
+impl Default for StatsdConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target: PushTarget::Udp("127.0.0.1:8125".to_string()),
+            prefix: "taskmanager".to_string(),
+            tags: Default::default(),
+            max_process_series: 0,
+            batch: PushBatchConfig::default(),
+        }
+    }
+}
+
+/// Threshold alerting settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertsConfig {
+    /// Evaluate rules every update cycle
+    pub enabled: bool,
+    /// Append fired/cleared events as JSON lines to this file
+    #[serde(default)]
+    pub export_path: Option<String>,
+    /// Rules evaluated against each snapshot
+    #[serde(default)]
+    pub rules: Vec<AlertRule>,
+}
+
+impl Default for AlertsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            export_path: None,
+            rules: vec![
+                AlertRule::new(
+                    "High CPU",
+                    AlertMetric::SystemCpuPercent,
+                    AlertComparison::Above,
+                    90.0,
+                )
+                .with_sustain_secs(30)
+                .with_hysteresis(10.0)
+                .with_cooldown_secs(300),
+                AlertRule::new(
+                    "Memory pressure",
+                    AlertMetric::MemoryLoadPercent,
+                    AlertComparison::Above,
+                    95.0,
+                )
+                .with_sustain_secs(10)
+                .with_hysteresis(5.0)
+                .with_cooldown_secs(300)
+                .with_severity(AlertSeverity::Critical),
+            ],
+        }
+    }
+}
+
+/// Alert severity, ordered from least to most severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+pub enum AlertSeverity {
+    Info,
+    #[default]
+    Warning,
+    Critical,
+}
+
+/// Value an alert rule watches
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum AlertMetric {
+    /// Total CPU usage (0-100)
+    SystemCpuPercent,
+    /// Physical memory load (0-100)
+    MemoryLoadPercent,
+    /// Available physical memory (bytes)
+    MemoryAvailableBytes,
+    DiskReadBps,
+    DiskWriteBps,
+    NetworkReceiveBps,
+    NetworkTransmitBps,
+    /// Private bytes of every process with this image name (case-insensitive)
+    ProcessPrivateBytes(String),
+    /// Working set of every process with this image name (case-insensitive)
+    ProcessWorkingSet(String),
+    /// Handle count of every process with this image name (case-insensitive)
+    ProcessHandleCount(String),
+    /// Value from a registered `MetricCollector` (`collector.metric`)
+    Custom(String),
+}
+
+/// Direction of a threshold comparison
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AlertComparison {
+    /// Breached while value > threshold
+    Above,
+    /// Breached while value < threshold
+    Below,
+}
+
+/// Single threshold rule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertRule {
+    /// Display name used in notifications and logs
+    pub name: String,
+    pub metric: AlertMetric,
+    pub comparison: AlertComparison,
+    pub threshold: f64,
+    /// Condition must hold this long before the rule fires (0 = immediately)
+    #[serde(default)]
+    pub sustain_secs: u64,
+    /// Distance back past the threshold required before the alert clears
+    #[serde(default)]
+    pub hysteresis: f64,
+    /// Minimum time between two firings of the same rule and subject
+    #[serde(default)]
+    pub cooldown_secs: u64,
+    #[serde(default)]
+    pub severity: AlertSeverity,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+impl AlertRule {
+    /// Create an enabled warning rule that fires immediately
+    pub fn new(
+        name: impl Into<String>,
+        metric: AlertMetric,
+        comparison: AlertComparison,
+        threshold: f64,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            metric,
+            comparison,
+            threshold,
+            sustain_secs: 0,
+            hysteresis: 0.0,
+            cooldown_secs: 0,
+            severity: AlertSeverity::Warning,
+            enabled: true,
+        }
+    }
+
+    pub fn with_sustain_secs(mut self, secs: u64) -> Self {
+        self.sustain_secs = secs;
+        self
+    }
+
+    pub fn with_hysteresis(mut self, hysteresis: f64) -> Self {
+        self.hysteresis = hysteresis;
+        self
+    }
+
+    pub fn with_cooldown_secs(mut self, secs: u64) -> Self {
+        self.cooldown_secs = secs;
+        self
+    }
+
+    pub fn with_severity(mut self, severity: AlertSeverity) -> Self {
+        self.severity = severity;
+        self
+    }
+}
+
+/// Statistical spike detection settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomaliesConfig {
+    pub enabled: bool,
+    pub method: AnomalyMethod,
+    /// Score threshold; the method's default when unset
+    #[serde(default)]
+    pub threshold: Option<f32>,
+    /// Number of busiest processes reported on each cycle
+    pub top_processes: usize,
+}
+
+impl Default for AnomaliesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            method: AnomalyMethod::ZScore,
+            threshold: None,
+            top_processes: 10,
+        }
+    }
+}
+
+/// What an agent client may do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum AgentScope {
+    /// Snapshots and history only
+    ReadOnly,
+    /// Also terminate processes and change priority/affinity
+    Control,
+}
+
+/// Shared secret accepted by the agent
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgentToken {
+    pub token: String,
+    pub scope: AgentScope,
+}
+
+/// Agent server settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentConfig {
+    /// Serve snapshots to remote clients
+    pub enabled: bool,
+    /// TCP port on 127.0.0.1; 0 picks a free port, or disables TCP if a Unix socket is set
+    pub port: u16,
+    /// Also listen on this Unix socket path (Unix platforms only)
+    #[serde(default)]
+    pub unix_socket: Option<String>,
+    /// Accepted tokens; clients without a matching token are rejected
+    pub tokens: Vec<AgentToken>,
+    /// Samples kept for history queries
+    pub history_capacity: usize,
+    /// Concurrent client connections
+    pub max_connections: usize,
+}
+
+impl Default for AgentConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 9183,
+            unix_socket: None,
+            tokens: Vec::new(),
+            history_capacity: 3600,
+            max_connections: 8,
+        }
+    }
+}
+
+/// JSON-RPC scripting endpoint settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcConfig {
+    /// Accept JSON-RPC calls on localhost
+    pub enabled: bool,
+    /// TCP port on 127.0.0.1
+    pub port: u16,
+    /// Expected `Authorization: Bearer <token>`; the endpoint refuses to
+    /// start without one
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+impl Default for RpcConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 9184,
+            token: None,
+        }
+    }
+}
+
+/// Custom metric collectors sampled by the updater
+///
+/// Values are published in `SystemMetrics::custom` as `<name>.<metric>`,
+/// where alert rules (`AlertMetric::Custom`) and exporters pick them up.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CollectorsConfig {
+    /// Numbers read from files, published as `<name>.value`
+    pub file_gauges: Vec<FileGaugeConfig>,
+}
+
+/// Gauge read from a text file containing a single number
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileGaugeConfig {
+    /// Collector name (`[a-z0-9_]`)
+    pub name: String,
+    /// File rewritten by the program publishing the value
+    pub path: String,
+    /// Unit shown next to values
+    #[serde(default)]
+    pub unit: String,
+    /// Seconds between reads
+    #[serde(default = "default_gauge_interval_secs")]
+    pub interval_secs: u64,
+}
+
+impl FileGaugeConfig {
+    pub fn new(name: impl Into<String>, path: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            path: path.into(),
+            unit: String::new(),
+            interval_secs: default_gauge_interval_secs(),
+        }
+    }
+}
+
+fn default_gauge_interval_secs() -> u64 {
+    5
+}
+
+/// Automatic process policy settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoliciesConfig {
+    /// Apply policies every update cycle
+    pub enabled: bool,
+    /// Record what would be done without touching any process
+    pub dry_run: bool,
+    /// Append every action (or dry-run decision) to this file
+    #[serde(default)]
+    pub audit_log_path: Option<String>,
+    /// Image-name patterns that policies never touch
+    #[serde(default = "default_policy_exclusions")]
+    pub exclusions: Vec<String>,
+    #[serde(default)]
+    pub rules: Vec<ProcessPolicy>,
+}
+
+impl Default for PoliciesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dry_run: true,
+            audit_log_path: None,
+            exclusions: default_policy_exclusions(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+/// Processes the system depends on; terminating or starving them destabilizes Windows
+fn default_policy_exclusions() -> Vec<String> {
+    [
+        "System",
+        "Registry",
+        "smss.exe",
+        "csrss.exe",
+        "wininit.exe",
+        "winlogon.exe",
+        "services.exe",
+        "lsass.exe",
+        "lsaiso.exe",
+        "svchost.exe",
+        "dwm.exe",
+        "fontdrvhost.exe",
+        "MsMpEng.exe",
+    ]
+    .iter()
+    .map(|name| name.to_string())
+    .collect()
+}
+
+/// Actions applied to processes whose image name matches `pattern`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessPolicy {
+    /// Display name used in the audit log
+    pub name: String,
+    /// Case-insensitive glob on the image name (`*` and `?`), e.g. `*compiler*`
+    pub pattern: String,
+    /// Priority class set once when a matching process appears
+    #[serde(default)]
+    pub priority: Option<PriorityClass>,
+    /// CPU list set once when a matching process appears, e.g. "4-15" or "0,2,4-7"
+    #[serde(default)]
+    pub affinity: Option<String>,
+    /// Terminate matching processes whose private bytes exceed this
+    #[serde(default)]
+    pub kill_above_private_bytes: Option<u64>,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+impl ProcessPolicy {
+    /// Create an enabled policy with no actions
+    pub fn new(name: impl Into<String>, pattern: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            pattern: pattern.into(),
+            priority: None,
+            affinity: None,
+            kill_above_private_bytes: None,
+            enabled: true,
+        }
+    }
+
+    pub fn with_priority(mut self, priority: PriorityClass) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    pub fn with_affinity(mut self, cpus: impl Into<String>) -> Self {
+        self.affinity = Some(cpus.into());
+        self
+    }
+
+    pub fn with_kill_above_private_bytes(mut self, bytes: u64) -> Self {
+        self.kill_above_private_bytes = Some(bytes);
+        self
+    }
+}
+
+/// Named set of overrides on top of the base configuration
+///
+/// E.g. "Low overhead" (slow refresh, short history) or "Debugging a leak"
+/// (memory columns, leak alerts). Unset fields inherit from `inherits`, or
+/// from the base configuration when it is unset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    /// Profile whose overrides apply first
+    #[serde(default)]
+    pub inherits: Option<String>,
+    #[serde(default)]
+    pub refresh_rate_ms: Option<u32>,
+    #[serde(default)]
+    pub history_length_sec: Option<u32>,
+    /// Column visibility, merged over the inherited visibility
+    #[serde(default)]
+    pub columns: BTreeMap<String, bool>,
+    /// Process filter expressions; replace the inherited filters
+    #[serde(default)]
+    pub filters: Option<Vec<String>>,
+    /// Alert rules; replace the inherited rules
+    #[serde(default)]
+    pub alert_rules: Option<Vec<AlertRule>>,
+}
+
+impl Profile {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            inherits: None,
+            refresh_rate_ms: None,
+            history_length_sec: None,
+            columns: BTreeMap::new(),
+            filters: None,
+            alert_rules: None,
+        }
+    }
+
+    pub fn with_parent(mut self, parent: impl Into<String>) -> Self {
+        self.inherits = Some(parent.into());
+        self
+    }
+
+    pub fn with_refresh_rate_ms(mut self, refresh_rate_ms: u32) -> Self {
+        self.refresh_rate_ms = Some(refresh_rate_ms);
+        self
+    }
+
+    pub fn with_history_length_sec(mut self, history_length_sec: u32) -> Self {
+        self.history_length_sec = Some(history_length_sec);
+        self
+    }
+
+    pub fn with_column(mut self, column: impl Into<String>, visible: bool) -> Self {
+        self.columns.insert(column.into(), visible);
+        self
+    }
+
+    pub fn with_filters(mut self, filters: Vec<String>) -> Self {
+        self.filters = Some(filters);
+        self
+    }
+
+    pub fn with_alert_rules(mut self, rules: Vec<AlertRule>) -> Self {
+        self.alert_rules = Some(rules);
+        self
+    }
+
+    /// Read a profile exported with `ConfigManager::export_profile`
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let json = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        serde_json::from_str(&json).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Layer `child` over these overrides
+    fn overlay(&mut self, child: &Profile) {
+        self.name = child.name.clone();
+        if child.refresh_rate_ms.is_some() {
+            self.refresh_rate_ms = child.refresh_rate_ms;
+        }
+        if child.history_length_sec.is_some() {
+            self.history_length_sec = child.history_length_sec;
+        }
+        self.columns
+            .extend(child.columns.iter().map(|(column, visible)| (column.clone(), *visible)));
+        if child.filters.is_some() {
+            self.filters = child.filters.clone();
+        }
+        if child.alert_rules.is_some() {
+            self.alert_rules = child.alert_rules.clone();
+        }
+    }
+
+    /// Apply the overrides to `config`
+    fn apply(&self, config: &mut AppConfig) {
+        if let Some(refresh_rate_ms) = self.refresh_rate_ms {
+            config.monitoring.refresh_rate_ms = refresh_rate_ms;
+        }
+        if let Some(history_length_sec) = self.history_length_sec {
+            config.monitoring.history_length_sec = history_length_sec;
+        }
+        for (column, visible) in &self.columns {
+            // Unknown columns are reported by validation
+            let _ = config.columns.set_visible(column, *visible);
+        }
+        if let Some(filters) = &self.filters {
+            config.filters = filters.clone();
+        }
+        if let Some(rules) = &self.alert_rules {
+            config.alerts.rules = rules.clone();
+        }
+    }
+}
+
+/// Named profiles and the one in use
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfilesConfig {
+    /// Profile applied on top of the base configuration; None uses the base
+    #[serde(default)]
+    pub active: Option<String>,
+    #[serde(default)]
+    pub profiles: Vec<Profile>,
+}
+
+impl ProfilesConfig {
+    /// Profile by name (case-insensitive)
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        self.profiles
+            .iter()
+            .find(|profile| profile.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.profiles.iter().map(|profile| profile.name.as_str()).collect()
+    }
+
+    /// `name` and its ancestors, base-most first
+    ///
+    /// Fails on unknown profiles and inheritance cycles.
+    pub fn chain(&self, name: &str) -> Result<Vec<&Profile>, String> {
+        let mut chain: Vec<&Profile> = Vec::new();
+        let mut next = Some(name);
+        while let Some(name) = next {
+            let profile = self
+                .get(name)
+                .ok_or_else(|| format!("Unknown profile '{}'", name))?;
+            if chain.iter().any(|seen| seen.name == profile.name) {
+                return Err(format!("Profile '{}' inherits from itself", profile.name));
+            }
+            chain.push(profile);
+            next = profile.inherits.as_deref();
+        }
+        chain.reverse();
+        Ok(chain)
+    }
+
+    /// `name` with all inherited overrides folded in, standalone for export
+    pub fn flatten(&self, name: &str) -> Result<Profile, String> {
+        let chain = self.chain(name)?;
+        let mut flat = Profile::new(name);
+        for profile in chain {
+            flat.overlay(profile);
+        }
+        flat.inherits = None;
+        Ok(flat)
+    }
+
+    /// Add `profile`, replacing one with the same name
+    pub fn insert(&mut self, profile: Profile) {
+        match self
+            .profiles
+            .iter_mut()
+            .find(|existing| existing.name.eq_ignore_ascii_case(&profile.name))
+        {
+            Some(existing) => *existing = profile,
+            None => self.profiles.push(profile),
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self {
+            x: 100,
+            y: 100,
+            width: 1200,
+            height: 800,
+            maximized: false,
+        }
+    }
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self {
+            preference: Theme::System,
+        }
+    }
+}
+
+impl Default for MonitoringConfig {
+    fn default() -> Self {
+        Self {
+            refresh_rate_ms: 1000,
+            history_length_sec: 60,
+            graph_type: 0, // Line
+        }
+    }
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            schema_version: CONFIG_SCHEMA_VERSION,
+            window: WindowConfig::default(),
+            theme: ThemeConfig::default(),
+            display: DisplayConfig::default(),
+            monitoring: MonitoringConfig::default(),
+            columns: ColumnLayout::default(),
+            startup: StartupConfig::default(),
+            exporters: ExportersConfig::default(),
+            alerts: AlertsConfig::default(),
+            policies: PoliciesConfig::default(),
+            leak_detection: LeakDetectorConfig::default(),
+            anomalies: AnomaliesConfig::default(),
+            agent: AgentConfig::default(),
+            rpc: RpcConfig::default(),
+            collectors: CollectorsConfig::default(),
+            filters: Vec::new(),
+            profiles: ProfilesConfig::default(),
+            keymap: BTreeMap::new(),
+            filter_presets: FilterPreset::defaults(),
+            custom_columns: Vec::new(),
+        }
+    }
+}
+
+impl AppConfig {
+    /// This configuration with profile `name` applied and marked active
+    pub fn with_profile(&self, name: &str) -> Result<AppConfig, String> {
+        let profile = self.profiles.flatten(name)?;
+        let mut config = self.clone();
+        profile.apply(&mut config);
+        config.profiles.active = Some(profile.name);
+        Ok(config)
+    }
+
+    /// Configuration in effect: the base with the active profile applied
+    ///
+    /// An active profile that no longer resolves is ignored.
+    pub fn effective(&self) -> AppConfig {
+        self.profiles
+            .active
+            .as_deref()
+            .and_then(|name| self.with_profile(name).ok())
+            .unwrap_or_else(|| self.clone())
+    }
+}
+
+/// Environment variable naming a config file to use instead of the registry
+pub const CONFIG_PATH_ENV: &str = "TASK_MANAGER_CONFIG";
+
+/// Persistent storage for `AppConfig`
+pub trait ConfigStore: Send + Sync {
+    /// Read the stored configuration; defaults if nothing has been saved yet
+    fn load(&self) -> Result<AppConfig, Box<dyn std::error::Error>>;
+
+    /// Replace the stored configuration
+    fn save(&self, config: &AppConfig) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Where the configuration lives, for messages
+    fn location(&self) -> String;
+
+    /// Backing file that can be watched for external edits
+    fn file_path(&self) -> Option<&Path> {
+        None
+    }
+}
+
+/// Store the configuration for the current user
+///
+/// Uses `TASK_MANAGER_CONFIG` if set, otherwise the default config file if
+/// one exists, otherwise the registry.
+pub fn default_store() -> Box<dyn ConfigStore> {
+    if let Some(path) = std::env::var_os(CONFIG_PATH_ENV).filter(|p| !p.is_empty()) {
+        return Box::new(FileStore::new(path));
+    }
+    match FileStore::default_path() {
+        Some(path) if path.exists() => Box::new(FileStore::new(path)),
+        _ => Box::new(RegistryStore),
+    }
+}
+
+/// Registry backend at HKCU\Software\TaskManager
+///
+/// The full configuration is kept as JSON in the `Config` value. The
+/// individual DWORD values are still written so older builds keep reading
+/// window, theme, monitoring and startup settings.
+pub struct RegistryStore;
+
+impl ConfigStore for RegistryStore {
+    fn load(&self) -> Result<AppConfig, Box<dyn std::error::Error>> {
+        ConfigManager::load_from_registry()
+    }
+
+    fn save(&self, config: &AppConfig) -> Result<(), Box<dyn std::error::Error>> {
+        ConfigManager::save_to_registry(config)
+    }
+
+    fn location(&self) -> String {
+        format!("HKCU\\{}", REGISTRY_KEY)
+    }
+}
+
+/// JSON file backend
+///
+/// Saves are atomic: the file is written next to the target and renamed over
+/// it, so a crash never leaves a truncated config behind. A file that cannot
+/// be parsed is renamed to `<name>.corrupt-<unix time>` and defaults are
+/// used; a file upgraded from an older schema is first copied to
+/// `<name>.v<version>.bak`.
+pub struct FileStore {
+    path: PathBuf,
+}
+
+impl FileStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// `%APPDATA%\TaskManager\config.json`, or
+    /// `$XDG_CONFIG_HOME/task-manager/config.json` (`~/.config` fallback)
+    pub fn default_path() -> Option<PathBuf> {
+        let non_empty = |name: &str| std::env::var_os(name).filter(|v| !v.is_empty());
+        if let Some(appdata) = non_empty("APPDATA") {
+            return Some(PathBuf::from(appdata).join("TaskManager").join("config.json"));
+        }
+        let base = non_empty("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| non_empty("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(base.join("task-manager").join("config.json"))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn sibling(&self, suffix: &str) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(suffix);
+        PathBuf::from(path)
+    }
+
+    /// Move an unreadable file out of the way
+    fn quarantine(&self) -> std::io::Result<PathBuf> {
+        let secs = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let target = self.sibling(&format!(".corrupt-{}", secs));
+        fs::rename(&self.path, &target)?;
+        Ok(target)
+    }
+}
+
+impl ConfigStore for FileStore {
+    fn load(&self) -> Result<AppConfig, Box<dyn std::error::Error>> {
+        let json = match fs::read_to_string(&self.path) {
+            Ok(json) => json,
+            // Not saved yet, use defaults
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(AppConfig::default()),
+            Err(e) => return Err(e.into()),
+        };
+
+        match parse_config(&json) {
+            Ok((config, stored)) => {
+                // Keep the original around until the user is happy with the upgrade
+                let backup = self.sibling(&format!(".v{}.bak", stored));
+                if stored < CONFIG_SCHEMA_VERSION && !backup.exists() {
+                    fs::write(&backup, &json)?;
+                }
+                Ok(config)
+            }
+            Err(e) => {
+                let moved = self.quarantine()?;
+                EventLogger::new("TaskManager").log_warning(&format!(
+                    "Invalid configuration {} ({}); moved to {} and using defaults",
+                    self.path.display(),
+                    e,
+                    moved.display()
+                ));
+                Ok(AppConfig::default())
+            }
+        }
+    }
+
+    fn save(&self, config: &AppConfig) -> Result<(), Box<dyn std::error::Error>> {
+        let json = serde_json::to_string_pretty(config)?;
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+
+        let temp = self.sibling(".tmp");
+        let written = fs::File::create(&temp).and_then(|mut file| {
+            file.write_all(json.as_bytes())?;
+            file.sync_all()
+        });
+        if let Err(e) = written.and_then(|_| fs::rename(&temp, &self.path)) {
+            let _ = fs::remove_file(&temp);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    fn location(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn file_path(&self) -> Option<&Path> {
+        Some(&self.path)
+    }
+}
+
+/// Top-level section of `AppConfig`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ConfigSection {
+    Window,
+    Theme,
+    Monitoring,
+    Columns,
+    Startup,
+    Exporters,
+    Alerts,
+    Policies,
+    LeakDetection,
+    Anomalies,
+    Agent,
+    Rpc,
+    Collectors,
+    Filters,
+    Profiles,
+    Keymap,
+    FilterPresets,
+    CustomColumns,
+    Display,
 }
 
-/// Startup options (T420)
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct StartupConfig {
-    /// Run at login
-    pub run_at_login: bool,
-    /// Start minimized
-    pub start_minimized: bool,
-    /// Performance mode (disable animations)
-    pub performance_mode: bool,
+impl ConfigSection {
+    pub const ALL: [ConfigSection; 19] = [
+        ConfigSection::Window,
+        ConfigSection::Theme,
+        ConfigSection::Monitoring,
+        ConfigSection::Columns,
+        ConfigSection::Startup,
+        ConfigSection::Exporters,
+        ConfigSection::Alerts,
+        ConfigSection::Policies,
+        ConfigSection::LeakDetection,
+        ConfigSection::Anomalies,
+        ConfigSection::Agent,
+        ConfigSection::Rpc,
+        ConfigSection::Collectors,
+        ConfigSection::Filters,
+        ConfigSection::Profiles,
+        ConfigSection::Keymap,
+        ConfigSection::FilterPresets,
+        ConfigSection::CustomColumns,
+        ConfigSection::Display,
+    ];
+
+    /// This section of `config` as JSON, for comparison
+    fn value(self, config: &AppConfig) -> serde_json::Value {
+        let value = match self {
+            ConfigSection::Window => serde_json::to_value(&config.window),
+            ConfigSection::Theme => serde_json::to_value(&config.theme),
+            ConfigSection::Monitoring => serde_json::to_value(&config.monitoring),
+            ConfigSection::Columns => serde_json::to_value(&config.columns),
+            ConfigSection::Startup => serde_json::to_value(&config.startup),
+            ConfigSection::Exporters => serde_json::to_value(&config.exporters),
+            ConfigSection::Alerts => serde_json::to_value(&config.alerts),
+            ConfigSection::Policies => serde_json::to_value(&config.policies),
+            ConfigSection::LeakDetection => serde_json::to_value(&config.leak_detection),
+            ConfigSection::Anomalies => serde_json::to_value(&config.anomalies),
+            ConfigSection::Agent => serde_json::to_value(&config.agent),
+            ConfigSection::Rpc => serde_json::to_value(&config.rpc),
+            ConfigSection::Collectors => serde_json::to_value(&config.collectors),
+            ConfigSection::Filters => serde_json::to_value(&config.filters),
+            ConfigSection::Profiles => serde_json::to_value(&config.profiles),
+            ConfigSection::Keymap => serde_json::to_value(&config.keymap),
+            ConfigSection::FilterPresets => serde_json::to_value(&config.filter_presets),
+            ConfigSection::CustomColumns => serde_json::to_value(&config.custom_columns),
+            ConfigSection::Display => serde_json::to_value(&config.display),
+        };
+        value.unwrap_or(serde_json::Value::Null)
+    }
+
+    /// Copy this section from `from` into `to`
+    fn copy(self, from: &AppConfig, to: &mut AppConfig) {
+        match self {
+            ConfigSection::Window => to.window = from.window.clone(),
+            ConfigSection::Theme => to.theme = from.theme.clone(),
+            ConfigSection::Monitoring => to.monitoring = from.monitoring.clone(),
+            ConfigSection::Columns => to.columns = from.columns.clone(),
+            ConfigSection::Startup => to.startup = from.startup.clone(),
+            ConfigSection::Exporters => to.exporters = from.exporters.clone(),
+            ConfigSection::Alerts => to.alerts = from.alerts.clone(),
+            ConfigSection::Policies => to.policies = from.policies.clone(),
+            ConfigSection::LeakDetection => to.leak_detection = from.leak_detection.clone(),
+            ConfigSection::Anomalies => to.anomalies = from.anomalies.clone(),
+            ConfigSection::Agent => to.agent = from.agent.clone(),
+            ConfigSection::Rpc => to.rpc = from.rpc.clone(),
+            ConfigSection::Collectors => to.collectors = from.collectors.clone(),
+            ConfigSection::Filters => to.filters = from.filters.clone(),
+            ConfigSection::Profiles => to.profiles = from.profiles.clone(),
+            ConfigSection::Keymap => to.keymap = from.keymap.clone(),
+            ConfigSection::FilterPresets => to.filter_presets = from.filter_presets.clone(),
+            ConfigSection::CustomColumns => to.custom_columns = from.custom_columns.clone(),
+            ConfigSection::Display => to.display = from.display.clone(),
+        }
+    }
 }
 
-impl Default for AppConfig {
-    fn default() -> Self {
-        Self {
-            window: WindowConfig {
-                x: 100,
-                y: 100,
-                width: 1200,
-                height: 800,
-                maximized: false,
-            },
-            theme: ThemeConfig {
-                preference: Theme::System,
-            },
-            monitoring: MonitoringConfig {
-                refresh_rate_ms: 1000,
-                history_length_sec: 60,
-                graph_type: 0, // Line
-            },
-            columns: ColumnConfig {
-                widths: Default::default(),
-                visibility: Default::default(),
-            },
-            startup: StartupConfig {
-                run_at_login: false,
-                start_minimized: false,
-                performance_mode: false,
-            },
-        }
-    }
-}
-
-/// Configuration manager with async loading and registry backend
+/// Sections that differ between two configurations
+pub fn changed_sections(old: &AppConfig, new: &AppConfig) -> Vec<ConfigSection> {
+    ConfigSection::ALL
+        .into_iter()
+        .filter(|section| section.value(old) != section.value(new))
+        .collect()
+}
+
+/// Configuration manager with async loading and a pluggable store
 pub struct ConfigManager {
     config: Arc<RwLock<AppConfig>>,
-    dirty: Arc<RwLock<bool>>,
+    /// Sections with unsaved in-memory edits
+    dirty: Arc<RwLock<BTreeSet<ConfigSection>>>,
+    store: Box<dyn ConfigStore>,
 }
 
 impl ConfigManager {
-    /// Create new configuration manager
+    /// Create new configuration manager backed by `default_store()`
     pub fn new() -> Self {
         Self {
             config: Arc::new(RwLock::new(AppConfig::default())),
-            dirty: Arc::new(RwLock::new(false)),
+            dirty: Arc::new(RwLock::new(BTreeSet::new())),
+            store: default_store(),
         }
     }
 
-    /// Load configuration from registry (T427)
+    /// Use a specific store for `load` and `save`
+    pub fn with_store(mut self, store: Box<dyn ConfigStore>) -> Self {
+        self.store = store;
+        self
+    }
+
+    /// Where `load` and `save` read and write
+    pub fn location(&self) -> String {
+        self.store.location()
+    }
+
+    /// Config file to watch for hot reload, if the store is file-based
+    pub fn file_path(&self) -> Option<PathBuf> {
+        self.store.file_path().map(Path::to_path_buf)
+    }
+
+    /// Load configuration from the store (T427)
+    ///
+    /// Out-of-range values are clamped (window bounds to the current virtual
+    /// desktop) and reported as warnings.
     ///
     /// Target: <50ms load time
     pub fn load(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let loaded = Self::load_from_registry()?;
+        let mut loaded = self.store.load()?;
+        let logger = EventLogger::new("TaskManager");
+        for fixed in validation::sanitize(&mut loaded, validation::virtual_desktop().as_ref()) {
+            logger.log_warning(&format!("Configuration {}", fixed));
+        }
+        if let Err(errors) = validation::validate(&loaded) {
+            for error in errors.errors {
+                logger.log_warning(&format!("Configuration {}", error));
+            }
+        }
         
         let mut config = self.config.write().unwrap();
         *config = loaded;
@@ -140,13 +1361,12 @@ impl ConfigManager {
         Ok(())
     }
 
-    /// Save configuration to registry
+    /// Save configuration to the store
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
         let config = self.config.read().unwrap();
-        Self::save_to_registry(&config)?;
+        self.store.save(&config)?;
         
-        let mut dirty = self.dirty.write().unwrap();
-        *dirty = false;
+        self.dirty.write().unwrap().clear();
         
         Ok(())
     }
@@ -156,6 +1376,11 @@ impl ConfigManager {
         self.config.read().unwrap().clone()
     }
 
+    /// Current configuration with the active profile applied
+    pub fn effective(&self) -> AppConfig {
+        self.config.read().unwrap().effective()
+    }
+
     /// Update window configuration (T424)
     pub fn set_window_bounds(&self, bounds: RECT, maximized: bool) {
         let mut config = self.config.write().unwrap();
@@ -165,7 +1390,7 @@ impl ConfigManager {
         config.window.height = bounds.bottom - bounds.top;
         config.window.maximized = maximized;
         
-        *self.dirty.write().unwrap() = true;
+        self.mark_dirty(ConfigSection::Window);
     }
 
     /// Update theme preference (T425)
@@ -173,33 +1398,64 @@ impl ConfigManager {
         let mut config = self.config.write().unwrap();
         config.theme.preference = theme;
         
-        *self.dirty.write().unwrap() = true;
+        self.mark_dirty(ConfigSection::Theme);
+    }
+
+    /// Update locale and units
+    pub fn set_display(&self, display: DisplayConfig) {
+        let mut config = self.config.write().unwrap();
+        config.display = display;
+
+        self.mark_dirty(ConfigSection::Display);
     }
 
     /// Update monitoring settings (T425)
-    pub fn set_monitoring(&self, refresh_rate_ms: u32, history_length_sec: u32, graph_type: u32) {
+    ///
+    /// Leaves the configuration unchanged if the combination is invalid.
+    pub fn set_monitoring(&self, refresh_rate_ms: u32, history_length_sec: u32, graph_type: u32) -> Result<(), ValidationErrors> {
+        let monitoring = MonitoringConfig {
+            refresh_rate_ms,
+            history_length_sec,
+            graph_type,
+        };
+        validation::validate_monitoring(&monitoring)?;
+
         let mut config = self.config.write().unwrap();
-        config.monitoring.refresh_rate_ms = refresh_rate_ms;
-        config.monitoring.history_length_sec = history_length_sec;
-        config.monitoring.graph_type = graph_type;
+        config.monitoring = monitoring;
         
-        *self.dirty.write().unwrap() = true;
+        self.mark_dirty(ConfigSection::Monitoring);
+        Ok(())
     }
 
     /// Update column width (T426)
-    pub fn set_column_width(&self, column_name: &str, width: i32) {
+    pub fn set_column_width(&self, column_name: &str, width: i32) -> Result<(), String> {
         let mut config = self.config.write().unwrap();
-        config.columns.widths.insert(column_name.to_string(), width);
+        config.columns.set_width(column_name, width)?;
         
-        *self.dirty.write().unwrap() = true;
+        self.mark_dirty(ConfigSection::Columns);
+        Ok(())
     }
 
     /// Update column visibility (T426)
-    pub fn set_column_visibility(&self, column_name: &str, visible: bool) {
+    pub fn set_column_visibility(&self, column_name: &str, visible: bool) -> Result<(), String> {
         let mut config = self.config.write().unwrap();
-        config.columns.visibility.insert(column_name.to_string(), visible);
+        config.columns.set_visible(column_name, visible)?;
         
-        *self.dirty.write().unwrap() = true;
+        self.mark_dirty(ConfigSection::Columns);
+        Ok(())
+    }
+
+    /// Replace the column layout, e.g. after columns were dragged around
+    ///
+    /// Leaves the configuration unchanged if the layout is invalid.
+    pub fn set_column_layout(&self, layout: ColumnLayout) -> Result<(), ValidationErrors> {
+        validation::validate_columns(&layout)?;
+
+        let mut config = self.config.write().unwrap();
+        config.columns = layout;
+
+        self.mark_dirty(ConfigSection::Columns);
+        Ok(())
     }
 
     /// Update startup options
@@ -209,7 +1465,88 @@ impl ConfigManager {
         config.startup.start_minimized = start_minimized;
         config.startup.performance_mode = performance_mode;
         
-        *self.dirty.write().unwrap() = true;
+        self.mark_dirty(ConfigSection::Startup);
+    }
+
+    /// Replace the key binding overrides
+    ///
+    /// Leaves the configuration unchanged if a binding does not parse or two
+    /// bindings conflict.
+    pub fn set_keymap(
+        &self,
+        keymap: BTreeMap<String, Vec<String>>,
+    ) -> Result<(), ValidationErrors> {
+        validation::validate_keymap(&keymap)?;
+
+        let mut config = self.config.write().unwrap();
+        config.keymap = keymap;
+
+        self.mark_dirty(ConfigSection::Keymap);
+        Ok(())
+    }
+
+    /// Replace the filter presets
+    ///
+    /// Leaves the configuration unchanged if a preset is invalid.
+    pub fn set_filter_presets(&self, presets: Vec<FilterPreset>) -> Result<(), ValidationErrors> {
+        validation::validate_filter_presets(&presets)?;
+
+        let mut config = self.config.write().unwrap();
+        config.filter_presets = presets;
+
+        self.mark_dirty(ConfigSection::FilterPresets);
+        Ok(())
+    }
+
+    /// Add a preset, or replace the one with the same name (ignoring case)
+    pub fn save_filter_preset(&self, preset: FilterPreset) -> Result<(), ValidationErrors> {
+        let mut presets = self.get().filter_presets;
+        match presets.iter().position(|p| p.name.eq_ignore_ascii_case(&preset.name)) {
+            Some(index) => presets[index] = preset,
+            None => presets.push(preset),
+        }
+        self.set_filter_presets(presets)
+    }
+
+    /// Remove preset `name`; returns false if there is none
+    pub fn delete_filter_preset(&self, name: &str) -> bool {
+        let mut config = self.config.write().unwrap();
+        let before = config.filter_presets.len();
+        config.filter_presets.retain(|p| !p.name.eq_ignore_ascii_case(name));
+        let removed = config.filter_presets.len() != before;
+        if removed {
+            self.mark_dirty(ConfigSection::FilterPresets);
+        }
+        removed
+    }
+
+    /// Move preset `name` to `index` (clamped to the end of the list)
+    pub fn move_filter_preset(&self, name: &str, index: usize) -> Result<(), String> {
+        let mut config = self.config.write().unwrap();
+        let from = config
+            .filter_presets
+            .iter()
+            .position(|p| p.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("Unknown filter preset '{}'", name))?;
+        let preset = config.filter_presets.remove(from);
+        let index = index.min(config.filter_presets.len());
+        config.filter_presets.insert(index, preset);
+
+        self.mark_dirty(ConfigSection::FilterPresets);
+        Ok(())
+    }
+
+    /// Replace the computed columns
+    ///
+    /// Leaves the configuration unchanged if a column is invalid.
+    pub fn set_custom_columns(&self, columns: Vec<CustomColumn>) -> Result<(), ValidationErrors> {
+        validation::validate_custom_columns(&columns)?;
+
+        let mut config = self.config.write().unwrap();
+        config.custom_columns = columns;
+
+        self.mark_dirty(ConfigSection::CustomColumns);
+        Ok(())
     }
 
     /// Export configuration to JSON file (T428)
@@ -221,20 +1558,101 @@ impl ConfigManager {
     }
 
     /// Import configuration from JSON file (T428)
+    ///
+    /// Older schema versions are migrated; missing fields take defaults. The
+    /// window is fitted to this machine's desktop; any other invalid value
+    /// rejects the import with `ValidationErrors`.
     pub fn import_from_file(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
         let json = std::fs::read_to_string(path)?;
-        let loaded: AppConfig = serde_json::from_str(&json)?;
+        let (mut loaded, _) = parse_config(&json).map_err(|e| format!("{}: {}", path, e))?;
+        if let Some(desktop) = validation::virtual_desktop() {
+            validation::clamp_window(&mut loaded.window, &desktop);
+        }
+        validation::validate(&loaded)?;
         
         let mut config = self.config.write().unwrap();
         *config = loaded;
         
-        *self.dirty.write().unwrap() = true;
+        self.dirty.write().unwrap().extend(ConfigSection::ALL);
+        Ok(())
+    }
+
+    /// Make `name` the active profile, or go back to the base with None
+    ///
+    /// Returns the sections of the effective configuration that changed.
+    pub fn switch_profile(&self, name: Option<&str>) -> Result<Vec<ConfigSection>, String> {
+        let mut config = self.config.write().unwrap();
+        let before = config.effective();
+        let active = match name {
+            Some(name) => config.with_profile(name)?.profiles.active,
+            None => None,
+        };
+        if config.profiles.active != active {
+            config.profiles.active = active;
+            self.mark_dirty(ConfigSection::Profiles);
+        }
+        Ok(changed_sections(&before, &config.effective()))
+    }
+
+    /// Write profile `name` with everything it inherits to a standalone file
+    pub fn export_profile(
+        &self,
+        name: &str,
+        path: &Path,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let profile = self.config.read().unwrap().profiles.flatten(name)?;
+        fs::write(path, serde_json::to_string_pretty(&profile)?)?;
         Ok(())
     }
 
+    /// Add a profile file written by `export_profile`, replacing one with the same name
+    ///
+    /// Returns the profile name.
+    pub fn import_profile(&self, path: &Path) -> Result<String, Box<dyn std::error::Error>> {
+        let profile = Profile::from_file(path)?;
+        let name = profile.name.clone();
+
+        let mut config = self.config.write().unwrap();
+        let mut updated = config.clone();
+        updated.profiles.insert(profile);
+        validation::validate(&updated)?;
+        *config = updated;
+
+        self.mark_dirty(ConfigSection::Profiles);
+        Ok(name)
+    }
+
     /// Check if configuration has unsaved changes
     pub fn is_dirty(&self) -> bool {
-        *self.dirty.read().unwrap()
+        !self.dirty.read().unwrap().is_empty()
+    }
+
+    /// Sections with unsaved changes
+    pub fn dirty_sections(&self) -> Vec<ConfigSection> {
+        self.dirty.read().unwrap().iter().copied().collect()
+    }
+
+    /// Take a configuration changed outside the app (e.g. the watched file)
+    ///
+    /// Sections with unsaved in-memory edits are kept; every other section is
+    /// replaced. Returns the sections whose effective values (active profile
+    /// applied) actually changed.
+    pub fn apply_external(&self, external: AppConfig) -> Vec<ConfigSection> {
+        // Same order as the setters (config, then dirty) to avoid deadlocks
+        let mut config = self.config.write().unwrap();
+        let dirty = self.dirty.read().unwrap();
+
+        let mut merged = external;
+        for section in dirty.iter() {
+            section.copy(&config, &mut merged);
+        }
+        let changed = changed_sections(&config.effective(), &merged.effective());
+        *config = merged;
+        changed
+    }
+
+    fn mark_dirty(&self, section: ConfigSection) {
+        self.dirty.write().unwrap().insert(section);
     }
 
     /// Load from Windows Registry
@@ -257,6 +1675,21 @@ impl ConfigManager {
                 return Ok(config);
             }
 
+            // Full configuration written by newer builds
+            if let Ok(json) = Self::read_string(hkey, "Config") {
+                match parse_config(&json) {
+                    Ok((loaded, _)) => {
+                        let _ = RegCloseKey(hkey);
+                        return Ok(loaded);
+                    }
+                    // Fall back to the individual values below
+                    Err(e) => EventLogger::new("TaskManager").log_warning(&format!(
+                        "Invalid configuration in registry ({}); using individual values",
+                        e
+                    )),
+                }
+            }
+
             // Load window position (T424)
             if let Ok(x) = Self::read_dword(hkey, "WindowX") {
                 config.window.x = x as i32;
@@ -360,6 +1793,9 @@ impl ConfigManager {
             Self::write_dword(hkey, "StartMinimized", if config.startup.start_minimized { 1 } else { 0 })?;
             Self::write_dword(hkey, "PerformanceMode", if config.startup.performance_mode { 1 } else { 0 })?;
 
+            // Everything, including columns and the sections above
+            Self::write_string(hkey, "Config", &serde_json::to_string(config)?)?;
+
             let _ = RegCloseKey(hkey);
         }
 
@@ -391,6 +1827,77 @@ impl ConfigManager {
         Ok(data)
     }
 
+    /// Read REG_SZ string from registry
+    unsafe fn read_string(hkey: windows::Win32::System::Registry::HKEY, name: &str) -> Result<String, Box<dyn std::error::Error>> {
+        let value_name = crate::util::strings::to_wide_string(name);
+        let mut data_size = 0u32;
+
+        // SAFETY: Size query with no buffer, all parameters valid
+        let result = unsafe {
+            RegQueryValueExW(
+                hkey,
+                PCWSTR::from_raw(value_name.as_ptr()),
+                Some(std::ptr::null_mut()),
+                Some(std::ptr::null_mut()),
+                None,
+                Some(&mut data_size),
+            )
+        };
+        if result.is_err() {
+            return Err("Failed to read registry value".into());
+        }
+
+        let mut data = vec![0u16; (data_size as usize).div_ceil(2)];
+        // SAFETY: Buffer holds data_size bytes
+        let result = unsafe {
+            RegQueryValueExW(
+                hkey,
+                PCWSTR::from_raw(value_name.as_ptr()),
+                Some(std::ptr::null_mut()),
+                Some(std::ptr::null_mut()),
+                Some(data.as_mut_ptr() as *mut u8),
+                Some(&mut data_size),
+            )
+        };
+        if result.is_err() {
+            return Err("Failed to read registry value".into());
+        }
+
+        data.truncate(data_size as usize / 2);
+        while data.last() == Some(&0) {
+            data.pop();
+        }
+        Ok(String::from_utf16(&data)?)
+    }
+
+    /// Write REG_SZ string to registry
+    unsafe fn write_string(hkey: windows::Win32::System::Registry::HKEY, name: &str, value: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let value_name = crate::util::strings::to_wide_string(name);
+        let wide = crate::util::strings::to_wide_string(value);
+
+        // SAFETY: Viewing the UTF-16 buffer (including terminator) as bytes
+        let data_bytes = unsafe {
+            std::slice::from_raw_parts(wide.as_ptr() as *const u8, wide.len() * 2)
+        };
+
+        // SAFETY: Called within unsafe context, all parameters valid
+        let result = unsafe {
+            RegSetValueExW(
+                hkey,
+                PCWSTR::from_raw(value_name.as_ptr()),
+                Some(0),
+                REG_SZ,
+                Some(data_bytes),
+            )
+        };
+
+        if result.is_err() {
+            return Err("Failed to write registry value".into());
+        }
+
+        Ok(())
+    }
+
     /// Write DWORD to registry
     unsafe fn write_dword(hkey: windows::Win32::System::Registry::HKEY, name: &str, value: u32) -> Result<(), Box<dyn std::error::Error>> {
         let value_name = crate::util::strings::to_wide_string(name);
@@ -481,7 +1988,7 @@ mod tests {
     #[test]
     fn test_monitoring_update() {
         let manager = ConfigManager::new();
-        manager.set_monitoring(2000, 300, 1);
+        manager.set_monitoring(2000, 300, 1).unwrap();
         
         let config = manager.get();
         assert_eq!(config.monitoring.refresh_rate_ms, 2000);
@@ -492,23 +1999,39 @@ mod tests {
     #[test]
     fn test_column_width() {
         let manager = ConfigManager::new();
-        manager.set_column_width("Name", 250);
-        manager.set_column_width("CPU", 80);
+        manager.set_column_width("Name", 250).unwrap();
+        manager.set_column_width("CPU", 80).unwrap();
         
         let config = manager.get();
-        assert_eq!(config.columns.widths.get("Name"), Some(&250));
-        assert_eq!(config.columns.widths.get("CPU"), Some(&80));
+        assert_eq!(config.columns.get("name").unwrap().width, 250);
+        assert_eq!(config.columns.get("cpu").unwrap().width, 80);
     }
 
     #[test]
     fn test_column_visibility() {
         let manager = ConfigManager::new();
-        manager.set_column_visibility("PID", true);
-        manager.set_column_visibility("Threads", false);
+        manager.set_column_visibility("PID", false).unwrap();
+        assert!(manager.set_column_visibility("Threads", false).is_err());
         
         let config = manager.get();
-        assert_eq!(config.columns.visibility.get("PID"), Some(&true));
-        assert_eq!(config.columns.visibility.get("Threads"), Some(&false));
+        assert!(!config.columns.get("pid").unwrap().visible);
+        assert!(config.columns.get("cpu").unwrap().visible);
+    }
+
+    #[test]
+    fn test_column_layout_saved_as_unit() {
+        let manager = ConfigManager::new();
+        let mut layout = manager.get().columns;
+        layout.move_column("handles", 1).unwrap();
+        layout.set_pinned("pid", true).unwrap();
+        manager.set_column_layout(layout.clone()).unwrap();
+        assert_eq!(manager.dirty_sections(), vec![ConfigSection::Columns]);
+        assert_eq!(manager.get().columns, layout);
+
+        layout.set_width("cpu", 0).unwrap();
+        let errors = manager.set_column_layout(layout).unwrap_err();
+        assert_eq!(errors.errors[0].field, "columns[3].width");
+        assert_eq!(manager.get().columns.get("cpu").unwrap().width, 80);
     }
 
     #[test]
@@ -526,7 +2049,7 @@ mod tests {
     fn test_json_export_import() {
         let manager = ConfigManager::new();
         manager.set_theme(Theme::Dark);
-        manager.set_monitoring(500, 3600, 2);
+        manager.set_monitoring(500, 3600, 2).unwrap();
         
         let temp_path = "test_config.json";
         manager.export_to_file(temp_path).unwrap();
@@ -541,4 +2064,310 @@ mod tests {
         
         let _ = std::fs::remove_file(temp_path);
     }
+
+    #[test]
+    fn test_apply_external_keeps_dirty_sections() {
+        let manager = ConfigManager::new();
+        manager.set_theme(Theme::Dark);
+
+        let mut external = AppConfig::default();
+        external.theme.preference = Theme::Light;
+        external.monitoring.refresh_rate_ms = 2000;
+        external.columns.set_width("name", 300).unwrap();
+
+        let changed = manager.apply_external(external.clone());
+        assert_eq!(changed, vec![ConfigSection::Monitoring, ConfigSection::Columns]);
+        let config = manager.get();
+        assert_eq!(config.theme.preference, Theme::Dark);
+        assert_eq!(config.monitoring.refresh_rate_ms, 2000);
+        assert_eq!(manager.dirty_sections(), vec![ConfigSection::Theme]);
+
+        // Same file again: nothing to apply
+        assert!(manager.apply_external(external).is_empty());
+    }
+
+    fn sample_profiles() -> ProfilesConfig {
+        ProfilesConfig {
+            active: None,
+            profiles: vec![
+                Profile::new("Low overhead")
+                    .with_refresh_rate_ms(5000)
+                    .with_history_length_sec(60)
+                    .with_column("Handles", false),
+                Profile::new("Debugging a leak")
+                    .with_parent("Low overhead")
+                    .with_history_length_sec(3600)
+                    .with_column("Leak Rate", true)
+                    .with_filters(vec!["mem>500MB".to_string()]),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_profile_inheritance_and_cycles() {
+        let config = AppConfig {
+            profiles: sample_profiles(),
+            ..AppConfig::default()
+        };
+        let leak = config.with_profile("debugging a leak").unwrap();
+        assert_eq!(leak.profiles.active.as_deref(), Some("Debugging a leak"));
+        assert_eq!(leak.monitoring.refresh_rate_ms, 5000);
+        assert_eq!(leak.monitoring.history_length_sec, 3600);
+        assert!(!leak.columns.get("handles").unwrap().visible);
+        assert!(leak.columns.get("leak_rate").unwrap().visible);
+        assert_eq!(leak.filters, vec!["mem>500MB".to_string()]);
+        assert_eq!(leak.alerts.rules, config.alerts.rules);
+
+        let flat = config.profiles.flatten("Debugging a leak").unwrap();
+        assert_eq!(flat.inherits, None);
+        assert_eq!(flat.refresh_rate_ms, Some(5000));
+        assert_eq!(flat.columns.len(), 2);
+
+        let mut cyclic = config.clone();
+        cyclic.profiles.profiles[0].inherits = Some("Debugging a leak".to_string());
+        assert!(cyclic.with_profile("Low overhead").is_err());
+        assert!(config.with_profile("Presentation").is_err());
+
+        // A dangling active profile falls back to the base
+        let mut dangling = config.clone();
+        dangling.profiles.active = Some("Presentation".to_string());
+        assert_eq!(dangling.effective().monitoring.refresh_rate_ms, 1000);
+    }
+
+    #[test]
+    fn test_switch_export_and_import_profile() {
+        let manager = ConfigManager::new();
+        manager.apply_external(AppConfig {
+            profiles: sample_profiles(),
+            ..AppConfig::default()
+        });
+
+        let changed = manager.switch_profile(Some("Low overhead")).unwrap();
+        assert_eq!(
+            changed,
+            vec![ConfigSection::Monitoring, ConfigSection::Columns, ConfigSection::Profiles]
+        );
+        assert_eq!(manager.effective().monitoring.refresh_rate_ms, 5000);
+        assert_eq!(manager.get().monitoring.refresh_rate_ms, 1000);
+        assert!(manager.switch_profile(Some("Presentation")).is_err());
+        assert_eq!(manager.dirty_sections(), vec![ConfigSection::Profiles]);
+
+        let path = std::env::temp_dir().join(format!("tm-profile-{}.json", std::process::id()));
+        manager.export_profile("Debugging a leak", &path).unwrap();
+        let exported = Profile::from_file(&path).unwrap();
+        assert_eq!(exported.history_length_sec, Some(3600));
+        assert_eq!(exported.refresh_rate_ms, Some(5000));
+
+        let other = ConfigManager::new();
+        assert_eq!(other.import_profile(&path).unwrap(), "Debugging a leak");
+        assert_eq!(
+            other.get().with_profile("Debugging a leak").unwrap().monitoring.refresh_rate_ms,
+            5000
+        );
+
+        fs::write(
+            &path,
+            r#"{"name": "Fast", "refresh_rate_ms": 1, "filters": ["cpu<5"]}"#,
+        )
+        .unwrap();
+        let error = other.import_profile(&path).unwrap_err();
+        let errors = error.downcast_ref::<ValidationErrors>().unwrap();
+        assert!(errors.for_field("profiles.profiles[1].refresh_rate_ms").is_some());
+        assert!(errors.for_field("profiles.profiles[1].filters[0]").is_some());
+        assert_eq!(other.get().profiles.profiles.len(), 1);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_invalid_monitoring_and_import_rejected() {
+        let manager = ConfigManager::new();
+        let errors = manager.set_monitoring(0, 86_400 * 365, 1).unwrap_err();
+        assert!(errors.for_field("monitoring.refresh_rate_ms").is_some());
+        assert!(errors.for_field("monitoring.history_length_sec").is_some());
+        assert!(!manager.is_dirty());
+        assert_eq!(manager.get().monitoring.refresh_rate_ms, 1000);
+
+        let temp_path = "test_config_invalid.json";
+        std::fs::write(temp_path, r#"{"columns": {"widths": {"Name": -40}}}"#).unwrap();
+        let error = manager.import_from_file(temp_path).unwrap_err();
+        let errors = error.downcast_ref::<ValidationErrors>().unwrap();
+        assert_eq!(errors.errors[0].field, "columns[0].width");
+        assert_eq!(manager.get().columns, ColumnLayout::default());
+        let _ = std::fs::remove_file(temp_path);
+    }
+
+    #[test]
+    fn test_file_store_round_trip() {
+        let dir = std::env::temp_dir().join(format!("tm-config-{}", std::process::id()));
+        let path = dir.join("nested").join("config.json");
+        let store = FileStore::new(&path);
+
+        // Missing file means defaults
+        assert_eq!(store.load().unwrap().monitoring.refresh_rate_ms, 1000);
+
+        let mut config = AppConfig::default();
+        config.columns.set_width("name", 250).unwrap();
+        config.columns.set_visible("handles", false).unwrap();
+        config.alerts.rules.push(AlertRule::new(
+            "cpu",
+            AlertMetric::SystemCpuPercent,
+            AlertComparison::Above,
+            99.0,
+        ));
+        config.rpc.port = 9999;
+        store.save(&config).unwrap();
+        assert!(!dir.join("nested").join("config.json.tmp").exists());
+
+        let manager = ConfigManager::new().with_store(Box::new(FileStore::new(&path)));
+        manager.load().unwrap();
+        let loaded = manager.get();
+        assert_eq!(loaded.columns, config.columns);
+        assert_eq!(loaded.alerts.rules.len(), config.alerts.rules.len());
+        assert_eq!(loaded.rpc.port, 9999);
+        assert_eq!(
+            serde_json::to_value(&loaded).unwrap(),
+            serde_json::to_value(&config).unwrap()
+        );
+
+        // Saving through the manager replaces the file and clears dirty state
+        manager.set_monitoring(500, 300, 1).unwrap();
+        manager.save().unwrap();
+        assert!(!manager.is_dirty());
+        assert_eq!(store.load().unwrap().monitoring.refresh_rate_ms, 500);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_v1_config_migrates_without_losing_layout() {
+        // Pre-versioning export: no schema_version, missing sections
+        let v1 = r#"{
+            "window": {"x": 10, "y": 20, "width": 800, "height": 600, "maximized": true},
+            "theme": {"preference": "Dark"},
+            "monitoring": {"refresh_rate_ms": 2000, "history_length_sec": 300},
+            "columns": {"widths": {"Name": 250}, "visibility": {"PID": false}},
+            "alerts": {"enabled": true, "rules": [{
+                "name": "hot", "metric": "SystemCpuPercent", "comparison": "Above",
+                "threshold": 95.0
+            }]}
+        }"#;
+        let (config, stored) = parse_config(v1).unwrap();
+        assert_eq!(stored, 1);
+        assert_eq!(config.schema_version, CONFIG_SCHEMA_VERSION);
+        assert_eq!(config.window.width, 800);
+        assert_eq!(config.theme.preference, Theme::Dark);
+        assert_eq!(config.monitoring.refresh_rate_ms, 2000);
+        assert_eq!(config.monitoring.graph_type, 0);
+        assert_eq!(config.columns.get("name").unwrap().width, 250);
+        assert!(!config.columns.get("pid").unwrap().visible);
+        assert!(config.columns.get("cpu").unwrap().visible);
+        assert!(!config.startup.run_at_login);
+        assert_eq!(config.alerts.rules.len(), 1);
+        assert_eq!(config.alerts.rules[0].name, "hot");
+        assert_eq!(config.rpc.port, RpcConfig::default().port);
+        assert_eq!(config.filter_presets, FilterPreset::defaults());
+
+        assert!(parse_config("[1, 2]").is_err());
+        assert!(parse_config(r#"{"schema_version": "two"}"#).is_err());
+    }
+
+    #[test]
+    fn test_edit_filter_presets() {
+        let manager = ConfigManager::new();
+        let custom = FilterPreset::new("Browsers")
+            .with_filter("name=chrome")
+            .with_sort("memory", true);
+        manager.save_filter_preset(custom.clone()).unwrap();
+        assert_eq!(manager.dirty_sections(), vec![ConfigSection::FilterPresets]);
+        assert_eq!(manager.get().filter_presets.last(), Some(&custom));
+
+        // Same name replaces; built-ins are editable
+        let edited = FilterPreset::new("high cpu").with_filter("cpu>50");
+        manager.save_filter_preset(edited.clone()).unwrap();
+        let presets = manager.get().filter_presets;
+        assert_eq!(presets.len(), 6);
+        assert_eq!(presets[1], edited);
+
+        manager.move_filter_preset("browsers", 0).unwrap();
+        assert_eq!(manager.get().filter_presets[0].name, "Browsers");
+        assert!(manager.move_filter_preset("Missing", 0).is_err());
+
+        assert!(manager.delete_filter_preset("All Processes"));
+        assert!(!manager.delete_filter_preset("All Processes"));
+        assert_eq!(manager.get().filter_presets.len(), 5);
+
+        let invalid = FilterPreset::new("Bad").with_filter("cpu>>5");
+        let errors = manager.save_filter_preset(invalid).unwrap_err();
+        assert_eq!(errors.errors[0].field, "filter_presets[5].filters[0]");
+        assert_eq!(manager.get().filter_presets.len(), 5);
+    }
+
+    #[test]
+    fn test_set_custom_columns() {
+        use crate::core::expression::ColumnFormat;
+
+        let manager = ConfigManager::new();
+        let columns = vec![CustomColumn::new(
+            "per_thread",
+            "memory_private / threads",
+            ColumnFormat::Bytes,
+        )];
+        manager.set_custom_columns(columns.clone()).unwrap();
+        assert_eq!(manager.dirty_sections(), vec![ConfigSection::CustomColumns]);
+
+        let json = serde_json::to_value(manager.get()).unwrap();
+        assert_eq!(json["custom_columns"][0]["format"], "bytes");
+        let (parsed, _) = parse_config(&json.to_string()).unwrap();
+        assert_eq!(parsed.custom_columns, columns);
+
+        let invalid = vec![CustomColumn::new("cpu", "1", ColumnFormat::Count)];
+        let errors = manager.set_custom_columns(invalid).unwrap_err();
+        assert_eq!(errors.errors[0].field, "custom_columns[0].name");
+        assert_eq!(manager.get().custom_columns, columns);
+    }
+
+    #[test]
+    fn test_set_display() {
+        let manager = ConfigManager::new();
+        assert_eq!(manager.get().display.number_format(), NumberFormat::new());
+
+        manager.set_display(DisplayConfig {
+            locale: Some("de_DE".to_string()),
+            units: UnitSystem::Si,
+        });
+        assert_eq!(manager.dirty_sections(), vec![ConfigSection::Display]);
+        let display = manager.get().display;
+        assert_eq!(display.locale().code(), "de-DE");
+        assert_eq!(display.number_format().bytes(1_500_000), "1,5 MB");
+
+        let json = serde_json::to_value(manager.get()).unwrap();
+        assert_eq!(json["display"]["units"], "si");
+        let (parsed, _) = parse_config(&json.to_string()).unwrap();
+        assert_eq!(parsed.display, display);
+    }
+
+    #[test]
+    fn test_file_store_backup_and_quarantine() {
+        let dir = std::env::temp_dir().join(format!("tm-config-q-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.json");
+        let store = FileStore::new(&path);
+
+        fs::write(&path, r#"{"columns": {"widths": {"CPU": 80}}}"#).unwrap();
+        let config = store.load().unwrap();
+        assert_eq!(config.columns.get("cpu").unwrap().width, 80);
+        assert!(dir.join("config.json.v1.bak").exists());
+
+        fs::write(&path, "{ not json").unwrap();
+        let config = store.load().unwrap();
+        assert_eq!(config.window.width, 1200);
+        assert!(!path.exists());
+        let quarantined = fs::read_dir(&dir)
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().contains(".corrupt-"))
+            .count();
+        assert_eq!(quarantined, 1);
+        let _ = fs::remove_dir_all(&dir);
+    }
 }
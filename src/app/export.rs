@@ -1,10 +1,11 @@
 //! Data export functionality for performance metrics
 //!
-//! Supports CSV, JSON, and SQLite export formats.
+//! Supports CSV, JSON, SQLite and self-contained HTML report formats.
 
 use std::fs::File;
 use std::io::{Write, BufWriter};
 use std::path::Path;
+use crate::app::report::{render_html_report, ReportHistory, ReportOptions};
 use crate::core::metrics::SystemMetrics;
 
 /// Export file format options
@@ -16,6 +17,8 @@ pub enum ExportFormat {
     Json,
     /// SQLite database format
     Sqlite,
+    /// Single-file HTML performance report with inline SVG charts
+    HtmlReport,
 }
 
 impl ExportFormat {
@@ -25,6 +28,7 @@ impl ExportFormat {
             ExportFormat::Csv => "csv",
             ExportFormat::Json => "json",
             ExportFormat::Sqlite => "db",
+            ExportFormat::HtmlReport => "html",
         }
     }
 
@@ -34,6 +38,7 @@ impl ExportFormat {
             ExportFormat::Csv => "CSV (Comma Separated Values)",
             ExportFormat::Json => "JSON (JavaScript Object Notation)",
             ExportFormat::Sqlite => "SQLite Database",
+            ExportFormat::HtmlReport => "HTML Performance Report",
         }
     }
 }
@@ -50,6 +55,7 @@ pub struct DataPoint {
 pub struct DataExporter {
     format: ExportFormat,
     data_points: Vec<DataPoint>,
+    report: Option<(ReportHistory, ReportOptions)>,
 }
 
 impl DataExporter {
@@ -58,9 +64,15 @@ impl DataExporter {
         Self {
             format,
             data_points: Vec::new(),
+            report: None,
         }
     }
 
+    /// Sets the recorded history window rendered by `ExportFormat::HtmlReport`
+    pub fn set_report_history(&mut self, history: ReportHistory, options: ReportOptions) {
+        self.report = Some((history, options));
+    }
+
     /// Adds a single data point to the export queue
     pub fn add_data_point(&mut self, timestamp: u64, metric_name: impl Into<String>, value: f32) {
         self.data_points.push(DataPoint {
@@ -78,6 +90,9 @@ impl DataExporter {
         self.add_data_point(timestamp, "memory_used_mb", memory_used_mb);
         self.add_data_point(timestamp, "memory_total_mb", memory_total_mb);
         self.add_data_point(timestamp, "memory_load_percent", metrics.memory_load_percent as f32);
+        for (name, value) in &metrics.custom {
+            self.add_data_point(timestamp, name.clone(), *value as f32);
+        }
     }
 
     pub fn clear(&mut self) {
@@ -89,6 +104,7 @@ impl DataExporter {
             ExportFormat::Csv => self.export_csv(path),
             ExportFormat::Json => self.export_json(path),
             ExportFormat::Sqlite => self.export_sqlite(path),
+            ExportFormat::HtmlReport => self.export_html_report(path),
         }
     }
 
@@ -163,6 +179,21 @@ impl DataExporter {
         Ok(())
     }
 
+    fn export_html_report(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let Some((history, options)) = &self.report else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "HTML report requires a recorded history window",
+            ));
+        };
+
+        let file = File::create(path)?;
+        let mut writer = BufWriter::new(file);
+        writer.write_all(render_html_report(history, options).as_bytes())?;
+        writer.flush()?;
+        Ok(())
+    }
+
     fn export_sqlite(&self, _path: impl AsRef<Path>) -> std::io::Result<()> {
         // SQLite export requires rusqlite crate
         // For now, return an error as it's not implemented
@@ -199,6 +230,7 @@ mod tests {
         assert_eq!(ExportFormat::Csv.extension(), "csv");
         assert_eq!(ExportFormat::Json.extension(), "json");
         assert_eq!(ExportFormat::Sqlite.extension(), "db");
+        assert_eq!(ExportFormat::HtmlReport.extension(), "html");
     }
 
     #[test]
@@ -224,4 +256,27 @@ mod tests {
         std::fs::remove_file(temp_file)?;
         Ok(())
     }
+
+    #[test]
+    fn test_html_report_requires_history() {
+        let exporter = DataExporter::new(ExportFormat::HtmlReport);
+        let temp_file = std::env::temp_dir().join("test_export_missing.html");
+        let err = exporter.export_to_file(&temp_file).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_html_report_export() -> std::io::Result<()> {
+        let mut exporter = DataExporter::new(ExportFormat::HtmlReport);
+        exporter.set_report_history(ReportHistory::new(10), ReportOptions::new());
+
+        let temp_file = std::env::temp_dir().join("test_export.html");
+        exporter.export_to_file(&temp_file)?;
+
+        let content = std::fs::read_to_string(&temp_file)?;
+        assert!(content.starts_with("<!DOCTYPE html>"));
+
+        std::fs::remove_file(temp_file)?;
+        Ok(())
+    }
 }
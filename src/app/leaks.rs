@@ -0,0 +1,176 @@
+//! Background leak analysis
+//!
+//! `LeakMonitor` feeds every snapshot through its own `ProcessStore` into a
+//! `core::leak::LeakDetector` and turns newly detected and resolved findings
+//! into `AlertEvent`s for the regular alert handlers. The detector is shared
+//! behind a mutex; `apply_leak_rates` copies its findings into process rows
+//! for the Leak Rate column, sorting, filters and computed columns.
+
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::app::alerts::{AlertEvent, AlertEventKind, AlertHandler};
+use crate::app::config::AlertSeverity;
+use crate::app::updater::SnapshotSink;
+use crate::core::filter::ProcessInfo;
+use crate::core::leak::{LeakChange, LeakDetector, LeakDetectorConfig, LeakFinding, LeakMetric};
+use crate::core::process::ProcessStore;
+use crate::util::logging::EventLogger;
+use crate::windows::monitor::ProcessSnapshot;
+
+/// Snapshot sink running the leak detector
+pub struct LeakMonitor {
+    store: ProcessStore,
+    detector: Arc<Mutex<LeakDetector>>,
+    handlers: Vec<Box<dyn AlertHandler>>,
+}
+
+impl LeakMonitor {
+    pub fn new(config: LeakDetectorConfig) -> Self {
+        Self {
+            store: ProcessStore::new(),
+            detector: Arc::new(Mutex::new(LeakDetector::new(config))),
+            handlers: Vec::new(),
+        }
+    }
+
+    /// Monitor for `config` reporting to the event log
+    pub fn from_config(config: &LeakDetectorConfig) -> Self {
+        Self::new(config.clone()).with_handler(Box::new(EventLogger::new("TaskManager")))
+    }
+
+    pub fn with_handler(mut self, handler: Box<dyn AlertHandler>) -> Self {
+        self.handlers.push(handler);
+        self
+    }
+
+    /// Shared detector, for reading findings from the UI thread
+    pub fn detector(&self) -> Arc<Mutex<LeakDetector>> {
+        Arc::clone(&self.detector)
+    }
+}
+
+impl SnapshotSink for LeakMonitor {
+    fn on_snapshot(&mut self, snapshot: &ProcessSnapshot) {
+        self.store.update(snapshot.processes.clone());
+        let (changes, config) = match self.detector.lock() {
+            Ok(mut detector) => (
+                detector.observe(&self.store, snapshot.timestamp),
+                detector.config().clone(),
+            ),
+            Err(_) => return,
+        };
+
+        for change in changes {
+            let (finding, kind) = match &change {
+                LeakChange::Detected(finding) => (finding, AlertEventKind::Fired),
+                LeakChange::Resolved(finding) => (finding, AlertEventKind::Cleared),
+            };
+            let min_growth = match finding.metric {
+                LeakMetric::PrivateBytes => config.min_private_growth_per_hour,
+                LeakMetric::Handles => config.min_handle_growth_per_hour,
+            };
+            let event = leak_event(finding, kind, min_growth, snapshot);
+            for handler in &mut self.handlers {
+                handler.on_alert(&event);
+            }
+        }
+    }
+}
+
+/// Set `leak_rate` on each row to its private bytes growth per hour
+///
+/// Rows without a finding, or whose PID now belongs to another image, get
+/// `None`.
+pub fn apply_leak_rates(rows: &mut [ProcessInfo], detector: &Mutex<LeakDetector>) {
+    let Ok(detector) = detector.lock() else {
+        return;
+    };
+    for row in rows {
+        row.leak_rate = detector
+            .finding(row.pid, LeakMetric::PrivateBytes)
+            .filter(|finding| *finding.name == *row.name)
+            .map(|finding| finding.growth_per_hour);
+    }
+}
+
+/// Alert event for a finding; `value` and `threshold` are growth per hour
+fn leak_event(
+    finding: &LeakFinding,
+    kind: AlertEventKind,
+    min_growth_per_hour: f64,
+    snapshot: &ProcessSnapshot,
+) -> AlertEvent {
+    let rule = match finding.metric {
+        LeakMetric::PrivateBytes => "Memory leak",
+        LeakMetric::Handles => "Handle leak",
+    };
+    let severity = match finding.time_to_limit {
+        Some(eta) if eta.as_secs() < 3600 => AlertSeverity::Critical,
+        _ => AlertSeverity::Warning,
+    };
+    AlertEvent {
+        rule: rule.to_string(),
+        severity,
+        kind,
+        value: finding.growth_per_hour,
+        threshold: min_growth_per_hour,
+        pid: Some(finding.pid),
+        process_name: Some(finding.name.to_string()),
+        timestamp: snapshot.timestamp,
+        unix_ms: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::cli::process_rows;
+    use crate::test_support::{ProcessBuilder, SnapshotBuilder};
+    use std::sync::mpsc::channel;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_leak_raises_alert_event() {
+        let (tx, rx) = channel();
+        let config = LeakDetectorConfig {
+            window_secs: 600,
+            ..LeakDetectorConfig::default()
+        };
+        let mut monitor = LeakMonitor::new(config).with_handler(Box::new(tx));
+        let detector = monitor.detector();
+
+        let start = Instant::now();
+        let mut last = SnapshotBuilder::new().build();
+        for i in 0..20u64 {
+            let leaky = ProcessBuilder::new(42, "leaky.exe")
+                .with_memory((100 + 10 * i) * 1024 * 1024)
+                .build();
+            last = SnapshotBuilder::new()
+                .with_timestamp(start + Duration::from_secs(10 * i))
+                .with_processes(vec![leaky])
+                .build();
+            monitor.on_snapshot(&last);
+        }
+
+        let event = rx.try_recv().unwrap();
+        assert_eq!(event.rule, "Memory leak");
+        assert_eq!(event.kind, AlertEventKind::Fired);
+        assert_eq!(event.pid, Some(42));
+        assert!(rx.try_recv().is_err());
+
+        // 10 MB every 10 seconds
+        let mut rows = process_rows(&last, &last, 1);
+        apply_leak_rates(&mut rows, &detector);
+        let rate = rows[0].leak_rate.unwrap();
+        assert!((rate - 3600.0 * 1024.0 * 1024.0).abs() < 1.0);
+
+        // Same PID, different image
+        rows[0].name = "other.exe".to_string();
+        apply_leak_rates(&mut rows, &detector);
+        assert_eq!(rows[0].leak_rate, None);
+    }
+}
@@ -1,7 +1,21 @@
 //! Application coordination layer
 
+pub mod agent;
+pub mod alerts;
+pub mod anomaly;
+pub mod cli;
+pub mod commands;
 pub mod config;
 pub mod export;
+pub mod leaks;
+pub mod policies;
+pub mod prometheus;
+pub mod push;
+pub mod reload;
+pub mod report;
+pub mod rpc;
+pub mod services;
 pub mod state;
 pub mod theme;
 pub mod updater;
+pub mod validation;
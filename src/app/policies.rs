@@ -0,0 +1,608 @@
+//! Automatic process policies
+//!
+//! Applies `ProcessPolicy` rules from `AppConfig` as processes appear:
+//! - Priority class and CPU affinity, set once per matching process
+//! - Termination when private bytes exceed a limit, checked every cycle and
+//!   attempted once per process
+//! - An exclusion list of critical processes that are never touched
+//! - Dry-run mode that records decisions without acting on them
+//! - An audit log of every action (in memory and optionally on disk)
+//!
+//! Process changes go through `ProcessActuator`, implemented for Windows by
+//! `WindowsActuator` on top of `windows::process::control`.
+
+use std::collections::{HashMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+
+use crate::app::config::{AppConfig, ConfigSection, PoliciesConfig, ProcessPolicy};
+use crate::app::updater::SnapshotSink;
+use crate::util::logging::EventLogger;
+use crate::windows::monitor::nt_query::ProcessInfo;
+use crate::windows::monitor::ProcessSnapshot;
+use crate::windows::process::control::{
+    affinity, priority, suspension, termination, PriorityClass, ProcessError,
+};
+
+/// Audit entries kept in memory
+const MAX_AUDIT_ENTRIES: usize = 1000;
+
+/// Change a policy makes to a process
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyAction {
+    SetPriority(PriorityClass),
+    /// Affinity mask (bit N = CPU N)
+    SetAffinity(usize),
+    /// Terminate; carries the private bytes that triggered it
+    Terminate(u64),
+}
+
+impl std::fmt::Display for PolicyAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SetPriority(class) => write!(f, "set priority {:?}", class),
+            Self::SetAffinity(mask) => write!(f, "set affinity 0x{:X}", mask),
+            Self::Terminate(bytes) => write!(f, "terminate (private bytes {})", bytes),
+        }
+    }
+}
+
+/// One audited policy decision
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditEntry {
+    /// Local time in RFC 3339 format
+    pub time: String,
+    pub policy: String,
+    pub pid: u32,
+    pub process_name: String,
+    pub action: PolicyAction,
+    pub dry_run: bool,
+    /// Error text if the action failed
+    pub error: Option<String>,
+}
+
+impl std::fmt::Display for AuditEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {}policy '{}' {} {} (pid {}): {}",
+            self.time,
+            if self.dry_run { "[dry-run] " } else { "" },
+            self.policy,
+            self.action,
+            self.process_name,
+            self.pid,
+            match &self.error {
+                Some(error) => error.as_str(),
+                None => "ok",
+            }
+        )
+    }
+}
+
+/// Performs process changes on behalf of the policy engine
+pub trait ProcessActuator: Send {
+    fn set_priority(&mut self, pid: u32, class: PriorityClass) -> Result<(), ProcessError>;
+    fn set_affinity(&mut self, pid: u32, mask: usize) -> Result<(), ProcessError>;
+    fn terminate(&mut self, pid: u32) -> Result<(), ProcessError>;
+
+    /// Freeze all threads; unsupported unless overridden
+    fn suspend(&mut self, pid: u32) -> Result<(), ProcessError> {
+        Err(ProcessError::InvalidOperation(format!(
+            "suspending process {} is not supported",
+            pid
+        )))
+    }
+
+    /// Undo `suspend`; unsupported unless overridden
+    fn resume(&mut self, pid: u32) -> Result<(), ProcessError> {
+        Err(ProcessError::InvalidOperation(format!(
+            "resuming process {} is not supported",
+            pid
+        )))
+    }
+}
+
+/// Actuator using the Win32 process control functions
+pub struct WindowsActuator;
+
+impl ProcessActuator for WindowsActuator {
+    fn set_priority(&mut self, pid: u32, class: PriorityClass) -> Result<(), ProcessError> {
+        priority::set_priority(pid, class)
+    }
+
+    fn set_affinity(&mut self, pid: u32, mask: usize) -> Result<(), ProcessError> {
+        // Drop CPUs this machine doesn't have rather than failing the whole mask
+        let (_, system_mask) = affinity::get_affinity(pid)?;
+        let effective = mask & system_mask;
+        if effective == 0 {
+            return Err(ProcessError::InvalidOperation(format!(
+                "affinity 0x{:X} selects no available CPU (system mask 0x{:X})",
+                mask, system_mask
+            )));
+        }
+        affinity::set_affinity(pid, effective)
+    }
+
+    fn terminate(&mut self, pid: u32) -> Result<(), ProcessError> {
+        termination::terminate_force(pid)
+    }
+
+    fn suspend(&mut self, pid: u32) -> Result<(), ProcessError> {
+        suspension::suspend(pid)
+    }
+
+    fn resume(&mut self, pid: u32) -> Result<(), ProcessError> {
+        suspension::resume(pid)
+    }
+}
+
+/// Parse a CPU list such as "4-15" or "0,2,4-7" into an affinity mask
+pub fn parse_cpu_list(list: &str) -> Result<usize, String> {
+    let max_cpu = usize::BITS - 1;
+    let mut mask = 0usize;
+
+    for part in list.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (start, end) = match part.split_once('-') {
+            Some((start, end)) => (start.trim(), end.trim()),
+            None => (part, part),
+        };
+        let start: u32 = start
+            .parse()
+            .map_err(|_| format!("Invalid CPU number '{}'", start))?;
+        let end: u32 = end
+            .parse()
+            .map_err(|_| format!("Invalid CPU number '{}'", end))?;
+        if start > end {
+            return Err(format!("Invalid CPU range '{}'", part));
+        }
+        if end > max_cpu {
+            return Err(format!("CPU {} is out of range (max {})", end, max_cpu));
+        }
+        for cpu in start..=end {
+            mask |= 1 << cpu;
+        }
+    }
+
+    if mask == 0 {
+        return Err("CPU list is empty".to_string());
+    }
+    Ok(mask)
+}
+
+/// Case-insensitive glob match supporting `*` (any run) and `?` (one character)
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    // Iterative matcher with single backtrack point for the last '*'
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Policy with its affinity list pre-parsed
+struct CompiledPolicy {
+    policy: ProcessPolicy,
+    affinity_mask: Option<usize>,
+}
+
+/// Evaluates policies against snapshots and applies them
+pub struct PolicyEngine {
+    policies: Vec<CompiledPolicy>,
+    exclusions: Vec<String>,
+    dry_run: bool,
+    actuator: Box<dyn ProcessActuator>,
+    /// PIDs whose one-shot actions were already applied (pid -> image name)
+    seen: HashMap<u32, String>,
+    /// PIDs a termination was already attempted or logged for (pid -> image name)
+    terminated: HashMap<u32, String>,
+    audit: VecDeque<AuditEntry>,
+    audit_file: Option<File>,
+}
+
+impl PolicyEngine {
+    /// Create an engine for the enabled policies in `config`
+    ///
+    /// Fails if a policy has an invalid CPU list.
+    pub fn new(
+        config: &PoliciesConfig,
+        actuator: Box<dyn ProcessActuator>,
+    ) -> Result<Self, String> {
+        Ok(Self {
+            policies: compile_policies(config)?,
+            exclusions: config.exclusions.clone(),
+            dry_run: config.dry_run,
+            actuator,
+            seen: HashMap::new(),
+            terminated: HashMap::new(),
+            audit: VecDeque::new(),
+            audit_file: None,
+        })
+    }
+
+    /// Engine using `WindowsActuator`, with the audit file opened if configured
+    pub fn from_config(config: &PoliciesConfig) -> io::Result<Self> {
+        let mut engine = Self::new(config, Box::new(WindowsActuator))
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        if let Some(path) = &config.audit_log_path {
+            engine.audit_file = Some(OpenOptions::new().create(true).append(true).open(path)?);
+        }
+        Ok(engine)
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Switch to the policies in `config`, keeping the audit log
+    ///
+    /// Running processes are evaluated again, so new one-shot actions and
+    /// terminations apply to them. On error the current policies stay in place.
+    pub fn reconfigure(&mut self, config: &PoliciesConfig) -> Result<(), String> {
+        self.policies = compile_policies(config)?;
+        self.exclusions = config.exclusions.clone();
+        self.dry_run = config.dry_run;
+        self.seen.clear();
+        self.terminated.clear();
+        Ok(())
+    }
+
+    /// Most recent audit entries, oldest first
+    pub fn audit_log(&self) -> impl Iterator<Item = &AuditEntry> {
+        self.audit.iter()
+    }
+
+    /// True if the process must never be touched
+    pub fn is_excluded(&self, process: &ProcessInfo) -> bool {
+        // Idle, System and ourselves
+        if process.pid == 0 || process.pid == 4 || process.pid == std::process::id() {
+            return true;
+        }
+        self.exclusions
+            .iter()
+            .any(|pattern| glob_match(pattern, &process.name))
+    }
+
+    /// Apply policies to the processes in a snapshot
+    pub fn apply(&mut self, snapshot: &ProcessSnapshot) {
+        // Forget exited processes so a reused PID is treated as new
+        let running = |pid: &u32, name: &mut String| {
+            snapshot
+                .processes
+                .iter()
+                .any(|p| p.pid == *pid && p.name == *name)
+        };
+        self.seen.retain(running);
+        self.terminated.retain(running);
+
+        for process in &snapshot.processes {
+            if self.is_excluded(process) {
+                continue;
+            }
+            let first_seen = !self.seen.contains_key(&process.pid);
+            // Dry runs and failed attempts are not repeated every cycle
+            let terminated = self.terminated.contains_key(&process.pid);
+
+            let mut actions = Vec::new();
+            for compiled in &self.policies {
+                let policy = &compiled.policy;
+                if !glob_match(&policy.pattern, &process.name) {
+                    continue;
+                }
+                if first_seen {
+                    if let Some(class) = policy.priority {
+                        actions.push((policy.name.clone(), PolicyAction::SetPriority(class)));
+                    }
+                    if let Some(mask) = compiled.affinity_mask {
+                        actions.push((policy.name.clone(), PolicyAction::SetAffinity(mask)));
+                    }
+                }
+                if let Some(limit) = policy.kill_above_private_bytes {
+                    if !terminated && process.memory_private > limit {
+                        actions.push((
+                            policy.name.clone(),
+                            PolicyAction::Terminate(process.memory_private),
+                        ));
+                    }
+                }
+            }
+            self.seen.insert(process.pid, process.name.clone());
+
+            for (policy, action) in actions {
+                let terminate = matches!(action, PolicyAction::Terminate(_));
+                self.execute(&policy, process, action);
+                if terminate {
+                    self.terminated.insert(process.pid, process.name.clone());
+                    // Nothing else to do for a process we just killed
+                    break;
+                }
+            }
+        }
+    }
+
+    fn execute(&mut self, policy: &str, process: &ProcessInfo, action: PolicyAction) {
+        let result = if self.dry_run {
+            Ok(())
+        } else {
+            match action {
+                PolicyAction::SetPriority(class) => self.actuator.set_priority(process.pid, class),
+                PolicyAction::SetAffinity(mask) => self.actuator.set_affinity(process.pid, mask),
+                PolicyAction::Terminate(_) => self.actuator.terminate(process.pid),
+            }
+        };
+
+        let entry = AuditEntry {
+            time: chrono::Local::now().to_rfc3339(),
+            policy: policy.to_string(),
+            pid: process.pid,
+            process_name: process.name.clone(),
+            action,
+            dry_run: self.dry_run,
+            error: result.err().map(|e| e.short_description()),
+        };
+
+        if let Some(file) = &mut self.audit_file {
+            let _ = writeln!(file, "{}", entry);
+        }
+        if self.audit.len() == MAX_AUDIT_ENTRIES {
+            self.audit.pop_front();
+        }
+        self.audit.push_back(entry);
+    }
+}
+
+impl SnapshotSink for PolicyEngine {
+    fn on_snapshot(&mut self, snapshot: &ProcessSnapshot) {
+        self.apply(snapshot);
+    }
+
+    /// Picks up policy changes; a new `audit_log_path` applies after a restart
+    fn on_config(&mut self, config: &AppConfig, changed: &[ConfigSection]) {
+        if changed.contains(&ConfigSection::Policies) {
+            if let Err(e) = self.reconfigure(&config.policies) {
+                EventLogger::new("TaskManager")
+                    .log_warning(&format!("Policies not reloaded: {}", e));
+            }
+        }
+    }
+}
+
+/// Enabled policies with their CPU lists parsed
+fn compile_policies(config: &PoliciesConfig) -> Result<Vec<CompiledPolicy>, String> {
+    config
+        .rules
+        .iter()
+        .filter(|p| p.enabled)
+        .map(|policy| {
+            let affinity_mask = match &policy.affinity {
+                Some(list) => Some(
+                    parse_cpu_list(list).map_err(|e| format!("Policy '{}': {}", policy.name, e))?,
+                ),
+                None => None,
+            };
+            Ok(CompiledPolicy {
+                policy: policy.clone(),
+                affinity_mask,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{ProcessBuilder, SnapshotBuilder};
+    use std::sync::{Arc, Mutex};
+
+    /// Records calls instead of touching processes
+    #[derive(Clone, Default)]
+    struct RecordingActuator {
+        calls: Arc<Mutex<Vec<(u32, PolicyAction)>>>,
+    }
+
+    impl ProcessActuator for RecordingActuator {
+        fn set_priority(&mut self, pid: u32, class: PriorityClass) -> Result<(), ProcessError> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push((pid, PolicyAction::SetPriority(class)));
+            Ok(())
+        }
+
+        fn set_affinity(&mut self, pid: u32, mask: usize) -> Result<(), ProcessError> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push((pid, PolicyAction::SetAffinity(mask)));
+            Ok(())
+        }
+
+        fn terminate(&mut self, pid: u32) -> Result<(), ProcessError> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push((pid, PolicyAction::Terminate(0)));
+            Err(ProcessError::NotFound(pid))
+        }
+    }
+
+    fn make_config(dry_run: bool) -> PoliciesConfig {
+        PoliciesConfig {
+            enabled: true,
+            dry_run,
+            rules: vec![
+                ProcessPolicy::new("compilers", "*compiler*")
+                    .with_priority(PriorityClass::BelowNormal)
+                    .with_affinity("4-15"),
+                ProcessPolicy::new("crashpad", "crashpad_handler*")
+                    .with_kill_above_private_bytes(1 << 30),
+            ],
+            ..PoliciesConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*compiler*", "RustCompilerHost.exe"));
+        assert!(glob_match("crashpad_handler*", "crashpad_handler.exe"));
+        assert!(glob_match("svc?ost.exe", "svchost.exe"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("*compiler", "compiler.exe"));
+        assert!(!glob_match("a?c", "ac"));
+    }
+
+    #[test]
+    fn test_parse_cpu_list() {
+        assert_eq!(parse_cpu_list("4-15"), Ok(0xFFF0));
+        assert_eq!(parse_cpu_list("0, 2,4-5"), Ok(0b110101));
+        assert!(parse_cpu_list("5-2").is_err());
+        assert!(parse_cpu_list("x").is_err());
+        assert!(parse_cpu_list("").is_err());
+        assert!(parse_cpu_list("0-64").is_err());
+    }
+
+    #[test]
+    fn test_priority_and_affinity_applied_once() {
+        let actuator = RecordingActuator::default();
+        let mut engine =
+            PolicyEngine::new(&make_config(false), Box::new(actuator.clone())).unwrap();
+        let snapshot = SnapshotBuilder::new()
+            .with_processes(vec![ProcessBuilder::new(100, "compiler.exe").build()])
+            .build();
+
+        engine.apply(&snapshot);
+        engine.apply(&snapshot);
+
+        let calls = actuator.calls.lock().unwrap();
+        assert_eq!(
+            *calls,
+            vec![
+                (100, PolicyAction::SetPriority(PriorityClass::BelowNormal)),
+                (100, PolicyAction::SetAffinity(0xFFF0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_kill_above_limit_audited() {
+        let actuator = RecordingActuator::default();
+        let mut engine =
+            PolicyEngine::new(&make_config(false), Box::new(actuator.clone())).unwrap();
+
+        let snapshot = SnapshotBuilder::new()
+            .with_processes(vec![
+                ProcessBuilder::new(200, "crashpad_handler.exe")
+                    .with_memory(2 << 30)
+                    .build(),
+                ProcessBuilder::new(201, "crashpad_handler.exe")
+                    .with_memory(10)
+                    .build(),
+            ])
+            .build();
+        engine.apply(&snapshot);
+        // The failed attempt is not repeated for the same process
+        engine.apply(&snapshot);
+
+        assert_eq!(actuator.calls.lock().unwrap().len(), 1);
+        assert_eq!(engine.audit_log().count(), 1);
+        let entry = engine.audit_log().next().unwrap();
+        assert_eq!(entry.pid, 200);
+        assert_eq!(entry.action, PolicyAction::Terminate(2 << 30));
+        assert!(entry.error.is_some());
+        assert!(entry.to_string().contains("policy 'crashpad' terminate"));
+    }
+
+    #[test]
+    fn test_dry_run_does_not_act() {
+        let actuator = RecordingActuator::default();
+        let mut engine = PolicyEngine::new(&make_config(true), Box::new(actuator.clone())).unwrap();
+
+        let snapshot = SnapshotBuilder::new()
+            .with_processes(vec![
+                ProcessBuilder::new(300, "compiler.exe").build(),
+                ProcessBuilder::new(301, "crashpad_handler.exe")
+                    .with_memory(2 << 30)
+                    .build(),
+            ])
+            .build();
+        engine.apply(&snapshot);
+        engine.apply(&snapshot);
+
+        assert!(actuator.calls.lock().unwrap().is_empty());
+        assert_eq!(engine.audit_log().count(), 3);
+        assert!(engine.audit_log().all(|e| e.dry_run && e.error.is_none()));
+    }
+
+    #[test]
+    fn test_exclusions() {
+        let mut config = make_config(false);
+        config
+            .rules
+            .push(ProcessPolicy::new("everything", "*").with_priority(PriorityClass::Idle));
+        let actuator = RecordingActuator::default();
+        let mut engine = PolicyEngine::new(&config, Box::new(actuator.clone())).unwrap();
+
+        engine.apply(
+            &SnapshotBuilder::new()
+                .with_processes(vec![
+                    ProcessBuilder::new(4, "System").build(),
+                    ProcessBuilder::new(500, "LSASS.EXE").build(),
+                    ProcessBuilder::new(std::process::id(), "self.exe").build(),
+                ])
+                .build(),
+        );
+
+        assert!(actuator.calls.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_affinity_rejected() {
+        let mut config = make_config(false);
+        config.rules[0].affinity = Some("15-4".to_string());
+        let result = PolicyEngine::new(&config, Box::new(RecordingActuator::default()));
+        assert!(result.err().unwrap().contains("compilers"));
+    }
+
+    #[test]
+    fn test_reconfigure_applies_new_policies() {
+        let actuator = RecordingActuator::default();
+        let mut engine = PolicyEngine::new(&make_config(true), Box::new(actuator.clone())).unwrap();
+        let snapshot = SnapshotBuilder::new()
+            .with_processes(vec![ProcessBuilder::new(600, "compiler.exe").build()])
+            .build();
+        engine.apply(&snapshot);
+        assert!(actuator.calls.lock().unwrap().is_empty());
+
+        // Invalid config keeps the dry-run engine
+        let mut invalid = make_config(false);
+        invalid.rules[0].affinity = Some("x".to_string());
+        let mut app = AppConfig {
+            policies: invalid,
+            ..AppConfig::default()
+        };
+        engine.on_config(&app, &[ConfigSection::Policies]);
+        assert!(engine.is_dry_run());
+
+        app.policies = make_config(false);
+        engine.on_config(&app, &[ConfigSection::Policies]);
+        engine.apply(&snapshot);
+        assert_eq!(actuator.calls.lock().unwrap().len(), 2);
+        assert_eq!(engine.audit_log().count(), 4);
+    }
+}
@@ -0,0 +1,703 @@
+//! Prometheus / OpenMetrics exposition endpoint
+//!
+//! Serves the latest snapshot produced by the `Updater` in Prometheus text
+//! format on a localhost-only HTTP listener:
+//! - System-wide CPU, memory, disk and network gauges
+//! - Per-process memory, thread, handle and CPU-time series labelled with
+//!   pid, name and user
+//! - A cap on per-process series so a scrape stays bounded on busy machines
+//!
+//! The endpoint never collects data itself; it is fed through `PrometheusSink`
+//! which is registered with `Updater::start_with_sinks`.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::{self, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::Duration;
+
+use crate::app::config::PrometheusConfig;
+use crate::app::updater::SnapshotSink;
+use crate::windows::monitor::nt_query::ProcessInfo;
+use crate::windows::monitor::ProcessSnapshot;
+
+/// Metric name prefix shared by every exported series
+const METRIC_PREFIX: &str = "taskmanager";
+
+/// Maximum accepted request header size (bytes)
+const MAX_REQUEST_SIZE: usize = 8192;
+
+/// Accept loop poll interval while waiting for connections
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Per-connection read/write timeout
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Text exposition flavour negotiated from the `Accept` header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpositionFormat {
+    /// Prometheus text format 0.0.4
+    Prometheus,
+    /// OpenMetrics 1.0.0 text format
+    OpenMetrics,
+}
+
+impl ExpositionFormat {
+    /// Pick a format from an HTTP `Accept` header value
+    pub fn from_accept(accept: Option<&str>) -> Self {
+        match accept {
+            Some(value) if value.contains("application/openmetrics-text") => Self::OpenMetrics,
+            _ => Self::Prometheus,
+        }
+    }
+
+    /// HTTP `Content-Type` for this format
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Prometheus => "text/plain; version=0.0.4; charset=utf-8",
+            Self::OpenMetrics => "application/openmetrics-text; version=1.0.0; charset=utf-8",
+        }
+    }
+}
+
+/// Extracts one per-process sample value
+type ProcessValue = fn(&ProcessInfo) -> f64;
+
+/// Snapshot plus resolved process owners, as published by the sink
+struct PublishedSnapshot {
+    snapshot: ProcessSnapshot,
+    users: HashMap<u32, String>,
+}
+
+/// Latest published snapshot shared between sink and listener
+type SharedSnapshot = Arc<RwLock<Option<PublishedSnapshot>>>;
+
+/// Render a snapshot in Prometheus (or OpenMetrics) text format
+///
+/// # Arguments
+///
+/// * `snapshot` - Snapshot to expose
+/// * `users` - Process owner by PID (missing entries export an empty label)
+/// * `max_process_series` - Maximum number of processes to export
+/// * `format` - Exposition flavour
+///
+/// Processes are ranked by working set so the cap keeps the heaviest ones.
+pub fn render_snapshot(
+    snapshot: &ProcessSnapshot,
+    users: &HashMap<u32, String>,
+    max_process_series: usize,
+    format: ExpositionFormat,
+) -> String {
+    let mut out =
+        String::with_capacity(4096 + snapshot.processes.len().min(max_process_series) * 512);
+    let metrics = &snapshot.system_metrics;
+
+    write_gauge(
+        &mut out,
+        "cpu_usage_percent",
+        "Total CPU usage across all cores",
+        metrics.cpu_total as f64,
+    );
+
+    if !metrics.cpu_cores.is_empty() {
+        write_header(
+            &mut out,
+            "cpu_core_usage_percent",
+            "gauge",
+            "Per-core CPU usage",
+            format,
+        );
+        for (core, usage) in metrics.cpu_cores.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "{}_cpu_core_usage_percent{{core=\"{}\"}} {}",
+                METRIC_PREFIX, core, usage
+            );
+        }
+    }
+
+    if !metrics.custom.is_empty() {
+        write_header(
+            &mut out,
+            "custom_metric",
+            "gauge",
+            "Values from registered metric collectors",
+            format,
+        );
+        for (name, value) in &metrics.custom {
+            let _ = writeln!(
+                out,
+                "{}_custom_metric{{name=\"{}\"}} {}",
+                METRIC_PREFIX,
+                escape_label_value(name),
+                value
+            );
+        }
+    }
+
+    write_gauge(
+        &mut out,
+        "memory_total_bytes",
+        "Total physical memory",
+        metrics.memory_total as f64,
+    );
+    write_gauge(
+        &mut out,
+        "memory_available_bytes",
+        "Available physical memory",
+        metrics.memory_available as f64,
+    );
+    write_gauge(
+        &mut out,
+        "memory_load_percent",
+        "Physical memory load",
+        metrics.memory_load_percent as f64,
+    );
+    write_gauge(
+        &mut out,
+        "disk_read_bytes_per_second",
+        "Disk read rate",
+        metrics.disk_read_bps as f64,
+    );
+    write_gauge(
+        &mut out,
+        "disk_write_bytes_per_second",
+        "Disk write rate",
+        metrics.disk_write_bps as f64,
+    );
+    write_gauge(
+        &mut out,
+        "network_receive_bytes_per_second",
+        "Network receive rate",
+        metrics.network_receive_bps as f64,
+    );
+    write_gauge(
+        &mut out,
+        "network_transmit_bytes_per_second",
+        "Network transmit rate",
+        metrics.network_transmit_bps as f64,
+    );
+    write_gauge(
+        &mut out,
+        "processes",
+        "Number of running processes",
+        snapshot.processes.len() as f64,
+    );
+
+    let ranked = snapshot.top_by_working_set(max_process_series);
+
+    let dropped = snapshot.processes.len() - ranked.len();
+    write_gauge(
+        &mut out,
+        "process_series_dropped",
+        "Processes omitted by the per-process series cap",
+        dropped as f64,
+    );
+
+    let labels: Vec<String> = ranked
+        .iter()
+        .map(|p| {
+            let user = users.get(&p.pid).map(String::as_str).unwrap_or("");
+            format!(
+                "pid=\"{}\",name=\"{}\",user=\"{}\"",
+                p.pid,
+                escape_label_value(&p.name),
+                escape_label_value(user)
+            )
+        })
+        .collect();
+
+    let families: [(&str, &str, &str, ProcessValue); 5] = [
+        (
+            "process_working_set_bytes",
+            "gauge",
+            "Process working set",
+            |p| p.memory_working_set as f64,
+        ),
+        (
+            "process_private_bytes",
+            "gauge",
+            "Process private bytes",
+            |p| p.memory_private as f64,
+        ),
+        (
+            "process_pagefile_bytes",
+            "gauge",
+            "Process page file usage",
+            |p| p.memory_pagefile as f64,
+        ),
+        ("process_threads", "gauge", "Process thread count", |p| {
+            p.thread_count as f64
+        }),
+        ("process_handles", "gauge", "Process handle count", |p| {
+            p.handle_count as f64
+        }),
+    ];
+
+    for (name, kind, help, value) in families.iter() {
+        write_header(&mut out, name, kind, help, format);
+        for (process, labels) in ranked.iter().zip(&labels) {
+            let _ = writeln!(
+                out,
+                "{}_{}{{{}}} {}",
+                METRIC_PREFIX,
+                name,
+                labels,
+                value(process)
+            );
+        }
+    }
+
+    // CPU time is a counter: 100ns units -> seconds
+    write_header(
+        &mut out,
+        "process_cpu_seconds",
+        "counter",
+        "Process user + kernel CPU time",
+        format,
+    );
+    for (process, labels) in ranked.iter().zip(&labels) {
+        let seconds = (process.cpu_time_user + process.cpu_time_kernel) as f64 / 10_000_000.0;
+        let _ = writeln!(
+            out,
+            "{}_process_cpu_seconds_total{{{}}} {}",
+            METRIC_PREFIX, labels, seconds
+        );
+    }
+
+    if format == ExpositionFormat::OpenMetrics {
+        out.push_str("# EOF\n");
+    }
+
+    out
+}
+
+/// Write a single unlabelled gauge with its HELP/TYPE header
+fn write_gauge(out: &mut String, name: &str, help: &str, value: f64) {
+    let _ = writeln!(out, "# HELP {}_{} {}", METRIC_PREFIX, name, help);
+    let _ = writeln!(out, "# TYPE {}_{} gauge", METRIC_PREFIX, name);
+    let _ = writeln!(out, "{}_{} {}", METRIC_PREFIX, name, value);
+}
+
+/// Write HELP/TYPE lines for a metric family
+///
+/// Prometheus text format names counters by their sample name (`_total`),
+/// OpenMetrics by the family name without the suffix.
+fn write_header(out: &mut String, name: &str, kind: &str, help: &str, format: ExpositionFormat) {
+    let family = if kind == "counter" && format == ExpositionFormat::Prometheus {
+        format!("{}_total", name)
+    } else {
+        name.to_string()
+    };
+    let _ = writeln!(out, "# HELP {}_{} {}", METRIC_PREFIX, family, help);
+    let _ = writeln!(out, "# TYPE {}_{} {}", METRIC_PREFIX, family, kind);
+}
+
+/// Escape a label value (backslash, double quote and newline)
+fn escape_label_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// Snapshot sink publishing into a `MetricsEndpoint`
+///
+/// Resolves process owners on the updater thread and caches them by PID so
+/// each process is looked up once for its lifetime.
+pub struct PrometheusSink {
+    shared: SharedSnapshot,
+    max_process_series: usize,
+    user_cache: HashMap<u32, String>,
+}
+
+impl SnapshotSink for PrometheusSink {
+    fn on_snapshot(&mut self, snapshot: &ProcessSnapshot) {
+        // Drop cache entries for processes that have exited
+        self.user_cache
+            .retain(|pid, _| snapshot.processes.iter().any(|p| p.pid == *pid));
+
+        // Only resolve owners for processes that make it past the cap
+        for process in snapshot.top_by_working_set(self.max_process_series) {
+            self.user_cache
+                .entry(process.pid)
+                .or_insert_with(|| lookup_user(process.pid));
+        }
+
+        let published = PublishedSnapshot {
+            snapshot: snapshot.clone(),
+            users: self.user_cache.clone(),
+        };
+        *self.shared.write().unwrap() = Some(published);
+    }
+}
+
+/// Resolve the owner of a process, empty if it cannot be opened
+fn lookup_user(pid: u32) -> String {
+    use crate::windows::process::details::{get_username, open_process_for_query};
+    use windows::Win32::Foundation::CloseHandle;
+
+    let Some(handle) = open_process_for_query(pid) else {
+        return String::new();
+    };
+    let user = get_username(handle).unwrap_or_default();
+    unsafe {
+        let _ = CloseHandle(handle);
+    }
+    user
+}
+
+/// Localhost HTTP listener serving `/metrics`
+///
+/// # Threading Model
+///
+/// Accepts connections on a dedicated background thread. Requests are handled
+/// one at a time; rendering reads the latest published snapshot under a read
+/// lock, so scrapes never block the updater for longer than a clone.
+pub struct MetricsEndpoint {
+    shared: SharedSnapshot,
+    max_process_series: usize,
+    local_addr: SocketAddr,
+    shutdown: Arc<AtomicBool>,
+    thread_handle: Option<thread::JoinHandle<()>>,
+}
+
+impl MetricsEndpoint {
+    /// Bind to `127.0.0.1:port` and start serving
+    ///
+    /// Port 0 binds an ephemeral port; see `local_addr()`.
+    pub fn start(config: &PrometheusConfig) -> io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, config.port))?;
+        listener.set_nonblocking(true)?;
+        let local_addr = listener.local_addr()?;
+
+        let shared: SharedSnapshot = Arc::new(RwLock::new(None));
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let max_process_series = config.max_process_series;
+
+        let thread_shared = Arc::clone(&shared);
+        let thread_shutdown = Arc::clone(&shutdown);
+        let thread_handle = thread::spawn(move || {
+            run_accept_loop(listener, thread_shared, thread_shutdown, max_process_series);
+        });
+
+        Ok(Self {
+            shared,
+            max_process_series,
+            local_addr,
+            shutdown,
+            thread_handle: Some(thread_handle),
+        })
+    }
+
+    /// Start the endpoint only if enabled in configuration
+    pub fn start_if_enabled(config: &PrometheusConfig) -> Option<io::Result<Self>> {
+        if config.enabled {
+            Some(Self::start(config))
+        } else {
+            None
+        }
+    }
+
+    /// Create a sink that publishes updater snapshots to this endpoint
+    pub fn sink(&self) -> PrometheusSink {
+        PrometheusSink {
+            shared: Arc::clone(&self.shared),
+            max_process_series: self.max_process_series,
+            user_cache: HashMap::new(),
+        }
+    }
+
+    /// Address the listener is bound to
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Stop the listener thread
+    pub fn shutdown(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+
+        if let Some(handle) = self.thread_handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for MetricsEndpoint {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Accept loop running on the listener thread
+fn run_accept_loop(
+    listener: TcpListener,
+    shared: SharedSnapshot,
+    shutdown: Arc<AtomicBool>,
+    max_process_series: usize,
+) {
+    while !shutdown.load(Ordering::SeqCst) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                if let Err(e) = handle_connection(stream, &shared, max_process_series) {
+                    eprintln!("Metrics endpoint: request failed: {}", e);
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                thread::sleep(ACCEPT_POLL_INTERVAL);
+            }
+            Err(e) => {
+                eprintln!("Metrics endpoint: accept failed: {}", e);
+                thread::sleep(ACCEPT_POLL_INTERVAL);
+            }
+        }
+    }
+}
+
+/// Serve one HTTP/1.x request and close the connection
+fn handle_connection(
+    mut stream: TcpStream,
+    shared: &SharedSnapshot,
+    max_process_series: usize,
+) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(CONNECTION_TIMEOUT))?;
+    stream.set_write_timeout(Some(CONNECTION_TIMEOUT))?;
+
+    let request = read_request_head(&mut stream)?;
+    let mut lines = request.lines();
+    let request_line = lines.next().unwrap_or("");
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let path = parts.next().unwrap_or("");
+
+    let accept = lines.find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.trim()
+            .eq_ignore_ascii_case("accept")
+            .then(|| value.trim())
+    });
+
+    if method != "GET" {
+        return write_response(
+            &mut stream,
+            "405 Method Not Allowed",
+            "text/plain",
+            "method not allowed\n",
+        );
+    }
+
+    // Ignore query strings: /metrics?foo=bar is still /metrics
+    let path = path.split('?').next().unwrap_or("");
+    if path != "/metrics" {
+        return write_response(&mut stream, "404 Not Found", "text/plain", "not found\n");
+    }
+
+    let format = ExpositionFormat::from_accept(accept);
+    let body = {
+        let guard = shared.read().unwrap();
+        guard.as_ref().map(|published| {
+            render_snapshot(
+                &published.snapshot,
+                &published.users,
+                max_process_series,
+                format,
+            )
+        })
+    };
+
+    match body {
+        Some(body) => write_response(&mut stream, "200 OK", format.content_type(), &body),
+        None => write_response(
+            &mut stream,
+            "503 Service Unavailable",
+            "text/plain",
+            "no snapshot collected yet\n",
+        ),
+    }
+}
+
+/// Read request line and headers (up to the blank line)
+fn read_request_head(stream: &mut TcpStream) -> io::Result<String> {
+    let mut buffer = Vec::with_capacity(1024);
+    let mut chunk = [0u8; 1024];
+
+    loop {
+        let n = stream.read(&mut chunk)?;
+        if n == 0 {
+            break;
+        }
+        buffer.extend_from_slice(&chunk[..n]);
+
+        if buffer.windows(4).any(|w| w == b"\r\n\r\n") || buffer.len() >= MAX_REQUEST_SIZE {
+            break;
+        }
+    }
+
+    Ok(String::from_utf8_lossy(&buffer).into_owned())
+}
+
+/// Write a complete HTTP response
+fn write_response(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &str,
+) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    stream.write_all(body.as_bytes())?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{ProcessBuilder, SnapshotBuilder};
+
+    const GB: u64 = 1024 * 1024 * 1024;
+
+    #[test]
+    fn test_render_system_metrics() {
+        let snapshot = SnapshotBuilder::new()
+            .with_cpu(42.5, vec![40.0, 45.0])
+            .with_memory(16 * GB, 8 * GB)
+            .build();
+        let text = render_snapshot(&snapshot, &HashMap::new(), 10, ExpositionFormat::Prometheus);
+
+        assert!(text.contains("# TYPE taskmanager_cpu_usage_percent gauge"));
+        assert!(text.contains("taskmanager_cpu_usage_percent 42.5"));
+        assert!(text.contains("taskmanager_cpu_core_usage_percent{core=\"1\"} 45"));
+        assert!(text.contains("taskmanager_memory_load_percent 50"));
+        assert!(!text.contains("# EOF"));
+    }
+
+    #[test]
+    fn test_render_process_labels() {
+        let chrome = ProcessBuilder::new(1234, "chrome.exe")
+            .with_cpu_time(20_000_000)
+            .with_kernel_time(5_000_000)
+            .with_working_set(1024)
+            .build();
+        let snapshot = SnapshotBuilder::new().with_processes(vec![chrome]).build();
+        let mut users = HashMap::new();
+        users.insert(1234, "DESKTOP\\alice".to_string());
+
+        let text = render_snapshot(&snapshot, &users, 10, ExpositionFormat::Prometheus);
+
+        assert!(text.contains(
+            "taskmanager_process_working_set_bytes{pid=\"1234\",name=\"chrome.exe\",user=\"DESKTOP\\\\alice\"} 1024"
+        ));
+        assert!(text.contains("# TYPE taskmanager_process_cpu_seconds_total counter"));
+        assert!(text.contains("taskmanager_process_cpu_seconds_total{pid=\"1234\""));
+        assert!(text.contains("} 2.5\n"));
+    }
+
+    #[test]
+    fn test_process_series_cap() {
+        let snapshot = SnapshotBuilder::new()
+            .with_processes(vec![
+                ProcessBuilder::new(1, "small.exe")
+                    .with_working_set(100)
+                    .build(),
+                ProcessBuilder::new(2, "large.exe")
+                    .with_working_set(10_000)
+                    .build(),
+                ProcessBuilder::new(3, "medium.exe")
+                    .with_working_set(1_000)
+                    .build(),
+            ])
+            .build();
+
+        let text = render_snapshot(&snapshot, &HashMap::new(), 2, ExpositionFormat::Prometheus);
+
+        assert!(text.contains("name=\"large.exe\""));
+        assert!(text.contains("name=\"medium.exe\""));
+        assert!(!text.contains("name=\"small.exe\""));
+        assert!(text.contains("taskmanager_process_series_dropped 1"));
+        assert!(text.contains("taskmanager_processes 3"));
+    }
+
+    #[test]
+    fn test_openmetrics_format() {
+        let snapshot = SnapshotBuilder::new()
+            .with_processes(vec![ProcessBuilder::new(1, "a.exe").build()])
+            .build();
+        let text = render_snapshot(
+            &snapshot,
+            &HashMap::new(),
+            10,
+            ExpositionFormat::OpenMetrics,
+        );
+
+        assert!(text.contains("# TYPE taskmanager_process_cpu_seconds counter"));
+        assert!(text.ends_with("# EOF\n"));
+        assert_eq!(
+            ExpositionFormat::from_accept(Some("application/openmetrics-text; version=1.0.0")),
+            ExpositionFormat::OpenMetrics
+        );
+        assert_eq!(
+            ExpositionFormat::from_accept(None),
+            ExpositionFormat::Prometheus
+        );
+    }
+
+    #[test]
+    fn test_escape_label_value() {
+        assert_eq!(escape_label_value("plain"), "plain");
+        assert_eq!(escape_label_value("a\"b"), "a\\\"b");
+        assert_eq!(escape_label_value("a\\b\nc"), "a\\\\b\\nc");
+    }
+
+    #[test]
+    fn test_endpoint_serves_metrics() {
+        let config = PrometheusConfig {
+            enabled: true,
+            port: 0,
+            max_process_series: 10,
+        };
+        let mut endpoint = MetricsEndpoint::start(&config).unwrap();
+
+        let fetch = |path: &str| {
+            let mut stream = TcpStream::connect(endpoint.local_addr()).unwrap();
+            write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+
+        // Nothing published yet
+        assert!(fetch("/metrics").starts_with("HTTP/1.1 503"));
+
+        let mut sink = endpoint.sink();
+        let ourselves = ProcessBuilder::new(std::process::id(), "test.exe").build();
+        let snapshot = SnapshotBuilder::new()
+            .with_processes(vec![ourselves])
+            .with_cpu(42.5, Vec::new())
+            .build();
+        sink.on_snapshot(&snapshot);
+
+        let response = fetch("/metrics");
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("taskmanager_cpu_usage_percent 42.5"));
+        assert!(response.contains("name=\"test.exe\""));
+
+        assert!(fetch("/other").starts_with("HTTP/1.1 404"));
+
+        endpoint.shutdown();
+    }
+}
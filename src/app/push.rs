@@ -0,0 +1,842 @@
+//! Push exporters: InfluxDB line protocol and StatsD gauges
+//!
+//! Complements the pull-based Prometheus endpoint. Every update cycle is
+//! encoded on the updater thread and handed to a per-exporter worker thread
+//! that owns the transport:
+//! - Targets: append to a file, UDP datagrams or a TCP stream
+//! - Batching by line count and payload size
+//! - Bounded retry queue (oldest batches dropped when the target is down)
+//! - Global tags from `AppConfig`
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Write as _;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::net::{TcpStream, ToSocketAddrs, UdpSocket};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::app::config::{
+    ExportersConfig, InfluxConfig, PushBatchConfig, PushTarget, StatsdConfig,
+};
+use crate::app::updater::SnapshotSink;
+use crate::windows::monitor::nt_query::ProcessInfo;
+use crate::windows::monitor::ProcessSnapshot;
+
+/// TCP connect/write timeout
+const TCP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Encode a snapshot as InfluxDB line protocol
+///
+/// Produces one `taskmanager_system` point, one `taskmanager_cpu_core` point
+/// per core and up to `max_process_series` `taskmanager_process` points
+/// (largest working set first).
+///
+/// # Arguments
+///
+/// * `snapshot` - Snapshot to encode
+/// * `tags` - Tags added to every point
+/// * `max_process_series` - Maximum number of process points
+/// * `timestamp_ns` - Point timestamp (nanoseconds since the Unix epoch)
+pub fn encode_influx(
+    snapshot: &ProcessSnapshot,
+    tags: &BTreeMap<String, String>,
+    max_process_series: usize,
+    timestamp_ns: u128,
+) -> Vec<String> {
+    let metrics = &snapshot.system_metrics;
+    let common_tags = format_influx_tags(tags, &[]);
+    let mut lines = Vec::with_capacity(1 + metrics.cpu_cores.len() + max_process_series);
+
+    lines.push(format!(
+        "taskmanager_system{} cpu_total={},memory_total={}i,memory_available={}i,\
+         memory_load_percent={}i,disk_read_bps={}i,disk_write_bps={}i,\
+         network_receive_bps={}i,network_transmit_bps={}i,processes={}i {}",
+        common_tags,
+        metrics.cpu_total,
+        metrics.memory_total,
+        metrics.memory_available,
+        metrics.memory_load_percent,
+        metrics.disk_read_bps,
+        metrics.disk_write_bps,
+        metrics.network_receive_bps,
+        metrics.network_transmit_bps,
+        snapshot.processes.len(),
+        timestamp_ns
+    ));
+
+    for (core, usage) in metrics.cpu_cores.iter().enumerate() {
+        lines.push(format!(
+            "taskmanager_cpu_core{} usage={} {}",
+            format_influx_tags(tags, &[("core", &core.to_string())]),
+            usage,
+            timestamp_ns
+        ));
+    }
+
+    for process in snapshot.top_by_working_set(max_process_series) {
+        let pid = process.pid.to_string();
+        lines.push(format!(
+            "taskmanager_process{} working_set={}i,private={}i,pagefile={}i,\
+             threads={}i,handles={}i,cpu_seconds={} {}",
+            format_influx_tags(tags, &[("pid", &pid), ("name", &process.name)]),
+            process.memory_working_set,
+            process.memory_private,
+            process.memory_pagefile,
+            process.thread_count,
+            process.handle_count,
+            cpu_seconds(process),
+            timestamp_ns
+        ));
+    }
+
+    lines
+}
+
+/// Encode a snapshot as StatsD gauges
+///
+/// Tags are appended DogStatsD-style (`|#key:value`) when non-empty.
+/// Per-process gauges are named `<prefix>.process.<name>_<pid>.<metric>`.
+pub fn encode_statsd(
+    snapshot: &ProcessSnapshot,
+    prefix: &str,
+    tags: &BTreeMap<String, String>,
+    max_process_series: usize,
+) -> Vec<String> {
+    let metrics = &snapshot.system_metrics;
+    let suffix = format_statsd_tags(tags);
+    let prefix = prefix.trim_end_matches('.');
+    let mut lines = Vec::new();
+
+    let mut gauge = |name: &str, value: f64| {
+        lines.push(format!("{}.{}:{}|g{}", prefix, name, value, suffix));
+    };
+
+    gauge("cpu.total", metrics.cpu_total as f64);
+    for (core, usage) in metrics.cpu_cores.iter().enumerate() {
+        gauge(&format!("cpu.core{}", core), *usage as f64);
+    }
+    gauge("memory.total_bytes", metrics.memory_total as f64);
+    gauge("memory.available_bytes", metrics.memory_available as f64);
+    gauge("memory.load_percent", metrics.memory_load_percent as f64);
+    gauge("disk.read_bps", metrics.disk_read_bps as f64);
+    gauge("disk.write_bps", metrics.disk_write_bps as f64);
+    gauge("network.receive_bps", metrics.network_receive_bps as f64);
+    gauge("network.transmit_bps", metrics.network_transmit_bps as f64);
+    gauge("processes", snapshot.processes.len() as f64);
+
+    for process in snapshot.top_by_working_set(max_process_series) {
+        let key = format!(
+            "process.{}_{}",
+            sanitize_statsd_name(&process.name),
+            process.pid
+        );
+        gauge(
+            &format!("{}.working_set_bytes", key),
+            process.memory_working_set as f64,
+        );
+        gauge(
+            &format!("{}.private_bytes", key),
+            process.memory_private as f64,
+        );
+        gauge(&format!("{}.threads", key), process.thread_count as f64);
+        gauge(&format!("{}.handles", key), process.handle_count as f64);
+    }
+
+    lines
+}
+
+/// Group lines into payloads bounded by line count and byte size
+///
+/// A single line longer than `max_bytes` is still sent on its own rather
+/// than dropped.
+pub fn batch_lines(lines: &[String], config: &PushBatchConfig) -> Vec<Vec<u8>> {
+    let max_lines = config.max_lines.max(1);
+    let mut batches = Vec::new();
+    let mut current = Vec::new();
+    let mut current_lines = 0;
+
+    for line in lines {
+        let needed = line.len() + 1;
+        if current_lines > 0
+            && (current_lines >= max_lines || current.len() + needed > config.max_bytes)
+        {
+            batches.push(std::mem::take(&mut current));
+            current_lines = 0;
+        }
+        current.extend_from_slice(line.as_bytes());
+        current.push(b'\n');
+        current_lines += 1;
+    }
+
+    if !current.is_empty() {
+        batches.push(current);
+    }
+
+    batches
+}
+
+/// Process CPU time in seconds (100ns units)
+fn cpu_seconds(process: &ProcessInfo) -> f64 {
+    (process.cpu_time_user + process.cpu_time_kernel) as f64 / 10_000_000.0
+}
+
+/// Format `tags` and the point's own tags as `,k=v,k=v`
+///
+/// The whole set is sorted by key, as InfluxDB recommends; a point tag
+/// replaces a configured tag with the same key.
+fn format_influx_tags(tags: &BTreeMap<String, String>, point: &[(&str, &str)]) -> String {
+    let mut merged: BTreeMap<&str, &str> = tags
+        .iter()
+        .map(|(key, value)| (key.as_str(), value.as_str()))
+        .collect();
+    merged.extend(point.iter().copied());
+
+    let mut out = String::new();
+    for (key, value) in merged {
+        let _ = write!(
+            out,
+            ",{}={}",
+            escape_influx_tag(key),
+            escape_influx_tag(value)
+        );
+    }
+    out
+}
+
+/// Escape tag keys and values (commas, equals signs and spaces)
+fn escape_influx_tag(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            ',' | '=' | ' ' => {
+                escaped.push('\\');
+                escaped.push(ch);
+            }
+            '\n' | '\r' => escaped.push(' '),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// Format DogStatsD tags as `|#k:v,k:v`
+fn format_statsd_tags(tags: &BTreeMap<String, String>) -> String {
+    if tags.is_empty() {
+        return String::new();
+    }
+    let joined: Vec<String> = tags
+        .iter()
+        .map(|(k, v)| format!("{}:{}", sanitize_statsd_name(k), sanitize_statsd_name(v)))
+        .collect();
+    format!("|#{}", joined.join(","))
+}
+
+/// Replace characters that StatsD treats as separators
+fn sanitize_statsd_name(value: &str) -> String {
+    value
+        .chars()
+        .map(|ch| match ch {
+            ':' | '|' | '@' | '#' | ',' | '.' | ' ' | '\n' => '_',
+            _ => ch,
+        })
+        .collect()
+}
+
+/// Bounded FIFO of payloads awaiting delivery
+///
+/// New batches go to the back; when the queue is full the oldest batch is
+/// dropped so a dead target cannot grow memory without bound.
+pub struct RetryQueue {
+    batches: VecDeque<Vec<u8>>,
+    capacity: usize,
+    dropped: u64,
+}
+
+impl RetryQueue {
+    /// Create a queue holding at most `capacity` batches
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            batches: VecDeque::with_capacity(capacity.min(1024)),
+            capacity: capacity.max(1),
+            dropped: 0,
+        }
+    }
+
+    /// Enqueue a batch, dropping the oldest if full
+    pub fn push(&mut self, batch: Vec<u8>) {
+        if self.batches.len() >= self.capacity {
+            self.batches.pop_front();
+            self.dropped += 1;
+        }
+        self.batches.push_back(batch);
+    }
+
+    /// Send queued batches in order until one fails
+    ///
+    /// Returns the number of batches delivered; the failing batch and
+    /// everything behind it stay queued for the next attempt. Lines of the
+    /// failing batch that were written completely are not sent again.
+    pub fn flush<F>(&mut self, mut send: F) -> usize
+    where
+        F: FnMut(&[u8]) -> Result<(), SendError>,
+    {
+        let mut sent = 0;
+        while let Some(batch) = self.batches.front_mut() {
+            if let Err(e) = send(batch) {
+                let written = &batch[..e.written.min(batch.len())];
+                let delivered = written
+                    .iter()
+                    .rposition(|&b| b == b'\n')
+                    .map_or(0, |i| i + 1);
+                batch.drain(..delivered);
+                if batch.is_empty() {
+                    self.batches.pop_front();
+                }
+                break;
+            }
+            self.batches.pop_front();
+            sent += 1;
+        }
+        sent
+    }
+
+    /// Number of batches waiting
+    pub fn len(&self) -> usize {
+        self.batches.len()
+    }
+
+    /// Check if nothing is waiting
+    pub fn is_empty(&self) -> bool {
+        self.batches.is_empty()
+    }
+
+    /// Total batches dropped because the queue was full
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
+/// Failed delivery of a payload
+#[derive(Debug)]
+pub struct SendError {
+    /// Bytes of the payload that reached the target before the failure
+    pub written: usize,
+    pub error: io::Error,
+}
+
+impl From<io::Error> for SendError {
+    fn from(error: io::Error) -> Self {
+        Self { written: 0, error }
+    }
+}
+
+/// Connected transport for a `PushTarget`
+enum Transport {
+    File(File),
+    Udp(UdpSocket, String),
+    Tcp(String, Option<TcpStream>),
+}
+
+impl Transport {
+    /// Open the target (TCP connects lazily on first send)
+    fn open(target: &PushTarget) -> io::Result<Self> {
+        match target {
+            PushTarget::File(path) => {
+                let file = OpenOptions::new().create(true).append(true).open(path)?;
+                Ok(Transport::File(file))
+            }
+            PushTarget::Udp(address) => {
+                let socket = UdpSocket::bind("0.0.0.0:0")?;
+                Ok(Transport::Udp(socket, address.clone()))
+            }
+            PushTarget::Tcp(address) => Ok(Transport::Tcp(address.clone(), None)),
+        }
+    }
+
+    /// Deliver one payload
+    fn send(&mut self, payload: &[u8]) -> Result<(), SendError> {
+        match self {
+            Transport::File(file) => {
+                write_counted(file, payload)?;
+                Ok(file.flush()?)
+            }
+            Transport::Udp(socket, address) => {
+                socket.send_to(payload, address.as_str())?;
+                Ok(())
+            }
+            Transport::Tcp(address, stream) => {
+                if stream.is_none() {
+                    let connected = connect_tcp(address)?;
+                    connected.set_write_timeout(Some(TCP_TIMEOUT))?;
+                    *stream = Some(connected);
+                }
+
+                let result = stream.as_mut().map_or(Ok(()), |s| write_counted(s, payload));
+                if result.is_err() {
+                    // Reconnect on the next attempt
+                    *stream = None;
+                }
+                result
+            }
+        }
+    }
+}
+
+/// `write_all` that reports how much was written when it fails
+fn write_counted(writer: &mut impl Write, payload: &[u8]) -> Result<(), SendError> {
+    let mut written = 0;
+    while written < payload.len() {
+        match writer.write(&payload[written..]) {
+            Ok(0) => {
+                let error = io::ErrorKind::WriteZero.into();
+                return Err(SendError { written, error });
+            }
+            Ok(n) => written += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(error) => return Err(SendError { written, error }),
+        }
+    }
+    Ok(())
+}
+
+/// Connect to `host:port`, trying each resolved address in turn
+fn connect_tcp(address: &str) -> io::Result<TcpStream> {
+    let mut last_error = None;
+    for addr in address.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&addr, TCP_TIMEOUT) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} did not resolve to any address", address),
+        )
+    }))
+}
+
+/// Delivery counters shared with the worker thread
+#[derive(Debug, Default)]
+pub struct PushStats {
+    /// Batches delivered
+    pub sent_batches: AtomicU64,
+    /// Batches dropped because the retry queue was full
+    pub dropped_batches: AtomicU64,
+    /// Batches currently waiting for retry
+    pub queued_batches: AtomicU64,
+}
+
+/// Line encoder for a push exporter
+enum Encoder {
+    Influx(InfluxConfig),
+    Statsd(StatsdConfig),
+}
+
+impl Encoder {
+    fn encode(&self, snapshot: &ProcessSnapshot) -> Vec<String> {
+        match self {
+            Encoder::Influx(config) => {
+                let timestamp_ns = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_nanos())
+                    .unwrap_or(0);
+                encode_influx(
+                    snapshot,
+                    &config.tags,
+                    config.max_process_series,
+                    timestamp_ns,
+                )
+            }
+            Encoder::Statsd(config) => encode_statsd(
+                snapshot,
+                &config.prefix,
+                &config.tags,
+                config.max_process_series,
+            ),
+        }
+    }
+
+    fn batch_config(&self) -> &PushBatchConfig {
+        match self {
+            Encoder::Influx(config) => &config.batch,
+            Encoder::Statsd(config) => &config.batch,
+        }
+    }
+}
+
+/// Push exporter registered with `Updater::start_with_sinks`
+///
+/// # Threading Model
+///
+/// `on_snapshot` only encodes and batches; a dedicated worker thread owns the
+/// transport and the retry queue, so a slow or unreachable target never
+/// delays metric collection.
+pub struct PushExporter {
+    encoder: Encoder,
+    batch_tx: Option<Sender<Vec<Vec<u8>>>>,
+    stats: Arc<PushStats>,
+    thread_handle: Option<thread::JoinHandle<()>>,
+}
+
+impl PushExporter {
+    /// Create an InfluxDB line protocol exporter
+    pub fn influx(config: &InfluxConfig) -> io::Result<Self> {
+        let transport = Transport::open(&config.target)?;
+        Ok(Self::spawn(Encoder::Influx(config.clone()), transport))
+    }
+
+    /// Create a StatsD gauge exporter
+    pub fn statsd(config: &StatsdConfig) -> io::Result<Self> {
+        let transport = Transport::open(&config.target)?;
+        Ok(Self::spawn(Encoder::Statsd(config.clone()), transport))
+    }
+
+    fn spawn(encoder: Encoder, transport: Transport) -> Self {
+        let (batch_tx, batch_rx) = channel();
+        let stats = Arc::new(PushStats::default());
+        let capacity = encoder.batch_config().retry_queue_capacity;
+
+        let thread_stats = Arc::clone(&stats);
+        let thread_handle = thread::spawn(move || {
+            run_push_loop(transport, capacity, batch_rx, thread_stats);
+        });
+
+        Self {
+            encoder,
+            batch_tx: Some(batch_tx),
+            stats,
+            thread_handle: Some(thread_handle),
+        }
+    }
+
+    /// Delivery counters
+    pub fn stats(&self) -> Arc<PushStats> {
+        Arc::clone(&self.stats)
+    }
+
+    /// Flush what can be delivered and stop the worker thread
+    pub fn shutdown(&mut self) {
+        // Dropping the sender ends the worker loop after a final flush
+        self.batch_tx.take();
+
+        if let Some(handle) = self.thread_handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl SnapshotSink for PushExporter {
+    fn on_snapshot(&mut self, snapshot: &ProcessSnapshot) {
+        let lines = self.encoder.encode(snapshot);
+        let batches = batch_lines(&lines, self.encoder.batch_config());
+
+        if let Some(tx) = &self.batch_tx {
+            let _ = tx.send(batches);
+        }
+    }
+}
+
+impl Drop for PushExporter {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Worker loop: enqueue each cycle's batches, then flush in order
+fn run_push_loop(
+    mut transport: Transport,
+    capacity: usize,
+    batch_rx: Receiver<Vec<Vec<u8>>>,
+    stats: Arc<PushStats>,
+) {
+    let mut queue = RetryQueue::new(capacity);
+
+    while let Ok(batches) = batch_rx.recv() {
+        for batch in batches {
+            queue.push(batch);
+        }
+
+        let sent = queue.flush(|payload| transport.send(payload));
+        stats.sent_batches.fetch_add(sent as u64, Ordering::Relaxed);
+        stats
+            .dropped_batches
+            .store(queue.dropped(), Ordering::Relaxed);
+        stats
+            .queued_batches
+            .store(queue.len() as u64, Ordering::Relaxed);
+    }
+
+    // Channel closed: last delivery attempt before exiting
+    let sent = queue.flush(|payload| transport.send(payload));
+    stats.sent_batches.fetch_add(sent as u64, Ordering::Relaxed);
+    stats
+        .queued_batches
+        .store(queue.len() as u64, Ordering::Relaxed);
+}
+
+/// Build the enabled push exporters from configuration
+///
+/// Returned sinks are passed to `Updater::start_with_sinks`.
+pub fn sinks_from_config(config: &ExportersConfig) -> io::Result<Vec<Box<dyn SnapshotSink>>> {
+    let mut sinks: Vec<Box<dyn SnapshotSink>> = Vec::new();
+
+    if config.influx.enabled {
+        sinks.push(Box::new(PushExporter::influx(&config.influx)?));
+    }
+    if config.statsd.enabled {
+        sinks.push(Box::new(PushExporter::statsd(&config.statsd)?));
+    }
+
+    Ok(sinks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{ProcessBuilder, SnapshotBuilder};
+    use std::io::BufRead;
+    use std::net::TcpListener;
+
+    fn tags(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_encode_influx() {
+        let app = ProcessBuilder::new(42, "my app.exe")
+            .with_cpu_time(10_000_000)
+            .with_memory(2048)
+            .build();
+        let snapshot = SnapshotBuilder::new()
+            .with_processes(vec![app])
+            .with_cpu(12.5, vec![10.0, 15.0])
+            .with_memory(1000, 400)
+            .build();
+        let lines = encode_influx(&snapshot, &tags(&[("host", "ws-01")]), 10, 1_000);
+
+        assert_eq!(lines.len(), 4); // system + 2 cores + 1 process
+        assert!(
+            lines[0].starts_with("taskmanager_system,host=ws-01 cpu_total=12.5,memory_total=1000i")
+        );
+        assert!(lines[0].ends_with(" 1000"));
+        assert_eq!(
+            lines[2],
+            "taskmanager_cpu_core,core=1,host=ws-01 usage=15 1000"
+        );
+        assert!(lines[3].starts_with(
+            "taskmanager_process,host=ws-01,name=my\\ app.exe,pid=42 working_set=2048i"
+        ));
+        assert!(lines[3].contains("cpu_seconds=1 "));
+
+        // Point tags sort in among the configured ones
+        let lines = encode_influx(&snapshot, &tags(&[("region", "eu"), ("dc", "a")]), 10, 1);
+        assert!(lines[3]
+            .starts_with("taskmanager_process,dc=a,name=my\\ app.exe,pid=42,region=eu "));
+    }
+
+    #[test]
+    fn test_encode_statsd() {
+        let service = ProcessBuilder::new(7, "svc.host.exe").with_threads(4).build();
+        let snapshot = SnapshotBuilder::new()
+            .with_processes(vec![service])
+            .with_cpu(12.5, Vec::new())
+            .with_memory(1000, 400)
+            .build();
+
+        let plain = encode_statsd(&snapshot, "tm.", &BTreeMap::new(), 0);
+        assert_eq!(plain[0], "tm.cpu.total:12.5|g");
+        assert!(plain.contains(&"tm.memory.load_percent:60|g".to_string()));
+        assert!(!plain.iter().any(|l| l.contains("process.")));
+
+        let tagged = encode_statsd(&snapshot, "tm", &tags(&[("host", "ws-01")]), 1);
+        assert_eq!(tagged[0], "tm.cpu.total:12.5|g|#host:ws-01");
+        assert!(tagged.contains(&"tm.process.svc_host_exe_7.threads:4|g|#host:ws-01".to_string()));
+    }
+
+    #[test]
+    fn test_batch_lines() {
+        let lines: Vec<String> = (0..5).map(|i| format!("line{}", i)).collect();
+
+        let by_count = PushBatchConfig {
+            max_lines: 2,
+            max_bytes: 10_000,
+            retry_queue_capacity: 4,
+        };
+        let batches = batch_lines(&lines, &by_count);
+        assert_eq!(batches.len(), 3);
+        assert_eq!(batches[0], b"line0\nline1\n");
+
+        let by_size = PushBatchConfig {
+            max_lines: 100,
+            max_bytes: 12,
+            retry_queue_capacity: 4,
+        };
+        let batches = batch_lines(&lines, &by_size);
+        assert_eq!(batches.len(), 3); // two 6-byte lines fit in 12 bytes
+    }
+
+    #[test]
+    fn test_retry_queue_bounded() {
+        let mut queue = RetryQueue::new(2);
+        queue.push(b"a".to_vec());
+        queue.push(b"b".to_vec());
+        queue.push(b"c".to_vec());
+
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.dropped(), 1);
+
+        // Failing target keeps everything queued
+        assert_eq!(
+            queue.flush(|_| Err(io::Error::other("down").into())),
+            0
+        );
+        assert_eq!(queue.len(), 2);
+
+        // Recovery delivers oldest first
+        let mut delivered = Vec::new();
+        assert_eq!(
+            queue.flush(|batch| {
+                delivered.push(batch.to_vec());
+                Ok(())
+            }),
+            2
+        );
+        assert_eq!(delivered, vec![b"b".to_vec(), b"c".to_vec()]);
+        assert!(queue.is_empty());
+
+        // A partial write only keeps the lines that did not fully arrive
+        queue.push(b"l1\nl2\nl3\n".to_vec());
+        let cut = |written| {
+            move |_: &[u8]| {
+                let error = io::Error::other("reset");
+                Err(SendError { written, error })
+            }
+        };
+        assert_eq!(queue.flush(cut(5)), 0);
+        let mut delivered = Vec::new();
+        queue.flush(|batch| {
+            delivered.push(batch.to_vec());
+            Err(SendError {
+                written: 0,
+                error: io::Error::other("down"),
+            })
+        });
+        assert_eq!(delivered, vec![b"l2\nl3\n".to_vec()]);
+        assert_eq!(queue.flush(cut(6)), 0);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_influx_over_udp() {
+        let listener = UdpSocket::bind("127.0.0.1:0").unwrap();
+        listener
+            .set_read_timeout(Some(Duration::from_secs(2)))
+            .unwrap();
+
+        let config = InfluxConfig {
+            enabled: true,
+            target: PushTarget::Udp(listener.local_addr().unwrap().to_string()),
+            tags: tags(&[("host", "test")]),
+            max_process_series: 1,
+            batch: PushBatchConfig::default(),
+        };
+        let mut exporter = PushExporter::influx(&config).unwrap();
+        let snapshot = SnapshotBuilder::new()
+            .with_processes(vec![ProcessBuilder::new(1, "a.exe").build()])
+            .build();
+        exporter.on_snapshot(&snapshot);
+
+        let mut buf = [0u8; 2048];
+        let n = listener.recv(&mut buf).unwrap();
+        let payload = String::from_utf8_lossy(&buf[..n]);
+        assert!(payload.starts_with("taskmanager_system,host=test "));
+        assert!(payload.contains("taskmanager_process,host=test,name=a.exe,pid=1 "));
+
+        exporter.shutdown();
+        assert_eq!(exporter.stats().sent_batches.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_statsd_over_udp() {
+        let listener = UdpSocket::bind("127.0.0.1:0").unwrap();
+        listener
+            .set_read_timeout(Some(Duration::from_secs(2)))
+            .unwrap();
+
+        let config = StatsdConfig {
+            target: PushTarget::Udp(listener.local_addr().unwrap().to_string()),
+            enabled: true,
+            ..Default::default()
+        };
+        let mut exporter = PushExporter::statsd(&config).unwrap();
+        exporter.on_snapshot(&SnapshotBuilder::new().with_cpu(12.5, Vec::new()).build());
+
+        let mut buf = [0u8; 2048];
+        let n = listener.recv(&mut buf).unwrap();
+        let payload = String::from_utf8_lossy(&buf[..n]);
+        assert!(payload.starts_with("taskmanager.cpu.total:12.5|g\n"));
+
+        exporter.shutdown();
+    }
+
+    #[test]
+    fn test_influx_over_tcp_hostname() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let config = InfluxConfig {
+            enabled: true,
+            target: PushTarget::Tcp(format!("localhost:{}", port)),
+            max_process_series: 0,
+            ..Default::default()
+        };
+        let mut exporter = PushExporter::influx(&config).unwrap();
+        exporter.on_snapshot(&SnapshotBuilder::new().build());
+
+        let (stream, _) = listener.accept().unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(2)))
+            .unwrap();
+        let mut line = String::new();
+        io::BufReader::new(stream).read_line(&mut line).unwrap();
+        assert!(line.starts_with("taskmanager_system "));
+
+        exporter.shutdown();
+    }
+
+    #[test]
+    fn test_influx_to_file() {
+        let path = std::env::temp_dir().join("test_push_influx.lp");
+        let _ = std::fs::remove_file(&path);
+
+        let config = InfluxConfig {
+            enabled: true,
+            target: PushTarget::File(path.to_string_lossy().into_owned()),
+            max_process_series: 0,
+            ..Default::default()
+        };
+        let mut exporter = PushExporter::influx(&config).unwrap();
+        exporter.on_snapshot(&SnapshotBuilder::new().build());
+        exporter.on_snapshot(&SnapshotBuilder::new().build());
+        exporter.shutdown();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            content
+                .lines()
+                .filter(|l| l.starts_with("taskmanager_system "))
+                .count(),
+            2
+        );
+
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,230 @@
+//! Configuration hot reload
+//!
+//! `ConfigWatcher` polls the config file (modification time and size) and
+//! merges each valid new version into the running `ConfigManager` with
+//! `apply_external`, which keeps sections that have unsaved in-memory edits.
+//! Every effective change is delivered as a `ConfigReload` naming the
+//! sections that differ, so only affected subsystems are touched:
+//! - `ConfigReload::apply_to` hands refresh rate, alert rule and policy
+//!   changes to the `Updater`, whose sinks pick them up in `on_config`
+//! - Theme, column and keymap changes are applied by the UI on its own thread
+//!
+//! A file that fails to parse or validate is logged and ignored; the running
+//! configuration stays in place. Unlike `FileStore::load` the file is not
+//! quarantined, since an editor or dotfile manager may be halfway through
+//! writing it.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::app::config::{parse_config, AppConfig, ConfigManager, ConfigSection};
+use crate::app::updater::Updater;
+use crate::app::validation;
+use crate::util::logging::EventLogger;
+
+/// How often the file is checked by default
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Shutdown latency of the watcher thread
+const SLEEP_SLICE: Duration = Duration::from_millis(50);
+
+/// Configuration changed on disk
+#[derive(Debug, Clone)]
+pub struct ConfigReload {
+    /// Effective configuration now held by the manager (active profile applied)
+    pub config: Arc<AppConfig>,
+    /// Sections whose values changed
+    pub changed: Vec<ConfigSection>,
+}
+
+impl ConfigReload {
+    pub fn affects(&self, section: ConfigSection) -> bool {
+        self.changed.contains(&section)
+    }
+
+    /// Forward the change to the updater thread and its sinks
+    pub fn apply_to(&self, updater: &Updater) {
+        updater.reconfigure(Arc::clone(&self.config), self.changed.clone());
+    }
+}
+
+/// Background watcher applying external edits of the config file
+pub struct ConfigWatcher {
+    shutdown: Arc<AtomicBool>,
+    thread_handle: Option<thread::JoinHandle<()>>,
+}
+
+impl ConfigWatcher {
+    /// Watch `path` and merge changes into `manager`
+    ///
+    /// The file's current contents are assumed to be loaded already; only
+    /// later modifications are reported.
+    pub fn start(
+        path: impl Into<PathBuf>,
+        manager: Arc<ConfigManager>,
+        poll_interval: Duration,
+    ) -> (Self, Receiver<ConfigReload>) {
+        let path = path.into();
+        let (reload_tx, reload_rx) = channel();
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let thread_shutdown = Arc::clone(&shutdown);
+        // Stamp now: edits made before the thread runs must still be seen
+        let stamp = file_stamp(&path);
+        let thread_handle = thread::spawn(move || {
+            run_watch_loop(
+                &path,
+                stamp,
+                &manager,
+                poll_interval,
+                &reload_tx,
+                &thread_shutdown,
+            );
+        });
+
+        let watcher = Self {
+            shutdown,
+            thread_handle: Some(thread_handle),
+        };
+        (watcher, reload_rx)
+    }
+
+    /// Watch the manager's config file, if it is stored in one
+    pub fn start_for(manager: Arc<ConfigManager>) -> Option<(Self, Receiver<ConfigReload>)> {
+        let path = manager.file_path()?;
+        Some(Self::start(path, manager, DEFAULT_POLL_INTERVAL))
+    }
+
+    /// Stop the watcher thread
+    pub fn shutdown(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+
+        if let Some(handle) = self.thread_handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for ConfigWatcher {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Modification time and size; None while the file is missing
+fn file_stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Parse, repair and validate the file the same way `ConfigManager` does
+fn read_config(path: &Path) -> Result<AppConfig, String> {
+    let json = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let (mut config, _) = parse_config(&json)?;
+    validation::sanitize(&mut config, validation::virtual_desktop().as_ref());
+    validation::validate(&config).map_err(|e| e.to_string())?;
+    Ok(config)
+}
+
+fn run_watch_loop(
+    path: &Path,
+    mut last_stamp: Option<(SystemTime, u64)>,
+    manager: &ConfigManager,
+    poll_interval: Duration,
+    reload_tx: &Sender<ConfigReload>,
+    shutdown: &AtomicBool,
+) {
+    let logger = EventLogger::new("TaskManager");
+    let mut next_check = Instant::now() + poll_interval;
+
+    while !shutdown.load(Ordering::SeqCst) {
+        thread::sleep(SLEEP_SLICE.min(poll_interval));
+        if Instant::now() < next_check {
+            continue;
+        }
+        next_check = Instant::now() + poll_interval;
+
+        let stamp = file_stamp(path);
+        // Deleted files keep the running configuration
+        if stamp.is_none() || stamp == last_stamp {
+            continue;
+        }
+        last_stamp = stamp;
+
+        match read_config(path) {
+            Ok(config) => {
+                let changed = manager.apply_external(config);
+                if changed.is_empty() {
+                    continue;
+                }
+                let reload = ConfigReload {
+                    config: Arc::new(manager.effective()),
+                    changed,
+                };
+                if reload_tx.send(reload).is_err() {
+                    // Receiver dropped, nobody is listening
+                    break;
+                }
+            }
+            Err(e) => logger.log_warning(&format!(
+                "Configuration {} not reloaded: {}",
+                path.display(),
+                e
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::config::FileStore;
+    use crate::app::theme::Theme;
+
+    fn write(path: &Path, config: &AppConfig) {
+        fs::write(path, serde_json::to_string_pretty(config).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_watcher_applies_changes_and_keeps_dirty_edits() {
+        let dir = std::env::temp_dir().join(format!("tm-reload-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.json");
+        write(&path, &AppConfig::default());
+
+        let manager = Arc::new(ConfigManager::new().with_store(Box::new(FileStore::new(&path))));
+        manager.load().unwrap();
+        manager.set_theme(Theme::Dark);
+        let (mut watcher, reloads) =
+            ConfigWatcher::start(&path, Arc::clone(&manager), Duration::from_millis(10));
+
+        let mut edited = AppConfig::default();
+        edited.theme.preference = Theme::Light;
+        edited.monitoring.refresh_rate_ms = 2000;
+        edited.columns.set_visible("leak_rate", true).unwrap();
+        write(&path, &edited);
+
+        let reload = reloads.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(
+            reload.changed,
+            vec![ConfigSection::Monitoring, ConfigSection::Columns]
+        );
+        assert!(!reload.affects(ConfigSection::Theme));
+        assert_eq!(reload.config.monitoring.refresh_rate_ms, 2000);
+        assert_eq!(manager.get().theme.preference, Theme::Dark);
+
+        // Half-written files are ignored
+        fs::write(&path, r#"{"monitoring": {"refresh_rate_ms": 500"#).unwrap();
+        assert!(reloads.recv_timeout(Duration::from_millis(300)).is_err());
+        assert_eq!(manager.get().monitoring.refresh_rate_ms, 2000);
+        assert!(path.exists());
+
+        watcher.shutdown();
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
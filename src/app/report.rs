@@ -0,0 +1,955 @@
+//! Self-contained HTML performance report
+//!
+//! Turns a recorded history window into a single HTML file that can be
+//! attached to a support ticket:
+//! - Inline SVG charts for CPU, memory, disk and network
+//! - Aggregate statistics (min/avg/p95/max via `MetricAggregation`)
+//! - Top-N process tables by peak memory and CPU time used in the window
+//! - Process start/exit events observed during the window
+//!
+//! The file has no external references (no scripts, fonts or stylesheets),
+//! so it renders the same offline and inside ticketing systems.
+//!
+//! History is captured by `HistoryRecorder`, a `SnapshotSink` registered with
+//! `Updater::start_with_sinks`.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use crate::app::updater::SnapshotSink;
+use crate::core::metrics::MetricAggregation;
+use crate::ui::controls::geometry::{axis_layout, line_points, PlotRect};
+use crate::ui::controls::svg::{Rgba, SvgDocument};
+use crate::util::format::NumberFormat;
+use crate::windows::monitor::ProcessSnapshot;
+
+/// Chart dimensions (SVG user units)
+const CHART_WIDTH: f32 = 720.0;
+const CHART_HEIGHT: f32 = 180.0;
+const CHART_MARGIN_LEFT: f32 = 64.0;
+const CHART_MARGIN_RIGHT: f32 = 12.0;
+const CHART_MARGIN_TOP: f32 = 12.0;
+const CHART_MARGIN_BOTTOM: f32 = 24.0;
+
+/// Series colors (Fluent palette accents)
+const COLOR_PRIMARY: Rgba = Rgba::new(0.0, 0.471, 0.831, 1.0);
+const COLOR_SECONDARY: Rgba = Rgba::new(0.761, 0.224, 0.702, 1.0);
+const GRID_COLOR: Rgba = Rgba::new(0.878, 0.878, 0.878, 1.0);
+const LABEL_COLOR: Rgba = Rgba::new(0.38, 0.38, 0.38, 1.0);
+
+/// One system-wide sample in the history window
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ReportSample {
+    /// Seconds since the first recorded snapshot
+    pub elapsed_secs: f64,
+    pub cpu_percent: f32,
+    pub memory_percent: f32,
+    pub memory_used_bytes: u64,
+    pub disk_read_bps: u64,
+    pub disk_write_bps: u64,
+    pub network_receive_bps: u64,
+    pub network_transmit_bps: u64,
+}
+
+/// Per-process totals accumulated over the window
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessRecord {
+    pub pid: u32,
+    pub name: String,
+    pub peak_working_set: u64,
+    pub peak_private: u64,
+    /// CPU time (100ns units) when the process was first seen
+    first_cpu_time: u64,
+    /// CPU time (100ns units) when the process was last seen
+    last_cpu_time: u64,
+    /// Seconds since the first snapshot when the process was last seen
+    last_seen_secs: f64,
+}
+
+impl ProcessRecord {
+    /// CPU time consumed inside the window (seconds)
+    pub fn cpu_seconds(&self) -> f64 {
+        self.last_cpu_time.saturating_sub(self.first_cpu_time) as f64 / 10_000_000.0
+    }
+}
+
+/// Process lifecycle event kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LifecycleKind {
+    Started,
+    Exited,
+}
+
+impl LifecycleKind {
+    fn label(&self) -> &'static str {
+        match self {
+            LifecycleKind::Started => "Started",
+            LifecycleKind::Exited => "Exited",
+        }
+    }
+}
+
+/// Process start or exit observed between two snapshots
+#[derive(Debug, Clone, PartialEq)]
+pub struct LifecycleEvent {
+    pub elapsed_secs: f64,
+    pub pid: u32,
+    pub name: String,
+    pub kind: LifecycleKind,
+}
+
+/// Bounded history window feeding the HTML report
+///
+/// Keeps the most recent `capacity` samples. Process records and lifecycle
+/// events older than the oldest retained sample are pruned with it, so memory
+/// stays bounded however long recording runs.
+#[derive(Debug, Clone)]
+pub struct ReportHistory {
+    capacity: usize,
+    started: Option<Instant>,
+    samples: VecDeque<ReportSample>,
+    /// Collector values per sample, aligned with `samples`
+    custom: VecDeque<BTreeMap<String, f64>>,
+    processes: HashMap<u32, ProcessRecord>,
+    events: VecDeque<LifecycleEvent>,
+    last_pids: HashSet<u32>,
+}
+
+impl ReportHistory {
+    /// Create an empty history keeping at most `capacity` samples
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            started: None,
+            samples: VecDeque::with_capacity(capacity.min(4096)),
+            custom: VecDeque::new(),
+            processes: HashMap::new(),
+            events: VecDeque::new(),
+            last_pids: HashSet::new(),
+        }
+    }
+
+    /// Append a snapshot to the window
+    pub fn record(&mut self, snapshot: &ProcessSnapshot) {
+        let started = *self.started.get_or_insert(snapshot.timestamp);
+        let elapsed_secs = snapshot
+            .timestamp
+            .saturating_duration_since(started)
+            .as_secs_f64();
+
+        let metrics = &snapshot.system_metrics;
+        self.samples.push_back(ReportSample {
+            elapsed_secs,
+            cpu_percent: metrics.cpu_total,
+            memory_percent: metrics.memory_load_percent as f32,
+            memory_used_bytes: metrics
+                .memory_total
+                .saturating_sub(metrics.memory_available),
+            disk_read_bps: metrics.disk_read_bps,
+            disk_write_bps: metrics.disk_write_bps,
+            network_receive_bps: metrics.network_receive_bps,
+            network_transmit_bps: metrics.network_transmit_bps,
+        });
+        self.custom.push_back(metrics.custom.clone());
+
+        let first_snapshot = self.samples.len() == 1 && self.last_pids.is_empty();
+        let mut current_pids = HashSet::with_capacity(snapshot.processes.len());
+
+        for process in &snapshot.processes {
+            current_pids.insert(process.pid);
+            let cpu_time = process.cpu_time_user + process.cpu_time_kernel;
+
+            let record = self
+                .processes
+                .entry(process.pid)
+                .or_insert_with(|| ProcessRecord {
+                    pid: process.pid,
+                    name: process.name.clone(),
+                    peak_working_set: 0,
+                    peak_private: 0,
+                    first_cpu_time: cpu_time,
+                    last_cpu_time: cpu_time,
+                    last_seen_secs: elapsed_secs,
+                });
+            record.peak_working_set = record.peak_working_set.max(process.memory_working_set);
+            record.peak_private = record.peak_private.max(process.memory_private);
+            record.last_cpu_time = cpu_time.max(record.first_cpu_time);
+            record.last_seen_secs = elapsed_secs;
+
+            if !first_snapshot && !self.last_pids.contains(&process.pid) {
+                self.events.push_back(LifecycleEvent {
+                    elapsed_secs,
+                    pid: process.pid,
+                    name: process.name.clone(),
+                    kind: LifecycleKind::Started,
+                });
+            }
+        }
+
+        for pid in self.last_pids.difference(&current_pids) {
+            if let Some(record) = self.processes.get(pid) {
+                self.events.push_back(LifecycleEvent {
+                    elapsed_secs,
+                    pid: *pid,
+                    name: record.name.clone(),
+                    kind: LifecycleKind::Exited,
+                });
+            }
+        }
+        self.last_pids = current_pids;
+
+        self.prune();
+    }
+
+    /// Drop samples beyond capacity and anything older than the window
+    fn prune(&mut self) {
+        while self.samples.len() > self.capacity {
+            self.samples.pop_front();
+            self.custom.pop_front();
+        }
+
+        let Some(oldest) = self.samples.front().map(|s| s.elapsed_secs) else {
+            return;
+        };
+        while self.events.front().is_some_and(|e| e.elapsed_secs < oldest) {
+            self.events.pop_front();
+        }
+        self.processes
+            .retain(|_, record| record.last_seen_secs >= oldest);
+    }
+
+    /// Samples in chronological order
+    pub fn samples(&self) -> impl Iterator<Item = &ReportSample> {
+        self.samples.iter()
+    }
+
+    /// Collector metrics (`collector.metric`) seen during the window, sorted
+    pub fn custom_metric_names(&self) -> Vec<&str> {
+        let names: BTreeSet<&str> = self
+            .custom
+            .iter()
+            .flat_map(|values| values.keys().map(String::as_str))
+            .collect();
+        names.into_iter().collect()
+    }
+
+    /// Values of a collector metric in chronological order as
+    /// (elapsed seconds, value); samples without the metric are skipped
+    pub fn custom_series(&self, name: &str) -> Vec<(f64, f64)> {
+        self.samples
+            .iter()
+            .zip(&self.custom)
+            .filter_map(|(sample, values)| Some((sample.elapsed_secs, *values.get(name)?)))
+            .collect()
+    }
+
+    /// Lifecycle events in chronological order
+    pub fn events(&self) -> impl Iterator<Item = &LifecycleEvent> {
+        self.events.iter()
+    }
+
+    /// Processes seen during the window
+    pub fn processes(&self) -> impl Iterator<Item = &ProcessRecord> {
+        self.processes.values()
+    }
+
+    /// Number of samples in the window
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Length of the window in seconds
+    pub fn duration_secs(&self) -> f64 {
+        match (self.samples.front(), self.samples.back()) {
+            (Some(first), Some(last)) => last.elapsed_secs - first.elapsed_secs,
+            _ => 0.0,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.started = None;
+        self.samples.clear();
+        self.custom.clear();
+        self.processes.clear();
+        self.events.clear();
+        self.last_pids.clear();
+    }
+}
+
+/// Snapshot sink recording into a shared `ReportHistory`
+///
+/// Clone the recorder before handing it to the updater; every clone shares
+/// the same window, so the UI thread can take a copy for export at any time.
+#[derive(Clone)]
+pub struct HistoryRecorder {
+    shared: Arc<Mutex<ReportHistory>>,
+}
+
+impl HistoryRecorder {
+    /// Create a recorder keeping at most `capacity` samples
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            shared: Arc::new(Mutex::new(ReportHistory::new(capacity))),
+        }
+    }
+
+    /// Copy of the current window
+    pub fn history(&self) -> ReportHistory {
+        self.shared.lock().unwrap().clone()
+    }
+
+    pub fn clear(&self) {
+        self.shared.lock().unwrap().clear();
+    }
+}
+
+impl SnapshotSink for HistoryRecorder {
+    fn on_snapshot(&mut self, snapshot: &ProcessSnapshot) {
+        self.shared.lock().unwrap().record(snapshot);
+    }
+}
+
+/// Report rendering options
+#[derive(Debug, Clone)]
+pub struct ReportOptions {
+    /// Document title and heading
+    pub title: String,
+    /// Rows per top-N process table
+    pub top_n: usize,
+    /// Maximum lifecycle events listed (most recent first)
+    pub max_events: usize,
+    /// Separators and byte units of every value in the report
+    pub numbers: NumberFormat,
+}
+
+impl ReportOptions {
+    pub fn new() -> Self {
+        Self {
+            title: "Task Manager Performance Report".to_string(),
+            top_n: 10,
+            max_events: 200,
+            numbers: NumberFormat::new(),
+        }
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    pub fn with_top_n(mut self, top_n: usize) -> Self {
+        self.top_n = top_n;
+        self
+    }
+
+    pub fn with_max_events(mut self, max_events: usize) -> Self {
+        self.max_events = max_events;
+        self
+    }
+
+    pub fn with_number_format(mut self, numbers: NumberFormat) -> Self {
+        self.numbers = numbers;
+        self
+    }
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Render the history window as a standalone HTML document
+pub fn render_html_report(history: &ReportHistory, options: &ReportOptions) -> String {
+    let mut out = String::with_capacity(64 * 1024);
+    let title = escape_html(&options.title);
+    let numbers = &options.numbers;
+
+    let _ = writeln!(out, "<!DOCTYPE html>");
+    let _ = writeln!(out, "<html lang=\"en\">");
+    let _ = writeln!(out, "<head>");
+    let _ = writeln!(out, "<meta charset=\"utf-8\">");
+    let _ = writeln!(out, "<title>{}</title>", title);
+    let _ = writeln!(out, "<style>{}</style>", REPORT_CSS);
+    let _ = writeln!(out, "</head>");
+    let _ = writeln!(out, "<body>");
+    let _ = writeln!(out, "<h1>{}</h1>", title);
+    let _ = writeln!(
+        out,
+        "<p class=\"meta\">Generated {} &middot; {} samples over {}</p>",
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S %z"),
+        numbers.integer(history.len() as i64),
+        numbers.duration(history.duration_secs())
+    );
+
+    if history.is_empty() {
+        let _ = writeln!(out, "<p>No samples were recorded.</p>");
+        let _ = writeln!(out, "</body>\n</html>");
+        return out;
+    }
+
+    write_statistics(&mut out, history, numbers);
+    write_charts(&mut out, history, numbers);
+    write_process_tables(&mut out, history, options.top_n, numbers);
+    write_events(&mut out, history, options.max_events, numbers);
+
+    let _ = writeln!(out, "</body>\n</html>");
+    out
+}
+
+/// Embedded stylesheet (kept inline so the report is a single file)
+const REPORT_CSS: &str = "body{font-family:'Segoe UI',sans-serif;margin:24px;color:#1b1b1b}\
+h1{font-weight:600}h2{margin-top:32px;font-weight:600}\
+.meta{color:#616161}\
+table{border-collapse:collapse;margin:8px 0}\
+th,td{padding:4px 12px;border-bottom:1px solid #e0e0e0;text-align:right}\
+th:first-child,td:first-child{text-align:left}\
+svg{display:block;margin:8px 0;background:#fafafa}\
+.legend span{margin-right:16px}";
+
+/// Summary table of min/avg/p95/max per metric
+fn write_statistics(out: &mut String, history: &ReportHistory, numbers: &NumberFormat) {
+    type Extract = fn(&ReportSample) -> f32;
+    let rows: [(&str, Extract, Format); 7] = [
+        ("CPU", |s| s.cpu_percent, format_percent),
+        ("Memory load", |s| s.memory_percent, format_percent),
+        ("Memory used", |s| s.memory_used_bytes as f32, format_bytes),
+        ("Disk read", |s| s.disk_read_bps as f32, format_rate),
+        ("Disk write", |s| s.disk_write_bps as f32, format_rate),
+        (
+            "Network receive",
+            |s| s.network_receive_bps as f32,
+            format_rate,
+        ),
+        (
+            "Network transmit",
+            |s| s.network_transmit_bps as f32,
+            format_rate,
+        ),
+    ];
+
+    let _ = writeln!(out, "<h2>Summary</h2>");
+    let _ = writeln!(
+        out,
+        "<table><tr><th>Metric</th><th>Min</th><th>Avg</th><th>P95</th><th>Max</th></tr>"
+    );
+    let custom: Vec<(String, Vec<f32>)> = history
+        .custom_metric_names()
+        .into_iter()
+        .map(|name| (escape_html(name), custom_values(history, name)))
+        .collect();
+    let fixed = rows.into_iter().map(|(name, extract, format)| {
+        let values = history.samples().map(extract).collect();
+        (name.to_string(), values, format)
+    });
+    let custom = custom
+        .into_iter()
+        .map(|(name, values)| (name, values, format_number as Format));
+
+    for (name, values, format) in fixed.chain(custom) {
+        let aggregation = MetricAggregation::new(values);
+        let cell = |value: Option<f32>| match value {
+            Some(value) => format(numbers, value),
+            None => "-".to_string(),
+        };
+        let _ = writeln!(
+            out,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            name,
+            cell(aggregation.min()),
+            cell(aggregation.avg()),
+            cell(aggregation.p95()),
+            cell(aggregation.max())
+        );
+    }
+    let _ = writeln!(out, "</table>");
+}
+
+/// CPU, memory, disk and network charts
+fn write_charts(out: &mut String, history: &ReportHistory, numbers: &NumberFormat) {
+    let series = |extract: fn(&ReportSample) -> f32| -> Vec<f32> {
+        history.samples().map(extract).collect()
+    };
+    let duration = history.duration_secs();
+
+    let _ = writeln!(out, "<h2>CPU</h2>");
+    write_chart(
+        out,
+        &[("CPU %", COLOR_PRIMARY, series(|s| s.cpu_percent))],
+        Some(100.0),
+        duration,
+        numbers,
+        format_percent,
+    );
+
+    let _ = writeln!(out, "<h2>Memory</h2>");
+    write_chart(
+        out,
+        &[("Memory load %", COLOR_PRIMARY, series(|s| s.memory_percent))],
+        Some(100.0),
+        duration,
+        numbers,
+        format_percent,
+    );
+
+    let _ = writeln!(out, "<h2>Disk</h2>");
+    write_chart(
+        out,
+        &[
+            ("Read", COLOR_PRIMARY, series(|s| s.disk_read_bps as f32)),
+            (
+                "Write",
+                COLOR_SECONDARY,
+                series(|s| s.disk_write_bps as f32),
+            ),
+        ],
+        None,
+        duration,
+        numbers,
+        format_rate,
+    );
+
+    let _ = writeln!(out, "<h2>Network</h2>");
+    write_chart(
+        out,
+        &[
+            (
+                "Receive",
+                COLOR_PRIMARY,
+                series(|s| s.network_receive_bps as f32),
+            ),
+            (
+                "Transmit",
+                COLOR_SECONDARY,
+                series(|s| s.network_transmit_bps as f32),
+            ),
+        ],
+        None,
+        duration,
+        numbers,
+        format_rate,
+    );
+
+    for name in history.custom_metric_names() {
+        let _ = writeln!(out, "<h2>{}</h2>", escape_html(name));
+        write_chart(
+            out,
+            &[(name, COLOR_PRIMARY, custom_values(history, name))],
+            None,
+            duration,
+            numbers,
+            format_number,
+        );
+    }
+}
+
+fn custom_values(history: &ReportHistory, name: &str) -> Vec<f32> {
+    history
+        .custom_series(name)
+        .into_iter()
+        .map(|(_, value)| value as f32)
+        .collect()
+}
+
+/// Render one inline SVG line chart with a legend
+///
+/// # Arguments
+/// * `series` - (label, stroke color, values) per line
+/// * `y_max` - Fixed upper bound, or `None` to scale to the largest value
+/// * `duration_secs` - Window length used for the time axis labels
+/// * `numbers`, `format` - Formatter for the value axis labels
+fn write_chart(
+    out: &mut String,
+    series: &[(&str, Rgba, Vec<f32>)],
+    y_max: Option<f32>,
+    duration_secs: f64,
+    numbers: &NumberFormat,
+    format: Format,
+) {
+    let bounds = PlotRect::from_size(CHART_WIDTH, CHART_HEIGHT).inset(
+        CHART_MARGIN_LEFT,
+        CHART_MARGIN_TOP,
+        CHART_MARGIN_RIGHT,
+        CHART_MARGIN_BOTTOM,
+    );
+    let y_max = y_max
+        .unwrap_or_else(|| {
+            series
+                .iter()
+                .flat_map(|(_, _, values)| values.iter().copied())
+                .fold(0.0, f32::max)
+        })
+        .max(1.0);
+
+    let value_labels: Vec<(f32, String)> = (0..=4)
+        .map(|step| {
+            let fraction = step as f32 / 4.0;
+            (fraction, format(numbers, y_max * fraction))
+        })
+        .collect();
+    let time_labels = vec![
+        (0.0, format!("-{}", numbers.duration(duration_secs))),
+        (1.0, "now".to_string()),
+    ];
+
+    let mut doc = SvgDocument::new(CHART_WIDTH, CHART_HEIGHT);
+    doc.axis(
+        &axis_layout(true, &value_labels, true, &bounds),
+        GRID_COLOR,
+        LABEL_COLOR,
+    );
+    doc.axis(
+        &axis_layout(false, &time_labels, false, &bounds),
+        GRID_COLOR,
+        LABEL_COLOR,
+    );
+    for (_, color, values) in series {
+        doc.polyline(&line_points(values, (0.0, y_max), &bounds), *color, 1.5);
+    }
+    out.push_str(&doc.finish());
+
+    let _ = write!(out, "<div class=\"legend\">");
+    for (label, color, _) in series {
+        let _ = write!(
+            out,
+            "<span><span style=\"color:{}\">&#9632;</span> {}</span>",
+            color.hex(),
+            escape_html(label)
+        );
+    }
+    let _ = writeln!(out, "</div>");
+}
+
+/// Top-N tables by peak working set and CPU time used in the window
+fn write_process_tables(
+    out: &mut String,
+    history: &ReportHistory,
+    top_n: usize,
+    numbers: &NumberFormat,
+) {
+    let mut by_memory: Vec<&ProcessRecord> = history.processes().collect();
+    by_memory.sort_by_key(|r| (std::cmp::Reverse(r.peak_working_set), r.pid));
+    by_memory.truncate(top_n);
+
+    let mut by_cpu: Vec<&ProcessRecord> = history.processes().collect();
+    by_cpu.sort_by(|a, b| {
+        b.cpu_seconds()
+            .total_cmp(&a.cpu_seconds())
+            .then(a.pid.cmp(&b.pid))
+    });
+    by_cpu.truncate(top_n);
+
+    let _ = writeln!(out, "<h2>Top processes by memory</h2>");
+    let _ = writeln!(
+        out,
+        "<table><tr><th>Name</th><th>PID</th><th>Peak working set</th><th>Peak private</th></tr>"
+    );
+    for record in by_memory {
+        let _ = writeln!(
+            out,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            escape_html(&record.name),
+            record.pid,
+            numbers.bytes(record.peak_working_set),
+            numbers.bytes(record.peak_private)
+        );
+    }
+    let _ = writeln!(out, "</table>");
+
+    let _ = writeln!(out, "<h2>Top processes by CPU time</h2>");
+    let _ = writeln!(
+        out,
+        "<table><tr><th>Name</th><th>PID</th><th>CPU time</th></tr>"
+    );
+    for record in by_cpu {
+        let _ = writeln!(
+            out,
+            "<tr><td>{}</td><td>{}</td><td>{} s</td></tr>",
+            escape_html(&record.name),
+            record.pid,
+            numbers.decimal(record.cpu_seconds(), 2)
+        );
+    }
+    let _ = writeln!(out, "</table>");
+}
+
+/// Lifecycle event table, most recent first
+fn write_events(
+    out: &mut String,
+    history: &ReportHistory,
+    max_events: usize,
+    numbers: &NumberFormat,
+) {
+    let _ = writeln!(out, "<h2>Process events</h2>");
+    if history.events.is_empty() {
+        let _ = writeln!(
+            out,
+            "<p>No processes started or exited during the window.</p>"
+        );
+        return;
+    }
+
+    let _ = writeln!(
+        out,
+        "<table><tr><th>Event</th><th>Time</th><th>Name</th><th>PID</th></tr>"
+    );
+    for event in history.events.iter().rev().take(max_events) {
+        let _ = writeln!(
+            out,
+            "<tr><td>{}</td><td>+{}</td><td>{}</td><td>{}</td></tr>",
+            event.kind.label(),
+            numbers.duration(event.elapsed_secs),
+            escape_html(&event.name),
+            event.pid
+        );
+    }
+    let _ = writeln!(out, "</table>");
+}
+
+/// Escape text for HTML element content and attribute values
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// Formatter for one kind of sampled value
+type Format = fn(&NumberFormat, f32) -> String;
+
+fn format_percent(numbers: &NumberFormat, value: f32) -> String {
+    numbers.percent(value as f64)
+}
+
+fn format_number(numbers: &NumberFormat, value: f32) -> String {
+    numbers.number(value as f64)
+}
+
+fn format_rate(numbers: &NumberFormat, bytes_per_sec: f32) -> String {
+    numbers.rate(bytes_per_sec.max(0.0) as f64)
+}
+
+fn format_bytes(numbers: &NumberFormat, bytes: f32) -> String {
+    numbers.bytes_f64(bytes.max(0.0) as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{ProcessBuilder, SnapshotBuilder};
+    use std::time::Duration;
+
+    const GB: u64 = 1024 * 1024 * 1024;
+
+    #[test]
+    fn test_history_lifecycle_events() {
+        let start = Instant::now();
+        let mut history = ReportHistory::new(10);
+
+        history.record(
+            &SnapshotBuilder::new()
+                .with_timestamp(start)
+                .with_processes(vec![ProcessBuilder::new(1, "a.exe")
+                    .with_working_set(100)
+                    .build()])
+                .with_cpu(10.0, Vec::new())
+                .with_memory(16 * GB, 8 * GB)
+                .build(),
+        );
+        history.record(
+            &SnapshotBuilder::new()
+                .with_timestamp(start + Duration::from_secs(1))
+                .with_processes(vec![
+                    ProcessBuilder::new(1, "a.exe")
+                        .with_working_set(200)
+                        .build(),
+                    ProcessBuilder::new(2, "b.exe").with_working_set(50).build(),
+                ])
+                .with_cpu(20.0, Vec::new())
+                .with_memory(16 * GB, 8 * GB)
+                .build(),
+        );
+        history.record(
+            &SnapshotBuilder::new()
+                .with_timestamp(start + Duration::from_secs(2))
+                .with_processes(vec![ProcessBuilder::new(2, "b.exe")
+                    .with_working_set(50)
+                    .build()])
+                .with_cpu(30.0, Vec::new())
+                .with_memory(16 * GB, 8 * GB)
+                .build(),
+        );
+
+        let events: Vec<_> = history.events().map(|e| (e.kind, e.pid)).collect();
+        assert_eq!(
+            events,
+            vec![(LifecycleKind::Started, 2), (LifecycleKind::Exited, 1)]
+        );
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.duration_secs(), 2.0);
+
+        let record = history.processes().find(|r| r.pid == 1).unwrap();
+        assert_eq!(record.peak_working_set, 200);
+    }
+
+    #[test]
+    fn test_history_capacity_prunes_window() {
+        let start = Instant::now();
+        let mut history = ReportHistory::new(2);
+
+        history.record(
+            &SnapshotBuilder::new()
+                .with_timestamp(start)
+                .with_processes(vec![ProcessBuilder::new(1, "old.exe")
+                    .with_working_set(100)
+                    .build()])
+                .with_cpu(10.0, Vec::new())
+                .with_memory(16 * GB, 8 * GB)
+                .build(),
+        );
+        for i in 1..=3 {
+            history.record(
+                &SnapshotBuilder::new()
+                    .with_timestamp(start + Duration::from_secs(i))
+                    .with_processes(vec![ProcessBuilder::new(2, "new.exe")
+                        .with_working_set(100)
+                        .build()])
+                    .with_cpu(10.0, Vec::new())
+                    .with_memory(16 * GB, 8 * GB)
+                    .build(),
+            );
+        }
+
+        assert_eq!(history.len(), 2);
+        assert!(history.processes().all(|r| r.pid == 2));
+        assert!(history.events().all(|e| e.elapsed_secs >= 2.0));
+    }
+
+    #[test]
+    fn test_cpu_seconds_within_window() {
+        let start = Instant::now();
+        let mut history = ReportHistory::new(10);
+
+        history.record(
+            &SnapshotBuilder::new()
+                .with_timestamp(start)
+                .with_processes(vec![ProcessBuilder::new(1, "a.exe")
+                    .with_working_set(100)
+                    .with_cpu_time(50_000_000)
+                    .build()])
+                .with_cpu(10.0, Vec::new())
+                .with_memory(16 * GB, 8 * GB)
+                .build(),
+        );
+        history.record(
+            &SnapshotBuilder::new()
+                .with_timestamp(start + Duration::from_secs(1))
+                .with_processes(vec![ProcessBuilder::new(1, "a.exe")
+                    .with_working_set(100)
+                    .with_cpu_time(80_000_000)
+                    .build()])
+                .with_cpu(10.0, Vec::new())
+                .with_memory(16 * GB, 8 * GB)
+                .build(),
+        );
+
+        let record = history.processes().next().unwrap();
+        assert_eq!(record.cpu_seconds(), 3.0);
+    }
+
+    #[test]
+    fn test_render_report_sections() {
+        let start = Instant::now();
+        let mut history = ReportHistory::new(10);
+        history.record(
+            &SnapshotBuilder::new()
+                .with_timestamp(start)
+                .with_processes(vec![ProcessBuilder::new(1, "<evil>.exe")
+                    .with_working_set(100)
+                    .build()])
+                .with_cpu(10.0, Vec::new())
+                .with_memory(16 * GB, 8 * GB)
+                .build(),
+        );
+        history.record(
+            &SnapshotBuilder::new()
+                .with_timestamp(start + Duration::from_secs(1))
+                .with_processes(vec![ProcessBuilder::new(1, "<evil>.exe")
+                    .with_working_set(100)
+                    .build()])
+                .with_cpu(90.0, Vec::new())
+                .with_memory(16 * GB, 8 * GB)
+                .build(),
+        );
+
+        let html = render_html_report(&history, &ReportOptions::new().with_title("Ticket 42"));
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Ticket 42</title>"));
+        assert_eq!(html.matches("<svg").count(), 4);
+        assert!(
+            html.contains("<td>CPU</td><td>10.0%</td><td>50.0%</td><td>90.0%</td><td>90.0%</td>")
+        );
+        assert!(html.contains("&lt;evil&gt;.exe"));
+        assert!(!html.contains("<evil>"));
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("src=\"http"));
+    }
+
+    #[test]
+    fn test_custom_metrics_in_history_and_report() {
+        let start = Instant::now();
+        let mut history = ReportHistory::new(2);
+        for (secs, depth) in [(0, None), (1, Some(3.0)), (2, Some(5.0))] {
+            let mut snapshot = SnapshotBuilder::new()
+                .with_timestamp(start + Duration::from_secs(secs))
+                .with_cpu(10.0, Vec::new())
+                .with_memory(16 * GB, 8 * GB)
+                .build();
+            if let Some(depth) = depth {
+                snapshot.system_metrics.custom.insert("queue.depth".to_string(), depth);
+            }
+            history.record(&snapshot);
+        }
+
+        assert_eq!(history.custom_metric_names(), vec!["queue.depth"]);
+        assert_eq!(history.custom_series("queue.depth"), vec![(1.0, 3.0), (2.0, 5.0)]);
+
+        let html = render_html_report(&history, &ReportOptions::new());
+        assert_eq!(html.matches("<svg").count(), 5);
+        assert!(html.contains("<td>queue.depth</td><td>3</td><td>4</td>"));
+    }
+
+    #[test]
+    fn test_render_empty_history() {
+        let html = render_html_report(&ReportHistory::new(10), &ReportOptions::default());
+        assert!(html.contains("No samples were recorded."));
+        assert!(!html.contains("<svg"));
+    }
+
+    #[test]
+    fn test_recorder_shares_history() {
+        let recorder = HistoryRecorder::new(10);
+        let mut sink: Box<dyn SnapshotSink> = Box::new(recorder.clone());
+
+        sink.on_snapshot(&SnapshotBuilder::new().build());
+
+        assert_eq!(recorder.history().len(), 1);
+        recorder.clear();
+        assert!(recorder.history().is_empty());
+    }
+}
@@ -0,0 +1,974 @@
+//! Local JSON-RPC scripting endpoint
+//!
+//! Opt-in (`RpcConfig`) JSON-RPC 2.0 over HTTP `POST /rpc` on 127.0.0.1, so
+//! automation can drive the task manager instead of shelling out:
+//! - `list_processes` `{filter?, sort?, ascending?, top?}`
+//! - `get_details` `{pid}`
+//! - `terminate`, `suspend` `{pid, confirm?}`; `resume` `{pid}`
+//! - `set_priority` `{pid, priority, confirm?}`
+//! - `set_affinity` `{pid, mask}` or `{pid, cpus: "0,2-3"}`
+//!
+//! Control calls go through `ProcessActuator`, and `ProcessError` maps to
+//! fixed error codes (see `RpcError`). Operations the UI asks about first
+//! (ending, suspending, realtime priority) fail with `CONFIRMATION_REQUIRED`
+//! unless the call passes `"confirm": true` or the user turned that dialog
+//! off in the shared `DialogPreferences`.
+//!
+//! Every request needs `Authorization: Bearer <token>` and a JSON content
+//! type; requests carrying an `Origin` header are refused so a web page
+//! cannot reach the endpoint through the user's browser.
+
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use crate::app::agent::constant_time_eq;
+use crate::app::cli;
+use crate::app::config::RpcConfig;
+use crate::app::leaks::apply_leak_rates;
+use crate::app::policies::{parse_cpu_list, ProcessActuator};
+use crate::app::updater::SnapshotSink;
+use crate::core::filter::{
+    parse_filter, parse_sort, ProcessFilter, ProcessInfo, ProcessSorter, SortColumn,
+    SortDirection,
+};
+use crate::core::leak::LeakDetector;
+use crate::ui::dialogs::confirm::DialogPreferences;
+use crate::windows::monitor::ProcessSnapshot;
+use crate::windows::process::control::{PriorityClass, ProcessError};
+use crate::windows::process::details::{self, ProcessDetails};
+
+/// Accept loop poll interval while waiting for connections
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Per-connection read/write timeout
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Largest accepted request (headers plus body)
+const MAX_REQUEST_SIZE: usize = 64 * 1024;
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+/// Process operation failed (`InvalidOperation`, `WindowsError`)
+pub const PROCESS_FAILED: i64 = -32000;
+pub const PROCESS_NOT_FOUND: i64 = -32001;
+pub const ACCESS_DENIED: i64 = -32002;
+/// The UI would ask first; repeat the call with `"confirm": true`
+pub const CONFIRMATION_REQUIRED: i64 = -32003;
+/// No snapshot collected yet
+pub const NO_DATA: i64 = -32004;
+pub const TIMEOUT: i64 = -32005;
+
+/// JSON-RPC error object
+#[derive(Debug, Clone, PartialEq)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+    /// Longer explanation (the text the UI's error dialog would show)
+    pub detail: Option<String>,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            detail: None,
+        }
+    }
+
+    fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(INVALID_PARAMS, message)
+    }
+
+    fn to_json(&self) -> Value {
+        let mut error = json!({ "code": self.code, "message": self.message });
+        if let Some(detail) = &self.detail {
+            error["data"] = json!({ "detail": detail });
+        }
+        error
+    }
+}
+
+impl From<ProcessError> for RpcError {
+    fn from(error: ProcessError) -> Self {
+        let code = match error {
+            ProcessError::NotFound(_) => PROCESS_NOT_FOUND,
+            ProcessError::AccessDenied(_) => ACCESS_DENIED,
+            ProcessError::Timeout => TIMEOUT,
+            _ => PROCESS_FAILED,
+        };
+        Self {
+            code,
+            message: error.short_description(),
+            detail: Some(error.user_message()),
+        }
+    }
+}
+
+/// Rows published by `RpcSink` for `list_processes`
+type SharedRows = Arc<RwLock<Option<Vec<ProcessInfo>>>>;
+
+/// Snapshot sink computing process rows (with CPU %) for the endpoint
+pub struct RpcSink {
+    shared: SharedRows,
+    previous: Option<ProcessSnapshot>,
+    cpus: usize,
+    leaks: Option<Arc<Mutex<LeakDetector>>>,
+}
+
+impl RpcSink {
+    fn new(shared: &SharedRows, leaks: &Option<Arc<Mutex<LeakDetector>>>) -> Self {
+        Self {
+            shared: Arc::clone(shared),
+            previous: None,
+            cpus: num_cpus::get(),
+            leaks: leaks.clone(),
+        }
+    }
+}
+
+impl SnapshotSink for RpcSink {
+    fn on_snapshot(&mut self, snapshot: &ProcessSnapshot) {
+        let previous = self.previous.as_ref().unwrap_or(snapshot);
+        let mut rows = cli::process_rows(previous, snapshot, self.cpus);
+        if let Some(detector) = &self.leaks {
+            apply_leak_rates(&mut rows, detector);
+        }
+        *self.shared.write().unwrap() = Some(rows);
+        self.previous = Some(snapshot.clone());
+    }
+}
+
+/// Dispatches JSON-RPC calls to process operations
+pub struct RpcHandler {
+    actuator: Box<dyn ProcessActuator>,
+    preferences: Arc<DialogPreferences>,
+    details_provider: fn(u32) -> Option<ProcessDetails>,
+    rows: SharedRows,
+    leaks: Option<Arc<Mutex<LeakDetector>>>,
+}
+
+impl RpcHandler {
+    /// Handler acting through `actuator`, confirming per `preferences`
+    pub fn new(actuator: Box<dyn ProcessActuator>, preferences: Arc<DialogPreferences>) -> Self {
+        Self {
+            actuator,
+            preferences,
+            details_provider: details::get_process_details,
+            rows: Arc::new(RwLock::new(None)),
+            leaks: None,
+        }
+    }
+
+    /// Replace the `get_details` lookup (defaults to `get_process_details`)
+    pub fn with_details_provider(mut self, provider: fn(u32) -> Option<ProcessDetails>) -> Self {
+        self.details_provider = provider;
+        self
+    }
+
+    /// Report leak rates from `LeakMonitor::detector` in `list_processes`
+    pub fn with_leak_detector(mut self, detector: Arc<Mutex<LeakDetector>>) -> Self {
+        self.leaks = Some(detector);
+        self
+    }
+
+    /// Create a sink that feeds `list_processes`
+    pub fn sink(&self) -> RpcSink {
+        RpcSink::new(&self.rows, &self.leaks)
+    }
+
+    /// Handle a request body; `None` if nothing should be sent back
+    /// (only notifications)
+    pub fn handle_body(&mut self, body: &str) -> Option<Value> {
+        let request: Value = match serde_json::from_str(body) {
+            Ok(request) => request,
+            Err(e) => {
+                return Some(error_response(
+                    Value::Null,
+                    &RpcError::new(PARSE_ERROR, e.to_string()),
+                ))
+            }
+        };
+
+        match request {
+            Value::Array(batch) if batch.is_empty() => Some(error_response(
+                Value::Null,
+                &RpcError::new(INVALID_REQUEST, "empty batch"),
+            )),
+            Value::Array(batch) => {
+                let responses: Vec<Value> = batch
+                    .into_iter()
+                    .filter_map(|call| self.handle_call(call))
+                    .collect();
+                (!responses.is_empty()).then_some(Value::Array(responses))
+            }
+            call => self.handle_call(call),
+        }
+    }
+
+    /// Handle one call object; notifications (no `id`) get no response
+    fn handle_call(&mut self, call: Value) -> Option<Value> {
+        let id = call.get("id").cloned();
+        let method = call.get("method").and_then(Value::as_str);
+        if call.get("jsonrpc").and_then(Value::as_str) != Some("2.0") || method.is_none() {
+            return Some(error_response(
+                id.unwrap_or(Value::Null),
+                &RpcError::new(INVALID_REQUEST, "expected a JSON-RPC 2.0 request"),
+            ));
+        }
+
+        let params = call.get("params").cloned().unwrap_or(Value::Null);
+        let result = self.dispatch(method.unwrap_or_default(), &params);
+        let id = id?;
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(error) => error_response(id, &error),
+        })
+    }
+
+    fn dispatch(&mut self, method: &str, params: &Value) -> Result<Value, RpcError> {
+        match method {
+            "list_processes" => self.list_processes(params),
+            "get_details" => self.get_details(pid_param(params)?),
+            "terminate" => {
+                let pid = pid_param(params)?;
+                self.check_control(pid)?;
+                if self.preferences.should_confirm_end_process() {
+                    require_confirm(params, "ending a process")?;
+                }
+                self.actuator.terminate(pid)?;
+                Ok(Value::Bool(true))
+            }
+            "suspend" => {
+                let pid = pid_param(params)?;
+                self.check_control(pid)?;
+                if self.preferences.should_confirm_suspend() {
+                    require_confirm(params, "suspending a process")?;
+                }
+                self.actuator.suspend(pid)?;
+                Ok(Value::Bool(true))
+            }
+            "resume" => {
+                let pid = pid_param(params)?;
+                self.check_control(pid)?;
+                self.actuator.resume(pid)?;
+                Ok(Value::Bool(true))
+            }
+            "set_priority" => {
+                let pid = pid_param(params)?;
+                let priority = params
+                    .get("priority")
+                    .cloned()
+                    .ok_or_else(|| RpcError::invalid_params("missing 'priority'"))?;
+                let class: PriorityClass = serde_json::from_value(priority)
+                    .map_err(|_| RpcError::invalid_params("invalid 'priority'"))?;
+                self.check_control(pid)?;
+                if class == PriorityClass::Realtime && self.preferences.should_confirm_realtime() {
+                    require_confirm(params, "realtime priority")?;
+                }
+                self.actuator.set_priority(pid, class)?;
+                Ok(Value::Bool(true))
+            }
+            "set_affinity" => {
+                let pid = pid_param(params)?;
+                let mask = match (params.get("mask"), params.get("cpus")) {
+                    (Some(mask), None) => mask
+                        .as_u64()
+                        .map(|mask| mask as usize)
+                        .ok_or_else(|| RpcError::invalid_params("invalid 'mask'"))?,
+                    (None, Some(Value::String(cpus))) => {
+                        parse_cpu_list(cpus).map_err(RpcError::invalid_params)?
+                    }
+                    _ => return Err(RpcError::invalid_params("expected one of 'mask' or 'cpus'")),
+                };
+                if mask == 0 {
+                    return Err(RpcError::invalid_params("affinity mask selects no CPU"));
+                }
+                self.check_control(pid)?;
+                self.actuator.set_affinity(pid, mask)?;
+                Ok(Value::Bool(true))
+            }
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("unknown method '{}'", method),
+            )),
+        }
+    }
+
+    fn list_processes(&self, params: &Value) -> Result<Value, RpcError> {
+        let mut filter = ProcessFilter::new();
+        let expressions = match params.get("filter") {
+            None => Vec::new(),
+            Some(Value::String(expr)) => vec![expr.as_str()],
+            Some(Value::Array(exprs)) => exprs
+                .iter()
+                .map(|expr| {
+                    expr.as_str()
+                        .ok_or_else(|| RpcError::invalid_params("invalid 'filter'"))
+                })
+                .collect::<Result<_, _>>()?,
+            Some(_) => return Err(RpcError::invalid_params("invalid 'filter'")),
+        };
+        for expr in expressions {
+            filter =
+                parse_filter(expr, filter).map_err(RpcError::invalid_params)?;
+        }
+
+        let sort = match params.get("sort").map(Value::as_str) {
+            None => SortColumn::Cpu,
+            Some(Some(name)) => {
+                parse_sort(name).ok_or_else(|| RpcError::invalid_params("invalid 'sort'"))?
+            }
+            Some(None) => return Err(RpcError::invalid_params("invalid 'sort'")),
+        };
+        let direction = if params
+            .get("ascending")
+            .and_then(Value::as_bool)
+            .unwrap_or(false)
+        {
+            SortDirection::Ascending
+        } else {
+            SortDirection::Descending
+        };
+
+        let guard = self.rows.read().unwrap();
+        let rows = guard
+            .as_ref()
+            .ok_or_else(|| RpcError::new(NO_DATA, "no snapshot collected yet"))?;
+        let mut selected = filter.apply(rows);
+        ProcessSorter::new(sort, direction).sort_refs(&mut selected);
+        if let Some(top) = params.get("top").and_then(Value::as_u64) {
+            selected.truncate(top as usize);
+        }
+        Ok(Value::Array(
+            selected.into_iter().map(cli::row_json).collect(),
+        ))
+    }
+
+    fn get_details(&self, pid: u32) -> Result<Value, RpcError> {
+        let name = self
+            .rows
+            .read()
+            .unwrap()
+            .as_ref()
+            .and_then(|rows| rows.iter().find(|p| p.pid == pid).map(|p| p.name.clone()));
+        let Some(details) = (self.details_provider)(pid) else {
+            // Listed but not openable means a protected or elevated process
+            return Err(match name {
+                Some(name) => {
+                    ProcessError::AccessDenied(format!("cannot open {} (pid {})", name, pid))
+                }
+                None => ProcessError::NotFound(pid),
+            }
+            .into());
+        };
+
+        let memory = &details.memory_details;
+        Ok(json!({
+            "pid": details.pid,
+            "name": name,
+            "full_path": details.full_path,
+            "command_line": details.command_line,
+            "username": details.username,
+            "integrity_level": format!("{:?}", details.integrity_level),
+            "handles": details.handle_count,
+            "gdi_objects": details.gdi_objects,
+            "user_objects": details.user_objects,
+            "memory": {
+                "private_bytes": memory.private_bytes,
+                "working_set_bytes": memory.working_set,
+                "peak_working_set_bytes": memory.peak_working_set,
+                "pagefile_bytes": memory.pagefile_usage,
+                "peak_pagefile_bytes": memory.peak_pagefile_usage,
+                "shared_bytes": memory.shared_bytes,
+            },
+        }))
+    }
+
+    /// Refuse to touch Idle, System or the task manager itself
+    fn check_control(&self, pid: u32) -> Result<(), RpcError> {
+        if pid == 0 || pid == 4 || pid == std::process::id() {
+            return Err(ProcessError::AccessDenied(format!("process {} is protected", pid)).into());
+        }
+        Ok(())
+    }
+}
+
+fn pid_param(params: &Value) -> Result<u32, RpcError> {
+    params
+        .get("pid")
+        .and_then(Value::as_u64)
+        .and_then(|pid| u32::try_from(pid).ok())
+        .ok_or_else(|| RpcError::invalid_params("missing or invalid 'pid'"))
+}
+
+fn require_confirm(params: &Value, action: &str) -> Result<(), RpcError> {
+    if params.get("confirm").and_then(Value::as_bool) == Some(true) {
+        Ok(())
+    } else {
+        Err(RpcError::new(
+            CONFIRMATION_REQUIRED,
+            format!("{} requires \"confirm\": true", action),
+        ))
+    }
+}
+
+fn error_response(id: Value, error: &RpcError) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": error.to_json() })
+}
+
+/// Localhost HTTP listener serving `POST /rpc`
+///
+/// # Threading Model
+///
+/// Accepts connections on a dedicated background thread and handles calls
+/// one at a time, so process operations never run concurrently. Process
+/// listings read the rows last published by `RpcSink`.
+pub struct RpcServer {
+    rows: SharedRows,
+    leaks: Option<Arc<Mutex<LeakDetector>>>,
+    local_addr: SocketAddr,
+    shutdown: Arc<AtomicBool>,
+    thread_handle: Option<thread::JoinHandle<()>>,
+}
+
+impl RpcServer {
+    /// Bind to `127.0.0.1:port` and start serving
+    ///
+    /// Port 0 binds an ephemeral port; see `local_addr()`. Fails with
+    /// `InvalidInput` if no token is configured.
+    pub fn start(config: &RpcConfig, handler: RpcHandler) -> io::Result<Self> {
+        let token = match config.token.as_deref() {
+            Some(token) if !token.is_empty() => token.to_string(),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "rpc.token must be set to enable the endpoint",
+                ))
+            }
+        };
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, config.port))?;
+        listener.set_nonblocking(true)?;
+        let local_addr = listener.local_addr()?;
+
+        let rows = Arc::clone(&handler.rows);
+        let leaks = handler.leaks.clone();
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let thread_shutdown = Arc::clone(&shutdown);
+        let thread_handle = thread::spawn(move || {
+            run_accept_loop(listener, handler, token, thread_shutdown);
+        });
+
+        Ok(Self {
+            rows,
+            leaks,
+            local_addr,
+            shutdown,
+            thread_handle: Some(thread_handle),
+        })
+    }
+
+    /// Start the endpoint only if enabled in configuration
+    pub fn start_if_enabled(config: &RpcConfig, handler: RpcHandler) -> Option<io::Result<Self>> {
+        if config.enabled {
+            Some(Self::start(config, handler))
+        } else {
+            None
+        }
+    }
+
+    /// Create a sink that publishes updater snapshots to this endpoint
+    pub fn sink(&self) -> RpcSink {
+        RpcSink::new(&self.rows, &self.leaks)
+    }
+
+    /// Address the listener is bound to
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Stop the listener thread
+    pub fn shutdown(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+
+        if let Some(handle) = self.thread_handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for RpcServer {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Accept loop running on the listener thread
+fn run_accept_loop(
+    listener: TcpListener,
+    mut handler: RpcHandler,
+    token: String,
+    shutdown: Arc<AtomicBool>,
+) {
+    while !shutdown.load(Ordering::SeqCst) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                if let Err(e) = handle_connection(stream, &mut handler, &token) {
+                    eprintln!("RPC endpoint: request failed: {}", e);
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                thread::sleep(ACCEPT_POLL_INTERVAL);
+            }
+            Err(e) => {
+                eprintln!("RPC endpoint: accept failed: {}", e);
+                thread::sleep(ACCEPT_POLL_INTERVAL);
+            }
+        }
+    }
+}
+
+/// Serve one HTTP/1.x request and close the connection
+fn handle_connection(
+    mut stream: TcpStream,
+    handler: &mut RpcHandler,
+    token: &str,
+) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(CONNECTION_TIMEOUT))?;
+    stream.set_write_timeout(Some(CONNECTION_TIMEOUT))?;
+
+    let Some((head, body)) = read_request(&mut stream)? else {
+        return write_response(
+            &mut stream,
+            "413 Payload Too Large",
+            "text/plain",
+            "request too large\n",
+        );
+    };
+    let mut lines = head.lines();
+    let mut parts = lines.next().unwrap_or("").split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let path = parts.next().unwrap_or("");
+    let headers: HashMap<String, &str> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim()))
+        .collect();
+
+    if path.split('?').next() != Some("/rpc") {
+        return write_response(&mut stream, "404 Not Found", "text/plain", "not found\n");
+    }
+    if method != "POST" {
+        return write_response(
+            &mut stream,
+            "405 Method Not Allowed",
+            "text/plain",
+            "method not allowed\n",
+        );
+    }
+    // Browsers always send Origin on cross-site POSTs; scripts never need it
+    if headers.contains_key("origin") {
+        return write_response(&mut stream, "403 Forbidden", "text/plain", "forbidden\n");
+    }
+    let presented = headers
+        .get("authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .unwrap_or("");
+    if !constant_time_eq(presented.as_bytes(), token.as_bytes()) {
+        return write_response(
+            &mut stream,
+            "401 Unauthorized",
+            "text/plain",
+            "unauthorized\n",
+        );
+    }
+    let json = headers.get("content-type").is_some_and(|value| {
+        let media_type = value.split(';').next().unwrap_or("");
+        media_type.trim().eq_ignore_ascii_case("application/json")
+    });
+    if !json {
+        return write_response(
+            &mut stream,
+            "415 Unsupported Media Type",
+            "text/plain",
+            "expected application/json\n",
+        );
+    }
+
+    match handler.handle_body(&body) {
+        Some(response) => write_response(
+            &mut stream,
+            "200 OK",
+            "application/json",
+            &response.to_string(),
+        ),
+        None => write_response(&mut stream, "204 No Content", "application/json", ""),
+    }
+}
+
+/// Read headers and the `Content-Length` body; `None` if over the size limit
+fn read_request(stream: &mut TcpStream) -> io::Result<Option<(String, String)>> {
+    let mut buffer = Vec::with_capacity(1024);
+    let mut chunk = [0u8; 4096];
+
+    let head_end = loop {
+        if let Some(pos) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        if buffer.len() >= MAX_REQUEST_SIZE {
+            return Ok(None);
+        }
+        let n = stream.read(&mut chunk)?;
+        if n == 0 {
+            break buffer.len();
+        }
+        buffer.extend_from_slice(&chunk[..n]);
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..head_end]).into_owned();
+    let content_length = head
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+    // A client-supplied length must not overflow or grow the body unbounded
+    let request_size = head_end.checked_add(content_length);
+    if request_size.filter(|n| *n <= MAX_REQUEST_SIZE).is_none() {
+        return Ok(None);
+    }
+
+    let mut body = buffer.split_off(head_end);
+    while body.len() < content_length {
+        let n = stream.read(&mut chunk)?;
+        if n == 0 {
+            break;
+        }
+        body.extend_from_slice(&chunk[..n]);
+    }
+    body.truncate(content_length);
+    Ok(Some((head, String::from_utf8_lossy(&body).into_owned())))
+}
+
+/// Write a complete HTTP response
+fn write_response(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &str,
+) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    stream.write_all(body.as_bytes())?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{ProcessBuilder, SnapshotBuilder};
+    use crate::windows::process::details::{IntegrityLevel, MemoryDetails};
+    use std::time::Instant;
+
+    /// Actuator recording calls; PID 13 does not exist
+    struct RecordingActuator(Arc<Mutex<Vec<String>>>);
+
+    impl RecordingActuator {
+        fn record(&self, pid: u32, call: String) -> Result<(), ProcessError> {
+            if pid == 13 {
+                return Err(ProcessError::NotFound(pid));
+            }
+            self.0.lock().unwrap().push(call);
+            Ok(())
+        }
+    }
+
+    impl ProcessActuator for RecordingActuator {
+        fn set_priority(&mut self, pid: u32, class: PriorityClass) -> Result<(), ProcessError> {
+            self.record(pid, format!("priority {} {:?}", pid, class))
+        }
+
+        fn set_affinity(&mut self, pid: u32, mask: usize) -> Result<(), ProcessError> {
+            self.record(pid, format!("affinity {} {:X}", pid, mask))
+        }
+
+        fn terminate(&mut self, pid: u32) -> Result<(), ProcessError> {
+            self.record(pid, format!("terminate {}", pid))
+        }
+
+        fn suspend(&mut self, pid: u32) -> Result<(), ProcessError> {
+            self.record(pid, format!("suspend {}", pid))
+        }
+    }
+
+    fn fake_details(pid: u32) -> Option<ProcessDetails> {
+        (pid == 100).then(|| ProcessDetails {
+            pid,
+            full_path: Some("C:\\Tools\\worker.exe".to_string()),
+            command_line: None,
+            memory_details: MemoryDetails {
+                private_bytes: 4096,
+                ..MemoryDetails::default()
+            },
+            handle_count: 12,
+            gdi_objects: 0,
+            user_objects: 0,
+            integrity_level: IntegrityLevel::Medium,
+            username: Some("alice".to_string()),
+        })
+    }
+
+    fn make_handler(calls: &Arc<Mutex<Vec<String>>>) -> RpcHandler {
+        let handler = RpcHandler::new(
+            Box::new(RecordingActuator(Arc::clone(calls))),
+            Arc::new(DialogPreferences::new()),
+        )
+        .with_details_provider(fake_details);
+
+        let mut sink = handler.sink();
+        let start = Instant::now();
+        for (offset, cpu_time) in [(0, 0), (1, 5_000_000)] {
+            let snapshot = SnapshotBuilder::new()
+                .with_timestamp(start + Duration::from_secs(offset))
+                .with_processes(vec![
+                    ProcessBuilder::new(100, "worker.exe")
+                        .with_cpu_time(cpu_time)
+                        .with_memory(400 * 1024 * 1024)
+                        .build(),
+                    ProcessBuilder::new(200, "idle.exe").with_memory(1024).build(),
+                    ProcessBuilder::new(300, "locked.exe").with_memory(1024).build(),
+                ])
+                .build();
+            sink.on_snapshot(&snapshot);
+        }
+        handler
+    }
+
+    fn call(handler: &mut RpcHandler, method: &str, params: Value) -> Value {
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        handler.handle_body(&body.to_string()).unwrap()
+    }
+
+    fn error_code(response: &Value) -> Option<i64> {
+        response["error"]["code"].as_i64()
+    }
+
+    #[test]
+    fn test_list_and_details() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut handler = make_handler(&calls);
+
+        let response = call(
+            &mut handler,
+            "list_processes",
+            json!({ "filter": ["mem>100MB"] }),
+        );
+        let rows = response["result"].as_array().unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0]["name"], "worker.exe");
+        assert!(rows[0]["cpu_percent"].as_f64().unwrap() > 0.0);
+
+        let response = call(
+            &mut handler,
+            "list_processes",
+            json!({ "sort": "pid", "ascending": true, "top": 2 }),
+        );
+        let pids: Vec<u64> = response["result"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|row| row["pid"].as_u64().unwrap())
+            .collect();
+        assert_eq!(pids, vec![100, 200]);
+
+        let response = call(&mut handler, "get_details", json!({ "pid": 100 }));
+        assert_eq!(response["result"]["name"], "worker.exe");
+        assert_eq!(response["result"]["memory"]["private_bytes"], 4096);
+        assert_eq!(response["result"]["integrity_level"], "Medium");
+
+        // Listed but not openable vs. not running at all
+        let response = call(&mut handler, "get_details", json!({ "pid": 300 }));
+        assert_eq!(error_code(&response), Some(ACCESS_DENIED));
+        let response = call(&mut handler, "get_details", json!({ "pid": 999 }));
+        assert_eq!(error_code(&response), Some(PROCESS_NOT_FOUND));
+    }
+
+    #[test]
+    fn test_control_requires_confirmation() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut handler = make_handler(&calls);
+
+        let response = call(&mut handler, "terminate", json!({ "pid": 100 }));
+        assert_eq!(error_code(&response), Some(CONFIRMATION_REQUIRED));
+        let response = call(
+            &mut handler,
+            "set_priority",
+            json!({ "pid": 100, "priority": "Realtime" }),
+        );
+        assert_eq!(error_code(&response), Some(CONFIRMATION_REQUIRED));
+        assert!(calls.lock().unwrap().is_empty());
+
+        let response = call(
+            &mut handler,
+            "terminate",
+            json!({ "pid": 100, "confirm": true }),
+        );
+        assert_eq!(response["result"], true);
+        // Only realtime needs confirming; affinity never does
+        call(
+            &mut handler,
+            "set_priority",
+            json!({ "pid": 100, "priority": "BelowNormal" }),
+        );
+        call(
+            &mut handler,
+            "set_affinity",
+            json!({ "pid": 100, "cpus": "0-1" }),
+        );
+
+        // "Don't ask again" in the UI also applies to scripts
+        handler.preferences.set_dont_ask_suspend(true);
+        call(&mut handler, "suspend", json!({ "pid": 100 }));
+
+        assert_eq!(
+            calls.lock().unwrap().as_slice(),
+            [
+                "terminate 100",
+                "priority 100 BelowNormal",
+                "affinity 100 3",
+                "suspend 100"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_error_mapping() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut handler = make_handler(&calls);
+
+        let response = call(
+            &mut handler,
+            "terminate",
+            json!({ "pid": 13, "confirm": true }),
+        );
+        assert_eq!(error_code(&response), Some(PROCESS_NOT_FOUND));
+        assert!(response["error"]["data"]["detail"]
+            .as_str()
+            .unwrap()
+            .contains("13"));
+
+        let response = call(
+            &mut handler,
+            "terminate",
+            json!({ "pid": 4, "confirm": true }),
+        );
+        assert_eq!(error_code(&response), Some(ACCESS_DENIED));
+        // Not overridden by the actuator
+        let response = call(&mut handler, "resume", json!({ "pid": 100 }));
+        assert_eq!(error_code(&response), Some(PROCESS_FAILED));
+
+        assert_eq!(
+            error_code(&call(&mut handler, "reboot", Value::Null)),
+            Some(METHOD_NOT_FOUND)
+        );
+        assert_eq!(
+            error_code(&call(&mut handler, "set_affinity", json!({ "pid": 100 }))),
+            Some(INVALID_PARAMS)
+        );
+        assert_eq!(
+            error_code(&handler.handle_body("{not json").unwrap()),
+            Some(PARSE_ERROR)
+        );
+
+        // Batch: notifications (no id) are executed but not answered
+        let batch = json!([
+            { "jsonrpc": "2.0", "method": "set_affinity", "params": { "pid": 100, "mask": 1 } },
+            { "jsonrpc": "2.0", "id": 7, "method": "list_processes" },
+        ]);
+        let response = handler.handle_body(&batch.to_string()).unwrap();
+        assert_eq!(response.as_array().unwrap().len(), 1);
+        assert_eq!(response[0]["id"], 7);
+        assert_eq!(calls.lock().unwrap().as_slice(), ["affinity 100 1"]);
+    }
+
+    #[test]
+    fn test_http_endpoint_requires_token() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let config = RpcConfig {
+            enabled: true,
+            port: 0,
+            token: Some("s3cret".to_string()),
+        };
+        let mut server = RpcServer::start(&config, make_handler(&calls)).unwrap();
+
+        let post = |headers: &str| {
+            let body = r#"{"jsonrpc":"2.0","id":1,"method":"list_processes","params":{"top":1}}"#;
+            let mut stream = TcpStream::connect(server.local_addr()).unwrap();
+            write!(
+                stream,
+                "POST /rpc HTTP/1.1\r\nHost: localhost\r\n{}Content-Length: {}\r\n\r\n{}",
+                headers,
+                body.len(),
+                body
+            )
+            .unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+
+        let json = "Content-Type: application/json\r\n";
+        let auth = "Authorization: Bearer s3cret\r\n";
+        assert!(post(json).starts_with("HTTP/1.1 401"));
+        let wrong = format!("{}Authorization: Bearer s3cre\r\n", json);
+        assert!(post(&wrong).starts_with("HTTP/1.1 401"));
+        assert!(post(auth).starts_with("HTTP/1.1 415"));
+        assert!(post(&format!("{}Content-Type: text/plain\r\n", auth)).starts_with("HTTP/1.1 415"));
+        let from_page = format!("{}{}Origin: https://example.com\r\n", auth, json);
+        assert!(post(&from_page).starts_with("HTTP/1.1 403"));
+
+        let response = post(&format!("{}Content-Type: application/json; charset=utf-8\r\n", auth));
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+        assert!(response.contains("worker.exe"));
+
+        // Oversized or overflowing lengths are refused before authentication
+        for length in [MAX_REQUEST_SIZE.to_string(), usize::MAX.to_string()] {
+            let mut stream = TcpStream::connect(server.local_addr()).unwrap();
+            write!(stream, "POST /rpc HTTP/1.1\r\nContent-Length: {}\r\n\r\n", length).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            assert!(response.starts_with("HTTP/1.1 413"), "{}", response);
+        }
+
+        server.shutdown();
+    }
+
+    #[test]
+    fn test_start_requires_token() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        for token in [None, Some(String::new())] {
+            let config = RpcConfig {
+                enabled: true,
+                port: 0,
+                token,
+            };
+            let error = RpcServer::start(&config, make_handler(&calls)).err().unwrap();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        }
+    }
+}
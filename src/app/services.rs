@@ -0,0 +1,355 @@
+//! Background services enabled in the configuration
+//!
+//! The window, the TUI and the agent start the same optional services next
+//! to their `Updater`:
+//! - The Prometheus endpoint (`exporters.prometheus`)
+//! - The InfluxDB and StatsD push exporters (`exporters.influx`,
+//!   `exporters.statsd`)
+//! - Threshold alerts (`alerts`), reported to the event log, the export
+//!   file and any handler added with `with_alert_handler`
+//! - Spike detection (`anomalies`), reported to the event log and any
+//!   channel added with `with_anomaly_sender`
+//! - Process policies (`policies`) and the JSON-RPC endpoint (`rpc`), added
+//!   with `with_policies` and `with_rpc` by frontends showing this machine,
+//!   since they act through `WindowsActuator`
+//! - Custom metric collectors (`collectors`), added with `with_collectors`
+//!   by the same frontends, since they sample this machine
+//!
+//! `take_sinks` and `take_collectors` hand the services' snapshot sinks and
+//! collectors to the updater; listeners keep running until the `Services`
+//! value is dropped. A service that fails to start (a port already in use,
+//! an unwritable file) is left out and reported through `errors`, so the
+//! frontend still comes up.
+
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::app::alerts::{AlertHandler, AlertSink};
+use crate::app::anomaly::{AnomalyEvent, AnomalySink};
+use crate::app::config::{AppConfig, CollectorsConfig, PoliciesConfig, RpcConfig};
+use crate::app::policies::{PolicyEngine, WindowsActuator};
+use crate::app::prometheus::MetricsEndpoint;
+use crate::app::push;
+use crate::app::rpc::{RpcHandler, RpcServer};
+use crate::app::updater::SnapshotSink;
+use crate::core::collector::{CollectorRegistry, FileGaugeCollector};
+use crate::ui::dialogs::confirm::DialogPreferences;
+
+/// Services started for one frontend
+pub struct Services {
+    sinks: Vec<Box<dyn SnapshotSink>>,
+    collectors: CollectorRegistry,
+    errors: Vec<String>,
+    metrics: Option<MetricsEndpoint>,
+    rpc: Option<RpcServer>,
+    /// Kept apart until `take_sinks` so handlers can still be added
+    alerts: Option<AlertSink>,
+    anomalies: Option<AnomalySink>,
+}
+
+impl Services {
+    /// Start the services enabled in `config`
+    pub fn start(config: &AppConfig) -> Self {
+        let mut services = Self {
+            sinks: Vec::new(),
+            collectors: CollectorRegistry::new(),
+            errors: Vec::new(),
+            metrics: None,
+            rpc: None,
+            alerts: None,
+            anomalies: None,
+        };
+
+        match MetricsEndpoint::start_if_enabled(&config.exporters.prometheus) {
+            Some(Ok(endpoint)) => {
+                services.sinks.push(Box::new(endpoint.sink()));
+                services.metrics = Some(endpoint);
+            }
+            Some(Err(e)) => services.fail("exporters.prometheus", e),
+            None => {}
+        }
+        match push::sinks_from_config(&config.exporters) {
+            Ok(sinks) => services.sinks.extend(sinks),
+            Err(e) => services.fail("exporters", e),
+        }
+        if config.alerts.enabled {
+            match AlertSink::from_config(&config.alerts) {
+                Ok(sink) => services.alerts = Some(sink),
+                Err(e) => services.fail("alerts", e),
+            }
+        }
+        if config.anomalies.enabled {
+            let sink = AnomalySink::from_config(&config.anomalies)
+                .with_number_format(config.display.number_format());
+            services.anomalies = Some(sink);
+        }
+
+        services
+    }
+
+    /// Also deliver alert events to `handler`, e.g. a channel to the UI thread
+    pub fn with_alert_handler(mut self, handler: Box<dyn AlertHandler>) -> Self {
+        self.alerts = self.alerts.map(|sink| sink.with_handler(handler));
+        self
+    }
+
+    /// Also send detected anomalies to `sender`, e.g. to flag graph samples
+    pub fn with_anomaly_sender(mut self, sender: Sender<AnomalyEvent>) -> Self {
+        self.anomalies = self.anomalies.map(|sink| sink.with_sender(sender));
+        self
+    }
+
+    /// Apply the process policies in `config` to this machine, if enabled
+    pub fn with_policies(mut self, config: &PoliciesConfig) -> Self {
+        if config.enabled {
+            match PolicyEngine::from_config(config) {
+                Ok(engine) => self.sinks.push(Box::new(engine)),
+                Err(e) => self.fail("policies", e),
+            }
+        }
+        self
+    }
+
+    /// Serve the JSON-RPC endpoint in `config` for this machine, if enabled
+    ///
+    /// `preferences` are the "don't ask again" choices of the frontend's
+    /// confirmation dialogs, so scripts need `confirm` where the user would.
+    pub fn with_rpc(mut self, config: &RpcConfig, preferences: Arc<DialogPreferences>) -> Self {
+        let handler = RpcHandler::new(Box::new(WindowsActuator), preferences);
+        match RpcServer::start_if_enabled(config, handler) {
+            Some(Ok(server)) => {
+                self.sinks.push(Box::new(server.sink()));
+                self.rpc = Some(server);
+            }
+            Some(Err(e)) => self.fail("rpc", e),
+            None => {}
+        }
+        self
+    }
+
+    /// Sample the custom metric collectors in `config` on this machine
+    pub fn with_collectors(mut self, config: &CollectorsConfig) -> Self {
+        for gauge in &config.file_gauges {
+            let collector = FileGaugeCollector::new(gauge.name.as_str(), gauge.path.as_str())
+                .with_unit(gauge.unit.as_str())
+                .with_interval(Duration::from_secs(gauge.interval_secs));
+            if let Err(e) = self.collectors.register(Box::new(collector)) {
+                self.fail("collectors", e);
+            }
+        }
+        self
+    }
+
+    /// Sinks to pass to the `Updater`; later calls return nothing
+    pub fn take_sinks(&mut self) -> Vec<Box<dyn SnapshotSink>> {
+        let mut sinks = std::mem::take(&mut self.sinks);
+        if let Some(alerts) = self.alerts.take() {
+            sinks.push(Box::new(alerts));
+        }
+        if let Some(anomalies) = self.anomalies.take() {
+            sinks.push(Box::new(anomalies));
+        }
+        sinks
+    }
+
+    /// Collectors to pass to `Updater::start_with_collectors`; later calls
+    /// return an empty registry
+    pub fn take_collectors(&mut self) -> CollectorRegistry {
+        std::mem::take(&mut self.collectors)
+    }
+
+    /// Services that failed to start, as "<config section>: <error>"
+    pub fn errors(&self) -> &[String] {
+        &self.errors
+    }
+
+    /// Address of the Prometheus endpoint, if it is running
+    pub fn metrics_addr(&self) -> Option<SocketAddr> {
+        self.metrics.as_ref().map(MetricsEndpoint::local_addr)
+    }
+
+    /// Address of the JSON-RPC endpoint, if it is running
+    pub fn rpc_addr(&self) -> Option<SocketAddr> {
+        self.rpc.as_ref().map(RpcServer::local_addr)
+    }
+
+    fn fail(&mut self, section: &str, error: impl fmt::Display) {
+        self.errors.push(format!("{}: {}", section, error));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::alerts::AlertEventKind;
+    use crate::app::config::{
+        AlertComparison, AlertMetric, AlertRule, FileGaugeConfig, ProcessPolicy, PushTarget,
+    };
+    use crate::core::metrics::SystemMetrics;
+    use crate::test_support::SnapshotBuilder;
+    use std::net::TcpListener;
+    use std::sync::mpsc::channel;
+    use std::time::Instant;
+
+    /// Configuration with every service turned off
+    fn disabled() -> AppConfig {
+        let mut config = AppConfig::default();
+        config.alerts.enabled = false;
+        config.anomalies.enabled = false;
+        config
+    }
+
+    #[test]
+    fn test_defaults() {
+        // Alerts and spike detection are on by default; listeners are opt-in
+        let mut services = Services::start(&AppConfig::default());
+        assert_eq!(services.take_sinks().len(), 2);
+        assert!(services.errors().is_empty());
+        assert_eq!(services.metrics_addr(), None);
+
+        let mut services = Services::start(&disabled());
+        assert!(services.take_sinks().is_empty());
+    }
+
+    #[test]
+    fn test_metrics_endpoint_started() {
+        let mut config = disabled();
+        config.exporters.prometheus.enabled = true;
+        config.exporters.prometheus.port = 0;
+
+        let mut services = Services::start(&config);
+        assert!(services.errors().is_empty());
+        assert!(services.metrics_addr().is_some());
+        assert_eq!(services.take_sinks().len(), 1);
+        assert!(services.take_sinks().is_empty());
+    }
+
+    #[test]
+    fn test_push_exporters_started() {
+        let path = std::env::temp_dir().join("test_services_influx.lp");
+        let mut config = disabled();
+        config.exporters.influx.enabled = true;
+        config.exporters.influx.target = PushTarget::File(path.to_string_lossy().into_owned());
+        config.exporters.statsd.enabled = true;
+        config.exporters.statsd.target = PushTarget::Udp("127.0.0.1:8125".to_string());
+
+        let mut services = Services::start(&config);
+        assert!(services.errors().is_empty());
+        assert_eq!(services.take_sinks().len(), 2);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_alert_handler_receives_events() {
+        let mut config = disabled();
+        config.alerts.enabled = true;
+        config.alerts.rules = vec![AlertRule::new(
+            "cpu",
+            AlertMetric::SystemCpuPercent,
+            AlertComparison::Above,
+            90.0,
+        )];
+        let (tx, rx) = channel();
+
+        let mut services = Services::start(&config).with_alert_handler(Box::new(tx));
+        let mut sinks = services.take_sinks();
+        assert_eq!(sinks.len(), 1);
+        sinks[0].on_snapshot(&SnapshotBuilder::new().with_cpu(95.0, Vec::new()).build());
+
+        let event = rx.try_recv().unwrap();
+        assert_eq!(event.rule, "cpu");
+        assert_eq!(event.kind, AlertEventKind::Fired);
+    }
+
+    #[test]
+    fn test_policies_added_on_request() {
+        let mut config = disabled();
+        config.policies.enabled = true;
+        config.policies.dry_run = true;
+
+        let mut services = Services::start(&config);
+        assert!(services.take_sinks().is_empty());
+        let mut services = Services::start(&config).with_policies(&config.policies);
+        assert_eq!(services.take_sinks().len(), 1);
+
+        config.policies.rules = vec![ProcessPolicy::new("bad", "*").with_affinity("x")];
+        let services = Services::start(&config).with_policies(&config.policies);
+        assert_eq!(services.errors().len(), 1);
+        assert!(services.errors()[0].starts_with("policies: "));
+    }
+
+    #[test]
+    fn test_rpc_added_on_request() {
+        let mut config = disabled();
+        config.rpc.enabled = true;
+        config.rpc.port = 0;
+        config.rpc.token = Some("secret".to_string());
+        let preferences = Arc::new(DialogPreferences::new());
+
+        let services = Services::start(&config);
+        assert_eq!(services.rpc_addr(), None);
+        let mut services = Services::start(&config).with_rpc(&config.rpc, preferences.clone());
+        assert!(services.errors().is_empty());
+        assert!(services.rpc_addr().is_some());
+        assert_eq!(services.take_sinks().len(), 1);
+
+        // The endpoint refuses to start without a token
+        config.rpc.token = None;
+        let services = Services::start(&config).with_rpc(&config.rpc, preferences);
+        assert_eq!(services.rpc_addr(), None);
+        assert_eq!(services.errors().len(), 1);
+        assert!(services.errors()[0].starts_with("rpc: "));
+    }
+
+    #[test]
+    fn test_collectors_added_on_request() {
+        let path = std::env::temp_dir().join("test_services_gauge.txt");
+        std::fs::write(&path, "42\n").unwrap();
+        let mut config = disabled();
+        let gauge = FileGaugeConfig::new("backlog", path.to_string_lossy());
+        config.collectors.file_gauges = vec![gauge.clone()];
+
+        assert!(Services::start(&config).take_collectors().is_empty());
+        let mut services = Services::start(&config).with_collectors(&config.collectors);
+        assert!(services.errors().is_empty());
+        let mut collectors = services.take_collectors();
+        let mut metrics = SystemMetrics::new();
+        assert!(collectors.collect(Instant::now(), &mut metrics).is_empty());
+        assert_eq!(metrics.custom.get("backlog.value"), Some(&42.0));
+        assert!(services.take_collectors().is_empty());
+
+        // A duplicate name is reported; the first gauge still runs
+        config.collectors.file_gauges.push(gauge);
+        let mut services = Services::start(&config).with_collectors(&config.collectors);
+        assert!(!services.take_collectors().is_empty());
+        assert_eq!(services.errors().len(), 1);
+        assert!(services.errors()[0].starts_with("collectors: "));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_failed_service_is_reported() {
+        let taken = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut config = disabled();
+        config.exporters.prometheus.enabled = true;
+        config.exporters.prometheus.port = taken.local_addr().unwrap().port();
+
+        let mut services = Services::start(&config);
+        assert!(services.take_sinks().is_empty());
+        assert_eq!(services.errors().len(), 1);
+        assert!(services.errors()[0].starts_with("exporters.prometheus: "));
+
+        // The other services still start
+        let path = std::env::temp_dir().join("missing-dir").join("out.lp");
+        config.exporters.prometheus.port = 0;
+        config.exporters.influx.enabled = true;
+        config.exporters.influx.target = PushTarget::File(path.to_string_lossy().into_owned());
+        let mut services = Services::start(&config);
+        assert_eq!(services.take_sinks().len(), 1);
+        assert_eq!(services.errors().len(), 1);
+        assert!(services.errors()[0].starts_with("exporters: "));
+    }
+}
@@ -1,6 +1,13 @@
 //! Application state management
 //!
 //! Connects the monitoring system, process store, and UI rendering.
+//! Snapshots are collected by an `Updater`, which also feeds the services
+//! enabled in the configuration; fired alerts are shown from the tray icon.
+//! Edits of the config file are picked up by a `ConfigWatcher` and applied
+//! on the window thread (`poll_config`).
+
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::Arc;
 
 use windows::core::Result;
 use windows::Win32::Foundation::HWND;
@@ -8,9 +15,17 @@ use windows::Win32::Foundation::HWND;
 // Note: Power status APIs not available in windows-rs 0.62
 // Using stub implementation for performance mode detection
 
+use crate::app::alerts::AlertEvent;
+use crate::app::config::{ConfigManager, ConfigSection};
+use crate::app::reload::{ConfigReload, ConfigWatcher};
+use crate::app::services::Services;
+use crate::app::updater::{UpdateMessage, Updater};
 use crate::core::process::ProcessStore;
 use crate::ui::d2d::renderer::Renderer;
 use crate::ui::d2d::resources::ResourcePool;
+use crate::ui::dialogs::confirm::DialogPreferences;
+use crate::ui::keymap::install_keymap;
+use crate::ui::systray::SystemTray;
 use crate::windows::monitor::SystemMonitor;
 
 /// Performance mode (T468)
@@ -35,8 +50,20 @@ pub struct AppState {
     /// Process data store
     process_store: ProcessStore,
     
-    /// System monitoring coordinator
-    monitor: SystemMonitor,
+    /// Background collection, feeding the services' sinks
+    updater: Updater,
+    updates: Receiver<UpdateMessage>,
+
+    /// Configured services; their listeners run while this is held
+    _services: Services,
+
+    /// "Don't ask again" choices, shared with the JSON-RPC endpoint
+    _dialog_preferences: Arc<DialogPreferences>,
+
+    /// Notification area icon for alerts (and a CPU/memory tooltip), while
+    /// alerting is enabled
+    tray: Option<SystemTray>,
+    alerts: Receiver<AlertEvent>,
     
     /// Frame counter for debugging
     frame_count: u64,
@@ -50,6 +77,12 @@ pub struct AppState {
     /// Window dimensions
     width: u32,
     height: u32,
+
+    /// Loaded configuration
+    config: Arc<ConfigManager>,
+
+    /// Hot reload of the config file, if it is stored in one
+    reloads: Option<(ConfigWatcher, Receiver<ConfigReload>)>,
 }
 
 impl AppState {
@@ -59,7 +92,7 @@ impl AppState {
     /// - Renderer: Created immediately (needed for window)
     /// - ResourcePool: Brushes created on first use (T312)
     /// - ProcessStore: Minimal allocation at init
-    /// - SystemMonitor: Lightweight, no system queries until update()
+    /// - Updater: Collects on its own thread from the start
     pub fn new(hwnd: HWND, width: u32, height: u32) -> Result<Self> {
         // Initialize Direct2D renderer
         let renderer = Renderer::new(hwnd, width, height)?;
@@ -70,39 +103,135 @@ impl AppState {
         // Initialize process store (SoA arrays, no data collection yet)
         let process_store = ProcessStore::new();
         
-        // Create system monitor (lightweight, no queries until update())
-        let monitor = SystemMonitor::new();
-        
         // Detect initial performance mode (T468)
         let performance_mode = Self::detect_performance_mode();
+
+        // Missing or unreadable configuration just means defaults
+        let config = Arc::new(ConfigManager::new());
+        let _ = config.load();
+        let _ = install_keymap(&config.get().keymap);
+        let reloads = ConfigWatcher::start_for(Arc::clone(&config));
+
+        let effective = config.effective();
+        let dialog_preferences = Arc::new(DialogPreferences::new());
+        let (alert_tx, alerts) = channel();
+        let mut services = Services::start(&effective)
+            .with_alert_handler(Box::new(alert_tx))
+            .with_policies(&effective.policies)
+            .with_rpc(&effective.rpc, Arc::clone(&dialog_preferences))
+            .with_collectors(&effective.collectors);
+        for error in services.errors() {
+            eprintln!("task-manager: {}", error);
+        }
+        // Without the icon, alerts still reach the event log
+        let tray = if effective.alerts.enabled {
+            SystemTray::new(hwnd)
+                .map_err(|e| eprintln!("task-manager: tray: {}", e))
+                .ok()
+                .map(|mut tray| {
+                    tray.set_number_format(effective.display.number_format());
+                    tray
+                })
+        } else {
+            None
+        };
+        let refresh_rate_ms = effective.monitoring.refresh_rate_ms as u64;
+        let (updater, updates) = Updater::start_with_collectors(
+            refresh_rate_ms,
+            Box::new(SystemMonitor::new()),
+            services.take_collectors(),
+            services.take_sinks(),
+        );
         
         Ok(Self {
             renderer,
             resources,
             process_store,
-            monitor,
+            updater,
+            updates,
+            _services: services,
+            _dialog_preferences: dialog_preferences,
+            tray,
+            alerts,
             frame_count: 0,
             performance_mode,
             active_tab: 0,
             width,
             height,
+            config,
+            reloads,
         })
     }
+
+    /// Configured update interval in milliseconds
+    pub fn refresh_rate_ms(&self) -> u32 {
+        self.config.effective().monitoring.refresh_rate_ms
+    }
+
+    /// Apply config file edits seen since the last call
+    ///
+    /// Key bindings take effect immediately and the updater and its sinks
+    /// are reconfigured; returns the changed sections so the window can react
+    /// to the rest (e.g. restart its update timer).
+    pub fn poll_config(&mut self) -> Vec<ConfigSection> {
+        let Some((_, reloads)) = &self.reloads else {
+            return Vec::new();
+        };
+
+        let mut changed = Vec::new();
+        while let Ok(reload) = reloads.try_recv() {
+            if reload.affects(ConfigSection::Keymap) {
+                let _ = install_keymap(&reload.config.keymap);
+            }
+            if reload.affects(ConfigSection::Display) {
+                if let Some(tray) = &mut self.tray {
+                    tray.set_number_format(reload.config.display.number_format());
+                }
+            }
+            reload.apply_to(&self.updater);
+            for section in reload.changed {
+                if !changed.contains(&section) {
+                    changed.push(section);
+                }
+            }
+        }
+        changed
+    }
     
-    /// Update process data from system
+    /// Apply the newest snapshot the updater collected since the last call
+    /// and show the alerts fired meanwhile
     pub fn update(&mut self) -> Result<()> {
-        // Collect current system state
-        match self.monitor.collect_all() {
-            Ok(snapshot) => {
-                // Update process store with new data
-                self.process_store.update(snapshot.processes);
-                Ok(())
+        while let Ok(event) = self.alerts.try_recv() {
+            if let Some(tray) = &mut self.tray {
+                tray.show_alert(&event);
             }
-            Err(e) => {
-                eprintln!("Failed to collect system data: {}", e);
-                Err(windows::core::Error::from(windows::Win32::Foundation::E_FAIL))
+        }
+
+        let mut latest = None;
+        let mut failed = false;
+        while let Ok(message) = self.updates.try_recv() {
+            match message {
+                UpdateMessage::Snapshot(snapshot) => latest = Some(snapshot),
+                UpdateMessage::Error(e) => {
+                    eprintln!("Failed to collect system data: {}", e);
+                    failed = true;
+                }
+                UpdateMessage::Shutdown => {}
             }
         }
+
+        // Update process store with new data
+        if let Some(snapshot) = latest {
+            if let Some(tray) = &mut self.tray {
+                let metrics = &snapshot.system_metrics;
+                let used = metrics.memory_total.saturating_sub(metrics.memory_available);
+                tray.update_tooltip(metrics.cpu_total, used, metrics.memory_total);
+            }
+            self.process_store.update(snapshot.processes);
+        } else if failed {
+            return Err(windows::core::Error::from(windows::Win32::Foundation::E_FAIL));
+        }
+        Ok(())
     }
     
     /// Render one frame
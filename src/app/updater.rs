@@ -1,9 +1,12 @@
 //! Background update loop for periodic metric collection
 
 use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
 use std::thread;
 use std::time::{Duration, Instant};
 
+use crate::app::config::{AppConfig, ConfigSection};
+use crate::core::collector::CollectorRegistry;
 use crate::windows::monitor::{ProcessSnapshot, SystemMonitor};
 
 /// Messages sent from updater thread to UI thread
@@ -17,6 +20,34 @@ pub enum UpdateMessage {
     Shutdown,
 }
 
+/// Consumer of snapshots produced by the update loop
+///
+/// Sinks run on the updater thread after each successful collection, before
+/// the snapshot is forwarded to the UI. Implementations must return quickly;
+/// anything slow (network I/O, disk writes) belongs on the sink's own thread.
+pub trait SnapshotSink: Send {
+    /// Observe a freshly collected snapshot
+    fn on_snapshot(&mut self, snapshot: &ProcessSnapshot);
+
+    /// Apply a reloaded configuration; `changed` lists the sections that differ
+    fn on_config(&mut self, _config: &AppConfig, _changed: &[ConfigSection]) {}
+}
+
+/// Producer of snapshots for the update loop
+///
+/// `SystemMonitor` collects from the local machine; `agent::AgentClient`
+/// fetches snapshots from an agent on another machine.
+pub trait ProcessSource: Send {
+    /// Collect the next snapshot
+    fn collect(&mut self) -> Result<ProcessSnapshot, String>;
+}
+
+impl ProcessSource for SystemMonitor {
+    fn collect(&mut self) -> Result<ProcessSnapshot, String> {
+        self.collect_all()
+    }
+}
+
 /// Control messages sent to updater thread
 #[derive(Debug)]
 pub enum ControlMessage {
@@ -24,6 +55,8 @@ pub enum ControlMessage {
     Pause,
     /// Resume updates
     Resume,
+    /// Apply a reloaded configuration to the refresh rate and sinks
+    Reconfigure(Arc<AppConfig>, Vec<ConfigSection>),
     /// Shutdown updater thread
     Shutdown,
 }
@@ -52,11 +85,110 @@ impl Updater {
     ///
     /// (Updater, Receiver) - Updater handle and receiver for updates
     pub fn start(refresh_rate_ms: u64) -> (Self, Receiver<UpdateMessage>) {
+        Self::start_with_sinks(refresh_rate_ms, Vec::new())
+    }
+
+    /// Start background updater that also feeds snapshots to `sinks`
+    ///
+    /// # Arguments
+    ///
+    /// * `refresh_rate_ms` - How often to collect metrics (milliseconds)
+    /// * `sinks` - Consumers invoked on the updater thread for every snapshot
+    ///
+    /// # Returns
+    ///
+    /// (Updater, Receiver) - Updater handle and receiver for updates
+    pub fn start_with_sinks(
+        refresh_rate_ms: u64,
+        sinks: Vec<Box<dyn SnapshotSink>>,
+    ) -> (Self, Receiver<UpdateMessage>) {
+        Self::start_with_source(refresh_rate_ms, Box::new(SystemMonitor::new()), sinks)
+    }
+
+    /// Start background updater collecting from `source` instead of the local system
+    ///
+    /// # Arguments
+    ///
+    /// * `refresh_rate_ms` - How often to collect metrics (milliseconds)
+    /// * `source` - Snapshot producer, e.g. a remote agent client
+    /// * `sinks` - Consumers invoked on the updater thread for every snapshot
+    ///
+    /// # Returns
+    ///
+    /// (Updater, Receiver) - Updater handle and receiver for updates
+    pub fn start_with_source(
+        refresh_rate_ms: u64,
+        source: Box<dyn ProcessSource>,
+        sinks: Vec<Box<dyn SnapshotSink>>,
+    ) -> (Self, Receiver<UpdateMessage>) {
+        Self::start_with_collectors(refresh_rate_ms, source, CollectorRegistry::new(), sinks)
+    }
+
+    /// Start background updater that also samples custom metric collectors
+    ///
+    /// Collector values are added to each snapshot's `system_metrics.custom`
+    /// before sinks run; collector failures are reported as `Error` messages
+    /// without dropping the snapshot.
+    ///
+    /// # Arguments
+    ///
+    /// * `refresh_rate_ms` - How often to collect metrics (milliseconds)
+    /// * `source` - Snapshot producer, e.g. `SystemMonitor`
+    /// * `collectors` - Registered `MetricCollector`s
+    /// * `sinks` - Consumers invoked on the updater thread for every snapshot
+    ///
+    /// # Returns
+    ///
+    /// (Updater, Receiver) - Updater handle and receiver for updates
+    pub fn start_with_collectors(
+        refresh_rate_ms: u64,
+        mut source: Box<dyn ProcessSource>,
+        mut collectors: CollectorRegistry,
+        sinks: Vec<Box<dyn SnapshotSink>>,
+    ) -> (Self, Receiver<UpdateMessage>) {
+        let (update_tx, update_rx) = channel();
+        let (control_tx, control_rx) = channel();
+
+        let thread_handle = thread::spawn(move || {
+            let error_tx = update_tx.clone();
+            let collect = move || {
+                Some(source.collect().map(|mut snapshot| {
+                    let metrics = &mut snapshot.system_metrics;
+                    let errors = collectors.collect(snapshot.timestamp, metrics);
+                    for error in errors {
+                        let _ = error_tx.send(UpdateMessage::Error(error));
+                    }
+                    snapshot
+                }))
+            };
+            run_update_loop(refresh_rate_ms, collect, sinks, update_tx, control_rx);
+        });
+
+        let updater = Self {
+            thread_handle: Some(thread_handle),
+            control_tx,
+        };
+
+        (updater, update_rx)
+    }
+
+    /// Start updater replaying recorded snapshots instead of live data
+    ///
+    /// Snapshots are delivered in order, one per refresh, through the same
+    /// sinks and channel as live collection; `Shutdown` follows the last one.
+    /// Frontends and sinks can be exercised this way without a live system.
+    pub fn start_replay(
+        refresh_rate_ms: u64,
+        snapshots: Vec<ProcessSnapshot>,
+        sinks: Vec<Box<dyn SnapshotSink>>,
+    ) -> (Self, Receiver<UpdateMessage>) {
         let (update_tx, update_rx) = channel();
         let (control_tx, control_rx) = channel();
 
         let thread_handle = thread::spawn(move || {
-            run_update_loop(refresh_rate_ms, update_tx, control_rx);
+            let mut snapshots = snapshots.into_iter();
+            let collect = move || snapshots.next().map(Ok);
+            run_update_loop(refresh_rate_ms, collect, sinks, update_tx, control_rx);
         });
 
         let updater = Self {
@@ -77,6 +209,14 @@ impl Updater {
         let _ = self.control_tx.send(ControlMessage::Resume);
     }
 
+    /// Apply a reloaded configuration on the updater thread
+    ///
+    /// Changes the refresh rate if `Monitoring` changed and passes the
+    /// configuration to every sink's `on_config`.
+    pub fn reconfigure(&self, config: Arc<AppConfig>, changed: Vec<ConfigSection>) {
+        let _ = self.control_tx.send(ControlMessage::Reconfigure(config, changed));
+    }
+
     /// Shutdown updater thread gracefully
     pub fn shutdown(&mut self) {
         let _ = self.control_tx.send(ControlMessage::Shutdown);
@@ -99,14 +239,17 @@ impl Drop for Updater {
 ///
 /// Maintains precise timing using sleep duration adjustment.
 /// Target: 1Hz (1000ms) with <5ms jitter.
+///
+/// `collect` returns `None` once the source is exhausted (replay only).
 fn run_update_loop(
     refresh_rate_ms: u64,
+    mut collect: impl FnMut() -> Option<Result<ProcessSnapshot, String>>,
+    mut sinks: Vec<Box<dyn SnapshotSink>>,
     update_tx: Sender<UpdateMessage>,
     control_rx: Receiver<ControlMessage>,
 ) {
-    let mut monitor = SystemMonitor::new();
     let mut paused = false;
-    let refresh_duration = Duration::from_millis(refresh_rate_ms);
+    let mut refresh_duration = Duration::from_millis(refresh_rate_ms);
 
     loop {
         let cycle_start = Instant::now();
@@ -116,6 +259,15 @@ fn run_update_loop(
             match msg {
                 ControlMessage::Pause => paused = true,
                 ControlMessage::Resume => paused = false,
+                ControlMessage::Reconfigure(config, changed) => {
+                    if changed.contains(&ConfigSection::Monitoring) {
+                        refresh_duration =
+                            Duration::from_millis(config.monitoring.refresh_rate_ms as u64);
+                    }
+                    for sink in sinks.iter_mut() {
+                        sink.on_config(&config, &changed);
+                    }
+                }
                 ControlMessage::Shutdown => {
                     let _ = update_tx.send(UpdateMessage::Shutdown);
                     break;
@@ -125,14 +277,22 @@ fn run_update_loop(
 
         // Collect metrics if not paused
         if !paused {
-            match monitor.collect_all() {
-                Ok(snapshot) => {
+            match collect() {
+                None => {
+                    let _ = update_tx.send(UpdateMessage::Shutdown);
+                    break;
+                }
+                Some(Ok(snapshot)) => {
+                    for sink in sinks.iter_mut() {
+                        sink.on_snapshot(&snapshot);
+                    }
+
                     if update_tx.send(UpdateMessage::Snapshot(snapshot)).is_err() {
                         // Receiver dropped, exit loop
                         break;
                     }
                 }
-                Err(e) => {
+                Some(Err(e)) => {
                     if update_tx.send(UpdateMessage::Error(e)).is_err() {
                         break;
                     }
@@ -176,6 +336,133 @@ mod tests {
         updater.shutdown();
     }
 
+    #[test]
+    fn test_updater_feeds_sinks() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        struct CountingSink(Arc<AtomicUsize>);
+
+        impl SnapshotSink for CountingSink {
+            fn on_snapshot(&mut self, _snapshot: &ProcessSnapshot) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let seen = Arc::new(AtomicUsize::new(0));
+        let sink: Box<dyn SnapshotSink> = Box::new(CountingSink(Arc::clone(&seen)));
+        let (mut updater, rx) = Updater::start_with_sinks(100, vec![sink]);
+
+        let _ = rx.recv_timeout(Duration::from_secs(1)).unwrap();
+        assert!(seen.load(Ordering::SeqCst) >= 1, "Sink should see the first snapshot");
+
+        updater.shutdown();
+    }
+
+    #[test]
+    fn test_updater_samples_collectors() {
+        use crate::core::collector::{MetricCollector, MetricDescriptor};
+
+        struct Depth;
+
+        impl MetricCollector for Depth {
+            fn name(&self) -> &str {
+                "queue"
+            }
+
+            fn descriptors(&self) -> Vec<MetricDescriptor> {
+                vec![MetricDescriptor::new("depth", "items", "Pending jobs")]
+            }
+
+            fn sample(&mut self) -> Result<Vec<(String, f64)>, String> {
+                Ok(vec![("depth".to_string(), 7.0)])
+            }
+        }
+
+        let mut collectors = CollectorRegistry::new();
+        collectors.register(Box::new(Depth)).unwrap();
+        let (mut updater, rx) = Updater::start_with_collectors(
+            100,
+            Box::new(SystemMonitor::new()),
+            collectors,
+            Vec::new(),
+        );
+
+        match rx.recv_timeout(Duration::from_secs(1)).unwrap() {
+            UpdateMessage::Snapshot(snapshot) => {
+                assert_eq!(snapshot.system_metrics.custom.get("queue.depth"), Some(&7.0));
+            }
+            other => panic!("Expected Snapshot message, got {:?}", other),
+        }
+
+        updater.shutdown();
+    }
+
+    #[test]
+    fn test_updater_reconfigure_reaches_sinks() {
+        use std::sync::Mutex;
+
+        struct ConfigSink(Arc<Mutex<Vec<ConfigSection>>>);
+
+        impl SnapshotSink for ConfigSink {
+            fn on_snapshot(&mut self, _snapshot: &ProcessSnapshot) {}
+
+            fn on_config(&mut self, _config: &AppConfig, changed: &[ConfigSection]) {
+                self.0.lock().unwrap().extend_from_slice(changed);
+            }
+        }
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink: Box<dyn SnapshotSink> = Box::new(ConfigSink(Arc::clone(&seen)));
+        let (mut updater, rx) = Updater::start_with_sinks(20, vec![sink]);
+
+        let mut config = AppConfig::default();
+        config.monitoring.refresh_rate_ms = 100;
+        updater.reconfigure(
+            Arc::new(config),
+            vec![ConfigSection::Monitoring, ConfigSection::Alerts],
+        );
+        for _ in 0..5 {
+            let _ = rx.recv_timeout(Duration::from_secs(1));
+        }
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![ConfigSection::Monitoring, ConfigSection::Alerts]
+        );
+
+        updater.shutdown();
+    }
+
+    #[test]
+    fn test_updater_replay() {
+        use crate::core::metrics::SystemMetrics;
+
+        let start = Instant::now();
+        let snapshots: Vec<ProcessSnapshot> = (0..3u64)
+            .map(|i| ProcessSnapshot {
+                timestamp: start + Duration::from_secs(i),
+                processes: Vec::new(),
+                system_metrics: SystemMetrics::new(),
+            })
+            .collect();
+        let (mut updater, rx) = Updater::start_replay(10, snapshots, Vec::new());
+
+        for i in 0..3u64 {
+            match rx.recv_timeout(Duration::from_secs(1)).unwrap() {
+                UpdateMessage::Snapshot(snapshot) => {
+                    assert_eq!(snapshot.timestamp, start + Duration::from_secs(i));
+                }
+                other => panic!("Expected Snapshot message, got {:?}", other),
+            }
+        }
+        assert!(matches!(
+            rx.recv_timeout(Duration::from_secs(1)),
+            Ok(UpdateMessage::Shutdown)
+        ));
+
+        updater.shutdown();
+    }
+
     #[test]
     fn test_updater_pause_resume() {
         let (mut updater, rx) = Updater::start(100);
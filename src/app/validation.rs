@@ -0,0 +1,833 @@
+//! Configuration validation
+//!
+//! `validate` checks per-field ranges and cross-field rules over `AppConfig`
+//! and reports every problem with the dotted path of the offending field, so
+//! the import path and the settings panel can point at it. `sanitize` repairs
+//! what can be clamped (refresh rate, history length, window and column
+//! sizes) when loading stored configurations, including window bounds that
+//! fall outside the current virtual desktop, and drops unknown columns. Profiles are checked by the
+//! values they resolve to after inheritance; filter presets by their filter
+//! expressions and column names; computed columns by their names and
+//! expressions, which may only refer to columns defined before them.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use windows::Win32::Foundation::RECT;
+
+use crate::app::config::{AlertRule, AppConfig, MonitoringConfig, WindowConfig};
+use crate::core::expression::{check_column_name, CustomColumn, Expression};
+use crate::core::filter::{parse_filter, parse_sort, ProcessFilter};
+use crate::ui::controls::filter_box::FilterPreset;
+use crate::ui::controls::table::{ColumnLayout, TableColumn};
+use crate::ui::keymap::Keymap;
+
+/// Fastest supported refresh interval
+pub const MIN_REFRESH_RATE_MS: u32 = 100;
+/// Slowest supported refresh interval
+pub const MAX_REFRESH_RATE_MS: u32 = 10_000;
+/// Shortest history window
+pub const MIN_HISTORY_SEC: u32 = 10;
+/// Longest history window (24 hours)
+pub const MAX_HISTORY_SEC: u32 = 86_400;
+/// History samples kept in memory (24 hours at 1s, a few MB of graph data)
+pub const MAX_HISTORY_SAMPLES: u64 = 86_400;
+/// Smallest usable main window
+pub const MIN_WINDOW_WIDTH: i32 = 400;
+pub const MIN_WINDOW_HEIGHT: i32 = 300;
+/// Largest window coordinate Win32 accepts
+pub const MAX_WINDOW_SIZE: i32 = 32_767;
+/// Column width bounds in pixels
+pub const MIN_COLUMN_WIDTH: i32 = 16;
+pub const MAX_COLUMN_WIDTH: i32 = 2_000;
+
+/// One invalid field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// Dotted path, e.g. `monitoring.refresh_rate_ms` or `alerts.rules[2].threshold`
+    pub field: String,
+    pub message: String,
+}
+
+impl ValidationError {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// All problems found in a configuration
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationErrors {
+    pub errors: Vec<ValidationError>,
+}
+
+impl ValidationErrors {
+    /// Error for `field`, if any
+    pub fn for_field(&self, field: &str) -> Option<&ValidationError> {
+        self.errors.iter().find(|e| e.field == field)
+    }
+
+    fn check(&mut self, ok: bool, field: impl Into<String>, message: impl Into<String>) {
+        if !ok {
+            self.errors.push(ValidationError::new(field, message));
+        }
+    }
+
+    fn into_result(self) -> Result<(), ValidationErrors> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, error) in self.errors.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+/// Check every field and cross-field rule
+pub fn validate(config: &AppConfig) -> Result<(), ValidationErrors> {
+    let mut errors = match validate_monitoring(&config.monitoring) {
+        Ok(()) => ValidationErrors::default(),
+        Err(errors) => errors,
+    };
+
+    let window = &config.window;
+    errors.check(
+        (MIN_WINDOW_WIDTH..=MAX_WINDOW_SIZE).contains(&window.width),
+        "window.width",
+        format!("must be {}-{}", MIN_WINDOW_WIDTH, MAX_WINDOW_SIZE),
+    );
+    errors.check(
+        (MIN_WINDOW_HEIGHT..=MAX_WINDOW_SIZE).contains(&window.height),
+        "window.height",
+        format!("must be {}-{}", MIN_WINDOW_HEIGHT, MAX_WINDOW_SIZE),
+    );
+
+    check_columns(&mut errors, &config.columns);
+    check_alert_rules(&mut errors, "alerts.rules", &config.alerts.rules);
+    check_filters(&mut errors, "filters", &config.filters);
+
+    for (i, policy) in config.policies.rules.iter().enumerate() {
+        let field = |name: &str| format!("policies.rules[{}].{}", i, name);
+        errors.check(
+            !policy.name.trim().is_empty(),
+            field("name"),
+            "must not be empty",
+        );
+        errors.check(
+            !policy.pattern.trim().is_empty(),
+            field("pattern"),
+            "must not be empty",
+        );
+    }
+
+    let exporters = &config.exporters;
+    errors.check(
+        !exporters.prometheus.enabled || exporters.prometheus.port != 0,
+        "exporters.prometheus.port",
+        "must be set when the endpoint is enabled",
+    );
+    for (name, batch) in [
+        ("influx", &exporters.influx.batch),
+        ("statsd", &exporters.statsd.batch),
+    ] {
+        errors.check(
+            batch.max_lines > 0,
+            format!("exporters.{}.batch.max_lines", name),
+            "must be at least 1",
+        );
+        errors.check(
+            batch.max_bytes > 0,
+            format!("exporters.{}.batch.max_bytes", name),
+            "must be at least 1",
+        );
+    }
+
+    if let Some(threshold) = config.anomalies.threshold {
+        errors.check(
+            threshold.is_finite() && threshold > 0.0,
+            "anomalies.threshold",
+            "must be a positive number",
+        );
+    }
+
+    let leaks = &config.leak_detection;
+    for (name, growth) in [
+        (
+            "min_private_growth_per_hour",
+            leaks.min_private_growth_per_hour,
+        ),
+        (
+            "min_handle_growth_per_hour",
+            leaks.min_handle_growth_per_hour,
+        ),
+    ] {
+        errors.check(
+            growth.is_finite() && growth > 0.0,
+            format!("leak_detection.{}", name),
+            "must be a positive number",
+        );
+    }
+    errors.check(
+        leaks.min_samples <= leaks.max_samples,
+        "leak_detection.min_samples",
+        "must not exceed max_samples",
+    );
+    errors.check(
+        (0.0..=1.0).contains(&leaks.min_monotonic_fraction),
+        "leak_detection.min_monotonic_fraction",
+        "must be between 0 and 1",
+    );
+
+    errors.check(
+        config.agent.history_capacity > 0,
+        "agent.history_capacity",
+        "must be at least 1",
+    );
+    errors.check(
+        config.agent.max_connections > 0,
+        "agent.max_connections",
+        "must be at least 1",
+    );
+    errors.check(
+        !config.rpc.enabled || config.rpc.port != 0,
+        "rpc.port",
+        "must be set when the endpoint is enabled",
+    );
+    errors.check(
+        !config.rpc.enabled || config.rpc.token.as_deref().is_some_and(|t| !t.is_empty()),
+        "rpc.token",
+        "must be set when the endpoint is enabled",
+    );
+
+    for (i, gauge) in config.collectors.file_gauges.iter().enumerate() {
+        let field = |name: &str| format!("collectors.file_gauges[{}].{}", i, name);
+        errors.check(
+            !gauge.path.trim().is_empty(),
+            field("path"),
+            "must not be empty",
+        );
+        errors.check(
+            gauge.interval_secs > 0,
+            field("interval_secs"),
+            "must be at least 1",
+        );
+    }
+
+    check_profiles(&mut errors, config);
+    if let Err(keymap) = validate_keymap(&config.keymap) {
+        errors.errors.extend(keymap.errors);
+    }
+    check_filter_presets(&mut errors, &config.filter_presets);
+    check_custom_columns(&mut errors, &config.custom_columns);
+
+    errors.into_result()
+}
+
+/// Check a column layout: known, unique columns with usable widths
+pub fn validate_columns(layout: &ColumnLayout) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::default();
+    check_columns(&mut errors, layout);
+    errors.into_result()
+}
+
+fn check_columns(errors: &mut ValidationErrors, layout: &ColumnLayout) {
+    for (i, entry) in layout.entries.iter().enumerate() {
+        let field = |name: &str| format!("columns[{}].{}", i, name);
+        let known = TableColumn::from_name(&entry.key).map(|column| column.key);
+        errors.check(
+            known == Some(entry.key.as_str()),
+            field("key"),
+            format!("unknown column '{}'", entry.key),
+        );
+        errors.check(
+            !layout.entries[..i]
+                .iter()
+                .any(|other| other.key == entry.key),
+            field("key"),
+            format!("duplicate column '{}'", entry.key),
+        );
+        errors.check(
+            (MIN_COLUMN_WIDTH..=MAX_COLUMN_WIDTH).contains(&entry.width),
+            field("width"),
+            format!("must be {}-{} pixels", MIN_COLUMN_WIDTH, MAX_COLUMN_WIDTH),
+        );
+    }
+}
+
+/// Check filter presets: unique names, parseable filters, known columns
+pub fn validate_filter_presets(presets: &[FilterPreset]) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::default();
+    check_filter_presets(&mut errors, presets);
+    errors.into_result()
+}
+
+/// Check computed columns: usable, unique names and parseable expressions
+pub fn validate_custom_columns(columns: &[CustomColumn]) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::default();
+    check_custom_columns(&mut errors, columns);
+    errors.into_result()
+}
+
+fn check_custom_columns(errors: &mut ValidationErrors, columns: &[CustomColumn]) {
+    for (i, column) in columns.iter().enumerate() {
+        let field = |name: &str| format!("custom_columns[{}].{}", i, name);
+        let earlier = &columns[..i];
+        if let Err(e) = check_column_name(&column.name) {
+            errors.check(false, field("name"), e);
+        }
+        errors.check(
+            !earlier
+                .iter()
+                .any(|other| other.name.eq_ignore_ascii_case(&column.name)),
+            field("name"),
+            format!("duplicate column '{}'", column.name),
+        );
+        match Expression::parse(&column.expression) {
+            Ok(expression) => {
+                for name in expression.columns() {
+                    errors.check(
+                        earlier
+                            .iter()
+                            .any(|other| other.name.eq_ignore_ascii_case(name)),
+                        field("expression"),
+                        format!("unknown column '{}'", name),
+                    );
+                }
+            }
+            Err(e) => errors.check(false, field("expression"), e),
+        }
+    }
+}
+
+/// Check key binding overrides: known commands, parseable keys, no conflicts
+pub fn validate_keymap(overrides: &BTreeMap<String, Vec<String>>) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::default();
+    let (keymap, invalid) = Keymap::from_config(overrides);
+    for error in invalid {
+        errors.check(false, format!("keymap.{}", error.command), error.message);
+    }
+    for conflict in keymap.conflicts() {
+        errors.check(
+            false,
+            format!("keymap.{}", conflict.hidden.1.name()),
+            conflict.to_string(),
+        );
+    }
+    errors.into_result()
+}
+
+fn check_alert_rules(errors: &mut ValidationErrors, prefix: &str, rules: &[AlertRule]) {
+    for (i, rule) in rules.iter().enumerate() {
+        let field = |name: &str| format!("{}[{}].{}", prefix, i, name);
+        errors.check(
+            !rule.name.trim().is_empty(),
+            field("name"),
+            "must not be empty",
+        );
+        errors.check(
+            rule.threshold.is_finite(),
+            field("threshold"),
+            "must be a number",
+        );
+        errors.check(
+            rule.hysteresis.is_finite() && rule.hysteresis >= 0.0,
+            field("hysteresis"),
+            "must be zero or positive",
+        );
+    }
+}
+
+fn check_filters(errors: &mut ValidationErrors, prefix: &str, filters: &[String]) {
+    for (i, expr) in filters.iter().enumerate() {
+        if let Err(e) = parse_filter(expr, ProcessFilter::new()) {
+            errors.check(false, format!("{}[{}]", prefix, i), e);
+        }
+    }
+}
+
+fn check_filter_presets(errors: &mut ValidationErrors, presets: &[FilterPreset]) {
+    for (i, preset) in presets.iter().enumerate() {
+        let field = |name: &str| format!("filter_presets[{}].{}", i, name);
+        errors.check(
+            !preset.name.trim().is_empty(),
+            field("name"),
+            "must not be empty",
+        );
+        errors.check(
+            !presets[..i]
+                .iter()
+                .any(|other| other.name.eq_ignore_ascii_case(&preset.name)),
+            field("name"),
+            format!("duplicate preset '{}'", preset.name),
+        );
+        check_filters(errors, &field("filters"), &preset.filters);
+        for (j, key) in preset.sort.iter().enumerate() {
+            errors.check(
+                parse_sort(&key.column).is_some(),
+                format!("{}[{}].column", field("sort"), j),
+                format!("unknown column '{}'", key.column),
+            );
+        }
+        for (j, column) in preset.columns.iter().enumerate() {
+            errors.check(
+                parse_sort(column).is_some(),
+                format!("{}[{}]", field("columns"), j),
+                format!("unknown column '{}'", column),
+            );
+        }
+    }
+}
+
+/// Names, inheritance and the values each profile ends up with
+fn check_profiles(errors: &mut ValidationErrors, config: &AppConfig) {
+    let profiles = &config.profiles;
+    for (i, profile) in profiles.profiles.iter().enumerate() {
+        let field = |name: &str| format!("profiles.profiles[{}].{}", i, name);
+        errors.check(
+            !profile.name.trim().is_empty(),
+            field("name"),
+            "must not be empty",
+        );
+        errors.check(
+            !profiles.profiles[..i]
+                .iter()
+                .any(|other| other.name.eq_ignore_ascii_case(&profile.name)),
+            field("name"),
+            format!("duplicate profile '{}'", profile.name),
+        );
+
+        match config.with_profile(&profile.name) {
+            Ok(effective) => {
+                // Report range errors against the profile's own fields
+                if let Err(monitoring) = validate_monitoring(&effective.monitoring) {
+                    for error in monitoring.errors {
+                        let name = error.field.trim_start_matches("monitoring.");
+                        errors.check(false, field(name), error.message);
+                    }
+                }
+            }
+            Err(e) => errors.check(false, field("inherits"), e),
+        }
+        if let Some(rules) = &profile.alert_rules {
+            check_alert_rules(errors, &field("alert_rules"), rules);
+        }
+        if let Some(filters) = &profile.filters {
+            check_filters(errors, &field("filters"), filters);
+        }
+        for name in profile.columns.keys() {
+            errors.check(
+                TableColumn::from_name(name).is_some(),
+                field(&format!("columns.{}", name)),
+                format!("unknown column '{}'", name),
+            );
+        }
+    }
+
+    if let Some(active) = &profiles.active {
+        errors.check(
+            profiles.get(active).is_some(),
+            "profiles.active",
+            format!("unknown profile '{}'", active),
+        );
+    }
+}
+
+/// Check refresh rate, history length and graph type
+///
+/// History is limited to `MAX_HISTORY_SAMPLES` samples, so long histories
+/// need slower refresh rates.
+pub fn validate_monitoring(monitoring: &MonitoringConfig) -> Result<(), ValidationErrors> {
+    let mut errors = ValidationErrors::default();
+    let refresh_ok =
+        (MIN_REFRESH_RATE_MS..=MAX_REFRESH_RATE_MS).contains(&monitoring.refresh_rate_ms);
+    let history_ok = (MIN_HISTORY_SEC..=MAX_HISTORY_SEC).contains(&monitoring.history_length_sec);
+    errors.check(
+        refresh_ok,
+        "monitoring.refresh_rate_ms",
+        format!("must be {}-{} ms", MIN_REFRESH_RATE_MS, MAX_REFRESH_RATE_MS),
+    );
+    errors.check(
+        history_ok,
+        "monitoring.history_length_sec",
+        format!("must be {}-{} seconds", MIN_HISTORY_SEC, MAX_HISTORY_SEC),
+    );
+    errors.check(
+        monitoring.graph_type <= 2,
+        "monitoring.graph_type",
+        "must be 0 (line), 1 (area) or 2 (both)",
+    );
+
+    if refresh_ok && history_ok {
+        let samples = history_samples(monitoring);
+        errors.check(
+            samples <= MAX_HISTORY_SAMPLES,
+            "monitoring.history_length_sec",
+            format!(
+                "{} s of history at {} ms needs {} samples, more than the {} that fit the \
+                 memory budget; use a slower refresh rate or a shorter history",
+                monitoring.history_length_sec,
+                monitoring.refresh_rate_ms,
+                samples,
+                MAX_HISTORY_SAMPLES
+            ),
+        );
+    }
+
+    errors.into_result()
+}
+
+fn history_samples(monitoring: &MonitoringConfig) -> u64 {
+    monitoring.history_length_sec as u64 * 1000 / monitoring.refresh_rate_ms.max(1) as u64
+}
+
+/// Clamp repairable values into range
+///
+/// Returns a description of each correction. Values that cannot be clamped
+/// meaningfully (alert rules, ports, ...) are left for `validate` to report.
+pub fn sanitize(config: &mut AppConfig, desktop: Option<&RECT>) -> Vec<ValidationError> {
+    let mut fixed = Vec::new();
+
+    let monitoring = &mut config.monitoring;
+    let refresh = monitoring
+        .refresh_rate_ms
+        .clamp(MIN_REFRESH_RATE_MS, MAX_REFRESH_RATE_MS);
+    if refresh != monitoring.refresh_rate_ms {
+        fixed.push(ValidationError::new(
+            "monitoring.refresh_rate_ms",
+            format!("{} clamped to {}", monitoring.refresh_rate_ms, refresh),
+        ));
+        monitoring.refresh_rate_ms = refresh;
+    }
+    let max_history =
+        (MAX_HISTORY_SAMPLES * refresh as u64 / 1000).min(MAX_HISTORY_SEC as u64) as u32;
+    let history = monitoring
+        .history_length_sec
+        .clamp(MIN_HISTORY_SEC, max_history.max(MIN_HISTORY_SEC));
+    if history != monitoring.history_length_sec {
+        fixed.push(ValidationError::new(
+            "monitoring.history_length_sec",
+            format!("{} clamped to {}", monitoring.history_length_sec, history),
+        ));
+        monitoring.history_length_sec = history;
+    }
+    if monitoring.graph_type > 2 {
+        fixed.push(ValidationError::new(
+            "monitoring.graph_type",
+            format!("{} reset to 0", monitoring.graph_type),
+        ));
+        monitoring.graph_type = 0;
+    }
+
+    let window = config.window.clone();
+    let width = window.width.clamp(MIN_WINDOW_WIDTH, MAX_WINDOW_SIZE);
+    let height = window.height.clamp(MIN_WINDOW_HEIGHT, MAX_WINDOW_SIZE);
+    config.window.width = width;
+    config.window.height = height;
+    if let Some(desktop) = desktop {
+        clamp_window(&mut config.window, desktop);
+    }
+    let after = &config.window;
+    if (after.x, after.y, after.width, after.height)
+        != (window.x, window.y, window.width, window.height)
+    {
+        fixed.push(ValidationError::new(
+            "window",
+            format!(
+                "{}x{} at ({}, {}) moved to {}x{} at ({}, {})",
+                window.width,
+                window.height,
+                window.x,
+                window.y,
+                after.width,
+                after.height,
+                after.x,
+                after.y
+            ),
+        ));
+    }
+
+    for (i, entry) in config.columns.entries.iter_mut().enumerate() {
+        let clamped = entry.width.clamp(MIN_COLUMN_WIDTH, MAX_COLUMN_WIDTH);
+        if clamped != entry.width {
+            fixed.push(ValidationError::new(
+                format!("columns[{}].width", i),
+                format!("{} clamped to {}", entry.width, clamped),
+            ));
+            entry.width = clamped;
+        }
+    }
+    for key in config.columns.repair() {
+        fixed.push(ValidationError::new(
+            "columns",
+            format!("unknown or repeated column '{}' removed", key),
+        ));
+    }
+
+    fixed
+}
+
+/// Fit the window inside `desktop`, shrinking it if it is larger
+///
+/// Returns true if the window was moved or resized.
+pub fn clamp_window(window: &mut WindowConfig, desktop: &RECT) -> bool {
+    let before = (window.x, window.y, window.width, window.height);
+    let desktop_width = desktop.right - desktop.left;
+    let desktop_height = desktop.bottom - desktop.top;
+    if desktop_width <= 0 || desktop_height <= 0 {
+        return false;
+    }
+
+    window.width = window.width.min(desktop_width);
+    window.height = window.height.min(desktop_height);
+    window.x = window.x.clamp(desktop.left, desktop.right - window.width);
+    window.y = window.y.clamp(desktop.top, desktop.bottom - window.height);
+    before != (window.x, window.y, window.width, window.height)
+}
+
+/// Bounding rectangle of all monitors, if known
+#[cfg(windows)]
+pub fn virtual_desktop() -> Option<RECT> {
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetSystemMetrics, SM_CXVIRTUALSCREEN, SM_CYVIRTUALSCREEN, SM_XVIRTUALSCREEN,
+        SM_YVIRTUALSCREEN,
+    };
+
+    // SAFETY: GetSystemMetrics has no preconditions
+    let (left, top, width, height) = unsafe {
+        (
+            GetSystemMetrics(SM_XVIRTUALSCREEN),
+            GetSystemMetrics(SM_YVIRTUALSCREEN),
+            GetSystemMetrics(SM_CXVIRTUALSCREEN),
+            GetSystemMetrics(SM_CYVIRTUALSCREEN),
+        )
+    };
+    // Zero on headless sessions
+    if width <= 0 || height <= 0 {
+        return None;
+    }
+    Some(RECT {
+        left,
+        top,
+        right: left + width,
+        bottom: top + height,
+    })
+}
+
+/// Bounding rectangle of all monitors, if known
+#[cfg(not(windows))]
+pub fn virtual_desktop() -> Option<RECT> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::config::{AlertRule, FileGaugeConfig};
+
+    #[test]
+    fn test_default_config_is_valid() {
+        assert_eq!(validate(&AppConfig::default()), Ok(()));
+    }
+
+    #[test]
+    fn test_field_and_cross_field_errors() {
+        let mut config = AppConfig::default();
+        config.monitoring.refresh_rate_ms = 0;
+        config.window.width = -5;
+        config.rpc.enabled = true;
+        config.columns.set_width("Name", 0).unwrap();
+        let mut gauge = FileGaugeConfig::new("backlog", " ");
+        gauge.interval_secs = 0;
+        config.collectors.file_gauges.push(gauge);
+        config.alerts.rules.push(AlertRule::new(
+            "",
+            crate::app::config::AlertMetric::SystemCpuPercent,
+            crate::app::config::AlertComparison::Above,
+            f64::NAN,
+        ));
+
+        let errors = validate(&config).unwrap_err();
+        let index = config.alerts.rules.len() - 1;
+        for field in [
+            "monitoring.refresh_rate_ms".to_string(),
+            "window.width".to_string(),
+            "columns[0].width".to_string(),
+            "rpc.token".to_string(),
+            "collectors.file_gauges[0].path".to_string(),
+            "collectors.file_gauges[0].interval_secs".to_string(),
+            format!("alerts.rules[{}].name", index),
+            format!("alerts.rules[{}].threshold", index),
+        ] {
+            assert!(errors.for_field(&field).is_some(), "missing {}", field);
+        }
+
+        // 24 hours at 100 ms exceeds the sample budget; at 1 s it fits
+        let mut monitoring = MonitoringConfig {
+            refresh_rate_ms: 100,
+            history_length_sec: 86_400,
+            ..Default::default()
+        };
+        let errors = validate_monitoring(&monitoring).unwrap_err();
+        assert!(errors.errors[0].message.contains("864000 samples"));
+        monitoring.refresh_rate_ms = 1000;
+        assert!(validate_monitoring(&monitoring).is_ok());
+    }
+
+    #[test]
+    fn test_leak_detection_errors() {
+        let mut config = AppConfig::default();
+        config.leak_detection.min_private_growth_per_hour = 0.0;
+        config.leak_detection.min_handle_growth_per_hour = f64::NAN;
+        config.leak_detection.min_samples = 61;
+        config.leak_detection.min_monotonic_fraction = 1.5;
+
+        let errors = validate(&config).unwrap_err();
+        let fields: Vec<&str> = errors.errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            [
+                "leak_detection.min_private_growth_per_hour",
+                "leak_detection.min_handle_growth_per_hour",
+                "leak_detection.min_samples",
+                "leak_detection.min_monotonic_fraction",
+            ]
+        );
+
+        config.leak_detection = Default::default();
+        config.leak_detection.min_monotonic_fraction = 1.0;
+        assert_eq!(validate(&config), Ok(()));
+    }
+
+    #[test]
+    fn test_filter_preset_errors() {
+        let mut config = AppConfig::default();
+        config.filter_presets.push(
+            FilterPreset::new("high CPU")
+                .with_filter("cpu>")
+                .with_sort("colour", false)
+                .with_columns(&["pid", "bogus"]),
+        );
+        config.filter_presets.push(FilterPreset::new(" "));
+
+        let errors = validate(&config).unwrap_err();
+        let fields: Vec<&str> = errors.errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            vec![
+                "filter_presets[5].name",
+                "filter_presets[5].filters[0]",
+                "filter_presets[5].sort[0].column",
+                "filter_presets[5].columns[1]",
+                "filter_presets[6].name",
+            ]
+        );
+        assert_eq!(errors.errors[0].message, "duplicate preset 'high CPU'");
+        assert!(validate_filter_presets(&FilterPreset::defaults()).is_ok());
+    }
+
+    #[test]
+    fn test_custom_column_errors() {
+        let config = AppConfig {
+            custom_columns: vec![
+                CustomColumn::new("per_thread", "memory_private / threads", Default::default()),
+                CustomColumn::new("threads", "per_thread * 2", Default::default()),
+                CustomColumn::new("PER_THREAD", "cpu +", Default::default()),
+                CustomColumn::new("later", "cpu_share", Default::default()),
+                CustomColumn::new("cpu_share", "cpu / total(cpu)", Default::default()),
+            ],
+            ..Default::default()
+        };
+
+        let errors = validate(&config).unwrap_err();
+        let fields: Vec<&str> = errors.errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            vec![
+                "custom_columns[1].name",
+                "custom_columns[2].name",
+                "custom_columns[2].expression",
+                "custom_columns[3].expression",
+            ]
+        );
+        assert_eq!(errors.errors[3].message, "unknown column 'cpu_share'");
+        assert!(validate_custom_columns(&config.custom_columns[..1]).is_ok());
+    }
+
+    #[test]
+    fn test_sanitize_clamps_and_fits_desktop() {
+        let mut config = AppConfig::default();
+        config.monitoring.refresh_rate_ms = 0;
+        config.monitoring.history_length_sec = u32::MAX;
+        // Registry DWORD read back from a negative width
+        config.window.width = 0xFFFF_FF00u32 as i32;
+        config.window.x = 5000;
+        config.columns.set_width("CPU", 80).unwrap();
+        config.columns.set_width("Name", 100_000).unwrap();
+        config.columns.entries[4].key = "threads".to_string();
+
+        let desktop = RECT {
+            left: -1920,
+            top: 0,
+            right: 1920,
+            bottom: 1080,
+        };
+        let fixed = sanitize(&mut config, Some(&desktop));
+        let fields: Vec<&str> = fixed.iter().map(|f| f.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            vec![
+                "monitoring.refresh_rate_ms",
+                "monitoring.history_length_sec",
+                "window",
+                "columns[0].width",
+                "columns",
+            ]
+        );
+        assert_eq!(config.monitoring.refresh_rate_ms, MIN_REFRESH_RATE_MS);
+        assert_eq!(config.monitoring.history_length_sec, 8_640);
+        assert_eq!(config.window.width, MIN_WINDOW_WIDTH);
+        assert_eq!(config.window.x, 1920 - MIN_WINDOW_WIDTH);
+        assert_eq!(config.columns.get("cpu").unwrap().width, 80);
+        assert_eq!(config.columns.get("name").unwrap().width, MAX_COLUMN_WIDTH);
+        // The dropped Handles entry comes back with defaults
+        assert_eq!(config.columns.entries.last().unwrap().key, "handles");
+        assert_eq!(validate(&config), Ok(()));
+
+        // Larger than the desktop: shrink and pin to the top-left corner
+        let mut window = WindowConfig {
+            width: 5000,
+            ..Default::default()
+        };
+        let small = RECT {
+            left: 0,
+            top: 0,
+            right: 1024,
+            bottom: 768,
+        };
+        assert!(clamp_window(&mut window, &small));
+        assert_eq!((window.x, window.width), (0, 1024));
+        assert!(!clamp_window(&mut window, &small));
+    }
+}
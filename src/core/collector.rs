@@ -0,0 +1,348 @@
+//! Pluggable metric collectors
+//!
+//! `SystemMonitor` gathers a fixed set of system metrics. Additional sources
+//! (application queue depths, license counts, ...) implement
+//! `MetricCollector` and are passed to `Updater::start_with_collectors`:
+//! - Each collector declares its metrics up front (`MetricDescriptor`)
+//! - `CollectorRegistry` samples each collector at its preferred interval
+//! - Values land in `SystemMetrics::custom` under `collector.metric`, so
+//!   history, alerts (`AlertMetric::Custom`) and export pick them up like
+//!   any built-in metric
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::core::metrics::SystemMetrics;
+
+/// Metric published by a collector
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetricDescriptor {
+    /// Name within the collector (`[a-z0-9_]`)
+    pub name: String,
+    /// Unit shown next to values (e.g. "items", "bytes", "%")
+    pub unit: String,
+    /// One-line description
+    pub help: String,
+}
+
+impl MetricDescriptor {
+    pub fn new(name: impl Into<String>, unit: impl Into<String>, help: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            unit: unit.into(),
+            help: help.into(),
+        }
+    }
+}
+
+/// Source of custom metric values
+///
+/// Collectors run on the updater thread; `sample` should return quickly.
+pub trait MetricCollector: Send {
+    /// Collector name, used as the key prefix (`[a-z0-9_]`)
+    fn name(&self) -> &str;
+
+    /// Metrics this collector publishes; undeclared values are dropped
+    fn descriptors(&self) -> Vec<MetricDescriptor>;
+
+    /// Current values as (metric name, value)
+    fn sample(&mut self) -> Result<Vec<(String, f64)>, String>;
+
+    /// How often to sample
+    ///
+    /// Due collectors are sampled on the next updater tick, so intervals
+    /// shorter than the refresh rate sample once per tick and longer ones
+    /// are rounded up to a whole tick.
+    fn interval(&self) -> Duration {
+        Duration::from_secs(1)
+    }
+}
+
+/// Key of a collector metric in `SystemMetrics::custom`
+pub fn metric_key(collector: &str, metric: &str) -> String {
+    format!("{}.{}", collector, metric)
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+struct Scheduled {
+    collector: Box<dyn MetricCollector>,
+    descriptors: Vec<MetricDescriptor>,
+    next_due: Option<Instant>,
+    /// Last sampled values by full key
+    values: BTreeMap<String, f64>,
+}
+
+/// Schedules collectors and merges their values into snapshots
+pub struct CollectorRegistry {
+    collectors: Vec<Scheduled>,
+}
+
+impl CollectorRegistry {
+    pub fn new() -> Self {
+        Self {
+            collectors: Vec::new(),
+        }
+    }
+
+    /// Add a collector
+    ///
+    /// Fails if the collector or one of its metrics has an invalid name, or
+    /// the collector name is already registered.
+    pub fn register(&mut self, collector: Box<dyn MetricCollector>) -> Result<(), String> {
+        let name = collector.name().to_string();
+        if !is_valid_name(&name) {
+            return Err(format!("Invalid collector name '{}'", name));
+        }
+        if self.collectors.iter().any(|c| c.collector.name() == name) {
+            return Err(format!("Collector '{}' is already registered", name));
+        }
+        let descriptors = collector.descriptors();
+        if let Some(bad) = descriptors.iter().find(|d| !is_valid_name(&d.name)) {
+            return Err(format!(
+                "Collector '{}': invalid metric name '{}'",
+                name, bad.name
+            ));
+        }
+
+        self.collectors.push(Scheduled {
+            collector,
+            descriptors,
+            next_due: None,
+            values: BTreeMap::new(),
+        });
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.collectors.is_empty()
+    }
+
+    /// All declared metrics as (key, descriptor)
+    pub fn descriptors(&self) -> Vec<(String, &MetricDescriptor)> {
+        self.collectors
+            .iter()
+            .flat_map(|scheduled| {
+                let collector = scheduled.collector.name();
+                scheduled
+                    .descriptors
+                    .iter()
+                    .map(move |d| (metric_key(collector, &d.name), d))
+            })
+            .collect()
+    }
+
+    /// Sample collectors that are due and write all current values into `metrics`
+    ///
+    /// Collectors that are not due keep their last values, so every snapshot
+    /// carries each metric once it has been sampled. A failing collector's
+    /// values are removed until it succeeds again. Returns one message per
+    /// failure.
+    pub fn collect(&mut self, now: Instant, metrics: &mut SystemMetrics) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        for scheduled in &mut self.collectors {
+            if scheduled.next_due.filter(|due| now < *due).is_none() {
+                scheduled.next_due = Some(now + scheduled.collector.interval());
+                let name = scheduled.collector.name().to_string();
+                match scheduled.collector.sample() {
+                    Ok(values) => {
+                        scheduled.values = values
+                            .into_iter()
+                            .filter(|(metric, _)| {
+                                scheduled.descriptors.iter().any(|d| d.name == *metric)
+                            })
+                            .filter(|(_, value)| value.is_finite())
+                            .map(|(metric, value)| (metric_key(&name, &metric), value))
+                            .collect();
+                    }
+                    Err(e) => {
+                        scheduled.values.clear();
+                        errors.push(format!("Collector '{}': {}", name, e));
+                    }
+                }
+            }
+            metrics.custom.extend(
+                scheduled
+                    .values
+                    .iter()
+                    .map(|(key, value)| (key.clone(), *value)),
+            );
+        }
+
+        errors
+    }
+}
+
+impl Default for CollectorRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Gauge read from a text file containing a single number
+///
+/// Lets other programs publish a value (e.g. a queue depth) by rewriting a
+/// file, without linking against the task manager.
+pub struct FileGaugeCollector {
+    name: String,
+    path: PathBuf,
+    descriptor: MetricDescriptor,
+    interval: Duration,
+}
+
+impl FileGaugeCollector {
+    /// Collector `name` publishing the metric `value` read from `path`
+    pub fn new(name: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        Self {
+            name: name.into(),
+            descriptor: MetricDescriptor::new(
+                "value",
+                "",
+                format!("Value read from {}", path.display()),
+            ),
+            path,
+            interval: Duration::from_secs(5),
+        }
+    }
+
+    pub fn with_unit(mut self, unit: impl Into<String>) -> Self {
+        self.descriptor.unit = unit.into();
+        self
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+}
+
+impl MetricCollector for FileGaugeCollector {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn descriptors(&self) -> Vec<MetricDescriptor> {
+        vec![self.descriptor.clone()]
+    }
+
+    fn sample(&mut self) -> Result<Vec<(String, f64)>, String> {
+        let text = fs::read_to_string(&self.path)
+            .map_err(|e| format!("cannot read {}: {}", self.path.display(), e))?;
+        let value = text
+            .trim()
+            .parse::<f64>()
+            .map_err(|_| format!("{} does not contain a number", self.path.display()))?;
+        Ok(vec![(self.descriptor.name.clone(), value)])
+    }
+
+    fn interval(&self) -> Duration {
+        self.interval
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Counter {
+        calls: u32,
+        fail: bool,
+    }
+
+    impl MetricCollector for Counter {
+        fn name(&self) -> &str {
+            "queue"
+        }
+
+        fn descriptors(&self) -> Vec<MetricDescriptor> {
+            vec![MetricDescriptor::new("depth", "items", "Pending jobs")]
+        }
+
+        fn sample(&mut self) -> Result<Vec<(String, f64)>, String> {
+            self.calls += 1;
+            if self.fail {
+                return Err("offline".to_string());
+            }
+            Ok(vec![
+                ("depth".to_string(), self.calls as f64),
+                ("undeclared".to_string(), 1.0),
+            ])
+        }
+
+        fn interval(&self) -> Duration {
+            Duration::from_secs(10)
+        }
+    }
+
+    #[test]
+    fn test_registry_schedules_and_keeps_last_values() {
+        let mut registry = CollectorRegistry::new();
+        registry
+            .register(Box::new(Counter {
+                calls: 0,
+                fail: false,
+            }))
+            .unwrap();
+        assert_eq!(registry.descriptors()[0].0, "queue.depth");
+
+        let start = Instant::now();
+        let mut values = Vec::new();
+        for secs in [0, 5, 10] {
+            let mut metrics = SystemMetrics::new();
+            assert!(registry
+                .collect(start + Duration::from_secs(secs), &mut metrics)
+                .is_empty());
+            assert_eq!(metrics.custom.len(), 1, "undeclared metrics are dropped");
+            values.push(metrics.custom["queue.depth"]);
+        }
+        // Not due at 5s: the value from 0s is repeated
+        assert_eq!(values, vec![1.0, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn test_registry_rejects_bad_names_and_reports_errors() {
+        let mut registry = CollectorRegistry::new();
+        assert!(registry
+            .register(Box::new(FileGaugeCollector::new("Bad Name", "x")))
+            .is_err());
+        registry
+            .register(Box::new(Counter {
+                calls: 0,
+                fail: true,
+            }))
+            .unwrap();
+        assert!(registry
+            .register(Box::new(Counter {
+                calls: 0,
+                fail: false,
+            }))
+            .is_err());
+
+        let mut metrics = SystemMetrics::new();
+        let errors = registry.collect(Instant::now(), &mut metrics);
+        assert_eq!(errors, vec!["Collector 'queue': offline".to_string()]);
+        assert!(metrics.custom.is_empty());
+    }
+
+    #[test]
+    fn test_file_gauge() {
+        let path = std::env::temp_dir().join(format!("tm-gauge-{}.txt", std::process::id()));
+        let mut gauge = FileGaugeCollector::new("backlog", &path);
+        assert!(gauge.sample().is_err());
+
+        fs::write(&path, " 42.5\n").unwrap();
+        assert_eq!(gauge.sample().unwrap(), vec![("value".to_string(), 42.5)]);
+        fs::write(&path, "n/a").unwrap();
+        assert!(gauge.sample().is_err());
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,638 @@
+//! Arithmetic expressions over process fields
+//!
+//! Shared by filter conditions (`threads>50`, `memory_private / threads > 1MB`)
+//! and user-defined computed columns. An expression combines numbers,
+//! process fields and `total(field)` (the field summed over all processes)
+//! with `+ - * /` and parentheses; any other name refers to a computed
+//! column defined earlier.
+//!
+//! Evaluation yields `None` when a value is unavailable: a process without
+//! a leak rate, an unknown column or a division by zero.
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::filter::ProcessInfo;
+use crate::util::format::NumberFormat;
+
+/// Process field usable in expressions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Pid,
+    ParentPid,
+    Cpu,
+    MemoryPrivate,
+    MemoryWorkingSet,
+    IoReadBytes,
+    IoWriteBytes,
+    IoReadRate,
+    IoWriteRate,
+    Threads,
+    Handles,
+    LeakRate,
+    Session,
+}
+
+impl Field {
+    /// Every field, in `Totals` order
+    pub const ALL: [Field; 13] = [
+        Field::Pid,
+        Field::ParentPid,
+        Field::Cpu,
+        Field::MemoryPrivate,
+        Field::MemoryWorkingSet,
+        Field::IoReadBytes,
+        Field::IoWriteBytes,
+        Field::IoReadRate,
+        Field::IoWriteRate,
+        Field::Threads,
+        Field::Handles,
+        Field::LeakRate,
+        Field::Session,
+    ];
+
+    /// Name used in expressions
+    pub fn name(self) -> &'static str {
+        match self {
+            Field::Pid => "pid",
+            Field::ParentPid => "parent_pid",
+            Field::Cpu => "cpu",
+            Field::MemoryPrivate => "memory_private",
+            Field::MemoryWorkingSet => "memory_working_set",
+            Field::IoReadBytes => "io_read_bytes",
+            Field::IoWriteBytes => "io_write_bytes",
+            Field::IoReadRate => "io_read_bps",
+            Field::IoWriteRate => "io_write_bps",
+            Field::Threads => "threads",
+            Field::Handles => "handles",
+            Field::LeakRate => "leak_rate",
+            Field::Session => "session",
+        }
+    }
+
+    /// Field by name or alias (`mem`, `cpu_usage`, `handle_count`), ignoring case
+    pub fn from_name(name: &str) -> Option<Field> {
+        let field = match name.to_ascii_lowercase().as_str() {
+            "cpu_usage" => Field::Cpu,
+            "mem" | "memory" | "private" => Field::MemoryPrivate,
+            "working_set" => Field::MemoryWorkingSet,
+            "io_read" => Field::IoReadBytes,
+            "io_write" => Field::IoWriteBytes,
+            "thread_count" => Field::Threads,
+            "handle_count" => Field::Handles,
+            "session_id" => Field::Session,
+            name => return Self::ALL.into_iter().find(|f| f.name() == name),
+        };
+        Some(field)
+    }
+
+    /// Value of this field for `process`
+    pub fn value(self, process: &ProcessInfo) -> Option<f64> {
+        let value = match self {
+            Field::Pid => process.pid as f64,
+            Field::ParentPid => process.parent_pid as f64,
+            Field::Cpu => process.cpu_usage,
+            Field::MemoryPrivate => process.memory_private as f64,
+            Field::MemoryWorkingSet => process.memory_working_set as f64,
+            Field::IoReadBytes => process.io_read_bytes as f64,
+            Field::IoWriteBytes => process.io_write_bytes as f64,
+            Field::IoReadRate => process.io_read_bps,
+            Field::IoWriteRate => process.io_write_bps,
+            Field::Threads => process.thread_count as f64,
+            Field::Handles => process.handle_count as f64,
+            Field::LeakRate => return process.leak_rate,
+            Field::Session => process.session_id as f64,
+        };
+        Some(value)
+    }
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|f| *f == self).unwrap_or(0)
+    }
+}
+
+/// Every field summed over a process list, for `total(field)`
+#[derive(Debug, Clone, Default)]
+pub struct Totals([f64; Field::ALL.len()]);
+
+impl Totals {
+    pub fn of<'a>(processes: impl IntoIterator<Item = &'a ProcessInfo>) -> Self {
+        let mut totals = Self::default();
+        for process in processes {
+            for field in Field::ALL {
+                totals.0[field.index()] += field.value(process).unwrap_or(0.0);
+            }
+        }
+        totals
+    }
+
+    pub fn get(&self, field: Field) -> f64 {
+        self.0[field.index()]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Number(f64),
+    Field(Field),
+    Total(Field),
+    /// Computed column, by name
+    Column(String),
+    Negate(Box<Node>),
+    Binary(Operator, Box<Node>, Box<Node>),
+}
+
+impl Node {
+    fn evaluate(&self, process: &ProcessInfo, totals: &Totals) -> Option<f64> {
+        match self {
+            Node::Number(value) => Some(*value),
+            Node::Field(field) => field.value(process),
+            Node::Total(field) => Some(totals.get(*field)),
+            Node::Column(name) => process
+                .custom
+                .iter()
+                .find(|column| column.name.eq_ignore_ascii_case(name))
+                .and_then(|column| column.value),
+            Node::Negate(node) => node.evaluate(process, totals).map(|value| -value),
+            Node::Binary(operator, left, right) => {
+                let left = left.evaluate(process, totals)?;
+                let right = right.evaluate(process, totals)?;
+                match operator {
+                    Operator::Add => Some(left + right),
+                    Operator::Subtract => Some(left - right),
+                    Operator::Multiply => Some(left * right),
+                    Operator::Divide if right == 0.0 => None,
+                    Operator::Divide => Some(left / right),
+                }
+            }
+        }
+    }
+
+    fn columns<'a>(&'a self, names: &mut Vec<&'a str>) {
+        match self {
+            Node::Column(name) => names.push(name),
+            Node::Negate(node) => node.columns(names),
+            Node::Binary(_, left, right) => {
+                left.columns(names);
+                right.columns(names);
+            }
+            Node::Number(_) | Node::Field(_) | Node::Total(_) => {}
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Symbol(char),
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut number = String::new();
+            while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit() || **c == '.') {
+                number.push(c);
+                chars.next();
+            }
+            let value = number
+                .parse()
+                .map_err(|_| format!("Invalid number '{}'", number))?;
+            tokens.push(Token::Number(value));
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let mut name = String::new();
+            while let Some(&c) = chars
+                .peek()
+                .filter(|c| c.is_ascii_alphanumeric() || **c == '_')
+            {
+                name.push(c);
+                chars.next();
+            }
+            tokens.push(Token::Name(name));
+        } else if "+-*/()".contains(c) {
+            tokens.push(Token::Symbol(c));
+            chars.next();
+        } else {
+            return Err(format!("Unexpected '{}'", c));
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent over `tokens`; `*` and `/` bind tighter than `+` and `-`
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, symbol: char) -> bool {
+        let found = self.peek() == Some(&Token::Symbol(symbol));
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, symbol: char) -> Result<(), String> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            Err(format!("Expected '{}'", symbol))
+        }
+    }
+
+    fn sum(&mut self) -> Result<Node, String> {
+        let mut node = self.product()?;
+        loop {
+            let operator = if self.eat('+') {
+                Operator::Add
+            } else if self.eat('-') {
+                Operator::Subtract
+            } else {
+                return Ok(node);
+            };
+            node = Node::Binary(operator, Box::new(node), Box::new(self.product()?));
+        }
+    }
+
+    fn product(&mut self) -> Result<Node, String> {
+        let mut node = self.unary()?;
+        loop {
+            let operator = if self.eat('*') {
+                Operator::Multiply
+            } else if self.eat('/') {
+                Operator::Divide
+            } else {
+                return Ok(node);
+            };
+            node = Node::Binary(operator, Box::new(node), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Node, String> {
+        if self.eat('-') {
+            Ok(Node::Negate(Box::new(self.unary()?)))
+        } else {
+            self.primary()
+        }
+    }
+
+    fn primary(&mut self) -> Result<Node, String> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(Node::Number(value)),
+            Some(Token::Symbol('(')) => {
+                let node = self.sum()?;
+                self.expect(')')?;
+                Ok(node)
+            }
+            Some(Token::Name(name)) if self.eat('(') => {
+                if !name.eq_ignore_ascii_case("total") {
+                    return Err(format!("Unknown function '{}'", name));
+                }
+                let field = match self.next() {
+                    Some(Token::Name(field)) => Field::from_name(&field)
+                        .ok_or_else(|| format!("Unknown field '{}' in total()", field))?,
+                    _ => return Err("total() takes a field name".to_string()),
+                };
+                self.expect(')')?;
+                Ok(Node::Total(field))
+            }
+            Some(Token::Name(name)) => Ok(match Field::from_name(&name) {
+                Some(field) => Node::Field(field),
+                None => Node::Column(name.to_ascii_lowercase()),
+            }),
+            Some(Token::Symbol(symbol)) => Err(format!("Unexpected '{}'", symbol)),
+            None => Err("Unexpected end of expression".to_string()),
+        }
+    }
+}
+
+/// Parsed arithmetic expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    source: String,
+    root: Node,
+}
+
+impl Expression {
+    /// Parse `text` such as `memory_private / threads` or `cpu / total(cpu) * 100`
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let tokens = tokenize(text)?;
+        if tokens.is_empty() {
+            return Err("Empty expression".to_string());
+        }
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+        };
+        let root = parser.sum()?;
+        if let Some(token) = parser.peek() {
+            return Err(match token {
+                Token::Number(value) => format!("Unexpected '{}'", value),
+                Token::Name(name) => format!("Unexpected '{}'", name),
+                Token::Symbol(symbol) => format!("Unexpected '{}'", symbol),
+            });
+        }
+        Ok(Self {
+            source: text.trim().to_string(),
+            root,
+        })
+    }
+
+    /// Text the expression was parsed from
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Value for `process`; `totals` backs `total(field)`
+    pub fn evaluate(&self, process: &ProcessInfo, totals: &Totals) -> Option<f64> {
+        self.root
+            .evaluate(process, totals)
+            .filter(|value| value.is_finite())
+    }
+
+    /// Names of the computed columns the expression refers to (lowercase)
+    pub fn columns(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        self.root.columns(&mut names);
+        names
+    }
+}
+
+/// Display format of a computed column (see `util::format`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnFormat {
+    /// Byte count in the configured units (`1.5 MB`)
+    Bytes,
+    /// Value followed by `%` (`12.5%`)
+    Percent,
+    /// Plain number, with adaptive precision when fractional
+    #[default]
+    Count,
+}
+
+impl ColumnFormat {
+    /// Cell text for `value`; empty when the value is unavailable
+    pub fn format(self, value: Option<f64>, numbers: &NumberFormat) -> String {
+        let Some(value) = value else {
+            return String::new();
+        };
+        match self {
+            ColumnFormat::Bytes => numbers.bytes_f64(value),
+            ColumnFormat::Percent => numbers.percent(value),
+            ColumnFormat::Count => numbers.number(value),
+        }
+    }
+}
+
+/// User-defined column computed from an expression (stored in `AppConfig`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomColumn {
+    /// Header label, also used to sort, filter and refer to the column
+    pub name: String,
+    pub expression: String,
+    #[serde(default)]
+    pub format: ColumnFormat,
+}
+
+impl CustomColumn {
+    pub fn new(
+        name: impl Into<String>,
+        expression: impl Into<String>,
+        format: ColumnFormat,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            expression: expression.into(),
+            format,
+        }
+    }
+}
+
+/// Check that `name` can be referred to from expressions
+pub fn check_column_name(name: &str) -> Result<(), String> {
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(format!(
+            "Column name '{}' must be letters, digits and underscores",
+            name
+        ));
+    }
+    let keyword = ["name", "total"]
+        .iter()
+        .any(|k| name.eq_ignore_ascii_case(k));
+    if Field::from_name(name).is_some() || keyword {
+        return Err(format!("Column name '{}' is reserved", name));
+    }
+    Ok(())
+}
+
+/// Value of a computed column on one process row
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnValue {
+    pub name: String,
+    pub value: Option<f64>,
+    pub format: ColumnFormat,
+}
+
+impl ColumnValue {
+    /// Cell text in the column's format
+    pub fn display(&self, numbers: &NumberFormat) -> String {
+        self.format.format(self.value, numbers)
+    }
+}
+
+/// Parsed computed columns, evaluated in definition order
+#[derive(Debug, Clone, Default)]
+pub struct ComputedColumns {
+    columns: Vec<(CustomColumn, Expression)>,
+}
+
+impl ComputedColumns {
+    /// Parse every column; a column may only refer to columns before it
+    pub fn new(columns: &[CustomColumn]) -> Result<Self, String> {
+        let mut parsed: Vec<(CustomColumn, Expression)> = Vec::new();
+        for column in columns {
+            let invalid = |e: String| format!("Column '{}': {}", column.name, e);
+            check_column_name(&column.name).map_err(invalid)?;
+            if parsed
+                .iter()
+                .any(|(c, _)| c.name.eq_ignore_ascii_case(&column.name))
+            {
+                return Err(invalid("defined twice".to_string()));
+            }
+            let expression = Expression::parse(&column.expression).map_err(invalid)?;
+            for name in expression.columns() {
+                if !parsed
+                    .iter()
+                    .any(|(c, _)| c.name.eq_ignore_ascii_case(name))
+                {
+                    return Err(invalid(format!("Unknown column '{}'", name)));
+                }
+            }
+            parsed.push((column.clone(), expression));
+        }
+        Ok(Self { columns: parsed })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Column definitions in order
+    pub fn definitions(&self) -> impl Iterator<Item = &CustomColumn> {
+        self.columns.iter().map(|(column, _)| column)
+    }
+
+    /// Index of column `name`, ignoring case
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.columns
+            .iter()
+            .position(|(column, _)| column.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Fill `ProcessInfo::custom` of every row; `total(field)` sums all rows
+    pub fn apply(&self, rows: &mut [ProcessInfo]) {
+        let totals = Totals::of(rows.iter());
+        for row in rows.iter_mut() {
+            row.custom.clear();
+            for (column, expression) in &self.columns {
+                let value = expression.evaluate(row, &totals);
+                row.custom.push(ColumnValue {
+                    name: column.name.clone(),
+                    value,
+                    format: column.format,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_process(pid: u32, cpu: f64, private: u64, threads: u32) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            parent_pid: 0,
+            name: format!("p{}.exe", pid),
+            cpu_usage: cpu,
+            memory_private: private,
+            memory_working_set: private,
+            io_read_bytes: 0,
+            io_write_bytes: 0,
+            io_read_bps: 0.0,
+            io_write_bps: 0.0,
+            thread_count: threads,
+            handle_count: 100,
+            leak_rate: None,
+            session_id: 1,
+            custom: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_and_evaluate() {
+        let process = make_process(1, 10.0, 8_000, 4);
+        let totals = Totals::of([&process]);
+        let eval = |text: &str| Expression::parse(text).unwrap().evaluate(&process, &totals);
+
+        assert_eq!(eval("memory_private / threads"), Some(2_000.0));
+        assert_eq!(eval("1 + 2 * 3"), Some(7.0));
+        assert_eq!(eval("(1 + 2) * 3"), Some(9.0));
+        assert_eq!(eval("-cpu + 1"), Some(-9.0));
+        assert_eq!(eval("MEM / Thread_Count"), Some(2_000.0));
+        assert_eq!(eval("cpu / total(cpu) * 100"), Some(100.0));
+        // Unavailable values propagate instead of failing
+        assert_eq!(eval("leak_rate * 2"), None);
+        assert_eq!(eval("cpu / (threads - 4)"), None);
+        assert_eq!(eval("unknown_column"), None);
+
+        assert!(Expression::parse("").is_err());
+        assert!(Expression::parse("cpu +").is_err());
+        assert!(Expression::parse("(cpu").is_err());
+        assert!(Expression::parse("cpu threads").is_err());
+        assert!(Expression::parse("max(cpu)").is_err());
+        assert!(Expression::parse("total(nothing)").is_err());
+        assert!(Expression::parse("cpu > 5").is_err());
+    }
+
+    #[test]
+    fn test_computed_columns() {
+        let columns = ComputedColumns::new(&[
+            CustomColumn::new(
+                "per_thread",
+                "memory_private / threads",
+                ColumnFormat::Bytes,
+            ),
+            CustomColumn::new("cpu_share", "cpu / total(cpu) * 100", ColumnFormat::Percent),
+            CustomColumn::new("double_share", "cpu_share * 2", ColumnFormat::Count),
+        ])
+        .unwrap();
+        let mut rows = vec![
+            make_process(1, 30.0, 4 * 1024 * 1024, 2),
+            make_process(2, 10.0, 1024, 0),
+        ];
+        columns.apply(&mut rows);
+
+        let numbers = NumberFormat::new();
+        let display: Vec<String> =
+            rows[0].custom.iter().map(|value| value.display(&numbers)).collect();
+        assert_eq!(display, vec!["2.0 MB", "75.0%", "150"]);
+        assert_eq!(rows[1].custom[0].value, None);
+        assert_eq!(rows[1].custom[0].display(&numbers), "");
+        assert_eq!(rows[1].custom[1].display(&numbers), "25.0%");
+        assert_eq!(columns.position("CPU_SHARE"), Some(1));
+
+        // Applying again replaces the values instead of appending
+        columns.apply(&mut rows);
+        assert_eq!(rows[0].custom.len(), 3);
+    }
+
+    #[test]
+    fn test_computed_column_errors() {
+        let column =
+            |name: &str, expression: &str| CustomColumn::new(name, expression, ColumnFormat::Count);
+        let error = |columns: &[CustomColumn]| ComputedColumns::new(columns).unwrap_err();
+
+        assert!(error(&[column("a", "b"), column("b", "1")]).contains("Unknown column 'b'"));
+        assert!(error(&[column("a", "1"), column("A", "2")]).contains("defined twice"));
+        assert!(error(&[column("cpu", "1")]).contains("reserved"));
+        assert!(error(&[column("my column", "1")]).contains("letters"));
+        assert!(error(&[column("a", "1 +")]).starts_with("Column 'a': "));
+        assert!(error(&[column("a", "a + 1")]).contains("Unknown column 'a'"));
+    }
+}
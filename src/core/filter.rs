@@ -5,10 +5,16 @@
 //! - CPU threshold filtering  
 //! - Memory threshold filtering
 //! - User ownership filtering
+//! - Session filtering (session 0 holds services and system processes)
+//! - Expression conditions over fields and computed columns (`threads>50`)
 //! - Column-based stable sorting
+//! - Parsing of filter expressions (`cpu>5`, `name=chrome`) and sort column
+//!   names, shared by the CLI, presets, validation and the RPC endpoint
 
 use std::cmp::Ordering;
 
+use crate::core::expression::{ColumnValue, Expression, Totals};
+
 /// Simple process info for filtering/sorting
 #[derive(Debug, Clone)]
 pub struct ProcessInfo {
@@ -28,8 +34,20 @@ pub struct ProcessInfo {
     pub io_read_bytes: u64,
     /// I/O write bytes
     pub io_write_bytes: u64,
+    /// I/O read rate in bytes per second
+    pub io_read_bps: f64,
+    /// I/O write rate in bytes per second
+    pub io_write_bps: f64,
+    /// Number of threads
+    pub thread_count: u32,
     /// Number of handles
     pub handle_count: u32,
+    /// Private bytes growth per hour, if flagged by the leak detector
+    pub leak_rate: Option<f64>,
+    /// Terminal Services session (0 for services and system processes)
+    pub session_id: u32,
+    /// Computed column values, filled by `ComputedColumns::apply`
+    pub custom: Vec<ColumnValue>,
 }
 
 /// T193-T196: Process filter criteria
@@ -45,6 +63,53 @@ pub struct ProcessFilter {
     pub owned_only: bool,
     /// Use regex for name matching
     pub use_regex: bool,
+    /// Show only processes in matching sessions
+    pub session: Option<SessionFilter>,
+    /// Expression criteria, all of which must hold
+    pub conditions: Vec<Condition>,
+}
+
+/// Session criterion of a `ProcessFilter`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionFilter {
+    /// Exactly this session (`session=0` selects services)
+    Equals(u32),
+    /// Any later session (`session>0` selects interactive users)
+    Above(u32),
+}
+
+/// Comparison operator of a `Condition`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Greater,
+    AtLeast,
+    Less,
+    AtMost,
+    Equal,
+}
+
+/// Expression criterion of a `ProcessFilter` (`memory_private / threads > 1MB`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    pub expression: Expression,
+    pub comparison: Comparison,
+    pub value: f64,
+}
+
+impl Condition {
+    /// Whether the expression compares true; unavailable values never match
+    pub fn matches(&self, process: &ProcessInfo, totals: &Totals) -> bool {
+        let Some(actual) = self.expression.evaluate(process, totals) else {
+            return false;
+        };
+        match self.comparison {
+            Comparison::Greater => actual > self.value,
+            Comparison::AtLeast => actual >= self.value,
+            Comparison::Less => actual < self.value,
+            Comparison::AtMost => actual <= self.value,
+            Comparison::Equal => actual == self.value,
+        }
+    }
 }
 
 impl ProcessFilter {
@@ -77,14 +142,33 @@ impl ProcessFilter {
         self
     }
 
+    /// Filter by session
+    pub fn with_session(mut self, session: SessionFilter) -> Self {
+        self.session = Some(session);
+        self
+    }
+
     /// T198: Enable regex matching
     pub fn with_regex(mut self) -> Self {
         self.use_regex = true;
         self
     }
 
+    /// Also require `condition`
+    pub fn with_condition(mut self, condition: Condition) -> Self {
+        self.conditions.push(condition);
+        self
+    }
+
     /// Check if process matches all filter criteria
+    ///
+    /// `total(field)` in conditions only sees `process`; `apply` sums the
+    /// whole list.
     pub fn matches(&self, process: &ProcessInfo) -> bool {
+        self.matches_within(process, &Totals::of([process]))
+    }
+
+    fn matches_within(&self, process: &ProcessInfo, totals: &Totals) -> bool {
         // Name filter
         if let Some(ref name) = self.name {
             if self.use_regex {
@@ -114,6 +198,16 @@ impl ProcessFilter {
             }
         }
 
+        match self.session {
+            Some(SessionFilter::Equals(id)) if process.session_id != id => return false,
+            Some(SessionFilter::Above(id)) if process.session_id <= id => return false,
+            _ => {}
+        }
+
+        if !self.conditions.iter().all(|c| c.matches(process, totals)) {
+            return false;
+        }
+
         // T196: Ownership filter (simplified - check if process is accessible)
         if self.owned_only {
             // Simplified: assume we can only see owned processes
@@ -125,7 +219,15 @@ impl ProcessFilter {
 
     /// Apply filter to process list
     pub fn apply<'a>(&self, processes: &'a [ProcessInfo]) -> Vec<&'a ProcessInfo> {
-        processes.iter().filter(|p| self.matches(p)).collect()
+        let totals = if self.conditions.is_empty() {
+            Totals::default()
+        } else {
+            Totals::of(processes)
+        };
+        processes
+            .iter()
+            .filter(|p| self.matches_within(p, &totals))
+            .collect()
     }
 }
 
@@ -142,6 +244,10 @@ pub enum SortColumn {
     Memory,
     /// Sort by handle count
     Handles,
+    /// Sort by leak detector growth rate (unflagged processes last)
+    LeakRate,
+    /// Sort by computed column N of `ProcessInfo::custom` (missing values last)
+    Custom(usize),
 }
 
 /// Sort direction
@@ -157,25 +263,55 @@ pub enum SortDirection {
 pub struct ProcessSorter {
     column: SortColumn,
     direction: SortDirection,
+    /// Tie-breaking keys, in order
+    then: Vec<(SortColumn, SortDirection)>,
 }
 
 impl ProcessSorter {
     /// Create new sorter
     pub fn new(column: SortColumn, direction: SortDirection) -> Self {
-        Self { column, direction }
+        Self {
+            column,
+            direction,
+            then: Vec::new(),
+        }
+    }
+
+    /// Order processes that are equal on the previous keys by `column`
+    pub fn then_by(mut self, column: SortColumn, direction: SortDirection) -> Self {
+        self.then.push((column, direction));
+        self
     }
 
     /// Compare two processes for sorting
     fn compare(&self, a: &ProcessInfo, b: &ProcessInfo) -> Ordering {
-        let cmp = match self.column {
+        std::iter::once((self.column, self.direction))
+            .chain(self.then.iter().copied())
+            .map(|(column, direction)| Self::compare_by(column, direction, a, b))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    }
+
+    fn compare_by(
+        column: SortColumn,
+        direction: SortDirection,
+        a: &ProcessInfo,
+        b: &ProcessInfo,
+    ) -> Ordering {
+        let cmp = match column {
             SortColumn::Name => a.name.cmp(&b.name),
             SortColumn::Pid => a.pid.cmp(&b.pid),
             SortColumn::Cpu => a.cpu_usage.partial_cmp(&b.cpu_usage).unwrap_or(Ordering::Equal),
             SortColumn::Memory => a.memory_private.cmp(&b.memory_private),
             SortColumn::Handles => a.handle_count.cmp(&b.handle_count),
+            SortColumn::LeakRate => compare_optional(a.leak_rate, b.leak_rate),
+            SortColumn::Custom(index) => {
+                let value = |p: &ProcessInfo| p.custom.get(index).and_then(|c| c.value);
+                compare_optional(value(a), value(b))
+            }
         };
 
-        match self.direction {
+        match direction {
             SortDirection::Ascending => cmp,
             SortDirection::Descending => cmp.reverse(),
         }
@@ -192,6 +328,16 @@ impl ProcessSorter {
     }
 }
 
+/// Order where a missing value is below any present one
+fn compare_optional(a: Option<f64>, b: Option<f64>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+        (Some(_), None) => Ordering::Greater,
+        (None, Some(_)) => Ordering::Less,
+        (None, None) => Ordering::Equal,
+    }
+}
+
 /// Combined filter and sort
 pub struct ProcessFilterSort {
     filter: ProcessFilter,
@@ -212,6 +358,99 @@ impl ProcessFilterSort {
     }
 }
 
+/// Sort column by name (`cpu`, `mem`, `pid`, `name`, `handles`)
+pub fn parse_sort(name: &str) -> Option<SortColumn> {
+    match name {
+        "cpu" => Some(SortColumn::Cpu),
+        "mem" | "memory" => Some(SortColumn::Memory),
+        "pid" => Some(SortColumn::Pid),
+        "name" => Some(SortColumn::Name),
+        "handles" => Some(SortColumn::Handles),
+        _ => None,
+    }
+}
+
+/// Add one filter expression (`cpu>5`, `mem>500MB`, `name=chrome`) to `filter`
+///
+/// Other left-hand sides are expressions over fields and computed columns
+/// (`threads>50`, `memory_private / threads >= 1MB`), compared with `>`,
+/// `>=` or `=` to a number, percentage or size.
+pub fn parse_filter(expr: &str, filter: ProcessFilter) -> Result<ProcessFilter, String> {
+    const OPERATORS: [char; 3] = ['>', '=', '~'];
+    let invalid = || format!("Invalid filter '{}'", expr);
+    let split = expr.find(OPERATORS).ok_or_else(invalid)?;
+    let key = expr[..split].trim().to_lowercase();
+    let rest = &expr[split..];
+    let value = rest.trim_start_matches(OPERATORS);
+    let op = &rest[..rest.len() - value.len()];
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(invalid());
+    }
+
+    match (key.as_str(), op) {
+        ("cpu", ">" | ">=") => {
+            let percent = value
+                .trim_end_matches('%')
+                .parse::<f64>()
+                .map_err(|_| invalid())?;
+            Ok(filter.with_cpu_threshold(percent))
+        }
+        ("mem" | "memory", ">" | ">=") => Ok(filter.with_memory_threshold(parse_size(value)?)),
+        ("name", "=" | "~") => Ok(filter.with_name(value)),
+        ("session", "=" | ">") => {
+            let id = value.parse::<u32>().map_err(|_| invalid())?;
+            Ok(filter.with_session(match op {
+                "=" => SessionFilter::Equals(id),
+                _ => SessionFilter::Above(id),
+            }))
+        }
+        ("name", _) => Err(invalid()),
+        (key, ">" | ">=" | "=") => {
+            let expression = Expression::parse(key)
+                .map_err(|e| format!("Invalid filter '{}': {}", expr, e))?;
+            let value = match value.strip_suffix('%') {
+                Some(percent) => percent.trim().parse::<f64>().map_err(|_| invalid())?,
+                None => match value.parse::<f64>() {
+                    Ok(number) => number,
+                    Err(_) => parse_size(value).map_err(|_| invalid())? as f64,
+                },
+            };
+            let comparison = match op {
+                ">" => Comparison::Greater,
+                ">=" => Comparison::AtLeast,
+                _ => Comparison::Equal,
+            };
+            Ok(filter.with_condition(Condition {
+                expression,
+                comparison,
+                value,
+            }))
+        }
+        _ => Err(invalid()),
+    }
+}
+
+/// Parse a size such as `500MB`, `1.5GB` or `4096` (bytes, 1024-based units)
+pub fn parse_size(text: &str) -> Result<u64, String> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let invalid = || format!("Invalid size '{}'", text);
+
+    let number: f64 = number.parse().map_err(|_| invalid())?;
+    let multiplier = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1.0,
+        "K" | "KB" => 1024.0,
+        "M" | "MB" => 1024.0 * 1024.0,
+        "G" | "GB" => 1024.0 * 1024.0 * 1024.0,
+        _ => return Err(invalid()),
+    };
+    Ok((number * multiplier) as u64)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -226,10 +465,38 @@ mod tests {
             memory_working_set: memory,
             io_read_bytes: 0,
             io_write_bytes: 0,
+            io_read_bps: 0.0,
+            io_write_bps: 0.0,
+            thread_count: 4,
             handle_count: 100,
+            leak_rate: None,
+            session_id: 1,
+            custom: Vec::new(),
         }
     }
 
+    #[test]
+    fn test_parse_filter_sort_and_size() {
+        let filter = parse_filter("cpu>5%", ProcessFilter::new()).unwrap();
+        let filter = parse_filter("mem>=1.5GB", filter).unwrap();
+        let filter = parse_filter("threads > 50", filter).unwrap();
+        assert_eq!(filter.cpu_threshold, Some(5.0));
+        assert_eq!(filter.memory_threshold, Some(1536 * 1024 * 1024));
+        assert_eq!(filter.conditions[0].comparison, Comparison::Greater);
+        assert_eq!(
+            parse_filter("name>chrome", ProcessFilter::new()).unwrap_err(),
+            "Invalid filter 'name>chrome'"
+        );
+        assert!(parse_filter("mem>lots", ProcessFilter::new()).is_err());
+
+        assert_eq!(parse_sort("memory"), Some(SortColumn::Memory));
+        assert_eq!(parse_sort("colour"), None);
+
+        assert_eq!(parse_size("1.5gb").unwrap(), 1536 * 1024 * 1024);
+        assert_eq!(parse_size("4096").unwrap(), 4096);
+        assert!(parse_size("5 parsecs").is_err());
+    }
+
     #[test]
     fn test_name_filter() {
         let processes = vec![
@@ -275,6 +542,27 @@ mod tests {
         assert!(filtered.iter().all(|p| p.memory_private >= 50_000_000));
     }
 
+    #[test]
+    fn test_session_filter() {
+        let mut processes = vec![
+            make_test_process("svchost.exe", 1000, 1.0, 10_000_000),
+            make_test_process("explorer.exe", 2000, 1.0, 10_000_000),
+            make_test_process("rdpclip.exe", 3000, 1.0, 10_000_000),
+        ];
+        processes[0].session_id = 0;
+        processes[2].session_id = 2;
+
+        let services = ProcessFilter::new().with_session(SessionFilter::Equals(0));
+        assert_eq!(services.apply(&processes).len(), 1);
+        let users = ProcessFilter::new().with_session(SessionFilter::Above(0));
+        let names: Vec<&str> = users
+            .apply(&processes)
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["explorer.exe", "rdpclip.exe"]);
+    }
+
     #[test]
     fn test_sorting_by_cpu() {
         let mut processes = vec![
@@ -307,6 +595,95 @@ mod tests {
         assert_eq!(processes[2].name, "notepad.exe");
     }
 
+    #[test]
+    fn test_sorting_with_tie_breakers() {
+        let mut processes = vec![
+            make_test_process("b.exe", 3, 1.0, 10_000_000),
+            make_test_process("a.exe", 2, 5.0, 10_000_000),
+            make_test_process("c.exe", 1, 1.0, 10_000_000),
+        ];
+
+        let sorter = ProcessSorter::new(SortColumn::Cpu, SortDirection::Descending)
+            .then_by(SortColumn::Pid, SortDirection::Ascending);
+        sorter.sort(&mut processes);
+
+        let names: Vec<&str> = processes.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["a.exe", "c.exe", "b.exe"]);
+    }
+
+    #[test]
+    fn test_sorting_by_leak_rate() {
+        let mut processes = vec![
+            make_test_process("steady.exe", 1000, 1.0, 10_000_000),
+            make_test_process("slow.exe", 2000, 1.0, 10_000_000),
+            make_test_process("fast.exe", 3000, 1.0, 10_000_000),
+        ];
+        processes[1].leak_rate = Some(1_000_000.0);
+        processes[2].leak_rate = Some(50_000_000.0);
+
+        let sorter = ProcessSorter::new(SortColumn::LeakRate, SortDirection::Descending);
+        sorter.sort(&mut processes);
+
+        assert_eq!(processes[0].name, "fast.exe");
+        assert_eq!(processes[1].name, "slow.exe");
+        assert_eq!(processes[2].name, "steady.exe");
+    }
+
+    #[test]
+    fn test_expression_conditions() {
+        let mut processes = vec![
+            make_test_process("a.exe", 1, 30.0, 8_000_000),
+            make_test_process("b.exe", 2, 10.0, 1_000_000),
+            make_test_process("c.exe", 3, 60.0, 1_000_000),
+        ];
+        processes[1].thread_count = 40;
+
+        let condition = |text: &str, comparison, value| Condition {
+            expression: Expression::parse(text).unwrap(),
+            comparison,
+            value,
+        };
+        let names = |filter: &ProcessFilter| -> Vec<String> {
+            filter.apply(&processes).iter().map(|p| p.name.clone()).collect()
+        };
+
+        let filter = ProcessFilter::new()
+            .with_condition(condition("memory_private / threads", Comparison::AtLeast, 1e6));
+        assert_eq!(names(&filter), vec!["a.exe"]);
+        let filter =
+            ProcessFilter::new().with_condition(condition("threads", Comparison::Less, 10.0));
+        assert_eq!(names(&filter), vec!["a.exe", "c.exe"]);
+        // Totals cover the whole list passed to apply
+        let filter = ProcessFilter::new()
+            .with_condition(condition("cpu / total(cpu)", Comparison::Greater, 0.5));
+        assert_eq!(names(&filter), vec!["c.exe"]);
+        let filter =
+            ProcessFilter::new().with_condition(condition("leak_rate", Comparison::AtLeast, 0.0));
+        assert!(names(&filter).is_empty());
+    }
+
+    #[test]
+    fn test_sorting_by_custom_column() {
+        let mut processes = vec![
+            make_test_process("none.exe", 1, 1.0, 10_000_000),
+            make_test_process("low.exe", 2, 1.0, 10_000_000),
+            make_test_process("high.exe", 3, 1.0, 10_000_000),
+        ];
+        for (process, value) in processes.iter_mut().zip([None, Some(1.0), Some(5.0)]) {
+            process.custom.push(ColumnValue {
+                name: "score".to_string(),
+                value,
+                format: Default::default(),
+            });
+        }
+
+        let sorter = ProcessSorter::new(SortColumn::Custom(0), SortDirection::Descending);
+        sorter.sort(&mut processes);
+
+        let names: Vec<&str> = processes.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["high.exe", "low.exe", "none.exe"]);
+    }
+
     #[test]
     fn test_filter_and_sort_combined() {
         let processes = vec![
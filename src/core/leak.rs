@@ -0,0 +1,454 @@
+//! Memory and handle leak detection
+//!
+//! Keeps a sliding window of private bytes and handle counts for every
+//! process in `ProcessStore` and fits a robust trend to each series:
+//! - Theil–Sen slope (median of pairwise slopes), so single spikes and
+//!   garbage-collection drops do not dominate the estimate
+//! - Monotonicity check, so only sustained growth is flagged
+//! - Projected time until a configured limit is reached
+//!
+//! Samples are thinned to at most `max_samples` per window, which bounds the
+//! O(n²) slope fit regardless of the refresh rate.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::process::ProcessStore;
+
+/// Leak detector settings (stored in `AppConfig`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeakDetectorConfig {
+    pub enabled: bool,
+    /// Length of the sliding window in seconds
+    pub window_secs: u64,
+    /// Samples kept per window (older samples are thinned out)
+    pub max_samples: usize,
+    /// Samples required before a process can be flagged
+    pub min_samples: usize,
+    /// Fraction of sample-to-sample steps that must not decrease (0.0-1.0)
+    pub min_monotonic_fraction: f64,
+    /// Minimum private bytes growth to flag (bytes per hour)
+    pub min_private_growth_per_hour: f64,
+    /// Minimum handle growth to flag (handles per hour)
+    pub min_handle_growth_per_hour: f64,
+    /// Private bytes limit used for the time-to-threshold projection
+    pub private_bytes_limit: u64,
+    /// Handle count limit used for the time-to-threshold projection
+    pub handle_limit: u32,
+}
+
+impl Default for LeakDetectorConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window_secs: 30 * 60,
+            max_samples: 60,
+            min_samples: 10,
+            min_monotonic_fraction: 0.8,
+            min_private_growth_per_hour: 50.0 * 1024.0 * 1024.0,
+            min_handle_growth_per_hour: 500.0,
+            private_bytes_limit: 4 * 1024 * 1024 * 1024,
+            handle_limit: 10_000,
+        }
+    }
+}
+
+/// Series a finding refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LeakMetric {
+    PrivateBytes,
+    Handles,
+}
+
+impl LeakMetric {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::PrivateBytes => "Private bytes",
+            Self::Handles => "Handles",
+        }
+    }
+}
+
+/// Robust linear fit `y = slope * x + intercept`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrendFit {
+    pub slope: f64,
+    pub intercept: f64,
+}
+
+impl TrendFit {
+    pub fn value_at(&self, x: f64) -> f64 {
+        self.slope * x + self.intercept
+    }
+}
+
+/// Theil–Sen estimator over (x, y) points
+///
+/// Returns `None` with fewer than two distinct x values.
+pub fn theil_sen(points: &[(f64, f64)]) -> Option<TrendFit> {
+    let mut slopes = Vec::with_capacity(points.len() * points.len().saturating_sub(1) / 2);
+    for (i, &(x1, y1)) in points.iter().enumerate() {
+        for &(x2, y2) in &points[i + 1..] {
+            if x2 != x1 {
+                slopes.push((y2 - y1) / (x2 - x1));
+            }
+        }
+    }
+    let slope = median(&mut slopes)?;
+
+    let mut intercepts: Vec<f64> = points.iter().map(|&(x, y)| y - slope * x).collect();
+    let intercept = median(&mut intercepts)?;
+
+    Some(TrendFit { slope, intercept })
+}
+
+fn median(values: &mut [f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(|a, b| a.total_cmp(b));
+    let mid = values.len() / 2;
+    if values.len() % 2 == 1 {
+        Some(values[mid])
+    } else {
+        Some((values[mid - 1] + values[mid]) / 2.0)
+    }
+}
+
+/// Fraction of consecutive steps that do not decrease
+fn monotonic_fraction(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let rising = values.windows(2).filter(|w| w[1] >= w[0]).count();
+    rising as f64 / (values.len() - 1) as f64
+}
+
+/// A process flagged for sustained growth
+#[derive(Debug, Clone, PartialEq)]
+pub struct LeakFinding {
+    pub pid: u32,
+    pub name: Arc<str>,
+    pub metric: LeakMetric,
+    /// Estimated growth per hour (bytes or handles)
+    pub growth_per_hour: f64,
+    /// Latest observed value
+    pub current: f64,
+    /// Fraction of non-decreasing steps in the window
+    pub monotonic_fraction: f64,
+    /// Estimated time until the configured limit is reached
+    pub time_to_limit: Option<Duration>,
+}
+
+/// Finding that appeared or went away in `LeakDetector::observe`
+#[derive(Debug, Clone, PartialEq)]
+pub enum LeakChange {
+    Detected(LeakFinding),
+    /// Growth stopped or the process exited; carries the last finding
+    Resolved(LeakFinding),
+}
+
+/// Sliding window for one process
+struct ProcessHistory {
+    name: Arc<str>,
+    /// (seconds since detector start, private bytes, handles)
+    samples: Vec<(f64, f64, f64)>,
+}
+
+/// Per-process trend analyzer
+pub struct LeakDetector {
+    config: LeakDetectorConfig,
+    origin: Option<Instant>,
+    histories: HashMap<u32, ProcessHistory>,
+    findings: HashMap<(u32, LeakMetric), LeakFinding>,
+}
+
+impl LeakDetector {
+    pub fn new(config: LeakDetectorConfig) -> Self {
+        Self {
+            config,
+            origin: None,
+            histories: HashMap::new(),
+            findings: HashMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> &LeakDetectorConfig {
+        &self.config
+    }
+
+    /// Current findings, in no particular order
+    pub fn findings(&self) -> impl Iterator<Item = &LeakFinding> {
+        self.findings.values()
+    }
+
+    /// Finding for a process and metric, if flagged
+    pub fn finding(&self, pid: u32, metric: LeakMetric) -> Option<&LeakFinding> {
+        self.findings.get(&(pid, metric))
+    }
+
+    /// Record the processes in `store` at time `at` and re-evaluate trends
+    pub fn observe(&mut self, store: &ProcessStore, at: Instant) -> Vec<LeakChange> {
+        let origin = *self.origin.get_or_insert(at);
+        let now = at.saturating_duration_since(origin).as_secs_f64();
+        let window = self.config.window_secs as f64;
+        let min_spacing = window / self.config.max_samples.max(1) as f64;
+
+        let mut present = HashSet::with_capacity(store.count());
+        for index in 0..store.count() {
+            let (Some(name), Some(private), Some(handles)) = (
+                store.name(index),
+                store.memory_private(index),
+                store.handle_count(index),
+            ) else {
+                continue;
+            };
+            let pid = store.pids()[index];
+            present.insert(pid);
+
+            let history = self.histories.entry(pid).or_insert_with(|| ProcessHistory {
+                name: Arc::from(name),
+                samples: Vec::new(),
+            });
+            // A reused PID starts a new series
+            if &*history.name != name {
+                history.name = Arc::from(name);
+                history.samples.clear();
+            }
+
+            let due = match history.samples.last() {
+                Some(&(last, _, _)) => now - last >= min_spacing,
+                None => true,
+            };
+            if due {
+                history.samples.push((now, private as f64, handles as f64));
+                history.samples.retain(|&(t, _, _)| now - t <= window);
+            }
+        }
+        self.histories.retain(|pid, _| present.contains(pid));
+
+        let mut changes = Vec::new();
+        let mut current = HashMap::new();
+        for (&pid, history) in &self.histories {
+            for metric in [LeakMetric::PrivateBytes, LeakMetric::Handles] {
+                if let Some(finding) = self.evaluate(pid, history, metric) {
+                    current.insert((pid, metric), finding);
+                }
+            }
+        }
+
+        for (key, finding) in &current {
+            if !self.findings.contains_key(key) {
+                changes.push(LeakChange::Detected(finding.clone()));
+            }
+        }
+        for (key, finding) in self.findings.drain() {
+            if !current.contains_key(&key) {
+                changes.push(LeakChange::Resolved(finding));
+            }
+        }
+        self.findings = current;
+        changes
+    }
+
+    fn evaluate(
+        &self,
+        pid: u32,
+        history: &ProcessHistory,
+        metric: LeakMetric,
+    ) -> Option<LeakFinding> {
+        if history.samples.len() < self.config.min_samples.max(2) {
+            return None;
+        }
+
+        let points: Vec<(f64, f64)> = history
+            .samples
+            .iter()
+            .map(|&(t, private, handles)| match metric {
+                LeakMetric::PrivateBytes => (t, private),
+                LeakMetric::Handles => (t, handles),
+            })
+            .collect();
+        let values: Vec<f64> = points.iter().map(|&(_, y)| y).collect();
+
+        let fit = theil_sen(&points)?;
+        let growth_per_hour = fit.slope * 3600.0;
+        let (min_growth, limit) = match metric {
+            LeakMetric::PrivateBytes => (
+                self.config.min_private_growth_per_hour,
+                self.config.private_bytes_limit as f64,
+            ),
+            LeakMetric::Handles => (
+                self.config.min_handle_growth_per_hour,
+                self.config.handle_limit as f64,
+            ),
+        };
+        if growth_per_hour < min_growth {
+            return None;
+        }
+
+        let monotonic = monotonic_fraction(&values);
+        if monotonic < self.config.min_monotonic_fraction {
+            return None;
+        }
+
+        let (last_t, _) = *points.last()?;
+        let current = *values.last()?;
+        let projected = fit.value_at(last_t).max(current);
+        // Flat or falling series (possible with non-positive thresholds) and
+        // projections too far out to represent have no estimate
+        let time_to_limit = if projected >= limit {
+            Some(Duration::ZERO)
+        } else if fit.slope > 0.0 {
+            Duration::try_from_secs_f64((limit - projected) / fit.slope).ok()
+        } else {
+            None
+        };
+
+        Some(LeakFinding {
+            pid,
+            name: Arc::clone(&history.name),
+            metric,
+            growth_per_hour,
+            current,
+            monotonic_fraction: monotonic,
+            time_to_limit,
+        })
+    }
+}
+
+impl Default for LeakDetector {
+    fn default() -> Self {
+        Self::new(LeakDetectorConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::ProcessBuilder;
+
+    const MB: u64 = 1024 * 1024;
+
+    fn test_config() -> LeakDetectorConfig {
+        LeakDetectorConfig {
+            window_secs: 600,
+            max_samples: 60,
+            min_samples: 10,
+            ..LeakDetectorConfig::default()
+        }
+    }
+
+    /// Feed one sample every 10 seconds; `series(i)` gives (private, handles)
+    fn run(
+        detector: &mut LeakDetector,
+        samples: usize,
+        series: impl Fn(usize) -> (u64, u32),
+    ) -> Vec<LeakChange> {
+        let start = Instant::now();
+        let mut store = ProcessStore::new();
+        let mut changes = Vec::new();
+        for i in 0..samples {
+            let (private, handles) = series(i);
+            let leaky = ProcessBuilder::new(100, "leaky.exe")
+                .with_handles(handles)
+                .with_memory(private)
+                .build();
+            store.update(vec![leaky]);
+            changes.extend(detector.observe(&store, start + Duration::from_secs(10 * i as u64)));
+        }
+        changes
+    }
+
+    #[test]
+    fn test_theil_sen_ignores_outlier() {
+        let mut points: Vec<(f64, f64)> =
+            (0..10).map(|i| (i as f64, 2.0 * i as f64 + 1.0)).collect();
+        points[5].1 = 1000.0;
+
+        let fit = theil_sen(&points).unwrap();
+        assert!((fit.slope - 2.0).abs() < 1e-9);
+        assert!((fit.intercept - 1.0).abs() < 1e-9);
+        assert!(theil_sen(&[(1.0, 1.0), (1.0, 2.0)]).is_none());
+    }
+
+    #[test]
+    fn test_steady_growth_flagged_with_projection() {
+        let mut detector = LeakDetector::new(test_config());
+        // 1 MB every 10 seconds = 360 MB/hour
+        let changes = run(&mut detector, 20, |i| (500 * MB + i as u64 * MB, 200));
+
+        assert_eq!(changes.len(), 1);
+        let finding = detector.finding(100, LeakMetric::PrivateBytes).unwrap();
+        assert!((finding.growth_per_hour - 360.0 * MB as f64).abs() < 1.0);
+        assert_eq!(finding.current, (519 * MB) as f64);
+
+        // (4096 - 519) MB at 0.1 MB/s
+        let eta = finding.time_to_limit.unwrap().as_secs_f64();
+        assert!((eta - 35_770.0).abs() < 1.0);
+        assert!(detector.finding(100, LeakMetric::Handles).is_none());
+    }
+
+    #[test]
+    fn test_sawtooth_not_flagged() {
+        let mut detector = LeakDetector::new(test_config());
+        // Grows then drops back every 3 samples (allocator / GC cycles)
+        let changes = run(&mut detector, 40, |i| {
+            (500 * MB + (i % 3) as u64 * 20 * MB, 200)
+        });
+
+        assert!(changes.is_empty());
+        assert_eq!(detector.findings().count(), 0);
+    }
+
+    #[test]
+    fn test_handle_leak_and_resolution() {
+        let mut detector = LeakDetector::new(test_config());
+        // 10 handles per 10 seconds = 3600/hour, then flat
+        let changes = run(&mut detector, 150, |i| {
+            (100 * MB, 1000 + i.min(40) as u32 * 10)
+        });
+
+        assert!(matches!(
+            changes.first(),
+            Some(LeakChange::Detected(f)) if f.metric == LeakMetric::Handles
+        ));
+        assert!(matches!(changes.last(), Some(LeakChange::Resolved(_))));
+        assert_eq!(detector.findings().count(), 0);
+    }
+
+    #[test]
+    fn test_flat_series_without_growth_threshold() {
+        let config = LeakDetectorConfig {
+            min_private_growth_per_hour: 0.0,
+            min_handle_growth_per_hour: -1.0,
+            ..test_config()
+        };
+        let mut detector = LeakDetector::new(config);
+        run(&mut detector, 20, |_| (500 * MB, 200));
+
+        let finding = detector.finding(100, LeakMetric::PrivateBytes).unwrap();
+        assert_eq!(finding.growth_per_hour, 0.0);
+        assert_eq!(finding.time_to_limit, None);
+        assert_eq!(
+            detector.finding(100, LeakMetric::Handles).unwrap().time_to_limit,
+            None
+        );
+    }
+
+    #[test]
+    fn test_exited_process_resolved() {
+        let mut detector = LeakDetector::new(test_config());
+        run(&mut detector, 20, |i| (500 * MB + i as u64 * MB, 200));
+        assert_eq!(detector.findings().count(), 1);
+
+        let changes = detector.observe(
+            &ProcessStore::new(),
+            Instant::now() + Duration::from_secs(300),
+        );
+        assert!(matches!(changes.as_slice(), [LeakChange::Resolved(f)] if f.pid == 100));
+    }
+}
@@ -1,5 +1,7 @@
 //! Core metrics abstraction and calculations
 
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::hash::Hash;
 use std::time::Instant;
 
 /// Type of metric being measured
@@ -40,6 +42,8 @@ pub struct SystemMetrics {
     pub network_receive_bps: u64,
     /// Network transmit bytes per second
     pub network_transmit_bps: u64,
+    /// Values from registered `MetricCollector`s, keyed `collector.metric`
+    pub custom: BTreeMap<String, f64>,
 }
 
 impl SystemMetrics {
@@ -56,6 +60,7 @@ impl SystemMetrics {
             disk_write_bps: 0,
             network_receive_bps: 0,
             network_transmit_bps: 0,
+            custom: BTreeMap::new(),
         }
     }
 }
@@ -210,6 +215,272 @@ impl MetricAggregation {
     }
 }
 
+/// Baseline model used by `AnomalyDetector`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum AnomalyMethod {
+    /// EWMA mean and variance; score is the z-score against the running baseline
+    ZScore,
+    /// Median and MAD over a rolling window; score is the modified z-score
+    /// (0.6745 * deviation / MAD), robust to earlier outliers in the window
+    Mad,
+}
+
+/// Anomaly detector settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnomalyConfig {
+    pub method: AnomalyMethod,
+    /// EWMA smoothing factor (0.0-1.0, ZScore only)
+    pub alpha: f32,
+    /// Score above which a sample is anomalous
+    pub threshold: f32,
+    /// Samples observed before anything is flagged
+    pub warmup: usize,
+    /// Minimum rise above the baseline, so tiny absolute changes on a flat
+    /// series are never flagged
+    pub min_delta: f32,
+    /// Rolling window length (Mad only)
+    pub window: usize,
+}
+
+impl AnomalyConfig {
+    pub fn new(method: AnomalyMethod) -> Self {
+        let threshold = match method {
+            AnomalyMethod::ZScore => 4.0,
+            AnomalyMethod::Mad => 3.5,
+        };
+        Self {
+            method,
+            alpha: 0.1,
+            threshold,
+            warmup: 30,
+            min_delta: 0.0,
+            window: 60,
+        }
+    }
+
+    pub fn with_alpha(mut self, alpha: f32) -> Self {
+        self.alpha = alpha.clamp(0.001, 1.0);
+        self
+    }
+
+    pub fn with_threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    pub fn with_warmup(mut self, warmup: usize) -> Self {
+        self.warmup = warmup;
+        self
+    }
+
+    pub fn with_min_delta(mut self, min_delta: f32) -> Self {
+        self.min_delta = min_delta;
+        self
+    }
+
+    pub fn with_window(mut self, window: usize) -> Self {
+        self.window = window.max(3);
+        self
+    }
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self::new(AnomalyMethod::ZScore)
+    }
+}
+
+/// A sample that rose unusually far above its baseline
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Anomaly {
+    pub value: f32,
+    /// Baseline before this sample (EWMA mean or window median)
+    pub expected: f32,
+    pub score: f32,
+}
+
+/// Spike detector for a single metric series
+///
+/// Only upward deviations are flagged. Results depend only on the sequence
+/// of values, so replaying a recorded trace always flags the same samples.
+#[derive(Debug, Clone)]
+pub struct AnomalyDetector {
+    config: AnomalyConfig,
+    seen: usize,
+    mean: f64,
+    variance: f64,
+    window: VecDeque<f32>,
+}
+
+impl AnomalyDetector {
+    pub fn new(config: AnomalyConfig) -> Self {
+        Self {
+            config,
+            seen: 0,
+            mean: 0.0,
+            variance: 0.0,
+            window: VecDeque::new(),
+        }
+    }
+
+    /// Indices of anomalous samples in a recorded trace
+    pub fn scan(config: AnomalyConfig, values: &[f32]) -> Vec<usize> {
+        let mut detector = Self::new(config);
+        values
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &value)| detector.observe(value).map(|_| i))
+            .collect()
+    }
+
+    pub fn config(&self) -> &AnomalyConfig {
+        &self.config
+    }
+
+    /// Number of samples observed since creation or `reset`
+    pub fn samples_seen(&self) -> usize {
+        self.seen
+    }
+
+    /// Forget the baseline
+    pub fn reset(&mut self) {
+        self.seen = 0;
+        self.mean = 0.0;
+        self.variance = 0.0;
+        self.window.clear();
+    }
+
+    /// Score `value` against the baseline, then fold it into the baseline
+    pub fn observe(&mut self, value: f32) -> Option<Anomaly> {
+        if !value.is_finite() {
+            return None;
+        }
+
+        let result = match self.config.method {
+            AnomalyMethod::ZScore => self.observe_zscore(value as f64),
+            AnomalyMethod::Mad => self.observe_mad(value),
+        };
+        self.seen += 1;
+        result
+    }
+
+    fn observe_zscore(&mut self, value: f64) -> Option<Anomaly> {
+        if self.seen == 0 {
+            self.mean = value;
+            return None;
+        }
+
+        let deviation = value - self.mean;
+        let std_dev = self.variance.sqrt();
+        let score = if std_dev > 0.0 {
+            deviation / std_dev
+        } else if deviation > 0.0 {
+            f64::INFINITY
+        } else {
+            0.0
+        };
+        let anomaly = self.flag(value as f32, self.mean as f32, score as f32);
+
+        // Incremental EWMA mean and variance
+        let alpha = self.config.alpha as f64;
+        self.mean += alpha * deviation;
+        self.variance = (1.0 - alpha) * (self.variance + alpha * deviation * deviation);
+
+        anomaly
+    }
+
+    fn observe_mad(&mut self, value: f32) -> Option<Anomaly> {
+        let anomaly = if self.window.is_empty() {
+            None
+        } else {
+            let mut sorted: Vec<f32> = self.window.iter().copied().collect();
+            let median = median_of(&mut sorted);
+            let mut deviations: Vec<f32> = sorted.iter().map(|v| (v - median).abs()).collect();
+            let mad = median_of(&mut deviations);
+
+            let deviation = value - median;
+            let score = if mad > 0.0 {
+                0.6745 * deviation / mad
+            } else if deviation > 0.0 {
+                f32::INFINITY
+            } else {
+                0.0
+            };
+            self.flag(value, median, score)
+        };
+
+        self.window.push_back(value);
+        while self.window.len() > self.config.window.max(3) {
+            self.window.pop_front();
+        }
+
+        anomaly
+    }
+
+    fn flag(&self, value: f32, expected: f32, score: f32) -> Option<Anomaly> {
+        let warmed_up = self.seen >= self.config.warmup;
+        let rise = value - expected;
+        if warmed_up && rise > self.config.min_delta && score > self.config.threshold {
+            Some(Anomaly {
+                value,
+                expected,
+                score,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/// Median of a non-empty slice (sorts in place)
+fn median_of(values: &mut [f32]) -> f32 {
+    values.sort_by(|a, b| a.total_cmp(b));
+    let mid = values.len() / 2;
+    if values.len() % 2 == 1 {
+        values[mid]
+    } else {
+        (values[mid - 1] + values[mid]) / 2.0
+    }
+}
+
+/// Independent anomaly detectors keyed by series (metric, PID, ...)
+#[derive(Debug, Clone)]
+pub struct AnomalyMonitor<K> {
+    config: AnomalyConfig,
+    detectors: HashMap<K, AnomalyDetector>,
+}
+
+impl<K: Eq + Hash> AnomalyMonitor<K> {
+    pub fn new(config: AnomalyConfig) -> Self {
+        Self {
+            config,
+            detectors: HashMap::new(),
+        }
+    }
+
+    /// Observe a value for `key`, creating its detector on first use
+    pub fn observe(&mut self, key: K, value: f32) -> Option<Anomaly> {
+        let config = &self.config;
+        self.detectors
+            .entry(key)
+            .or_insert_with(|| AnomalyDetector::new(config.clone()))
+            .observe(value)
+    }
+
+    /// Drop detectors for series that no longer exist
+    pub fn retain(&mut self, mut keep: impl FnMut(&K) -> bool) {
+        self.detectors.retain(|key, _| keep(key));
+    }
+
+    pub fn len(&self) -> usize {
+        self.detectors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.detectors.is_empty()
+    }
+}
+
 /// SIMD-accelerated metric aggregation (T327)
 /// 
 /// Uses AVX2 instructions for 8x f32 parallel operations
@@ -352,4 +623,68 @@ mod tests {
         let p95 = agg.p95().unwrap();
         assert!((p95 - 10.0).abs() < 0.1, "P95 should be ~10.0, got {}", p95);
     }
+
+    /// Recorded CPU trace: noisy idle around 5% with two build spikes
+    fn cpu_trace() -> Vec<f32> {
+        (0..120)
+            .map(|i| match i {
+                60 => 85.0,
+                95 => 70.0,
+                _ => 5.0 + ((i * 7) % 5) as f32 * 0.5,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_zscore_flags_spikes_deterministically() {
+        let config = AnomalyConfig::new(AnomalyMethod::ZScore).with_min_delta(10.0);
+        let first = AnomalyDetector::scan(config.clone(), &cpu_trace());
+        let second = AnomalyDetector::scan(config, &cpu_trace());
+
+        assert_eq!(first, vec![60, 95]);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_mad_flags_spikes() {
+        let config = AnomalyConfig::new(AnomalyMethod::Mad).with_min_delta(10.0);
+        assert_eq!(AnomalyDetector::scan(config, &cpu_trace()), vec![60, 95]);
+    }
+
+    #[test]
+    fn test_anomaly_warmup_and_min_delta() {
+        let mut detector = AnomalyDetector::new(AnomalyConfig::default().with_warmup(5));
+        for _ in 0..4 {
+            detector.observe(1.0);
+        }
+        // Still warming up
+        assert!(detector.observe(50.0).is_none());
+
+        let flat = vec![2.0; 40];
+        let mut trace = flat.clone();
+        trace.push(2.5);
+        let config = AnomalyConfig::default().with_min_delta(1.0);
+        assert!(AnomalyDetector::scan(config, &trace).is_empty());
+
+        let mut trace = flat;
+        trace.push(40.0);
+        let mut detector = AnomalyDetector::new(AnomalyConfig::default().with_min_delta(1.0));
+        let flagged: Vec<_> = trace.iter().filter_map(|&v| detector.observe(v)).collect();
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].expected, 2.0);
+    }
+
+    #[test]
+    fn test_anomaly_monitor_keys() {
+        let mut monitor = AnomalyMonitor::new(AnomalyConfig::default().with_warmup(3));
+        for _ in 0..10 {
+            monitor.observe(1u32, 1.0);
+            monitor.observe(2u32, 1.0);
+        }
+        assert!(monitor.observe(1, 90.0).is_some());
+        assert!(monitor.observe(2, 1.0).is_none());
+
+        monitor.retain(|pid| *pid == 2);
+        assert_eq!(monitor.len(), 1);
+    }
 }
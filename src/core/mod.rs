@@ -1,6 +1,9 @@
 //! Core business logic (platform-agnostic)
 
+pub mod collector;
+pub mod expression;
 pub mod filter;
+pub mod leak;
 pub mod metrics;
 pub mod process;
 pub mod system;
@@ -281,6 +281,24 @@ impl ProcessStore {
         }
     }
 
+    /// Get private memory by index
+    pub fn memory_private(&self, index: usize) -> Option<u64> {
+        if index < self.count {
+            Some(self.memory_private[index])
+        } else {
+            None
+        }
+    }
+
+    /// Get handle count by index
+    pub fn handle_count(&self, index: usize) -> Option<u32> {
+        if index < self.count {
+            Some(self.handle_counts[index])
+        } else {
+            None
+        }
+    }
+
     /// Filter processes by name (returns iterator over indices)
     ///
     /// # Zero Allocations
@@ -320,6 +338,7 @@ impl Default for ProcessStore {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::ProcessBuilder;
 
     #[test]
     fn test_process_store_creates() {
@@ -332,30 +351,21 @@ mod tests {
         let mut store = ProcessStore::new();
         
         let processes = vec![
-            ProcessInfo {
-                pid: 100,
-                parent_pid: 0,
-                name: "test.exe".to_string(),
-                thread_count: 1,
-                handle_count: 10,
-                cpu_time_user: 1000,
-                cpu_time_kernel: 500,
-                memory_working_set: 1024 * 1024,
-                memory_pagefile: 512 * 1024,
-                memory_private: 256 * 1024,
-            },
-            ProcessInfo {
-                pid: 200,
-                parent_pid: 100,
-                name: "child.exe".to_string(),
-                thread_count: 2,
-                handle_count: 20,
-                cpu_time_user: 2000,
-                cpu_time_kernel: 1000,
-                memory_working_set: 2 * 1024 * 1024,
-                memory_pagefile: 1024 * 1024,
-                memory_private: 512 * 1024,
-            },
+            ProcessBuilder::new(100, "test.exe")
+                .with_cpu_time(1000)
+                .with_kernel_time(500)
+                .with_working_set(1024 * 1024)
+                .with_private(256 * 1024, 512 * 1024)
+                .build(),
+            ProcessBuilder::new(200, "child.exe")
+                .with_parent(100)
+                .with_threads(2)
+                .with_handles(20)
+                .with_cpu_time(2000)
+                .with_kernel_time(1000)
+                .with_working_set(2 * 1024 * 1024)
+                .with_private(512 * 1024, 1024 * 1024)
+                .build(),
         ];
 
         store.update(processes);
@@ -367,18 +377,12 @@ mod tests {
         let mut store = ProcessStore::new();
         
         let processes = vec![
-            ProcessInfo {
-                pid: 100,
-                parent_pid: 0,
-                name: "test.exe".to_string(),
-                thread_count: 1,
-                handle_count: 10,
-                cpu_time_user: 1000,
-                cpu_time_kernel: 500,
-                memory_working_set: 1024 * 1024,
-                memory_pagefile: 512 * 1024,
-                memory_private: 256 * 1024,
-            },
+            ProcessBuilder::new(100, "test.exe")
+                .with_cpu_time(1000)
+                .with_kernel_time(500)
+                .with_working_set(1024 * 1024)
+                .with_private(256 * 1024, 512 * 1024)
+                .build(),
         ];
 
         store.update(processes);
@@ -393,30 +397,20 @@ mod tests {
         let mut store = ProcessStore::new();
         
         let processes = vec![
-            ProcessInfo {
-                pid: 100,
-                parent_pid: 0,
-                name: "chrome.exe".to_string(),
-                thread_count: 1,
-                handle_count: 10,
-                cpu_time_user: 1000,
-                cpu_time_kernel: 500,
-                memory_working_set: 1024 * 1024,
-                memory_pagefile: 512 * 1024,
-                memory_private: 256 * 1024,
-            },
-            ProcessInfo {
-                pid: 200,
-                parent_pid: 0,
-                name: "firefox.exe".to_string(),
-                thread_count: 2,
-                handle_count: 20,
-                cpu_time_user: 2000,
-                cpu_time_kernel: 1000,
-                memory_working_set: 2 * 1024 * 1024,
-                memory_pagefile: 1024 * 1024,
-                memory_private: 512 * 1024,
-            },
+            ProcessBuilder::new(100, "chrome.exe")
+                .with_cpu_time(1000)
+                .with_kernel_time(500)
+                .with_working_set(1024 * 1024)
+                .with_private(256 * 1024, 512 * 1024)
+                .build(),
+            ProcessBuilder::new(200, "firefox.exe")
+                .with_threads(2)
+                .with_handles(20)
+                .with_cpu_time(2000)
+                .with_kernel_time(1000)
+                .with_working_set(2 * 1024 * 1024)
+                .with_private(512 * 1024, 1024 * 1024)
+                .build(),
         ];
 
         store.update(processes);
@@ -18,3 +18,6 @@ pub mod core;
 pub mod ui;
 pub mod util;
 pub mod windows;
+
+#[cfg(test)]
+pub(crate) mod test_support;
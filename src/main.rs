@@ -2,6 +2,7 @@
 #[global_allocator]
 static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
+use task_manager::app::cli;
 use task_manager::ui::window::Window;
 use task_manager::util::profiling::StartupProfiler;
 
@@ -13,6 +14,12 @@ use task_manager::util::profiling::StartupProfiler;
 /// - UI layout: <50ms
 /// - Monitoring setup: <100ms (T314: lazy, deferred to first update)
 fn main() -> windows::core::Result<()> {
+    // Any arguments select headless CLI mode (list, kill, watch, export)
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        std::process::exit(cli::run(&args));
+    }
+
     let mut profiler = StartupProfiler::new();
 
     println!("Rust Task Manager v0.1.0");
@@ -0,0 +1,176 @@
+//! Shared fixtures for unit tests
+//!
+//! Builders for raw process records and monitor snapshots, so test modules
+//! only spell out the fields they care about, and a command handler that
+//! records what was dispatched to it.
+
+use crate::app::commands::{Action, CommandContext, CommandHandler};
+use crate::core::metrics::SystemMetrics;
+use crate::windows::monitor::nt_query::ProcessInfo;
+use crate::windows::monitor::ProcessSnapshot;
+use std::time::Instant;
+
+/// Builds a `nt_query::ProcessInfo`
+///
+/// Defaults: no parent, one thread, ten handles, no CPU time or memory,
+/// session 0, unknown creation time, no I/O.
+pub(crate) struct ProcessBuilder {
+    info: ProcessInfo,
+}
+
+impl ProcessBuilder {
+    pub(crate) fn new(pid: u32, name: &str) -> Self {
+        Self {
+            info: ProcessInfo {
+                pid,
+                parent_pid: 0,
+                name: name.to_string(),
+                thread_count: 1,
+                handle_count: 10,
+                cpu_time_user: 0,
+                cpu_time_kernel: 0,
+                memory_working_set: 0,
+                memory_pagefile: 0,
+                memory_private: 0,
+                session_id: 0,
+                create_time: 0,
+                io_read_bytes: 0,
+                io_write_bytes: 0,
+            },
+        }
+    }
+
+    pub(crate) fn with_parent(mut self, parent_pid: u32) -> Self {
+        self.info.parent_pid = parent_pid;
+        self
+    }
+
+    pub(crate) fn with_threads(mut self, count: u32) -> Self {
+        self.info.thread_count = count;
+        self
+    }
+
+    pub(crate) fn with_handles(mut self, count: u32) -> Self {
+        self.info.handle_count = count;
+        self
+    }
+
+    /// User-mode CPU time in 100ns units
+    pub(crate) fn with_cpu_time(mut self, user: u64) -> Self {
+        self.info.cpu_time_user = user;
+        self
+    }
+
+    /// Kernel-mode CPU time in 100ns units
+    pub(crate) fn with_kernel_time(mut self, kernel: u64) -> Self {
+        self.info.cpu_time_kernel = kernel;
+        self
+    }
+
+    /// Sets working set, page file usage and private bytes alike
+    pub(crate) fn with_memory(self, bytes: u64) -> Self {
+        self.with_working_set(bytes).with_private(bytes, bytes)
+    }
+
+    pub(crate) fn with_working_set(mut self, bytes: u64) -> Self {
+        self.info.memory_working_set = bytes;
+        self
+    }
+
+    /// Private bytes and page file usage
+    pub(crate) fn with_private(mut self, private: u64, pagefile: u64) -> Self {
+        self.info.memory_private = private;
+        self.info.memory_pagefile = pagefile;
+        self
+    }
+
+    /// Creation time in 100ns units
+    pub(crate) fn with_create_time(mut self, time: u64) -> Self {
+        self.info.create_time = time;
+        self
+    }
+
+    /// Cumulative I/O transfer counts in bytes
+    pub(crate) fn with_io(mut self, read: u64, write: u64) -> Self {
+        self.info.io_read_bytes = read;
+        self.info.io_write_bytes = write;
+        self
+    }
+
+    pub(crate) fn build(self) -> ProcessInfo {
+        self.info
+    }
+}
+
+/// Builds a `ProcessSnapshot`, taken now with empty system metrics by default
+pub(crate) struct SnapshotBuilder {
+    snapshot: ProcessSnapshot,
+}
+
+impl SnapshotBuilder {
+    pub(crate) fn new() -> Self {
+        Self {
+            snapshot: ProcessSnapshot {
+                timestamp: Instant::now(),
+                processes: Vec::new(),
+                system_metrics: SystemMetrics::new(),
+            },
+        }
+    }
+
+    pub(crate) fn with_timestamp(mut self, at: Instant) -> Self {
+        self.snapshot.timestamp = at;
+        self
+    }
+
+    pub(crate) fn with_processes(mut self, processes: Vec<ProcessInfo>) -> Self {
+        self.snapshot.processes = processes;
+        self
+    }
+
+    /// Total CPU usage and per-core usage
+    pub(crate) fn with_cpu(mut self, total: f32, cores: Vec<f32>) -> Self {
+        self.snapshot.system_metrics.cpu_total = total;
+        self.snapshot.system_metrics.cpu_cores = cores;
+        self
+    }
+
+    /// Physical memory; the load percentage is derived from the two
+    pub(crate) fn with_memory(mut self, total: u64, available: u64) -> Self {
+        let metrics = &mut self.snapshot.system_metrics;
+        metrics.memory_total = total;
+        metrics.memory_available = available;
+        metrics.memory_load_percent = ((total - available) * 100 / total) as u32;
+        self
+    }
+
+    pub(crate) fn build(self) -> ProcessSnapshot {
+        self.snapshot
+    }
+}
+
+/// `CommandHandler` recording executed actions
+pub(crate) struct CommandRecorder {
+    pub(crate) context: CommandContext,
+    pub(crate) executed: Vec<Action>,
+}
+
+impl CommandRecorder {
+    pub(crate) fn new(context: CommandContext) -> Self {
+        Self {
+            context,
+            executed: Vec::new(),
+        }
+    }
+}
+
+impl CommandHandler for CommandRecorder {
+    fn context(&self) -> CommandContext {
+        self.context
+    }
+
+    fn execute(&mut self, action: Action) -> Result<(), String> {
+        self.executed.push(action);
+        Ok(())
+    }
+}
@@ -6,11 +6,16 @@
 //! - Go to Details
 //! - Open File Location
 //! - UAC shield icons for privileged operations
+//!
+//! A chosen item runs its `app::commands` command through
+//! `ContextMenuItem::dispatch`, like shortcuts and the command palette.
 
 use windows::core::Result;
 use windows::Win32::Foundation::{HWND, POINT};
 use windows::Win32::UI::WindowsAndMessaging::*;
 
+use crate::app::commands::{CommandHandler, CommandRegistry};
+
 /// T204: Context menu items
 #[repr(u16)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -81,6 +86,36 @@ impl ContextMenuItem {
         }
     }
 
+    /// Id of the `app::commands` command this item runs
+    fn command_id(&self) -> &'static str {
+        match self {
+            Self::EndProcessGraceful => "process.end",
+            Self::EndProcessForce => "process.end_force",
+            Self::SetPriorityRealtime => "process.priority.realtime",
+            Self::SetPriorityHigh => "process.priority.high",
+            Self::SetPriorityAboveNormal => "process.priority.above_normal",
+            Self::SetPriorityNormal => "process.priority.normal",
+            Self::SetPriorityBelowNormal => "process.priority.below_normal",
+            Self::SetPriorityIdle => "process.priority.idle",
+            Self::GoToDetails => "process.details",
+            Self::OpenFileLocation => "process.open_location",
+            Self::SuspendProcess => "process.suspend",
+            Self::ResumeProcess => "process.resume",
+        }
+    }
+
+    /// Run this item's command on `handler`
+    ///
+    /// Fails if the command is disabled for the handler's current context
+    /// (e.g. nothing selected) or its execution fails.
+    pub fn dispatch(
+        &self,
+        registry: &CommandRegistry,
+        handler: &mut dyn CommandHandler,
+    ) -> std::result::Result<(), String> {
+        registry.dispatch(self.command_id(), handler)
+    }
+
     /// T205: Check if item requires elevated privileges
     #[allow(dead_code)]
     fn requires_elevation(&self) -> bool {
@@ -300,6 +335,9 @@ impl Drop for ProcessContextMenu {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::app::commands::{Action, CommandContext};
+    use crate::test_support::CommandRecorder;
+    use crate::windows::process::control::PriorityClass;
 
     #[test]
     fn test_menu_item_conversion() {
@@ -339,6 +377,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_menu_items_dispatch_commands() {
+        let registry = CommandRegistry::defaults();
+        let context = CommandContext {
+            selected: 1,
+            elevated: true,
+            window_visible: true,
+        };
+        let mut handler = CommandRecorder::new(context);
+        for id in 1001..=1031 {
+            if let Some(item) = ContextMenuItem::from_id(id) {
+                assert_eq!(item.dispatch(&registry, &mut handler), Ok(()), "{:?}", item);
+            }
+        }
+        assert_eq!(handler.executed.len(), 12);
+        assert_eq!(handler.executed[1], Action::EndProcessForce);
+        assert_eq!(
+            handler.executed[3],
+            Action::SetPriority(PriorityClass::High)
+        );
+
+        // Disabled commands are not run
+        handler.context.selected = 0;
+        assert!(ContextMenuItem::SuspendProcess
+            .dispatch(&registry, &mut handler)
+            .is_err());
+        assert_eq!(handler.executed.len(), 12);
+    }
+
     #[test]
     fn test_menu_item_roundtrip() {
         for id in 1001..=1031 {
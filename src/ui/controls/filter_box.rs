@@ -3,10 +3,16 @@
 //! Text input for real-time filtering with:
 //! - Debounced input (50ms)
 //! - Clear button
-//! - Filter presets dropdown
+//! - Named filter presets (built-in and user-defined)
 
 use std::time::{Duration, Instant};
 
+use serde::{Deserialize, Serialize};
+
+use crate::core::filter::{
+    parse_filter, parse_sort, ProcessFilter, ProcessSorter, SortColumn, SortDirection,
+};
+
 /// T200: Filter box state
 pub struct FilterBox {
     /// Current filter text
@@ -99,48 +105,137 @@ impl Default for FilterBox {
     }
 }
 
-/// T203: Filter presets
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum FilterPreset {
-    /// No filter applied
-    None,
-    /// Show only high CPU processes (CPU > 25%)
-    HighCpu,
-    /// Show only high memory processes (Memory > 100MB)
-    HighMemory,
-    /// Show only system-owned processes
-    SystemProcesses,
-    /// Show only user-owned processes
-    UserProcesses,
+/// T203: Named filter preset
+///
+/// Saves filter expressions in the `--filter` syntax (`cpu>25`,
+/// `session=0`), sort keys and visible columns under a name. Presets live in
+/// `AppConfig::filter_presets` in menu order; the built-ins from `defaults`
+/// are ordinary entries users can edit, reorder or delete. Preset N runs
+/// with the `filter_preset_N` shortcut (Alt+N by default).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FilterPreset {
+    pub name: String,
+    /// Filter expressions, all of which must match
+    #[serde(default)]
+    pub filters: Vec<String>,
+    /// Sort keys, primary first; empty keeps the current order
+    #[serde(default)]
+    pub sort: Vec<SortKey>,
+    /// Visible columns by `--sort` name (`cpu`, `mem`, ...); empty keeps the current columns
+    #[serde(default)]
+    pub columns: Vec<String>,
+}
+
+/// Sort column of a preset
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SortKey {
+    /// Column name as accepted by `--sort`
+    pub column: String,
+    #[serde(default)]
+    pub descending: bool,
 }
 
 impl FilterPreset {
-    /// Get preset label
-    pub fn label(&self) -> &'static str {
-        match self {
-            Self::None => "All Processes",
-            Self::HighCpu => "High CPU (>25%)",
-            Self::HighMemory => "High Memory (>100MB)",
-            Self::SystemProcesses => "System Processes",
-            Self::UserProcesses => "User Processes",
+    /// Preset showing everything
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            filters: Vec::new(),
+            sort: Vec::new(),
+            columns: Vec::new(),
         }
     }
 
-    /// Get all presets
-    pub fn all() -> &'static [FilterPreset] {
-        &[
-            Self::None,
-            Self::HighCpu,
-            Self::HighMemory,
-            Self::SystemProcesses,
-            Self::UserProcesses,
+    pub fn with_filter(mut self, expr: impl Into<String>) -> Self {
+        self.filters.push(expr.into());
+        self
+    }
+
+    /// Add a sort key after the existing ones
+    pub fn with_sort(mut self, column: impl Into<String>, descending: bool) -> Self {
+        self.sort.push(SortKey {
+            column: column.into(),
+            descending,
+        });
+        self
+    }
+
+    pub fn with_columns(mut self, columns: &[&str]) -> Self {
+        self.columns = columns.iter().map(|c| c.to_string()).collect();
+        self
+    }
+
+    /// Built-in presets, used until the user saves their own list
+    pub fn defaults() -> Vec<FilterPreset> {
+        vec![
+            Self::new("All Processes"),
+            Self::new("High CPU")
+                .with_filter("cpu>25")
+                .with_sort("cpu", true),
+            Self::new("High Memory")
+                .with_filter("mem>100MB")
+                .with_sort("mem", true),
+            Self::new("System Processes")
+                .with_filter("session=0")
+                .with_sort("name", false),
+            Self::new("User Processes")
+                .with_filter("session>0")
+                .with_sort("name", false),
         ]
     }
+
+    /// All filter expressions combined
+    pub fn filter(&self) -> Result<ProcessFilter, String> {
+        self.filters
+            .iter()
+            .try_fold(ProcessFilter::new(), |filter, expr| {
+                parse_filter(expr, filter)
+            })
+    }
+
+    /// Sorter over all sort keys; None if the preset keeps the current order
+    pub fn sorter(&self) -> Result<Option<ProcessSorter>, String> {
+        let mut keys = self.sort_keys()?.into_iter();
+        let Some((column, direction)) = keys.next() else {
+            return Ok(None);
+        };
+        let sorter = keys.fold(ProcessSorter::new(column, direction), |sorter, (c, d)| {
+            sorter.then_by(c, d)
+        });
+        Ok(Some(sorter))
+    }
+
+    pub fn sort_keys(&self) -> Result<Vec<(SortColumn, SortDirection)>, String> {
+        self.sort
+            .iter()
+            .map(|key| {
+                let column = column_by_name(&key.column)?;
+                let direction = if key.descending {
+                    SortDirection::Descending
+                } else {
+                    SortDirection::Ascending
+                };
+                Ok((column, direction))
+            })
+            .collect()
+    }
+
+    pub fn visible_columns(&self) -> Result<Vec<SortColumn>, String> {
+        self.columns
+            .iter()
+            .map(|name| column_by_name(name))
+            .collect()
+    }
+}
+
+fn column_by_name(name: &str) -> Result<SortColumn, String> {
+    parse_sort(name).ok_or_else(|| format!("Unknown column '{}'", name))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::filter::SessionFilter;
     use std::thread;
 
     #[test]
@@ -215,19 +310,62 @@ mod tests {
     }
 
     #[test]
-    fn test_filter_presets() {
-        let presets = FilterPreset::all();
+    fn test_default_presets() {
+        let presets = FilterPreset::defaults();
         assert_eq!(presets.len(), 5);
-        
-        for preset in presets {
-            assert!(!preset.label().is_empty());
+        for preset in &presets {
+            assert!(!preset.name.is_empty());
+            assert!(preset.filter().is_ok());
+            assert!(preset.sorter().is_ok());
         }
+
+        let high_cpu = presets[1].filter().unwrap();
+        assert_eq!(high_cpu.cpu_threshold, Some(25.0));
+        assert_eq!(
+            presets[2].filter().unwrap().memory_threshold,
+            Some(100 * 1024 * 1024)
+        );
+        assert_eq!(
+            presets[3].filter().unwrap().session,
+            Some(SessionFilter::Equals(0))
+        );
+        assert!(presets[0].sorter().unwrap().is_none());
     }
 
     #[test]
-    fn test_preset_labels() {
-        assert_eq!(FilterPreset::None.label(), "All Processes");
-        assert_eq!(FilterPreset::HighCpu.label(), "High CPU (>25%)");
-        assert_eq!(FilterPreset::HighMemory.label(), "High Memory (>100MB)");
+    fn test_preset_parsing_and_serde() {
+        let preset = FilterPreset::new("Browsers")
+            .with_filter("name=chrome")
+            .with_sort("mem", true)
+            .with_sort("pid", false)
+            .with_columns(&["pid", "mem"]);
+        assert_eq!(
+            preset.sort_keys().unwrap(),
+            vec![
+                (SortColumn::Memory, SortDirection::Descending),
+                (SortColumn::Pid, SortDirection::Ascending),
+            ]
+        );
+        assert_eq!(
+            preset.visible_columns().unwrap(),
+            vec![SortColumn::Pid, SortColumn::Memory]
+        );
+
+        let json = serde_json::to_string(&preset).unwrap();
+        assert_eq!(serde_json::from_str::<FilterPreset>(&json).unwrap(), preset);
+        let minimal: FilterPreset = serde_json::from_str(r#"{"name": "Everything"}"#).unwrap();
+        assert_eq!(minimal, FilterPreset::new("Everything"));
+
+        assert!(FilterPreset::new("x")
+            .with_filter("cpu<5")
+            .filter()
+            .is_err());
+        assert_eq!(
+            FilterPreset::new("x")
+                .with_sort("speed", true)
+                .sorter()
+                .err(),
+            Some("Unknown column 'speed'".to_string())
+        );
     }
 }
@@ -0,0 +1,294 @@
+//! Backend-agnostic graph geometry
+//!
+//! Converts graph data into plain points and rectangles:
+//! - Polyline and filled-area geometry for time series
+//! - Grid line and label placement for axes
+//! - Cell layout for heat maps
+//!
+//! Nothing here depends on Direct2D, so the same geometry feeds the D2D
+//! renderer, the SVG emitter (`svg`) and unit tests on any platform.
+
+/// Point in output coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Point {
+    pub fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+}
+
+/// Axis-aligned rectangle in output coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlotRect {
+    pub left: f32,
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+}
+
+impl PlotRect {
+    pub fn new(left: f32, top: f32, right: f32, bottom: f32) -> Self {
+        Self {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    /// Rectangle anchored at the origin
+    pub fn from_size(width: f32, height: f32) -> Self {
+        Self::new(0.0, 0.0, width, height)
+    }
+
+    pub fn width(&self) -> f32 {
+        self.right - self.left
+    }
+
+    pub fn height(&self) -> f32 {
+        self.bottom - self.top
+    }
+
+    /// Shrink by the given margins (left, top, right, bottom)
+    pub fn inset(&self, left: f32, top: f32, right: f32, bottom: f32) -> Self {
+        Self::new(
+            self.left + left,
+            self.top + top,
+            self.right - right,
+            self.bottom - bottom,
+        )
+    }
+}
+
+/// Map values to a polyline spanning `bounds`
+///
+/// The first value sits on the left edge and the last on the right edge.
+/// Values are clamped to `range`; a flat range draws along the vertical
+/// center.
+///
+/// # Arguments
+/// * `values` - Samples, oldest first
+/// * `range` - (min, max) of the value axis
+/// * `bounds` - Plot area
+pub fn line_points(values: &[f32], range: (f32, f32), bounds: &PlotRect) -> Vec<Point> {
+    let (min, max) = range;
+    let span = max - min;
+    let step = if values.len() > 1 {
+        bounds.width() / (values.len() - 1) as f32
+    } else {
+        0.0
+    };
+
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, &value)| {
+            let x = if values.len() > 1 {
+                bounds.left + step * i as f32
+            } else {
+                bounds.right
+            };
+            let fraction = if span > 0.0 {
+                ((value - min) / span).clamp(0.0, 1.0)
+            } else {
+                0.5
+            };
+            Point::new(x, bounds.bottom - fraction * bounds.height())
+        })
+        .collect()
+}
+
+/// Closed polygon filling the area between the line and the bottom edge
+///
+/// Returns the line points followed by the two baseline corners, or an empty
+/// list if there is nothing to fill.
+pub fn area_points(values: &[f32], range: (f32, f32), bounds: &PlotRect) -> Vec<Point> {
+    let mut points = line_points(values, range, bounds);
+    if let (Some(first), Some(last)) = (points.first().copied(), points.last().copied()) {
+        points.push(Point::new(last.x, bounds.bottom));
+        points.push(Point::new(first.x, bounds.bottom));
+    }
+    points
+}
+
+/// Text alignment relative to a label anchor point
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextAnchor {
+    Start,
+    Middle,
+    End,
+}
+
+/// Axis label placed in output coordinates
+#[derive(Debug, Clone, PartialEq)]
+pub struct AxisLabel {
+    pub position: Point,
+    pub anchor: TextAnchor,
+    pub text: String,
+}
+
+/// Grid lines and labels for one axis
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AxisLayout {
+    pub grid_lines: Vec<(Point, Point)>,
+    pub labels: Vec<AxisLabel>,
+}
+
+/// Gap between the plot edge and axis labels
+const LABEL_GAP: f32 = 6.0;
+
+/// Lay out grid lines and labels for an axis
+///
+/// # Arguments
+/// * `vertical` - True for the value (Y) axis, false for the time (X) axis
+/// * `labels` - (position, text) pairs, position 0.0 = start, 1.0 = end
+/// * `grid_visible` - Whether to emit grid lines at label positions
+/// * `bounds` - Plot area; labels are placed just outside it
+pub fn axis_layout(
+    vertical: bool,
+    labels: &[(f32, String)],
+    grid_visible: bool,
+    bounds: &PlotRect,
+) -> AxisLayout {
+    let mut layout = AxisLayout::default();
+
+    for (position, text) in labels {
+        if vertical {
+            let y = bounds.bottom - position * bounds.height();
+            if grid_visible {
+                layout
+                    .grid_lines
+                    .push((Point::new(bounds.left, y), Point::new(bounds.right, y)));
+            }
+            layout.labels.push(AxisLabel {
+                position: Point::new(bounds.left - LABEL_GAP, y),
+                anchor: TextAnchor::End,
+                text: text.clone(),
+            });
+        } else {
+            let x = bounds.left + position * bounds.width();
+            if grid_visible {
+                layout
+                    .grid_lines
+                    .push((Point::new(x, bounds.top), Point::new(x, bounds.bottom)));
+            }
+            let anchor = if *position <= 0.0 {
+                TextAnchor::Start
+            } else if *position >= 1.0 {
+                TextAnchor::End
+            } else {
+                TextAnchor::Middle
+            };
+            layout.labels.push(AxisLabel {
+                position: Point::new(x, bounds.bottom + LABEL_GAP * 2.0),
+                anchor,
+                text: text.clone(),
+            });
+        }
+    }
+
+    layout
+}
+
+/// Lay out `count` equally sized cells in a (rows, cols) grid
+///
+/// Cells are filled row by row with `padding` between cells and around the
+/// edge.
+pub fn grid_cells(
+    count: usize,
+    layout: (usize, usize),
+    padding: f32,
+    bounds: &PlotRect,
+) -> Vec<PlotRect> {
+    let (rows, cols) = layout;
+    if rows == 0 || cols == 0 {
+        return Vec::new();
+    }
+
+    let cell_width = (bounds.width() - padding * (cols + 1) as f32) / cols as f32;
+    let cell_height = (bounds.height() - padding * (rows + 1) as f32) / rows as f32;
+
+    (0..count.min(rows * cols))
+        .map(|index| {
+            let row = index / cols;
+            let col = index % cols;
+            let left = bounds.left + padding + (cell_width + padding) * col as f32;
+            let top = bounds.top + padding + (cell_height + padding) * row as f32;
+            PlotRect::new(left, top, left + cell_width, top + cell_height)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_points_span_bounds() {
+        let bounds = PlotRect::from_size(100.0, 50.0);
+        let points = line_points(&[0.0, 50.0, 100.0], (0.0, 100.0), &bounds);
+
+        assert_eq!(
+            points,
+            vec![
+                Point::new(0.0, 50.0),
+                Point::new(50.0, 25.0),
+                Point::new(100.0, 0.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_line_points_clamp_and_flat_range() {
+        let bounds = PlotRect::from_size(10.0, 10.0);
+        let clamped = line_points(&[-5.0, 500.0], (0.0, 100.0), &bounds);
+        assert_eq!(clamped[0].y, 10.0);
+        assert_eq!(clamped[1].y, 0.0);
+
+        let flat = line_points(&[7.0], (7.0, 7.0), &bounds);
+        assert_eq!(flat, vec![Point::new(10.0, 5.0)]);
+    }
+
+    #[test]
+    fn test_area_points_close_to_baseline() {
+        let bounds = PlotRect::from_size(10.0, 10.0);
+        let area = area_points(&[0.0, 100.0], (0.0, 100.0), &bounds);
+
+        assert_eq!(area.len(), 4);
+        assert_eq!(area[2], Point::new(10.0, 10.0));
+        assert_eq!(area[3], Point::new(0.0, 10.0));
+        assert!(area_points(&[], (0.0, 1.0), &bounds).is_empty());
+    }
+
+    #[test]
+    fn test_axis_layout() {
+        let bounds = PlotRect::new(40.0, 0.0, 140.0, 100.0);
+        let labels = vec![(0.0, "0%".to_string()), (1.0, "100%".to_string())];
+
+        let vertical = axis_layout(true, &labels, true, &bounds);
+        assert_eq!(
+            vertical.grid_lines[1],
+            (Point::new(40.0, 0.0), Point::new(140.0, 0.0))
+        );
+        assert_eq!(vertical.labels[0].position, Point::new(34.0, 100.0));
+        assert_eq!(vertical.labels[0].anchor, TextAnchor::End);
+
+        let horizontal = axis_layout(false, &labels, false, &bounds);
+        assert!(horizontal.grid_lines.is_empty());
+        assert_eq!(horizontal.labels[0].anchor, TextAnchor::Start);
+        assert_eq!(horizontal.labels[1].position.x, 140.0);
+    }
+
+    #[test]
+    fn test_grid_cells() {
+        let cells = grid_cells(3, (2, 2), 2.0, &PlotRect::from_size(42.0, 42.0));
+
+        assert_eq!(cells.len(), 3);
+        assert_eq!(cells[0], PlotRect::new(2.0, 2.0, 20.0, 20.0));
+        assert_eq!(cells[2], PlotRect::new(2.0, 22.0, 20.0, 40.0));
+    }
+}
@@ -1,13 +1,24 @@
 //! Graph rendering for performance visualization
+//!
+//! Graphs render through Direct2D on screen and through the backend-agnostic
+//! `geometry` + `svg` path for exports and tests.
 
 use windows::{
     core::*,
     Win32::Graphics::Direct2D::Common::*,
     Win32::Graphics::Direct2D::*,
 };
+use windows_numerics::Vector2;
+
+use super::geometry::{self, AxisLayout, PlotRect, Point, TextAnchor};
+use super::svg::{Rgba, SvgDocument};
+use crate::util::format::NumberFormat;
 
 const MAX_DATA_POINTS: usize = 3600;
 
+/// Marker color for anomalous samples
+const ANOMALY_COLOR: Rgba = Rgba::new(0.91, 0.07, 0.14, 1.0);
+
 // Workaround for missing D2D_POINT_2F - use the Common module's D2D_POINT_2F if available
 // Otherwise just skip rendering points for now
 
@@ -26,6 +37,8 @@ pub trait Graph {
 /// Circular buffer for graph data
 pub struct CircularBuffer {
     data: Vec<f32>,
+    /// Anomaly marks, parallel to `data`
+    flags: Vec<bool>,
     capacity: usize,
     head: usize,
     count: usize,
@@ -36,6 +49,7 @@ impl CircularBuffer {
     pub fn new(capacity: usize) -> Self {
         Self {
             data: vec![0.0; capacity],
+            flags: vec![false; capacity],
             capacity,
             head: 0,
             count: 0,
@@ -44,7 +58,13 @@ impl CircularBuffer {
 
     /// Adds a value to the buffer, overwriting the oldest value if full
     pub fn push(&mut self, value: f32) {
+        self.push_flagged(value, false);
+    }
+
+    /// Adds a value marked as anomalous (or not)
+    pub fn push_flagged(&mut self, value: f32, anomalous: bool) {
         self.data[self.head] = value;
+        self.flags[self.head] = anomalous;
         self.head = (self.head + 1) % self.capacity;
         if self.count < self.capacity {
             self.count += 1;
@@ -60,6 +80,20 @@ impl CircularBuffer {
         Some(self.data[actual_index])
     }
 
+    /// Returns true if the value at `index` was marked anomalous
+    pub fn is_flagged(&self, index: usize) -> bool {
+        if index >= self.count {
+            return false;
+        }
+        let actual_index = (self.head + self.capacity - self.count + index) % self.capacity;
+        self.flags[actual_index]
+    }
+
+    /// Indices (0 = oldest) of values marked anomalous
+    pub fn flagged_indices(&self) -> Vec<usize> {
+        (0..self.count).filter(|&i| self.is_flagged(i)).collect()
+    }
+
     /// Returns the number of values currently in the buffer
     pub fn len(&self) -> usize {
         self.count
@@ -91,6 +125,23 @@ impl CircularBuffer {
         self.head = 0;
         self.count = 0;
     }
+
+    /// Copies the values into a Vec, oldest first
+    pub fn to_vec(&self) -> Vec<f32> {
+        (0..self.count).filter_map(|i| self.get(i)).collect()
+    }
+}
+
+impl From<&D2D_RECT_F> for PlotRect {
+    fn from(rect: &D2D_RECT_F) -> Self {
+        PlotRect::new(rect.left, rect.top, rect.right, rect.bottom)
+    }
+}
+
+impl From<D2D1_COLOR_F> for Rgba {
+    fn from(color: D2D1_COLOR_F) -> Self {
+        Rgba::new(color.r, color.g, color.b, color.a)
+    }
 }
 
 /// Y-axis scaling mode
@@ -113,6 +164,7 @@ pub struct LineGraph {
     scale_mode: ScaleMode,
     color: D2D1_COLOR_F,
     line_width: f32,
+    fill_area: bool,
     title: String,
     zoom_level: f32,
     pan_offset: f32,
@@ -126,6 +178,7 @@ impl LineGraph {
             scale_mode: ScaleMode::Fixed { min: 0.0, max: 100.0 },
             color,
             line_width: 1.5,
+            fill_area: false,
             title: title.into(),
             zoom_level: 1.0,
             pan_offset: 0.0,
@@ -147,11 +200,60 @@ impl LineGraph {
         self.line_width = width;
     }
 
+    /// Sets whether the area under the line is filled
+    pub fn set_fill_area(&mut self, fill: bool) {
+        self.fill_area = fill;
+    }
+
     /// Returns the graph title
     pub fn title(&self) -> &str {
         &self.title
     }
 
+    /// Polyline through the buffered values, oldest at the left edge
+    pub fn line_geometry(&self, bounds: &PlotRect) -> Vec<Point> {
+        geometry::line_points(&self.buffer.to_vec(), self.get_range(), bounds)
+    }
+
+    /// Line points of samples marked anomalous
+    pub fn anomaly_geometry(&self, bounds: &PlotRect) -> Vec<Point> {
+        let points = self.line_geometry(bounds);
+        self.buffer
+            .flagged_indices()
+            .into_iter()
+            .filter_map(|i| points.get(i).copied())
+            .collect()
+    }
+
+    /// Adds a data point, marking it for highlighting if anomalous
+    pub fn add_flagged_point(&mut self, value: f32, anomalous: bool) {
+        self.buffer.push_flagged(value, anomalous);
+    }
+
+    /// Closed polygon under the line, down to the bottom edge
+    pub fn area_geometry(&self, bounds: &PlotRect) -> Vec<Point> {
+        geometry::area_points(&self.buffer.to_vec(), self.get_range(), bounds)
+    }
+
+    /// Draws the series into an SVG document
+    pub fn write_svg(&self, doc: &mut SvgDocument, bounds: &PlotRect) {
+        let color = Rgba::from(self.color);
+        if self.fill_area {
+            doc.polygon(&self.area_geometry(bounds), color.with_alpha(0.2));
+        }
+        doc.polyline(&self.line_geometry(bounds), color, self.line_width);
+        for point in self.anomaly_geometry(bounds) {
+            doc.circle(point, self.line_width * 2.0 + 1.0, ANOMALY_COLOR);
+        }
+    }
+
+    /// Renders the graph as a standalone SVG document
+    pub fn to_svg(&self, width: f32, height: f32) -> String {
+        let mut doc = SvgDocument::new(width, height);
+        self.write_svg(&mut doc, &PlotRect::from_size(width, height));
+        doc.finish()
+    }
+
     /// Zooms the graph by the specified delta (positive = zoom in, negative = zoom out)
     pub fn zoom(&mut self, delta: f32) {
         self.zoom_level = (self.zoom_level + delta).max(0.5).min(10.0);
@@ -175,7 +277,7 @@ impl Graph for LineGraph {
             return Ok(());
         }
 
-        let _brush = unsafe { context.CreateSolidColorBrush(&self.color, None)? };
+        let brush = unsafe { context.CreateSolidColorBrush(&self.color, None)? };
         
         // Get data range for coordinate transformation
         let (min_val, max_val) = self.get_range();
@@ -184,16 +286,25 @@ impl Graph for LineGraph {
             return Ok(());
         }
 
-        // Coordinate transformation: data space -> screen space
-        let _width = bounds.right - bounds.left;
-        let _height = bounds.bottom - bounds.top;
-        let _count = self.buffer.len();
-        
-        // Draw lines between consecutive points (simplified without path geometry)
-        // TODO: Use path geometry for proper line drawing once D2D types are resolved
-        
-        // For now, skip point rendering due to D2D_POINT_2F type unavailability
-        // Points would be rendered here using FillEllipse with proper point coordinates
+        // Same polyline as the SVG path, as one open figure
+        let points: Vec<Vector2> = self
+            .line_geometry(&PlotRect::from(bounds))
+            .iter()
+            .map(|point| Vector2::new(point.x, point.y))
+            .collect();
+        let Some((&start, rest)) = points.split_first() else {
+            return Ok(());
+        };
+
+        unsafe {
+            let path = context.GetFactory()?.CreatePathGeometry()?;
+            let sink = path.Open()?;
+            sink.BeginFigure(start, D2D1_FIGURE_BEGIN_HOLLOW);
+            sink.AddLines(rest);
+            sink.EndFigure(D2D1_FIGURE_END_OPEN);
+            sink.Close()?;
+            context.DrawGeometry(&path, &brush, self.line_width, None);
+        }
 
         Ok(())
     }
@@ -251,6 +362,52 @@ impl MultiLineGraph {
         // TODO: Implement series visibility toggling
     }
 
+    /// Draws all series and the legend into an SVG document
+    pub fn write_svg(&self, doc: &mut SvgDocument, bounds: &PlotRect) {
+        for series in &self.series {
+            series.write_svg(doc, bounds);
+        }
+
+        if !self.legend_visible || self.series.is_empty() {
+            return;
+        }
+
+        // Same placement as the Direct2D legend
+        let legend = PlotRect::new(
+            bounds.right - 150.0,
+            bounds.top + 10.0,
+            bounds.right - 10.0,
+            bounds.top + 10.0 + (self.series.len() as f32) * 25.0 + 10.0,
+        );
+        doc.rect(&legend, Rgba::new(0.1, 0.1, 0.1, 0.8), None);
+
+        let mut entry_y = legend.top + 10.0;
+        for series in &self.series {
+            let swatch = PlotRect::new(
+                legend.left + 10.0,
+                entry_y,
+                legend.left + 30.0,
+                entry_y + 15.0,
+            );
+            doc.rect(&swatch, Rgba::from(series.color), None);
+            doc.text(
+                Point::new(legend.left + 38.0, entry_y + 7.5),
+                series.title(),
+                11.0,
+                TextAnchor::Start,
+                Rgba::new(1.0, 1.0, 1.0, 1.0),
+            );
+            entry_y += 25.0;
+        }
+    }
+
+    /// Renders the graph as a standalone SVG document
+    pub fn to_svg(&self, width: f32, height: f32) -> String {
+        let mut doc = SvgDocument::new(width, height);
+        self.write_svg(&mut doc, &PlotRect::from_size(width, height));
+        doc.finish()
+    }
+
     /// Renders all series in the graph
     pub unsafe fn render(&self, context: &ID2D1DeviceContext, bounds: &D2D_RECT_F) -> Result<()> {
         // Render all series
@@ -324,6 +481,8 @@ pub struct GraphAxis {
     vertical: bool,
     labels: Vec<(f32, String)>,
     grid_visible: bool,
+    /// Formats the generated time and percentage labels
+    numbers: NumberFormat,
 }
 
 impl GraphAxis {
@@ -333,6 +492,7 @@ impl GraphAxis {
             vertical: true,
             labels: Vec::new(),
             grid_visible: true,
+            numbers: NumberFormat::new(),
         }
     }
 
@@ -342,6 +502,7 @@ impl GraphAxis {
             vertical: false,
             labels: Vec::new(),
             grid_visible: true,
+            numbers: NumberFormat::new(),
         }
     }
 
@@ -350,6 +511,11 @@ impl GraphAxis {
         self.grid_visible = visible;
     }
 
+    /// Format labels set from now on with `numbers`
+    pub fn set_number_format(&mut self, numbers: NumberFormat) {
+        self.numbers = numbers;
+    }
+
     /// Adds a label at the specified position (0.0 = start, 1.0 = end)
     pub fn add_label(&mut self, position: f32, text: impl Into<String>) {
         self.labels.push((position, text.into()));
@@ -367,7 +533,8 @@ impl GraphAxis {
         for i in 0..=4 {
             let seconds = (max_seconds as f32 * i as f32 / 4.0) as u32;
             let pos = 1.0 - (i as f32 / 4.0); // Reverse: 1.0 = now, 0.0 = oldest
-            self.add_label(pos, format!("{seconds}s"));
+            let label = self.numbers.duration(seconds as f64);
+            self.add_label(pos, label);
         }
     }
 
@@ -377,10 +544,25 @@ impl GraphAxis {
         for i in 0..=4 {
             let percent = i * 25;
             let pos = i as f32 / 4.0;
-            self.add_label(pos, format!("{percent}%"));
+            let label = format!("{}%", self.numbers.integer(percent));
+            self.add_label(pos, label);
         }
     }
 
+    /// Grid lines and label positions for the given plot area
+    pub fn layout(&self, bounds: &PlotRect) -> AxisLayout {
+        geometry::axis_layout(self.vertical, &self.labels, self.grid_visible, bounds)
+    }
+
+    /// Draws grid lines and labels into an SVG document
+    pub fn write_svg(&self, doc: &mut SvgDocument, bounds: &PlotRect) {
+        doc.axis(
+            &self.layout(bounds),
+            Rgba::new(0.5, 0.5, 0.5, 0.15),
+            Rgba::new(0.4, 0.4, 0.4, 1.0),
+        );
+    }
+
     /// Renders the axis with labels and grid lines
     pub unsafe fn render(&self, context: &ID2D1DeviceContext, bounds: &D2D_RECT_F) -> Result<()> {
         if !self.grid_visible {
@@ -534,4 +716,72 @@ mod tests {
         graph.add_data_point(50.0);
         assert_eq!(graph.point_count(), 1);
     }
+
+    #[test]
+    fn test_line_graph_svg() {
+        let color = D2D1_COLOR_F { r: 1.0, g: 0.0, b: 0.0, a: 1.0 };
+        let mut graph = LineGraph::new(color, "CPU");
+        graph.add_data_point(0.0);
+        graph.add_data_point(100.0);
+
+        let points = graph.line_geometry(&PlotRect::from_size(100.0, 50.0));
+        assert_eq!(points, vec![Point::new(0.0, 50.0), Point::new(100.0, 0.0)]);
+
+        graph.set_fill_area(true);
+        let svg = graph.to_svg(100.0, 50.0);
+        assert!(svg.contains("<polygon points=\"0.0,50.0 100.0,0.0 100.0,50.0 0.0,50.0\""));
+        assert!(svg.contains("<polyline points=\"0.0,50.0 100.0,0.0\" fill=\"none\""));
+        assert!(svg.contains("stroke=\"#ff0000\""));
+    }
+
+    #[test]
+    fn test_anomaly_flags_survive_wraparound() {
+        let mut buffer = CircularBuffer::new(3);
+        buffer.push(1.0);
+        buffer.push_flagged(9.0, true);
+        buffer.push(1.0);
+        buffer.push(1.0);
+        assert_eq!(buffer.flagged_indices(), vec![0]);
+
+        let color = D2D1_COLOR_F { r: 0.0, g: 0.0, b: 1.0, a: 1.0 };
+        let mut graph = LineGraph::new(color, "CPU");
+        graph.add_data_point(10.0);
+        graph.add_flagged_point(90.0, true);
+        let markers = graph.anomaly_geometry(&PlotRect::from_size(100.0, 100.0));
+        assert_eq!(markers, vec![Point::new(100.0, 10.0)]);
+        assert!(graph.to_svg(100.0, 100.0).contains("<circle cx=\"100.0\" cy=\"10.0\""));
+    }
+
+    #[test]
+    fn test_multi_line_graph_svg_legend() {
+        let mut graph = MultiLineGraph::new();
+        graph.add_series(LineGraph::new(D2D1_COLOR_F { r: 0.0, g: 0.0, b: 1.0, a: 1.0 }, "Read"));
+        graph.add_series(LineGraph::new(D2D1_COLOR_F { r: 1.0, g: 0.0, b: 1.0, a: 1.0 }, "Write"));
+        graph.add_data_point(0, 10.0);
+        graph.add_data_point(1, 20.0);
+
+        let svg = graph.to_svg(400.0, 200.0);
+        assert_eq!(svg.matches("<polyline").count(), 2);
+        assert!(svg.contains(">Read</text>"));
+        assert!(svg.contains(">Write</text>"));
+
+        graph.set_legend_visible(false);
+        assert!(!graph.to_svg(400.0, 200.0).contains("<text"));
+    }
+
+    #[test]
+    fn test_axis_layout_from_labels() {
+        let mut axis = GraphAxis::vertical();
+        axis.set_percent_labels();
+        let layout = axis.layout(&PlotRect::new(40.0, 0.0, 140.0, 100.0));
+
+        assert_eq!(layout.grid_lines.len(), 5);
+        assert_eq!(layout.labels[2].text, "50%");
+        assert_eq!(layout.labels[2].position.y, 50.0);
+
+        let mut time = GraphAxis::horizontal();
+        time.set_time_labels(3600);
+        let labels: Vec<&str> = time.labels.iter().map(|(_, text)| text.as_str()).collect();
+        assert_eq!(labels, ["0s", "15m", "30m", "45m", "1h"]);
+    }
 }
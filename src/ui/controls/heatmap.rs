@@ -8,6 +8,12 @@ use windows::{
     Win32::Graphics::Direct2D::*,
 };
 
+use super::geometry::{self, PlotRect};
+use super::svg::{Rgba, SvgDocument};
+
+/// Gap between cells (pixels)
+const CELL_PADDING: f32 = 2.0;
+
 /// CPU heat map widget displaying per-core usage
 pub struct HeatMap {
     /// Per-core CPU usage values (0.0-100.0)
@@ -72,30 +78,16 @@ impl HeatMap {
         self.show_labels = show;
     }
 
+    /// Cell rectangles for each core, in core order
+    pub fn cells(&self, bounds: &PlotRect) -> Vec<PlotRect> {
+        geometry::grid_cells(self.core_values.len(), self.layout, CELL_PADDING, bounds)
+    }
+
     /// Get the core index at the given point (for hit testing)
     pub fn hit_test(&self, bounds: &D2D_RECT_F, x: f32, y: f32) -> Option<usize> {
-        let (rows, cols) = self.layout;
-        let padding = 2.0;
-        let cell_width = (bounds.right - bounds.left - padding * (cols + 1) as f32) / cols as f32;
-        let cell_height = (bounds.bottom - bounds.top - padding * (rows + 1) as f32) / rows as f32;
-
-        let rel_x = x - bounds.left;
-        let rel_y = y - bounds.top;
-
-        for index in 0..self.core_values.len() {
-            let row = index / cols;
-            let col = index % cols;
-            let cell_x = padding + (cell_width + padding) * col as f32;
-            let cell_y = padding + (cell_height + padding) * row as f32;
-
-            if rel_x >= cell_x && rel_x <= cell_x + cell_width
-                && rel_y >= cell_y && rel_y <= cell_y + cell_height
-            {
-                return Some(index);
-            }
-        }
-
-        None
+        self.cells(&PlotRect::from(bounds)).iter().position(|cell| {
+            x >= cell.left && x <= cell.right && y >= cell.top && y <= cell.bottom
+        })
     }
 
     /// Map CPU usage to color gradient (blue -> cyan -> green -> yellow -> red)
@@ -117,24 +109,43 @@ impl HeatMap {
         D2D1_COLOR_F { r, g, b, a: 1.0 }
     }
 
-    /// Render the heat map
-    pub unsafe fn render(&self, context: &ID2D1DeviceContext, bounds: &D2D_RECT_F) -> Result<()> {
-        let (rows, cols) = self.layout;
-        let padding = 2.0;
-        let cell_width = (bounds.right - bounds.left - padding * (cols + 1) as f32) / cols as f32;
-        let cell_height = (bounds.bottom - bounds.top - padding * (rows + 1) as f32) / rows as f32;
+    /// Draws the cells (and core labels if enabled) into an SVG document
+    pub fn write_svg(&self, doc: &mut SvgDocument, bounds: &PlotRect) {
+        let border = Rgba::new(0.2, 0.2, 0.2, 1.0);
+        let cells = self.cells(bounds);
+        for (index, (cell, &value)) in cells.iter().zip(&self.core_values).enumerate() {
+            doc.rect(cell, Rgba::from(self.value_to_color(value)), Some((border, 1.0)));
+            if self.show_labels {
+                let center = geometry::Point::new(
+                    (cell.left + cell.right) / 2.0,
+                    (cell.top + cell.bottom) / 2.0,
+                );
+                doc.text(
+                    center,
+                    &format!("{} {:.0}%", index, value),
+                    10.0,
+                    geometry::TextAnchor::Middle,
+                    Rgba::new(0.0, 0.0, 0.0, 1.0),
+                );
+            }
+        }
+    }
 
-        for (index, &value) in self.core_values.iter().enumerate() {
-            let row = index / cols;
-            let col = index % cols;
-            let cell_x = bounds.left + padding + (cell_width + padding) * col as f32;
-            let cell_y = bounds.top + padding + (cell_height + padding) * row as f32;
+    /// Renders the heat map as a standalone SVG document
+    pub fn to_svg(&self, width: f32, height: f32) -> String {
+        let mut doc = SvgDocument::new(width, height);
+        self.write_svg(&mut doc, &PlotRect::from_size(width, height));
+        doc.finish()
+    }
 
+    /// Render the heat map
+    pub unsafe fn render(&self, context: &ID2D1DeviceContext, bounds: &D2D_RECT_F) -> Result<()> {
+        for (cell, &value) in self.cells(&PlotRect::from(bounds)).iter().zip(&self.core_values) {
             let cell_rect = D2D_RECT_F {
-                left: cell_x,
-                top: cell_y,
-                right: cell_x + cell_width,
-                bottom: cell_y + cell_height,
+                left: cell.left,
+                top: cell.top,
+                right: cell.right,
+                bottom: cell.bottom,
             };
 
             let color = self.value_to_color(value);
@@ -165,4 +176,27 @@ mod tests {
         assert_eq!(HeatMap::calculate_layout(4), (2, 2));
         assert_eq!(HeatMap::calculate_layout(16), (4, 4));
     }
+
+    #[test]
+    fn test_hit_test_matches_cells() {
+        let heatmap = HeatMap::new(4);
+        let bounds = D2D_RECT_F { left: 10.0, top: 10.0, right: 52.0, bottom: 52.0 };
+
+        assert_eq!(heatmap.hit_test(&bounds, 15.0, 15.0), Some(0));
+        assert_eq!(heatmap.hit_test(&bounds, 45.0, 45.0), Some(3));
+        assert_eq!(heatmap.hit_test(&bounds, 11.0, 11.0), None);
+    }
+
+    #[test]
+    fn test_heatmap_svg() {
+        let mut heatmap = HeatMap::new(2);
+        heatmap.set_interpolation(1.0);
+        heatmap.update(&[0.0, 100.0]);
+
+        let svg = heatmap.to_svg(100.0, 50.0);
+        assert_eq!(svg.matches("<rect").count(), 2);
+        assert!(svg.contains("fill=\"#0000ff\""));
+        assert!(svg.contains("fill=\"#ff0000\""));
+        assert!(svg.contains(">1 100%</text>"));
+    }
 }
@@ -3,8 +3,10 @@
 pub mod button;
 pub mod context_menu;
 pub mod filter_box;
+pub mod geometry;
 pub mod graph;
 pub mod heatmap;
+pub mod svg;
 pub mod table;
 pub mod tabview;
 pub mod time_range_selector;
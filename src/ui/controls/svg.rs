@@ -0,0 +1,293 @@
+//! SVG emitter for graph geometry
+//!
+//! Writes the output of `geometry` as a standalone SVG document so graphs
+//! can be embedded in exported reports and compared as text in tests.
+//! Coordinates are written with one decimal place, so identical input always
+//! produces identical markup.
+
+use std::fmt::Write as _;
+
+use super::geometry::{AxisLayout, PlotRect, Point, TextAnchor};
+
+/// Color with components in 0.0-1.0 (same layout as `D2D1_COLOR_F`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rgba {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl Rgba {
+    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// Same color with a different alpha
+    pub fn with_alpha(self, a: f32) -> Self {
+        Self { a, ..self }
+    }
+
+    /// CSS hex notation without alpha (`#rrggbb`)
+    pub fn hex(&self) -> String {
+        let channel = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+        format!(
+            "#{:02x}{:02x}{:02x}",
+            channel(self.r),
+            channel(self.g),
+            channel(self.b)
+        )
+    }
+}
+
+/// SVG document builder
+pub struct SvgDocument {
+    width: f32,
+    height: f32,
+    body: String,
+}
+
+impl SvgDocument {
+    /// Creates an empty document with the given size in pixels
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            width,
+            height,
+            body: String::new(),
+        }
+    }
+
+    /// Filled rectangle with an optional (color, width) stroke
+    pub fn rect(&mut self, rect: &PlotRect, fill: Rgba, stroke: Option<(Rgba, f32)>) {
+        let _ = write!(
+            self.body,
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\"{}",
+            rect.left,
+            rect.top,
+            rect.width(),
+            rect.height(),
+            fill_attrs(fill)
+        );
+        if let Some((color, width)) = stroke {
+            self.body.push_str(&stroke_attrs(color, width));
+        }
+        self.body.push_str("/>\n");
+    }
+
+    /// Straight line segment
+    pub fn line(&mut self, from: Point, to: Point, stroke: Rgba, width: f32) {
+        let _ = writeln!(
+            self.body,
+            "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\"{}/>",
+            from.x,
+            from.y,
+            to.x,
+            to.y,
+            stroke_attrs(stroke, width)
+        );
+    }
+
+    /// Open polyline (skipped when empty)
+    pub fn polyline(&mut self, points: &[Point], stroke: Rgba, width: f32) {
+        if points.is_empty() {
+            return;
+        }
+        let _ = writeln!(
+            self.body,
+            "<polyline points=\"{}\" fill=\"none\"{} stroke-linejoin=\"round\"/>",
+            format_points(points),
+            stroke_attrs(stroke, width)
+        );
+    }
+
+    /// Closed filled polygon (skipped when empty)
+    pub fn polygon(&mut self, points: &[Point], fill: Rgba) {
+        if points.is_empty() {
+            return;
+        }
+        let _ = writeln!(
+            self.body,
+            "<polygon points=\"{}\"{}/>",
+            format_points(points),
+            fill_attrs(fill)
+        );
+    }
+
+    /// Filled circle
+    pub fn circle(&mut self, center: Point, radius: f32, fill: Rgba) {
+        let _ = writeln!(
+            self.body,
+            "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{:.1}\"{}/>",
+            center.x,
+            center.y,
+            radius,
+            fill_attrs(fill)
+        );
+    }
+
+    /// Text label anchored at `position`
+    pub fn text(&mut self, position: Point, text: &str, size: f32, anchor: TextAnchor, fill: Rgba) {
+        let anchor = match anchor {
+            TextAnchor::Start => "start",
+            TextAnchor::Middle => "middle",
+            TextAnchor::End => "end",
+        };
+        let _ = writeln!(
+            self.body,
+            "<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"{:.1}\" text-anchor=\"{}\" dominant-baseline=\"middle\"{}>{}</text>",
+            position.x,
+            position.y,
+            size,
+            anchor,
+            fill_attrs(fill),
+            escape_xml(text)
+        );
+    }
+
+    /// Grid lines and labels from an axis layout
+    pub fn axis(&mut self, layout: &AxisLayout, grid: Rgba, label: Rgba) {
+        for (from, to) in &layout.grid_lines {
+            self.line(*from, *to, grid, 0.5);
+        }
+        for axis_label in &layout.labels {
+            self.text(
+                axis_label.position,
+                &axis_label.text,
+                10.0,
+                axis_label.anchor,
+                label,
+            );
+        }
+    }
+
+    /// Completes the document and returns the markup
+    pub fn finish(self) -> String {
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w:.0}\" height=\"{h:.0}\" viewBox=\"0 0 {w:.0} {h:.0}\">\n{body}</svg>\n",
+            w = self.width,
+            h = self.height,
+            body = self.body
+        )
+    }
+}
+
+fn format_points(points: &[Point]) -> String {
+    let mut out = String::with_capacity(points.len() * 12);
+    for (i, point) in points.iter().enumerate() {
+        if i > 0 {
+            out.push(' ');
+        }
+        let _ = write!(out, "{:.1},{:.1}", point.x, point.y);
+    }
+    out
+}
+
+fn fill_attrs(color: Rgba) -> String {
+    if color.a >= 1.0 {
+        format!(" fill=\"{}\"", color.hex())
+    } else {
+        format!(
+            " fill=\"{}\" fill-opacity=\"{:.2}\"",
+            color.hex(),
+            color.a.max(0.0)
+        )
+    }
+}
+
+fn stroke_attrs(color: Rgba, width: f32) -> String {
+    let mut attrs = format!(" stroke=\"{}\" stroke-width=\"{:.1}\"", color.hex(), width);
+    if color.a < 1.0 {
+        let _ = write!(attrs, " stroke-opacity=\"{:.2}\"", color.a.max(0.0));
+    }
+    attrs
+}
+
+/// Escape text for XML element content
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::controls::geometry::{axis_layout, line_points};
+
+    #[test]
+    fn test_rgba_hex() {
+        assert_eq!(Rgba::new(1.0, 0.0, 0.5, 1.0).hex(), "#ff0080");
+        assert_eq!(Rgba::new(2.0, -1.0, 0.0, 1.0).hex(), "#ff0000");
+    }
+
+    #[test]
+    fn test_polyline_snapshot() {
+        let mut doc = SvgDocument::new(100.0, 50.0);
+        let points = line_points(
+            &[0.0, 100.0],
+            (0.0, 100.0),
+            &PlotRect::from_size(100.0, 50.0),
+        );
+        doc.polyline(&points, Rgba::new(0.0, 0.0, 1.0, 1.0), 1.5);
+
+        assert_eq!(
+            doc.finish(),
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"100\" height=\"50\" viewBox=\"0 0 100 50\">\n\
+             <polyline points=\"0.0,50.0 100.0,0.0\" fill=\"none\" stroke=\"#0000ff\" stroke-width=\"1.5\" stroke-linejoin=\"round\"/>\n\
+             </svg>\n"
+        );
+    }
+
+    #[test]
+    fn test_translucent_fill_and_escaping() {
+        let mut doc = SvgDocument::new(10.0, 10.0);
+        doc.rect(
+            &PlotRect::from_size(10.0, 10.0),
+            Rgba::new(0.0, 0.0, 0.0, 0.25),
+            None,
+        );
+        doc.text(
+            Point::new(1.0, 1.0),
+            "<a & b>",
+            10.0,
+            TextAnchor::Start,
+            Rgba::new(0.0, 0.0, 0.0, 1.0),
+        );
+        let svg = doc.finish();
+
+        assert!(svg.contains("fill-opacity=\"0.25\""));
+        assert!(svg.contains("&lt;a &amp; b&gt;"));
+    }
+
+    #[test]
+    fn test_axis_emits_lines_and_labels() {
+        let bounds = PlotRect::new(40.0, 0.0, 140.0, 100.0);
+        let layout = axis_layout(true, &[(0.5, "50%".to_string())], true, &bounds);
+        let mut doc = SvgDocument::new(150.0, 110.0);
+        doc.axis(
+            &layout,
+            Rgba::new(0.5, 0.5, 0.5, 1.0),
+            Rgba::new(0.0, 0.0, 0.0, 1.0),
+        );
+        let svg = doc.finish();
+
+        assert!(svg.contains("<line x1=\"40.0\" y1=\"50.0\" x2=\"140.0\" y2=\"50.0\""));
+        assert!(svg.contains(">50%</text>"));
+    }
+
+    #[test]
+    fn test_empty_shapes_skipped() {
+        let mut doc = SvgDocument::new(10.0, 10.0);
+        doc.polyline(&[], Rgba::new(0.0, 0.0, 0.0, 1.0), 1.0);
+        doc.polygon(&[], Rgba::new(0.0, 0.0, 0.0, 1.0));
+        assert!(!doc.finish().contains("points"));
+    }
+}
@@ -6,8 +6,14 @@
 //! - Row selection (mouse + keyboard)
 //! - Multi-selection (Ctrl+Click, Shift+Click)
 //! - Alternating row colors
+//! - Column layout (`ColumnLayout`): order, width, visibility, pinning,
+//!   alignment and byte units of every column, persisted as one unit in
+//!   `AppConfig::columns`
 
+use crate::core::expression::ColumnValue;
 use crate::core::filter::{SortColumn, SortDirection};
+use crate::util::format::NumberFormat;
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
 /// Simple process info for table display
@@ -27,6 +33,30 @@ pub struct ProcessInfo {
     pub memory_working_set: u64,
     /// Number of handles
     pub handle_count: u32,
+    /// Private bytes growth per hour, if flagged by the leak detector
+    pub leak_rate: Option<f64>,
+    /// Computed column values, in definition order
+    pub custom: Vec<ColumnValue>,
+}
+
+/// Horizontal placement of cell text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColumnAlignment {
+    Left,
+    Right,
+}
+
+/// How byte values (Memory, Leak Rate) are shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum UnitMode {
+    /// Scaled to B, KB, MB or GB (or the configured `UnitSystem`)
+    #[default]
+    Auto,
+    /// Raw byte count
+    Bytes,
+    /// Whole kilobytes, like the classic Task Manager
+    Kilobytes,
+    Megabytes,
 }
 
 /// T185: Table column definition
@@ -34,57 +64,252 @@ pub struct ProcessInfo {
 pub struct TableColumn {
     /// Column identifier for sorting
     pub id: SortColumn,
-    /// Column width in pixels
+    /// Stable name used in `ColumnLayout` (`cpu`, `leak_rate`)
+    pub key: &'static str,
+    /// Default column width in pixels
     pub width: f32,
     /// Column header label
     pub label: &'static str,
+    /// Default cell alignment
+    pub alignment: ColumnAlignment,
 }
 
 impl TableColumn {
     /// Name column definition
     pub const NAME: Self = Self {
         id: SortColumn::Name,
+        key: "name",
         width: 200.0,
         label: "Name",
+        alignment: ColumnAlignment::Left,
     };
 
     /// PID column definition
     pub const PID: Self = Self {
         id: SortColumn::Pid,
+        key: "pid",
         width: 80.0,
         label: "PID",
+        alignment: ColumnAlignment::Right,
     };
 
     /// CPU column definition
     pub const CPU: Self = Self {
         id: SortColumn::Cpu,
+        key: "cpu",
         width: 80.0,
         label: "CPU %",
+        alignment: ColumnAlignment::Right,
     };
 
     /// Memory column definition
     pub const MEMORY: Self = Self {
         id: SortColumn::Memory,
+        key: "memory",
         width: 120.0,
         label: "Memory",
+        alignment: ColumnAlignment::Right,
     };
 
     /// Handles column definition
     pub const HANDLES: Self = Self {
         id: SortColumn::Handles,
+        key: "handles",
         width: 80.0,
         label: "Handles",
+        alignment: ColumnAlignment::Right,
+    };
+
+    /// Leak rate column definition (not shown by default)
+    pub const LEAK_RATE: Self = Self {
+        id: SortColumn::LeakRate,
+        key: "leak_rate",
+        width: 110.0,
+        label: "Leak Rate",
+        alignment: ColumnAlignment::Right,
     };
+
+    /// T185: Every column the table can show, in default order
+    pub const ALL: [TableColumn; 6] = [
+        TableColumn::NAME,
+        TableColumn::PID,
+        TableColumn::CPU,
+        TableColumn::MEMORY,
+        TableColumn::HANDLES,
+        TableColumn::LEAK_RATE,
+    ];
+
+    /// Column by key or header label, ignoring case (`cpu`, `CPU`, `Leak Rate`)
+    pub fn from_name(name: &str) -> Option<TableColumn> {
+        let name = name.trim();
+        Self::ALL
+            .into_iter()
+            .find(|c| c.key.eq_ignore_ascii_case(name) || c.label.eq_ignore_ascii_case(name))
+    }
+
+    pub fn for_id(id: SortColumn) -> TableColumn {
+        Self::ALL
+            .into_iter()
+            .find(|c| c.id == id)
+            .unwrap_or(TableColumn::NAME)
+    }
+}
+
+/// Persisted settings of one column
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnSettings {
+    /// `TableColumn::key` of the column
+    pub key: String,
+    /// Width in pixels
+    pub width: i32,
+    pub visible: bool,
+    /// Kept at the left edge when the table scrolls horizontally
+    #[serde(default)]
+    pub pinned: bool,
+    /// Cell alignment; the column's default when unset
+    #[serde(default)]
+    pub alignment: Option<ColumnAlignment>,
+    /// Units of byte values; ignored by other columns
+    #[serde(default)]
+    pub units: UnitMode,
+}
+
+impl ColumnSettings {
+    /// Default settings of `column`
+    pub fn new(column: &TableColumn) -> Self {
+        Self {
+            key: column.key.to_string(),
+            width: column.width as i32,
+            visible: column.id != SortColumn::LeakRate,
+            pinned: column.id == SortColumn::Name,
+            alignment: None,
+            units: UnitMode::Auto,
+        }
+    }
+}
+
+/// Order and settings of every table column
+///
+/// Stored order is display order, except that pinned columns are drawn
+/// before all others. Columns are addressed by key or header label.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ColumnLayout {
+    pub entries: Vec<ColumnSettings>,
+}
+
+impl Default for ColumnLayout {
+    fn default() -> Self {
+        Self {
+            entries: TableColumn::ALL.iter().map(ColumnSettings::new).collect(),
+        }
+    }
 }
 
-/// T185: Default table columns
-pub const DEFAULT_COLUMNS: &[TableColumn] = &[
-    TableColumn::NAME,
-    TableColumn::PID,
-    TableColumn::CPU,
-    TableColumn::MEMORY,
-    TableColumn::HANDLES,
-];
+impl ColumnLayout {
+    pub fn get(&self, name: &str) -> Option<&ColumnSettings> {
+        let column = TableColumn::from_name(name)?;
+        self.entries.iter().find(|entry| entry.key == column.key)
+    }
+
+    fn entry_mut(&mut self, name: &str) -> Result<&mut ColumnSettings, String> {
+        let unknown = || format!("Unknown column '{}'", name);
+        let column = TableColumn::from_name(name).ok_or_else(unknown)?;
+        self.entries
+            .iter_mut()
+            .find(|entry| entry.key == column.key)
+            .ok_or_else(unknown)
+    }
+
+    pub fn set_width(&mut self, name: &str, width: i32) -> Result<(), String> {
+        self.entry_mut(name)?.width = width;
+        Ok(())
+    }
+
+    pub fn set_visible(&mut self, name: &str, visible: bool) -> Result<(), String> {
+        self.entry_mut(name)?.visible = visible;
+        Ok(())
+    }
+
+    pub fn set_pinned(&mut self, name: &str, pinned: bool) -> Result<(), String> {
+        self.entry_mut(name)?.pinned = pinned;
+        Ok(())
+    }
+
+    pub fn set_alignment(&mut self, name: &str, alignment: ColumnAlignment) -> Result<(), String> {
+        self.entry_mut(name)?.alignment = Some(alignment);
+        Ok(())
+    }
+
+    pub fn set_units(&mut self, name: &str, units: UnitMode) -> Result<(), String> {
+        self.entry_mut(name)?.units = units;
+        Ok(())
+    }
+
+    /// Move column `name` to position `index` (clamped to the end)
+    pub fn move_column(&mut self, name: &str, index: usize) -> Result<(), String> {
+        let key = self.entry_mut(name)?.key.clone();
+        let from = self
+            .entries
+            .iter()
+            .position(|entry| entry.key == key)
+            .unwrap_or(0);
+        let entry = self.entries.remove(from);
+        let index = index.min(self.entries.len());
+        self.entries.insert(index, entry);
+        Ok(())
+    }
+
+    /// Alignment of `column` in this layout
+    pub fn alignment(&self, column: &TableColumn) -> ColumnAlignment {
+        self.get(column.key)
+            .and_then(|entry| entry.alignment)
+            .unwrap_or(column.alignment)
+    }
+
+    pub fn units(&self, column: &TableColumn) -> UnitMode {
+        self.get(column.key)
+            .map_or(UnitMode::Auto, |entry| entry.units)
+    }
+
+    /// Visible columns in display order: pinned first, then the rest
+    pub fn displayed(&self) -> Vec<(TableColumn, &ColumnSettings)> {
+        let visible: Vec<(TableColumn, &ColumnSettings)> = self
+            .entries
+            .iter()
+            .filter(|entry| entry.visible)
+            .filter_map(|entry| Some((TableColumn::from_name(&entry.key)?, entry)))
+            .collect();
+        let (mut pinned, unpinned): (Vec<_>, Vec<_>) =
+            visible.into_iter().partition(|(_, entry)| entry.pinned);
+        pinned.extend(unpinned);
+        pinned
+    }
+
+    /// Drop unknown and repeated keys, append missing columns with defaults
+    ///
+    /// Returns the dropped keys. Layouts saved by older builds gain the
+    /// columns added since.
+    pub fn repair(&mut self) -> Vec<String> {
+        let mut seen = HashSet::new();
+        let mut dropped = Vec::new();
+        self.entries.retain(|entry| {
+            let known = TableColumn::ALL.iter().any(|c| c.key == entry.key);
+            if known && seen.insert(entry.key.clone()) {
+                true
+            } else {
+                dropped.push(entry.key.clone());
+                false
+            }
+        });
+        for column in &TableColumn::ALL {
+            if !seen.contains(column.key) {
+                self.entries.push(ColumnSettings::new(column));
+            }
+        }
+        dropped
+    }
+}
 
 /// T191: Table selection state
 #[derive(Debug, Default)]
@@ -162,8 +387,10 @@ impl TableSelection {
 
 /// T186-T190: Virtualized process table
 pub struct ProcessTable {
-    /// Table columns
-    columns: Vec<TableColumn>,
+    /// Column order, widths and visibility
+    layout: ColumnLayout,
+    /// Unpinned columns scrolled out to the left
+    column_scroll: usize,
     /// Current sort state
     sort_column: SortColumn,
     sort_direction: SortDirection,
@@ -175,19 +402,23 @@ pub struct ProcessTable {
     row_height: f32,
     /// Header height in pixels
     header_height: f32,
+    /// Separators and byte units for cell text
+    numbers: NumberFormat,
 }
 
 impl ProcessTable {
     /// Create new table
     pub fn new() -> Self {
         Self {
-            columns: DEFAULT_COLUMNS.to_vec(),
+            layout: ColumnLayout::default(),
+            column_scroll: 0,
             sort_column: SortColumn::Cpu,
             sort_direction: SortDirection::Descending,
             selection: TableSelection::new(),
             scroll_offset: 0,
             row_height: 24.0,
             header_height: 30.0,
+            numbers: NumberFormat::new(),
         }
     }
 
@@ -206,6 +437,92 @@ impl ProcessTable {
         }
     }
 
+    /// Use `layout` (e.g. `AppConfig::columns`), repairing unknown columns
+    pub fn set_layout(&mut self, mut layout: ColumnLayout) {
+        layout.repair();
+        self.layout = layout;
+        self.scroll_columns(0);
+    }
+
+    pub fn layout(&self) -> &ColumnLayout {
+        &self.layout
+    }
+
+    /// Format cells with `numbers` (`DisplayConfig::number_format`)
+    pub fn set_number_format(&mut self, numbers: NumberFormat) {
+        self.numbers = numbers;
+    }
+
+    /// Visible columns as drawn: pinned columns, then the unpinned ones
+    /// after the horizontal scroll position
+    pub fn displayed_columns(&self) -> Vec<(TableColumn, &ColumnSettings)> {
+        let mut unpinned = 0;
+        self.layout
+            .displayed()
+            .into_iter()
+            .filter(|(_, entry)| {
+                if entry.pinned {
+                    return true;
+                }
+                unpinned += 1;
+                unpinned > self.column_scroll
+            })
+            .collect()
+    }
+
+    /// Scroll unpinned columns horizontally by `delta` columns
+    ///
+    /// At least one unpinned column stays in view.
+    pub fn scroll_columns(&mut self, delta: isize) {
+        let unpinned = self
+            .layout
+            .displayed()
+            .iter()
+            .filter(|(_, entry)| !entry.pinned)
+            .count();
+        let scroll = self.column_scroll as isize + delta;
+        self.column_scroll = scroll.clamp(0, unpinned.saturating_sub(1) as isize) as usize;
+    }
+
+    pub fn column_scroll(&self) -> usize {
+        self.column_scroll
+    }
+
+    /// Header dragged and dropped at `x`: move `column` to the drop target's place
+    ///
+    /// Returns false if `x` is not over another column.
+    pub fn on_header_drop(&mut self, column: SortColumn, x: f32) -> bool {
+        let target = match self.column_at_point(x) {
+            Some(target) if target != column => TableColumn::for_id(target),
+            _ => return false,
+        };
+        let index = self
+            .layout
+            .entries
+            .iter()
+            .position(|entry| entry.key == target.key);
+        match index {
+            Some(index) => self
+                .layout
+                .move_column(TableColumn::for_id(column).key, index)
+                .is_ok(),
+            None => false,
+        }
+    }
+
+    /// Resize `column` to `width` pixels
+    pub fn set_column_width(&mut self, column: SortColumn, width: i32) {
+        let _ = self
+            .layout
+            .set_width(TableColumn::for_id(column).key, width);
+    }
+
+    /// Sort by `column` in `direction` (e.g. from a filter preset)
+    pub fn set_sort(&mut self, column: SortColumn, direction: SortDirection) {
+        self.sort_column = column;
+        self.sort_direction = direction;
+    }
+
     /// Get current sort state
     pub fn sort_state(&self) -> (SortColumn, SortDirection) {
         (self.sort_column, self.sort_direction)
@@ -290,14 +607,27 @@ impl ProcessTable {
         (start_row, end_row, visible_rows)
     }
 
-    /// Format cell text for display
+    /// Format cell text for display, in the column's units
     pub fn format_cell_text(&self, column: SortColumn, process: &ProcessInfo) -> String {
+        let units = self.layout.units(&TableColumn::for_id(column));
+        let numbers = &self.numbers;
         match column {
             SortColumn::Name => process.name.clone(),
             SortColumn::Pid => process.pid.to_string(),
-            SortColumn::Cpu => format!("{:.1}", process.cpu_usage),
-            SortColumn::Memory => format_bytes(process.memory_private),
-            SortColumn::Handles => process.handle_count.to_string(),
+            SortColumn::Cpu => numbers.decimal(process.cpu_usage, 1),
+            SortColumn::Memory => format_units(process.memory_private, units, numbers),
+            SortColumn::Handles => numbers.integer(process.handle_count as i64),
+            SortColumn::LeakRate => match process.leak_rate {
+                Some(rate) => {
+                    format!("+{}/h", format_units(rate.max(0.0) as u64, units, numbers))
+                }
+                None => String::new(),
+            },
+            SortColumn::Custom(index) => process
+                .custom
+                .get(index)
+                .map(|value| value.display(numbers))
+                .unwrap_or_default(),
         }
     }
 
@@ -327,30 +657,36 @@ impl ProcessTable {
     /// Calculate which column is at the given X coordinate
     pub fn column_at_point(&self, x: f32) -> Option<SortColumn> {
         let mut col_x = 0.0;
-        for column in &self.columns {
-            if x >= col_x && x < col_x + column.width {
+        for (column, entry) in self.displayed_columns() {
+            let width = entry.width as f32;
+            if x >= col_x && x < col_x + width {
                 return Some(column.id);
             }
-            col_x += column.width;
+            col_x += width;
         }
         None
     }
 }
 
-/// Format bytes as human-readable string
-fn format_bytes(bytes: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = KB * 1024;
-    const GB: u64 = MB * 1024;
-
-    if bytes >= GB {
-        format!("{:.1} GB", bytes as f64 / GB as f64)
-    } else if bytes >= MB {
-        format!("{:.1} MB", bytes as f64 / MB as f64)
-    } else if bytes >= KB {
-        format!("{:.1} KB", bytes as f64 / KB as f64)
-    } else {
-        format!("{} B", bytes)
+/// `text` padded or cut to `width` characters
+pub fn fit(text: &str, width: usize, alignment: ColumnAlignment) -> String {
+    let text: String = text.chars().take(width).collect();
+    match alignment {
+        ColumnAlignment::Left => format!("{:<width$}", text, width = width),
+        ColumnAlignment::Right => format!("{:>width$}", text, width = width),
+    }
+}
+
+/// Format a byte count in `units`
+fn format_units(bytes: u64, units: UnitMode, numbers: &NumberFormat) -> String {
+    let bytes = bytes.min(i64::MAX as u64);
+    match units {
+        UnitMode::Auto => numbers.bytes(bytes),
+        UnitMode::Bytes => numbers.integer(bytes as i64),
+        UnitMode::Kilobytes => format!("{} K", numbers.integer((bytes / 1024) as i64)),
+        UnitMode::Megabytes => {
+            format!("{} MB", numbers.decimal(bytes as f64 / (1024.0 * 1024.0), 1))
+        }
     }
 }
 
@@ -367,6 +703,8 @@ mod tests {
             memory_private: memory,
             memory_working_set: memory,
             handle_count: 100,
+            leak_rate: None,
+            custom: Vec::new(),
         }
     }
 
@@ -433,11 +771,85 @@ mod tests {
     }
 
     #[test]
-    fn test_format_bytes() {
-        assert_eq!(format_bytes(500), "500 B");
-        assert_eq!(format_bytes(2048), "2.0 KB");
-        assert_eq!(format_bytes(1024 * 1024 * 5), "5.0 MB");
-        assert_eq!(format_bytes(1024 * 1024 * 1024 * 2), "2.0 GB");
+    fn test_format_units() {
+        let numbers = NumberFormat::new();
+        let auto = |bytes: u64| format_units(bytes, UnitMode::Auto, &numbers);
+        assert_eq!(auto(500), "500 B");
+        assert_eq!(auto(2048), "2.0 KB");
+        assert_eq!(auto(1024 * 1024 * 5), "5.0 MB");
+        assert_eq!(auto(1024 * 1024 * 1024 * 2), "2.0 GB");
+        assert_eq!(format_units(123_456, UnitMode::Bytes, &numbers), "123,456");
+        let german = NumberFormat::new().with_separators(',', Some('.'));
+        assert_eq!(format_units(1536 * 1024, UnitMode::Megabytes, &german), "1,5 MB");
+    }
+
+    #[test]
+    fn test_column_layout() {
+        let mut layout = ColumnLayout::default();
+        assert_eq!(layout.entries.len(), TableColumn::ALL.len());
+        assert!(!layout.get("leak_rate").unwrap().visible);
+        assert_eq!(layout.get("CPU %"), layout.get("cpu"));
+        assert!(layout.set_width("Threads", 50).is_err());
+
+        layout.move_column("memory", 0).unwrap();
+        layout.set_visible("Leak Rate", true).unwrap();
+        layout
+            .set_alignment("name", ColumnAlignment::Right)
+            .unwrap();
+        let keys: Vec<&str> = layout.displayed().iter().map(|(c, _)| c.key).collect();
+        // Pinned Name stays first
+        assert_eq!(
+            keys,
+            ["name", "memory", "pid", "cpu", "handles", "leak_rate"]
+        );
+        assert_eq!(layout.alignment(&TableColumn::NAME), ColumnAlignment::Right);
+        assert_eq!(layout.alignment(&TableColumn::PID), ColumnAlignment::Right);
+
+        let json = serde_json::to_string(&layout).unwrap();
+        assert!(json.starts_with("[{\"key\":\"memory\""));
+        assert_eq!(serde_json::from_str::<ColumnLayout>(&json).unwrap(), layout);
+
+        layout.entries.retain(|entry| entry.key != "pid");
+        layout.entries.push(ColumnSettings {
+            key: "threads".to_string(),
+            ..ColumnSettings::new(&TableColumn::CPU)
+        });
+        layout.entries.push(ColumnSettings::new(&TableColumn::CPU));
+        assert_eq!(layout.repair(), ["threads", "cpu"]);
+        assert_eq!(layout.entries.last().unwrap().key, "pid");
+    }
+
+    #[test]
+    fn test_table_renders_from_layout() {
+        let mut table = ProcessTable::new();
+        let mut layout = ColumnLayout::default();
+        layout.set_units("memory", UnitMode::Kilobytes).unwrap();
+        layout.set_pinned("name", false).unwrap();
+        table.set_layout(layout);
+
+        let process = make_test_process("app.exe", 42, 1.5, 5 * 1024 * 1024);
+        assert_eq!(
+            table.format_cell_text(SortColumn::Memory, &process),
+            "5,120 K"
+        );
+        assert_eq!(fit("12345", 3, ColumnAlignment::Left), "123");
+        assert_eq!(fit("42", 4, ColumnAlignment::Right), "  42");
+
+        // Name 0-200, PID 200-280, CPU 280-360
+        assert_eq!(table.column_at_point(250.0), Some(SortColumn::Pid));
+        assert!(table.on_header_drop(SortColumn::Cpu, 10.0));
+        assert_eq!(table.column_at_point(10.0), Some(SortColumn::Cpu));
+        assert!(!table.on_header_drop(SortColumn::Cpu, 10.0));
+
+        table.scroll_columns(2);
+        assert_eq!(table.column_at_point(10.0), Some(SortColumn::Pid));
+        table.scroll_columns(10);
+        let keys: Vec<&str> = table
+            .displayed_columns()
+            .iter()
+            .map(|(c, _)| c.key)
+            .collect();
+        assert_eq!(keys, ["handles"]);
     }
 
     #[test]
@@ -2,7 +2,7 @@
 //!
 //! Implements T435-T440:
 //! - Tab system foundation
-//! - Keyboard navigation (Ctrl+Tab, Ctrl+1-6)
+//! - Keyboard navigation (Ctrl+Tab, Ctrl+1-7 by default; see `ui::keymap`)
 //! - Tab overflow handling
 //! - Registry persistence for active tab
 //! - Fluent styling with accent color
@@ -12,6 +12,8 @@ use windows::Win32::System::Registry::*;
 use windows::core::PCWSTR;
 use std::sync::atomic::{AtomicU32, Ordering};
 
+use crate::ui::input::{KeyModifiers, KeyboardEvent, Shortcut};
+
 /// Tab identifiers for all panels
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u32)]
@@ -159,30 +161,35 @@ impl TabView {
 
     /// Handle keyboard navigation
     ///
-    /// Returns true if key was handled
+    /// Returns true if key was handled. Keys are resolved through the active
+    /// keymap.
     pub fn handle_key(&self, vk_code: u16, ctrl_down: bool, shift_down: bool) -> bool {
-        if !ctrl_down {
-            return false;
-        }
+        let event = KeyboardEvent::KeyDown {
+            vkey: vk_code,
+            repeat_count: 1,
+            modifiers: KeyModifiers {
+                ctrl: ctrl_down,
+                shift: shift_down,
+                alt: false,
+            },
+        };
+        Shortcut::from_keyboard_event(&event).is_some_and(|shortcut| self.handle_shortcut(shortcut))
+    }
 
-        match vk_code {
-            0x09 => {
-                // VK_TAB = 0x09: Ctrl+Tab next, Ctrl+Shift+Tab previous
-                let current = self.active_tab();
-                let next = if shift_down { current.prev() } else { current.next() };
-                self.set_active_tab(next);
+    /// Apply a tab command; returns false for other shortcuts
+    pub fn handle_shortcut(&self, shortcut: Shortcut) -> bool {
+        let tab = match shortcut {
+            Shortcut::NextTab => Some(self.active_tab().next()),
+            Shortcut::PreviousTab => Some(self.active_tab().prev()),
+            Shortcut::SwitchToTab(index) => TabId::from_u32(index as u32),
+            _ => None,
+        };
+        match tab {
+            Some(tab) => {
+                self.set_active_tab(tab);
                 true
             }
-            0x31..=0x37 => {
-                // Ctrl+1 through Ctrl+7: direct tab access
-                if let Some(tab) = TabId::from_u32((vk_code - 0x31) as u32) {
-                    self.set_active_tab(tab);
-                    true
-                } else {
-                    false
-                }
-            }
-            _ => false,
+            None => false,
         }
     }
 
@@ -454,6 +461,11 @@ mod tests {
 
         // Without Ctrl, no action
         assert!(!tab_view.handle_key(0x31, false, false));
+
+        // Ctrl+Shift+Tab = previous tab
+        assert!(tab_view.handle_key(0x09, true, true));
+        assert_eq!(tab_view.active_tab(), TabId::Performance);
+        assert!(!tab_view.handle_shortcut(Shortcut::Find));
     }
 
     #[test]
@@ -1,28 +1,118 @@
 // Internationalization and localization (T476-T478)
 //
-// String resource loading with fallback to English
+// Messages live in JSON bundles under `locales/` (`en.json`, `fr.json`,
+// `fr-CA.json`, ...), compiled in and optionally overridden from a directory
+// at runtime. A lookup walks the locale's fallback chain (fr-CA -> fr -> en)
+// and returns the key itself when no bundle has it.
+//
+// A message is either a string or an object of CLDR plural forms
+// (`zero`, `one`, `two`, `few`, `many`, `other`); `other` is required and
+// used when the language's rule picks a form the bundle lacks. `{0}`, `{1}`
+// are positional parameters. `number.*` keys hold the decimal and grouping
+// separators used by `util::format::NumberFormat`.
+//
+// The pseudo-locale `qps-ploc` accents and pads every English message, so
+// untranslated strings and truncated labels stand out.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+
+use crate::util::format::NumberFormat;
 
 // Note: GetUserDefaultLocaleName not available in windows-rs 0.62
 // Using stub implementation
 
-/// Supported locales
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum Locale {
-    EnglishUS,
-    // Future: Add more locales
-    // FrenchFR,
-    // GermanDE,
-    // JapaneseJP,
+/// Bundles compiled into the binary, by locale code
+const BUILTIN_BUNDLES: [(&str, &str); 5] = [
+    ("en", include_str!("../../locales/en.json")),
+    ("de", include_str!("../../locales/de.json")),
+    ("fr", include_str!("../../locales/fr.json")),
+    ("fr-CA", include_str!("../../locales/fr-CA.json")),
+    ("pl", include_str!("../../locales/pl.json")),
+];
+
+/// Locale every chain ends with
+const FALLBACK_LANGUAGE: &str = "en";
+
+/// Keys holding number separators rather than text
+const NUMBER_PREFIX: &str = "number.";
+
+/// Locale tag such as `en-US`, `fr-CA` or `zh-Hant-TW`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Locale {
+    code: String,
 }
 
 impl Locale {
+    /// Code of the pseudo-localisation locale
+    pub const PSEUDO: &'static str = "qps-ploc";
+
+    /// Locale from a tag, normalising case and `_` separators (`fr_ca` -> `fr-CA`)
+    pub fn new(code: &str) -> Self {
+        if code.eq_ignore_ascii_case(Self::PSEUDO) {
+            return Self::pseudo();
+        }
+        let code = code
+            .split(['-', '_'])
+            .filter(|part| !part.is_empty())
+            .enumerate()
+            .map(|(i, part)| match part.len() {
+                _ if i == 0 => part.to_ascii_lowercase(),
+                2 => part.to_ascii_uppercase(),
+                4 => {
+                    // Script subtag; the first char need not be one byte
+                    let mut chars = part.chars();
+                    let first = chars.next().unwrap_or_default().to_ascii_uppercase();
+                    first.to_string() + &chars.as_str().to_ascii_lowercase()
+                }
+                _ => part.to_ascii_lowercase(),
+            })
+            .collect::<Vec<_>>()
+            .join("-");
+        Self { code }
+    }
+
+    /// US English, the default locale
+    pub fn english() -> Self {
+        Self::new("en-US")
+    }
+
+    /// Accented, padded English for spotting hard-coded and truncated text
+    pub fn pseudo() -> Self {
+        Self {
+            code: Self::PSEUDO.to_string(),
+        }
+    }
+
     /// Get locale code string
-    pub fn code(&self) -> &'static str {
-        match self {
-            Locale::EnglishUS => "en-US",
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// Language subtag (`fr` for `fr-CA`)
+    pub fn language(&self) -> &str {
+        self.code.split('-').next().unwrap_or("")
+    }
+
+    pub fn is_pseudo(&self) -> bool {
+        self.code == Self::PSEUDO
+    }
+
+    /// This locale, then ever shorter tags, then English
+    pub fn fallback_chain(&self) -> Vec<Locale> {
+        let mut chain = vec![self.clone()];
+        if !self.is_pseudo() {
+            let mut code = self.code.as_str();
+            while let Some((parent, _)) = code.rsplit_once('-') {
+                chain.push(Self::new(parent));
+                code = parent;
+            }
+        }
+        if chain.iter().all(|locale| locale.code != FALLBACK_LANGUAGE) {
+            chain.push(Self::new(FALLBACK_LANGUAGE));
         }
+        chain
     }
 
     /// Detect system locale (T478)
@@ -30,148 +120,399 @@ impl Locale {
     pub fn detect_system_locale() -> Self {
         // TODO: Implement GetUserDefaultLocaleName when API becomes available
         // For now, default to English US
-        Locale::EnglishUS
+        Self::english()
+    }
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self::english()
+    }
+}
+
+/// CLDR plural category
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PluralCategory {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+impl PluralCategory {
+    pub const ALL: [PluralCategory; 6] = [
+        PluralCategory::Zero,
+        PluralCategory::One,
+        PluralCategory::Two,
+        PluralCategory::Few,
+        PluralCategory::Many,
+        PluralCategory::Other,
+    ];
+
+    /// Name used in bundles (`one`, `few`)
+    pub fn name(self) -> &'static str {
+        match self {
+            PluralCategory::Zero => "zero",
+            PluralCategory::One => "one",
+            PluralCategory::Two => "two",
+            PluralCategory::Few => "few",
+            PluralCategory::Many => "many",
+            PluralCategory::Other => "other",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|category| category.name() == name)
     }
 
-    /// Parse locale from code string
-    #[allow(dead_code)]
-    fn from_code(code: &str) -> Self {
-        match code {
-            "en-US" | "en" => Locale::EnglishUS,
-            _ => Locale::EnglishUS, // Fallback to English
+    /// Category of the whole number `n` under the CLDR rules of `language`
+    ///
+    /// Languages without a rule here use the English one/other split.
+    pub fn for_count(language: &str, n: u64) -> Self {
+        let (mod10, mod100) = (n % 10, n % 100);
+        let teen = (12..=14).contains(&mod100);
+        match language {
+            "ja" | "ko" | "zh" | "th" | "vi" | "id" => PluralCategory::Other,
+            "fr" | "pt" => match n {
+                0 | 1 => PluralCategory::One,
+                n if n % 1_000_000 == 0 => PluralCategory::Many,
+                _ => PluralCategory::Other,
+            },
+            "pl" => match n {
+                1 => PluralCategory::One,
+                _ if (2..=4).contains(&mod10) && !teen => PluralCategory::Few,
+                _ => PluralCategory::Many,
+            },
+            "ru" | "uk" => match mod10 {
+                1 if mod100 != 11 => PluralCategory::One,
+                2..=4 if !teen => PluralCategory::Few,
+                _ => PluralCategory::Many,
+            },
+            "cs" | "sk" => match n {
+                1 => PluralCategory::One,
+                2..=4 => PluralCategory::Few,
+                _ => PluralCategory::Other,
+            },
+            _ if n == 1 => PluralCategory::One,
+            _ => PluralCategory::Other,
         }
     }
 }
 
-impl Default for Locale {
-    fn default() -> Self {
-        Locale::EnglishUS
+/// One bundle entry
+#[derive(Debug, Clone, PartialEq)]
+enum Message {
+    Text(String),
+    /// Forms by category; always has `Other`
+    Plural(BTreeMap<PluralCategory, String>),
+}
+
+impl Message {
+    fn texts(&self) -> Vec<&str> {
+        match self {
+            Message::Text(text) => vec![text],
+            Message::Plural(forms) => forms.values().map(String::as_str).collect(),
+        }
+    }
+}
+
+/// Messages of one locale, parsed from a resource file
+#[derive(Debug, Clone)]
+pub struct Bundle {
+    locale: Locale,
+    messages: HashMap<String, Message>,
+}
+
+impl Bundle {
+    /// Parse a JSON bundle for locale `code`
+    pub fn parse(code: &str, json: &str) -> Result<Self, String> {
+        let invalid = |e: String| format!("Locale bundle '{}': {}", code, e);
+        let value: serde_json::Value =
+            serde_json::from_str(json).map_err(|e| invalid(e.to_string()))?;
+        let entries = value
+            .as_object()
+            .ok_or_else(|| invalid("expected an object of messages".to_string()))?;
+
+        let mut messages = HashMap::new();
+        for (key, value) in entries {
+            let message = match value {
+                serde_json::Value::String(text) => Message::Text(text.clone()),
+                serde_json::Value::Object(forms) => {
+                    let mut plural = BTreeMap::new();
+                    for (name, text) in forms {
+                        let category = PluralCategory::from_name(name).ok_or_else(|| {
+                            invalid(format!("unknown plural form '{}' in '{}'", name, key))
+                        })?;
+                        let text = text.as_str().ok_or_else(|| {
+                            invalid(format!(
+                                "plural form '{}' of '{}' must be a string",
+                                name, key
+                            ))
+                        })?;
+                        plural.insert(category, text.to_string());
+                    }
+                    if !plural.contains_key(&PluralCategory::Other) {
+                        return Err(invalid(format!("'{}' has no 'other' form", key)));
+                    }
+                    Message::Plural(plural)
+                }
+                _ => {
+                    return Err(invalid(format!(
+                        "'{}' must be a string or plural forms",
+                        key
+                    )))
+                }
+            };
+            messages.insert(key.clone(), message);
+        }
+        Ok(Self {
+            locale: Locale::new(code),
+            messages,
+        })
+    }
+
+    /// Load `<code>.json`, taking the locale from the file name
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let code = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or_else(|| format!("Invalid locale file name '{}'", path.display()))?;
+        let json = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+        Self::parse(code, &json)
+    }
+
+    pub fn locale(&self) -> &Locale {
+        &self.locale
+    }
+
+    /// Message keys, in no particular order
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.messages.keys().map(String::as_str)
+    }
+
+    /// Plain text of `key`; the `other` form of plural messages
+    pub fn get(&self, key: &str) -> Option<&str> {
+        match self.messages.get(key)? {
+            Message::Text(text) => Some(text),
+            Message::Plural(forms) => forms.get(&PluralCategory::Other).map(String::as_str),
+        }
+    }
+
+    /// Form of `key` for `count` under this bundle's plural rules
+    pub fn get_plural(&self, key: &str, count: u64) -> Option<&str> {
+        match self.messages.get(key)? {
+            Message::Text(text) => Some(text),
+            Message::Plural(forms) => {
+                let category = PluralCategory::for_count(self.locale.language(), count);
+                forms
+                    .get(&category)
+                    .or_else(|| forms.get(&PluralCategory::Other))
+                    .map(String::as_str)
+            }
+        }
+    }
+
+    /// `{0}`-style parameters used anywhere in `key`
+    pub fn placeholders(&self, key: &str) -> BTreeSet<String> {
+        let texts = self
+            .messages
+            .get(key)
+            .map(Message::texts)
+            .unwrap_or_default();
+        texts.into_iter().flat_map(placeholders).collect()
+    }
+
+    /// Pseudo-localised copy of this bundle
+    fn pseudo_localized(&self) -> Self {
+        let messages = self
+            .messages
+            .iter()
+            .map(|(key, message)| {
+                let message = match message {
+                    // Separators stay usable for `NumberFormat`
+                    Message::Text(_) if key.starts_with(NUMBER_PREFIX) => message.clone(),
+                    Message::Text(text) => Message::Text(pseudo_localize(text)),
+                    Message::Plural(forms) => Message::Plural(
+                        forms
+                            .iter()
+                            .map(|(category, text)| (*category, pseudo_localize(text)))
+                            .collect(),
+                    ),
+                };
+                (key.clone(), message)
+            })
+            .collect();
+        Self {
+            locale: Locale::pseudo(),
+            messages,
+        }
+    }
+}
+
+/// `{N}` parameters in `text`
+fn placeholders(text: &str) -> Vec<String> {
+    let mut found = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        rest = &rest[start..];
+        match rest.find('}') {
+            Some(end) if rest[1..end].chars().all(|c| c.is_ascii_digit()) && end > 1 => {
+                found.push(rest[..=end].to_string());
+                rest = &rest[end + 1..];
+            }
+            _ => rest = &rest[1..],
+        }
+    }
+    found
+}
+
+/// Accent every letter and pad by 40%, keeping `{N}` parameters intact
+fn pseudo_localize(text: &str) -> String {
+    const PLAIN: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    const ACCENTED: &str = "åƀçðéƒĝĥîĵķļɱñöþǫŕšţûṽŵẋýžÅƁÇÐÉƑĜĤÎĴĶĻṀÑÖÞǪŔŠŢÛṼŴẊÝŽ";
+    if text.is_empty() {
+        return String::new();
+    }
+
+    let mut accented = String::new();
+    let mut in_placeholder = false;
+    for c in text.chars() {
+        match c {
+            '{' => in_placeholder = true,
+            '}' => in_placeholder = false,
+            _ => {}
+        }
+        let replacement = match PLAIN.chars().position(|plain| plain == c) {
+            Some(index) if !in_placeholder => ACCENTED.chars().nth(index).unwrap_or(c),
+            _ => c,
+        };
+        accented.push(replacement);
+    }
+    let padding = (text.chars().count() * 2).div_ceil(5);
+    format!("[{} {}]", accented, "~".repeat(padding))
+}
+
+/// Replace `{0}`, `{1}`, ... with `params`
+fn substitute(template: &str, params: &[&str]) -> String {
+    let mut result = template.to_string();
+    for (i, param) in params.iter().enumerate() {
+        let placeholder = format!("{{{}}}", i);
+        result = result.replace(&placeholder, param);
     }
+    result
 }
 
 /// String resource manager (T476, T477)
 pub struct StringResources {
     locale: Locale,
-    strings: HashMap<&'static str, &'static str>,
+    /// Bundles in lookup order, most specific first
+    bundles: Vec<Bundle>,
 }
 
 impl StringResources {
-    /// Create string resources for locale (T477)
+    /// Create string resources for locale from the built-in bundles (T477)
     pub fn new(locale: Locale) -> Self {
-        let mut strings = HashMap::new();
-        
-        // Load strings for the specified locale
-        match locale {
-            Locale::EnglishUS => Self::load_english(&mut strings),
+        Self::build(locale, None).expect("built-in locale bundles are valid")
+    }
+
+    /// Like `new`, with `<dir>/<code>.json` files taking precedence over the
+    /// built-in bundle of the same locale
+    pub fn with_directory(locale: Locale, dir: &Path) -> Result<Self, String> {
+        Self::build(locale, Some(dir))
+    }
+
+    fn build(locale: Locale, dir: Option<&Path>) -> Result<Self, String> {
+        let mut bundles = Vec::new();
+        for fallback in locale.fallback_chain() {
+            if let Some(dir) = dir {
+                let path = dir.join(format!("{}.json", fallback.code()));
+                if path.is_file() {
+                    bundles.push(Bundle::load(&path)?);
+                }
+            }
+            if let Some((code, json)) = BUILTIN_BUNDLES
+                .iter()
+                .find(|(code, _)| *code == fallback.code())
+            {
+                bundles.push(Bundle::parse(code, json)?);
+            }
         }
-        
-        Self {
-            locale,
-            strings,
-        }
-    }
-
-    /// Load English strings (T476)
-    fn load_english(strings: &mut HashMap<&'static str, &'static str>) {
-        // Window titles
-        strings.insert("app.title", "Task Manager");
-        strings.insert("app.admin", "Administrator");
-        
-        // Tabs
-        strings.insert("tab.processes", "Processes");
-        strings.insert("tab.performance", "Performance");
-        strings.insert("tab.startup", "Startup");
-        strings.insert("tab.services", "Services");
-        strings.insert("tab.users", "Users");
-        strings.insert("tab.details", "Details");
-        strings.insert("tab.gpu", "GPU");
-        
-        // Columns
-        strings.insert("column.name", "Name");
-        strings.insert("column.pid", "PID");
-        strings.insert("column.cpu", "CPU");
-        strings.insert("column.memory", "Memory");
-        strings.insert("column.status", "Status");
-        strings.insert("column.description", "Description");
-        
-        // Actions
-        strings.insert("action.end_task", "End Task");
-        strings.insert("action.refresh", "Refresh");
-        strings.insert("action.start", "Start");
-        strings.insert("action.stop", "Stop");
-        strings.insert("action.restart", "Restart");
-        strings.insert("action.show", "Show");
-        strings.insert("action.hide", "Hide");
-        strings.insert("action.exit", "Exit");
-        
-        // Status
-        strings.insert("status.running", "Running");
-        strings.insert("status.stopped", "Stopped");
-        strings.insert("status.enabled", "Enabled");
-        strings.insert("status.disabled", "Disabled");
-        
-        // Dialogs
-        strings.insert("dialog.confirm_end_task", "Do you want to end this process?");
-        strings.insert("dialog.confirm_end_task_title", "Confirm End Task");
-        strings.insert("dialog.error", "Error");
-        strings.insert("dialog.warning", "Warning");
-        strings.insert("dialog.information", "Information");
-        
-        // Status bar
-        strings.insert("statusbar.processes", "Processes");
-        strings.insert("statusbar.cpu", "CPU");
-        strings.insert("statusbar.memory", "Memory");
-        strings.insert("statusbar.updated", "Updated");
-        strings.insert("statusbar.ago", "ago");
-        
-        // Settings
-        strings.insert("settings.title", "Settings");
-        strings.insert("settings.theme", "Theme");
-        strings.insert("settings.refresh_rate", "Refresh Rate");
-        strings.insert("settings.history_length", "History Length");
-        strings.insert("settings.graph_type", "Graph Type");
-        strings.insert("settings.startup", "Startup Options");
-        strings.insert("settings.performance_mode", "Performance Mode");
-        
-        // Themes
-        strings.insert("theme.light", "Light");
-        strings.insert("theme.dark", "Dark");
-        strings.insert("theme.system", "System");
-        
-        // Performance modes
-        strings.insert("perf_mode.performance", "Performance");
-        strings.insert("perf_mode.battery_saver", "Battery Saver");
-        strings.insert("perf_mode.manual", "Manual");
-        
-        // Units
-        strings.insert("unit.percent", "%");
-        strings.insert("unit.mb", "MB");
-        strings.insert("unit.gb", "GB");
-        strings.insert("unit.seconds", "seconds");
-        strings.insert("unit.second", "second");
+        if locale.is_pseudo() {
+            if let Some(english) = bundles.last() {
+                bundles.insert(0, english.pseudo_localized());
+            }
+        }
+        Ok(Self { locale, bundles })
+    }
+
+    /// Put `bundle` in front of the fallback chain
+    pub fn with_bundle(mut self, bundle: Bundle) -> Self {
+        self.bundles.insert(0, bundle);
+        self
     }
 
     /// Get localized string with fallback (T477)
     pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
-        self.strings.get(key).copied().unwrap_or(key)
+        self.bundles
+            .iter()
+            .find_map(|bundle| bundle.get(key))
+            .unwrap_or(key)
     }
 
     /// Get formatted string with parameters
     pub fn get_formatted(&self, key: &str, params: &[&str]) -> String {
-        let template = self.get(key);
-        let mut result = template.to_string();
-        
-        for (i, param) in params.iter().enumerate() {
-            let placeholder = format!("{{{}}}", i);
-            result = result.replace(&placeholder, param);
-        }
-        
-        result
+        substitute(self.get(key), params)
+    }
+
+    /// Plural form of `key` for `count`, with parameters
+    ///
+    /// The form follows the rules of the bundle the message comes from, so a
+    /// message that falls back to English also uses English rules.
+    pub fn get_plural(&self, key: &str, count: u64, params: &[&str]) -> String {
+        let template = self
+            .bundles
+            .iter()
+            .find_map(|bundle| bundle.get_plural(key, count))
+            .unwrap_or(key);
+        substitute(template, params)
+    }
+
+    /// Separators of this locale (`number.decimal_separator`,
+    /// `number.group_separator`), with Windows byte units
+    ///
+    /// An empty group separator turns grouping off.
+    pub fn number_format(&self) -> NumberFormat {
+        let decimal = self.get("number.decimal_separator").chars().next();
+        let grouping = self.get("number.group_separator").chars().next();
+        NumberFormat::new().with_separators(decimal.unwrap_or('.'), grouping)
+    }
+
+    /// `3 minutes ago`, in the largest whole unit of `secs`; `just now` for 0
+    pub fn relative_time(&self, secs: u64) -> String {
+        let (key, count) = match secs {
+            0 => return self.get("time.just_now").to_string(),
+            1..=59 => ("time.seconds_ago", secs),
+            60..=3599 => ("time.minutes_ago", secs / 60),
+            3600..=86_399 => ("time.hours_ago", secs / 3600),
+            _ => ("time.days_ago", secs / 86_400),
+        };
+        let text = self.number_format().integer(count as i64);
+        self.get_plural(key, count, &[&text])
     }
 
     /// Get current locale
-    pub fn locale(&self) -> Locale {
-        self.locale
+    pub fn locale(&self) -> &Locale {
+        &self.locale
+    }
+
+    /// Codes of the built-in bundles
+    pub fn builtin_locales() -> impl Iterator<Item = &'static str> {
+        BUILTIN_BUNDLES.iter().map(|(code, _)| *code)
     }
 }
 
@@ -185,16 +526,21 @@ impl Default for StringResources {
 mod tests {
     use super::*;
 
+    fn builtin(code: &str) -> Bundle {
+        let (_, json) = BUILTIN_BUNDLES.iter().find(|(c, _)| *c == code).unwrap();
+        Bundle::parse(code, json).unwrap()
+    }
+
     #[test]
     fn test_locale_detection() {
         let locale = Locale::detect_system_locale();
         // Should not crash and return a valid locale
-        assert_eq!(locale.code().len() > 0, true);
+        assert!(!locale.code().is_empty());
     }
 
     #[test]
     fn test_string_loading() {
-        let strings = StringResources::new(Locale::EnglishUS);
+        let strings = StringResources::new(Locale::english());
         assert_eq!(strings.get("app.title"), "Task Manager");
         assert_eq!(strings.get("tab.processes"), "Processes");
         assert_eq!(strings.get("nonexistent.key"), "nonexistent.key");
@@ -202,17 +548,167 @@ mod tests {
 
     #[test]
     fn test_formatted_strings() {
-        let strings = StringResources::new(Locale::EnglishUS);
-        // Manually insert a template for testing
-        let mut test_strings = strings;
-        test_strings.strings.insert("test.format", "Hello {0}, you have {1} messages");
-        
-        let formatted = test_strings.get_formatted("test.format", &["Alice", "5"]);
+        let bundle = Bundle::parse(
+            "en",
+            r#"{"test.format": "Hello {0}, you have {1} messages"}"#,
+        );
+        let strings = StringResources::new(Locale::english()).with_bundle(bundle.unwrap());
+
+        let formatted = strings.get_formatted("test.format", &["Alice", "5"]);
         assert_eq!(formatted, "Hello Alice, you have 5 messages");
     }
 
     #[test]
     fn test_locale_code() {
-        assert_eq!(Locale::EnglishUS.code(), "en-US");
+        assert_eq!(Locale::english().code(), "en-US");
+        assert_eq!(Locale::new("fr_ca").code(), "fr-CA");
+        assert_eq!(Locale::new("ZH-hant-tw").code(), "zh-Hant-TW");
+        assert_eq!(Locale::new("QPS-PLOC"), Locale::pseudo());
+        assert_eq!(Locale::new("x-éAB").code(), "x-éab");
+    }
+
+    #[test]
+    fn test_fallback_chain() {
+        let codes = |code: &str| -> Vec<String> {
+            Locale::new(code)
+                .fallback_chain()
+                .iter()
+                .map(|locale| locale.code().to_string())
+                .collect()
+        };
+        assert_eq!(codes("fr-CA"), vec!["fr-CA", "fr", "en"]);
+        assert_eq!(codes("en-US"), vec!["en-US", "en"]);
+        assert_eq!(codes("en"), vec!["en"]);
+        assert_eq!(
+            codes("zh-Hant-TW"),
+            vec!["zh-Hant-TW", "zh-Hant", "zh", "en"]
+        );
+        assert_eq!(codes(Locale::PSEUDO), vec![Locale::PSEUDO, "en"]);
+
+        // Regional overrides, then the language, then English
+        let canadian = StringResources::new(Locale::new("fr-CA"));
+        assert_eq!(canadian.get("action.end_task"), "Terminer la tâche");
+        assert_eq!(canadian.get("tab.processes"), "Processus");
+        let french = StringResources::new(Locale::new("fr-FR"));
+        assert_eq!(french.get("action.end_task"), "Fin de tâche");
+        let unknown = StringResources::new(Locale::new("sv-SE"));
+        assert_eq!(unknown.get("tab.processes"), "Processes");
+    }
+
+    #[test]
+    fn test_plural_categories() {
+        let category = |language: &str, n: u64| PluralCategory::for_count(language, n).name();
+        assert_eq!(category("en", 1), "one");
+        assert_eq!(category("en", 0), "other");
+        assert_eq!(category("fr", 0), "one");
+        assert_eq!(category("fr", 2), "other");
+        assert_eq!(category("fr", 2_000_000), "many");
+        assert_eq!(category("pl", 3), "few");
+        assert_eq!(category("pl", 13), "many");
+        assert_eq!(category("pl", 22), "few");
+        assert_eq!(category("pl", 25), "many");
+        assert_eq!(category("ru", 21), "one");
+        assert_eq!(category("ru", 11), "many");
+        assert_eq!(category("ja", 1), "other");
+
+        let polish = StringResources::new(Locale::new("pl-PL"));
+        let count = |n: u64| polish.get_plural("statusbar.process_count", n, &[&n.to_string()]);
+        assert_eq!(count(1), "1 proces");
+        assert_eq!(count(3), "3 procesy");
+        assert_eq!(count(12), "12 procesów");
+        let english = StringResources::new(Locale::english());
+        assert_eq!(
+            english.get_plural("time.minutes_ago", 1, &["1"]),
+            "1 minute ago"
+        );
+        assert_eq!(
+            english.get_plural("time.minutes_ago", 0, &["0"]),
+            "0 minutes ago"
+        );
+        assert_eq!(english.get("time.minutes_ago"), "{0} minutes ago");
+    }
+
+    #[test]
+    fn test_pseudo_locale() {
+        let strings = StringResources::new(Locale::pseudo());
+        let title = strings.get("app.title");
+        assert_eq!(title, "[Ţåšķ Ṁåñåĝéŕ ~~~~~]");
+        let count = strings.get_plural("statusbar.process_count", 2, &["2"]);
+        assert!(count.starts_with("[2 þŕöçéššéš "));
+        // Every key stays translated, unlike a missing string
+        assert_ne!(
+            strings.get("settings.performance_mode"),
+            "settings.performance_mode"
+        );
+    }
+
+    #[test]
+    fn test_numbers_and_relative_times() {
+        let english = StringResources::new(Locale::english());
+        assert_eq!(english.number_format().decimal(1234.5, 1), "1,234.5");
+        assert_eq!(english.relative_time(0), "just now");
+        assert_eq!(english.relative_time(1), "1 second ago");
+        assert_eq!(english.relative_time(185), "3 minutes ago");
+        assert_eq!(english.relative_time(2 * 86_400), "2 days ago");
+
+        let german = StringResources::new(Locale::new("de-AT"));
+        assert_eq!(german.number_format().decimal(1234.5, 1), "1.234,5");
+        assert_eq!(german.relative_time(3600), "vor 1 Stunde");
+        let french = StringResources::new(Locale::new("fr-CA"));
+        assert_eq!(french.number_format().percent(12.5), "12,5%");
+        let pseudo = StringResources::new(Locale::pseudo());
+        assert_eq!(pseudo.number_format(), NumberFormat::new());
+    }
+
+    #[test]
+    fn test_bundle_errors_and_directory_override() {
+        assert!(Bundle::parse("en", "[]").is_err());
+        assert!(Bundle::parse("en", r#"{"a": 5}"#).is_err());
+        assert!(Bundle::parse("en", r#"{"a": {"one": "x"}}"#)
+            .unwrap_err()
+            .contains("'other'"));
+        assert!(Bundle::parse("en", r#"{"a": {"several": "x", "other": "y"}}"#).is_err());
+
+        let dir = std::env::temp_dir().join(format!("tm-locales-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("fr.json"), r#"{"tab.processes": "Tâches"}"#).unwrap();
+        let strings = StringResources::with_directory(Locale::new("fr-CA"), &dir).unwrap();
+        assert_eq!(strings.get("tab.processes"), "Tâches");
+        assert_eq!(strings.get("action.end_task"), "Terminer la tâche");
+        assert_eq!(strings.get("tab.startup"), "Démarrage");
+
+        fs::write(dir.join("fr.json"), "{").unwrap();
+        assert!(StringResources::with_directory(Locale::new("fr"), &dir).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Every bundle uses only English keys with the same parameters; full
+    /// language bundles translate every key, regional ones may override a few
+    #[test]
+    fn test_bundles_match_english_keys() {
+        let english = builtin("en");
+        for code in StringResources::builtin_locales() {
+            let bundle = builtin(code);
+            for key in bundle.keys() {
+                assert!(
+                    english.get(key).is_some(),
+                    "{}: unknown key '{}'",
+                    code,
+                    key
+                );
+                assert_eq!(
+                    bundle.placeholders(key),
+                    english.placeholders(key),
+                    "{}: parameters of '{}'",
+                    code,
+                    key
+                );
+            }
+            if !code.contains('-') {
+                for key in english.keys() {
+                    assert!(bundle.get(key).is_some(), "{}: missing key '{}'", code, key);
+                }
+            }
+        }
     }
 }
@@ -5,6 +5,8 @@
 
 use windows::Win32::Foundation::{LPARAM, WPARAM};
 
+use crate::ui::keymap::{active_keymap, KeyResolution, KeyStroke};
+
 // Mouse button flags (from Windows SDK)
 const MK_LBUTTON: u32 = 0x0001;
 const MK_RBUTTON: u32 = 0x0002;
@@ -236,7 +238,9 @@ impl Default for FocusManager {
 }
 
 /// Keyboard shortcut definition (T408)
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// Keys are assigned by the active `Keymap`; the defaults are listed here.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Shortcut {
     /// Ctrl+F - Open filter box
     Find,
@@ -252,87 +256,82 @@ pub enum Shortcut {
     Escape,
     /// Enter - Activate focused element
     Enter,
-    /// Ctrl+1 through Ctrl+6 - Switch to specific tab
+    /// Ctrl+1 through Ctrl+7 - Switch to specific tab
     SwitchToTab(u8),
+    /// Shift+Delete - End selected process and its descendants
+    KillTree,
+    /// Ctrl+T - Toggle between the flat list and the process tree
+    ToggleTreeView,
+    /// Ctrl+P - Search and run any command by name
+    CommandPalette,
+    /// Alt+1 through Alt+9 - Apply the filter preset at this position
+    FilterPreset(u8),
 }
 
+/// Filter presets reachable with a `Shortcut::FilterPreset` binding
+pub const PRESET_SHORTCUTS: usize = 9;
+
 impl Shortcut {
+    /// Every command, for keymap editors and persistence
+    pub fn all() -> Vec<Self> {
+        let mut all = vec![
+            Shortcut::Find,
+            Shortcut::Delete,
+            Shortcut::Refresh,
+            Shortcut::NextTab,
+            Shortcut::PreviousTab,
+            Shortcut::Escape,
+            Shortcut::Enter,
+            Shortcut::KillTree,
+            Shortcut::ToggleTreeView,
+            Shortcut::CommandPalette,
+        ];
+        all.extend((0..7).map(Shortcut::SwitchToTab));
+        all.extend((0..PRESET_SHORTCUTS as u8).map(Shortcut::FilterPreset));
+        all
+    }
+
+    /// Command name used in `AppConfig::keymap` (e.g. `kill_tree`, `switch_to_tab_2`)
+    pub fn name(&self) -> String {
+        match self {
+            Shortcut::Find => "find".to_string(),
+            Shortcut::Delete => "delete".to_string(),
+            Shortcut::Refresh => "refresh".to_string(),
+            Shortcut::NextTab => "next_tab".to_string(),
+            Shortcut::PreviousTab => "previous_tab".to_string(),
+            Shortcut::Escape => "escape".to_string(),
+            Shortcut::Enter => "enter".to_string(),
+            Shortcut::SwitchToTab(n) => format!("switch_to_tab_{}", *n as u32 + 1),
+            Shortcut::KillTree => "kill_tree".to_string(),
+            Shortcut::ToggleTreeView => "toggle_tree_view".to_string(),
+            Shortcut::CommandPalette => "command_palette".to_string(),
+            Shortcut::FilterPreset(n) => format!("filter_preset_{}", *n as u32 + 1),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::all().into_iter().find(|shortcut| shortcut.name() == name)
+    }
+
     /// Try to parse a keyboard event into a shortcut
+    ///
+    /// Looks the key up in the active keymap. Chords need a
+    /// `KeymapResolver`; only single-key bindings match here.
     pub fn from_keyboard_event(event: &KeyboardEvent) -> Option<Self> {
-        use windows::Win32::UI::Input::KeyboardAndMouse::*;
-        
-        match event {
-            KeyboardEvent::KeyDown { vkey, modifiers, .. } => {
-                let vkey = *vkey;
-                
-                // Escape key
-                if vkey == VK_ESCAPE.0 {
-                    return Some(Shortcut::Escape);
-                }
-                
-                // Enter key
-                if vkey == VK_RETURN.0 {
-                    return Some(Shortcut::Enter);
-                }
-                
-                // Delete key
-                if vkey == VK_DELETE.0 {
-                    return Some(Shortcut::Delete);
-                }
-                
-                // F5 key (refresh)
-                if vkey == VK_F5.0 {
-                    return Some(Shortcut::Refresh);
-                }
-                
-                // Ctrl+F (find)
-                if vkey == 'F' as u16 && modifiers.ctrl && !modifiers.shift {
-                    return Some(Shortcut::Find);
-                }
-                
-                // Ctrl+Tab (next tab)
-                if vkey == VK_TAB.0 && modifiers.ctrl && !modifiers.shift {
-                    return Some(Shortcut::NextTab);
-                }
-                
-                // Ctrl+Shift+Tab (previous tab)
-                if vkey == VK_TAB.0 && modifiers.ctrl && modifiers.shift {
-                    return Some(Shortcut::PreviousTab);
-                }
-                
-                // Ctrl+1 through Ctrl+6 (switch to specific tab)
-                if modifiers.ctrl && !modifiers.shift {
-                    if vkey >= '1' as u16 && vkey <= '6' as u16 {
-                        let tab_index = (vkey - '1' as u16) as u8;
-                        return Some(Shortcut::SwitchToTab(tab_index));
-                    }
-                }
-                
-                None
-            }
+        let stroke = KeyStroke::from_event(event)?;
+        match active_keymap().lookup(&[stroke]) {
+            KeyResolution::Command(shortcut) => Some(shortcut),
             _ => None,
         }
     }
-    
-    /// Get human-readable shortcut string
-    pub fn to_string(&self) -> &'static str {
-        match self {
-            Shortcut::Find => "Ctrl+F",
-            Shortcut::Delete => "Delete",
-            Shortcut::Refresh => "F5",
-            Shortcut::NextTab => "Ctrl+Tab",
-            Shortcut::PreviousTab => "Ctrl+Shift+Tab",
-            Shortcut::Escape => "Escape",
-            Shortcut::Enter => "Enter",
-            Shortcut::SwitchToTab(n) => match n {
-                0 => "Ctrl+1",
-                1 => "Ctrl+2",
-                2 => "Ctrl+3",
-                3 => "Ctrl+4",
-                4 => "Ctrl+5",
-                5 => "Ctrl+6",
-                _ => "Unknown",
-            },
+}
+
+/// Human-readable key binding from the active keymap; empty when unbound
+impl std::fmt::Display for Shortcut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match active_keymap().label(*self) {
+            Some(label) => write!(f, "{}", label),
+            None => Ok(()),
         }
     }
 }
@@ -367,5 +366,16 @@ mod tests {
         assert_eq!(Shortcut::Delete.to_string(), "Delete");
         assert_eq!(Shortcut::Refresh.to_string(), "F5");
         assert_eq!(Shortcut::SwitchToTab(0).to_string(), "Ctrl+1");
+        assert_eq!(Shortcut::KillTree.to_string(), "Shift+Delete");
+        assert_eq!(Shortcut::FilterPreset(1).to_string(), "Alt+2");
+    }
+
+    #[test]
+    fn test_shortcut_names_round_trip() {
+        for shortcut in Shortcut::all() {
+            assert_eq!(Shortcut::from_name(&shortcut.name()), Some(shortcut));
+        }
+        assert_eq!(Shortcut::SwitchToTab(1).name(), "switch_to_tab_2");
+        assert_eq!(Shortcut::from_name("switch_to_tab_8"), None);
     }
 }
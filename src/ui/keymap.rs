@@ -0,0 +1,619 @@
+//! Remappable keyboard shortcuts
+//!
+//! A `Keymap` binds key sequences to `Shortcut` commands. Bindings are
+//! written as text: a single key such as "F5" or "Ctrl+Shift+K", or a chord of
+//! several keys separated by spaces ("Ctrl+K Ctrl+T"). User overrides are
+//! stored in `AppConfig::keymap` by command name and merged over the
+//! defaults with `Keymap::from_config`.
+//!
+//! The keymap installed with `set_active_keymap` is used by
+//! `Shortcut::from_keyboard_event` and to render shortcuts for display.
+//! Chords need state between key presses; `KeymapResolver` keeps the keys
+//! typed so far.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use crate::ui::input::{KeyboardEvent, Shortcut, PRESET_SHORTCUTS};
+
+/// Named keys other than letters, digits and function keys
+const KEY_NAMES: [(&str, u16); 15] = [
+    ("Backspace", 0x08),
+    ("Tab", 0x09),
+    ("Enter", 0x0D),
+    ("Escape", 0x1B),
+    ("Space", 0x20),
+    ("PageUp", 0x21),
+    ("PageDown", 0x22),
+    ("End", 0x23),
+    ("Home", 0x24),
+    ("Left", 0x25),
+    ("Up", 0x26),
+    ("Right", 0x27),
+    ("Down", 0x28),
+    ("Insert", 0x2D),
+    ("Delete", 0x2E),
+];
+
+/// Alternative spellings accepted when parsing
+const KEY_ALIASES: [(&str, &str); 6] = [
+    ("Esc", "Escape"),
+    ("Return", "Enter"),
+    ("Del", "Delete"),
+    ("Ins", "Insert"),
+    ("PgUp", "PageUp"),
+    ("PgDn", "PageDown"),
+];
+
+const VK_F1: u16 = 0x70;
+const VK_F24: u16 = 0x87;
+
+/// Shift, Ctrl, Alt and their left/right variants
+fn is_modifier_key(vkey: u16) -> bool {
+    matches!(vkey, 0x10..=0x12 | 0xA0..=0xA5)
+}
+
+/// One key with modifiers, e.g. Ctrl+Shift+K
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct KeyStroke {
+    /// Windows virtual-key code
+    pub vkey: u16,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl KeyStroke {
+    pub const fn key(vkey: u16) -> Self {
+        Self {
+            vkey,
+            ctrl: false,
+            shift: false,
+            alt: false,
+        }
+    }
+
+    pub const fn ctrl(mut self) -> Self {
+        self.ctrl = true;
+        self
+    }
+
+    pub const fn shift(mut self) -> Self {
+        self.shift = true;
+        self
+    }
+
+    pub const fn alt(mut self) -> Self {
+        self.alt = true;
+        self
+    }
+
+    /// Key-down events only; modifier keys on their own are not strokes
+    pub fn from_event(event: &KeyboardEvent) -> Option<Self> {
+        match *event {
+            KeyboardEvent::KeyDown {
+                vkey, modifiers, ..
+            } if !is_modifier_key(vkey) => Some(Self {
+                vkey,
+                ctrl: modifiers.ctrl,
+                shift: modifiers.shift,
+                alt: modifiers.alt,
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for KeyStroke {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, String> {
+        let mut parts: Vec<&str> = text.split('+').map(str::trim).collect();
+        let key = parts.pop().filter(|key| !key.is_empty());
+        let Some(key) = key else {
+            return Err(format!("Missing key in '{}'", text));
+        };
+
+        let mut stroke = Self::key(parse_key(key).ok_or_else(|| format!("Unknown key '{}'", key))?);
+        for modifier in parts {
+            let flag = match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => &mut stroke.ctrl,
+                "shift" => &mut stroke.shift,
+                "alt" => &mut stroke.alt,
+                _ => return Err(format!("Unknown modifier '{}'", modifier)),
+            };
+            if *flag {
+                return Err(format!("Repeated modifier '{}'", modifier));
+            }
+            *flag = true;
+        }
+        Ok(stroke)
+    }
+}
+
+fn parse_key(name: &str) -> Option<u16> {
+    let name = KEY_ALIASES
+        .iter()
+        .find(|(alias, _)| alias.eq_ignore_ascii_case(name))
+        .map_or(name, |(_, canonical)| canonical);
+    if let Some((_, vkey)) = KEY_NAMES
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+    {
+        return Some(*vkey);
+    }
+
+    let mut chars = name.chars();
+    match (chars.next(), chars.as_str()) {
+        (Some(c), "") if c.is_ascii_alphanumeric() => Some(c.to_ascii_uppercase() as u16),
+        (Some('F' | 'f'), number) => {
+            let n: u16 = number.parse().ok()?;
+            (1..=24).contains(&n).then_some(VK_F1 + n - 1)
+        }
+        _ => None,
+    }
+}
+
+fn key_name(vkey: u16) -> String {
+    if let Some((name, _)) = KEY_NAMES.iter().find(|(_, key)| *key == vkey) {
+        return name.to_string();
+    }
+    match vkey {
+        0x30..=0x39 | 0x41..=0x5A => char::from(vkey as u8).to_string(),
+        VK_F1..=VK_F24 => format!("F{}", vkey - VK_F1 + 1),
+        _ => format!("0x{:02X}", vkey),
+    }
+}
+
+impl fmt::Display for KeyStroke {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        write!(f, "{}", key_name(self.vkey))
+    }
+}
+
+/// Keys pressed one after another; a single stroke for plain shortcuts
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct KeySequence(pub Vec<KeyStroke>);
+
+impl KeySequence {
+    pub fn single(stroke: KeyStroke) -> Self {
+        Self(vec![stroke])
+    }
+
+    pub fn strokes(&self) -> &[KeyStroke] {
+        &self.0
+    }
+}
+
+impl FromStr for KeySequence {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, String> {
+        let strokes = text
+            .split_whitespace()
+            .map(KeyStroke::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+        if strokes.is_empty() {
+            return Err("Empty key binding".to_string());
+        }
+        Ok(Self(strokes))
+    }
+}
+
+impl fmt::Display for KeySequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, stroke) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", stroke)?;
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of looking up the keys typed so far
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyResolution {
+    Command(Shortcut),
+    /// Start of a chord; more keys are needed
+    Pending,
+    Unbound,
+}
+
+/// Two bindings that cannot both work
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyConflict {
+    /// Binding that wins
+    pub binding: (KeySequence, Shortcut),
+    /// Binding it hides: the same keys, or a chord starting with them
+    pub hidden: (KeySequence, Shortcut),
+}
+
+impl fmt::Display for KeyConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (keys, shortcut) = &self.binding;
+        let (hidden_keys, hidden) = &self.hidden;
+        if keys == hidden_keys {
+            write!(
+                f,
+                "{} is bound to both {} and {}",
+                keys,
+                shortcut.name(),
+                hidden.name()
+            )
+        } else {
+            write!(
+                f,
+                "{} ({}) is unreachable because {} runs {}",
+                hidden_keys,
+                hidden.name(),
+                keys,
+                shortcut.name()
+            )
+        }
+    }
+}
+
+/// Invalid entry in `AppConfig::keymap`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeymapError {
+    /// Command name as written in the configuration
+    pub command: String,
+    pub message: String,
+}
+
+/// Key sequences bound to commands
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    /// In priority order: the first binding of a sequence wins
+    bindings: Vec<(KeySequence, Shortcut)>,
+}
+
+impl Keymap {
+    /// Keymap without any bindings
+    pub fn new() -> Self {
+        Self {
+            bindings: Vec::new(),
+        }
+    }
+
+    /// Defaults merged with the overrides saved in `AppConfig::keymap`
+    ///
+    /// Each entry replaces every default binding of its command; an empty
+    /// list unbinds it. Invalid entries are skipped and reported.
+    pub fn from_config(overrides: &BTreeMap<String, Vec<String>>) -> (Self, Vec<KeymapError>) {
+        let mut keymap = Self::default();
+        let mut errors = Vec::new();
+
+        for (command, bindings) in overrides {
+            let error = |message: String| KeymapError {
+                command: command.clone(),
+                message,
+            };
+            let Some(shortcut) = Shortcut::from_name(command) else {
+                errors.push(error(format!("Unknown command '{}'", command)));
+                continue;
+            };
+            match bindings
+                .iter()
+                .map(|binding| binding.parse::<KeySequence>())
+                .collect::<Result<Vec<_>, _>>()
+            {
+                Ok(sequences) => {
+                    keymap.unbind(shortcut);
+                    for sequence in sequences {
+                        keymap.bind(sequence, shortcut);
+                    }
+                }
+                Err(message) => errors.push(error(message)),
+            }
+        }
+        (keymap, errors)
+    }
+
+    /// Overrides that turn the defaults into this keymap, for `AppConfig::keymap`
+    pub fn to_config(&self) -> BTreeMap<String, Vec<String>> {
+        let defaults = Self::default();
+        Shortcut::all()
+            .into_iter()
+            .filter(|shortcut| self.bindings_for(*shortcut) != defaults.bindings_for(*shortcut))
+            .map(|shortcut| {
+                let bindings = self
+                    .bindings_for(shortcut)
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect();
+                (shortcut.name(), bindings)
+            })
+            .collect()
+    }
+
+    /// Add a binding after the existing ones
+    pub fn bind(&mut self, sequence: KeySequence, shortcut: Shortcut) {
+        let binding = (sequence, shortcut);
+        if !self.bindings.contains(&binding) {
+            self.bindings.push(binding);
+        }
+    }
+
+    /// Remove every binding of `shortcut`
+    pub fn unbind(&mut self, shortcut: Shortcut) {
+        self.bindings.retain(|(_, bound)| *bound != shortcut);
+    }
+
+    pub fn bindings_for(&self, shortcut: Shortcut) -> Vec<&KeySequence> {
+        self.bindings
+            .iter()
+            .filter(|(_, bound)| *bound == shortcut)
+            .map(|(sequence, _)| sequence)
+            .collect()
+    }
+
+    /// Primary binding of `shortcut` as text, if it is bound
+    pub fn label(&self, shortcut: Shortcut) -> Option<String> {
+        self.bindings_for(shortcut)
+            .first()
+            .map(|sequence| sequence.to_string())
+    }
+
+    /// Command for the keys typed so far
+    pub fn lookup(&self, strokes: &[KeyStroke]) -> KeyResolution {
+        if let Some((_, shortcut)) = self
+            .bindings
+            .iter()
+            .find(|(sequence, _)| sequence.strokes() == strokes)
+        {
+            return KeyResolution::Command(*shortcut);
+        }
+        let is_prefix = self
+            .bindings
+            .iter()
+            .any(|(sequence, _)| sequence.strokes().starts_with(strokes));
+        if is_prefix && !strokes.is_empty() {
+            KeyResolution::Pending
+        } else {
+            KeyResolution::Unbound
+        }
+    }
+
+    /// Bindings hidden by an earlier one with the same keys, or by a shorter
+    /// binding their chord starts with
+    pub fn conflicts(&self) -> Vec<KeyConflict> {
+        let mut conflicts = Vec::new();
+        for (i, (sequence, shortcut)) in self.bindings.iter().enumerate() {
+            for (j, (other, other_shortcut)) in self.bindings.iter().enumerate() {
+                let hides = if sequence == other {
+                    i < j && shortcut != other_shortcut
+                } else {
+                    other.strokes().starts_with(sequence.strokes())
+                };
+                if hides {
+                    conflicts.push(KeyConflict {
+                        binding: (sequence.clone(), *shortcut),
+                        hidden: (other.clone(), *other_shortcut),
+                    });
+                }
+            }
+        }
+        conflicts
+    }
+}
+
+impl Default for Keymap {
+    /// Built-in bindings
+    fn default() -> Self {
+        use Shortcut::*;
+
+        let tab = KeyStroke::key(0x09);
+        let delete = KeyStroke::key(0x2E);
+        let mut keymap = Self::new();
+        let defaults = [
+            (KeyStroke::key(b'F' as u16).ctrl(), Find),
+            (delete, Delete),
+            (KeyStroke::key(VK_F1 + 4), Refresh),
+            (tab.ctrl(), NextTab),
+            (tab.ctrl().shift(), PreviousTab),
+            (KeyStroke::key(0x1B), Escape),
+            (KeyStroke::key(0x0D), Enter),
+            (delete.shift(), KillTree),
+            (KeyStroke::key(b'T' as u16).ctrl(), ToggleTreeView),
+            (KeyStroke::key(b'P' as u16).ctrl(), CommandPalette),
+        ];
+        for (stroke, shortcut) in defaults {
+            keymap.bind(KeySequence::single(stroke), shortcut);
+        }
+        for tab in 0..TAB_COUNT {
+            let digit = KeyStroke::key(b'1' as u16 + tab as u16).ctrl();
+            keymap.bind(KeySequence::single(digit), SwitchToTab(tab));
+        }
+        for preset in 0..PRESET_SHORTCUTS as u8 {
+            let digit = KeyStroke::key(b'1' as u16 + preset as u16).alt();
+            keymap.bind(KeySequence::single(digit), FilterPreset(preset));
+        }
+        keymap
+    }
+}
+
+/// Tabs reachable with Ctrl+1 through Ctrl+7
+const TAB_COUNT: u8 = 7;
+
+/// Tracks the keys of a chord in progress
+#[derive(Debug, Clone, Default)]
+pub struct KeymapResolver {
+    pending: Vec<KeyStroke>,
+}
+
+impl KeymapResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a key event to the chord in progress
+    ///
+    /// Keys that do not continue the chord start a new one. While a chord is
+    /// pending, events that are not key strokes (characters, key-ups, bare
+    /// modifiers) report `Pending` so they are not handled on their own.
+    pub fn feed(&mut self, keymap: &Keymap, event: &KeyboardEvent) -> KeyResolution {
+        let Some(stroke) = KeyStroke::from_event(event) else {
+            return if self.pending.is_empty() {
+                KeyResolution::Unbound
+            } else {
+                KeyResolution::Pending
+            };
+        };
+
+        self.pending.push(stroke);
+        let mut resolution = keymap.lookup(&self.pending);
+        if resolution == KeyResolution::Unbound && self.pending.len() > 1 {
+            self.pending = vec![stroke];
+            resolution = keymap.lookup(&self.pending);
+        }
+        if resolution != KeyResolution::Pending {
+            self.pending.clear();
+        }
+        resolution
+    }
+
+    pub fn is_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Keys of the chord in progress, e.g. "Ctrl+K"
+    pub fn pending_keys(&self) -> String {
+        KeySequence(self.pending.clone()).to_string()
+    }
+
+    pub fn reset(&mut self) {
+        self.pending.clear();
+    }
+}
+
+static ACTIVE_KEYMAP: RwLock<Option<Arc<Keymap>>> = RwLock::new(None);
+static DEFAULT_KEYMAP: OnceLock<Arc<Keymap>> = OnceLock::new();
+
+/// Keymap used by `Shortcut::from_keyboard_event` and shortcut labels
+pub fn active_keymap() -> Arc<Keymap> {
+    if let Some(keymap) = ACTIVE_KEYMAP.read().unwrap().as_ref() {
+        return Arc::clone(keymap);
+    }
+    Arc::clone(DEFAULT_KEYMAP.get_or_init(|| Arc::new(Keymap::default())))
+}
+
+/// Replace the active keymap, e.g. after loading or reloading the configuration
+pub fn set_active_keymap(keymap: Keymap) {
+    *ACTIVE_KEYMAP.write().unwrap() = Some(Arc::new(keymap));
+}
+
+/// Activate the keymap saved in `AppConfig::keymap`
+///
+/// Returns the entries that were skipped because they are invalid.
+pub fn install_keymap(overrides: &BTreeMap<String, Vec<String>>) -> Vec<KeymapError> {
+    let (keymap, errors) = Keymap::from_config(overrides);
+    set_active_keymap(keymap);
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::input::KeyModifiers;
+
+    fn key_down(vkey: u16, ctrl: bool, shift: bool) -> KeyboardEvent {
+        KeyboardEvent::KeyDown {
+            vkey,
+            repeat_count: 1,
+            modifiers: KeyModifiers {
+                ctrl,
+                shift,
+                alt: false,
+            },
+        }
+    }
+
+    #[test]
+    fn test_parse_and_render_bindings() {
+        let stroke: KeyStroke = "shift+ctrl+k".parse().unwrap();
+        assert_eq!(stroke, KeyStroke::key(b'K' as u16).ctrl().shift());
+        assert_eq!(stroke.to_string(), "Ctrl+Shift+K");
+        assert_eq!("Esc".parse::<KeyStroke>().unwrap().to_string(), "Escape");
+        assert_eq!("F12".parse::<KeyStroke>().unwrap().vkey, 0x7B);
+
+        let chord: KeySequence = "Ctrl+K  Ctrl+T".parse().unwrap();
+        assert_eq!(chord.strokes().len(), 2);
+        assert_eq!(chord.to_string(), "Ctrl+K Ctrl+T");
+
+        for bad in ["", "Ctrl+", "Hyper+K", "Ctrl+Ctrl+K", "F25", "Ctrl+KK"] {
+            assert!(bad.parse::<KeySequence>().is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_chords_and_conflicts() {
+        let mut overrides = BTreeMap::new();
+        overrides.insert("kill_tree".to_string(), vec!["Ctrl+K Ctrl+T".to_string()]);
+        overrides.insert("bogus".to_string(), vec!["F1".to_string()]);
+        let (keymap, errors) = Keymap::from_config(&overrides);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].command, "bogus");
+        assert!(keymap.conflicts().is_empty());
+        assert_eq!(
+            keymap.label(Shortcut::KillTree).as_deref(),
+            Some("Ctrl+K Ctrl+T")
+        );
+
+        let mut resolver = KeymapResolver::new();
+        let ctrl_k = key_down(b'K' as u16, true, false);
+        assert_eq!(resolver.feed(&keymap, &ctrl_k), KeyResolution::Pending);
+        assert_eq!(resolver.pending_keys(), "Ctrl+K");
+        // The bare Ctrl key-down between the chord's keys is ignored
+        assert_eq!(
+            resolver.feed(&keymap, &key_down(0x11, true, false)),
+            KeyResolution::Pending
+        );
+        assert_eq!(
+            resolver.feed(&keymap, &key_down(b'T' as u16, true, false)),
+            KeyResolution::Command(Shortcut::KillTree)
+        );
+        // A key that breaks the chord is looked up on its own
+        resolver.feed(&keymap, &ctrl_k);
+        assert_eq!(
+            resolver.feed(&keymap, &key_down(b'F' as u16, true, false)),
+            KeyResolution::Command(Shortcut::Find)
+        );
+        assert!(!resolver.is_pending());
+
+        let mut conflicting = keymap.clone();
+        conflicting.bind("Ctrl+K".parse().unwrap(), Shortcut::Refresh);
+        conflicting.bind("Ctrl+F".parse().unwrap(), Shortcut::Refresh);
+        let messages: Vec<String> = conflicting
+            .conflicts()
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Ctrl+F is bound to both find and refresh",
+                "Ctrl+K Ctrl+T (kill_tree) is unreachable because Ctrl+K runs refresh",
+            ]
+        );
+
+        assert_eq!(
+            conflicting.to_config()["refresh"],
+            vec!["F5", "Ctrl+K", "Ctrl+F"]
+        );
+        assert_eq!(Keymap::default().to_config(), BTreeMap::new());
+    }
+}
@@ -8,7 +8,9 @@ pub mod dialogs;
 pub mod fluent;
 pub mod i18n;
 pub mod input;
+pub mod keymap;
 pub mod layout;
 pub mod panels;
 pub mod systray;
+pub mod tui;
 pub mod window;
@@ -3,12 +3,16 @@
 use crate::core::metrics::SystemMetrics;
 use crate::ui::controls::graph::{Graph, LineGraph, MultiLineGraph, ScaleMode, GraphAxis};
 use crate::ui::layout::Rect;
+use crate::util::format::NumberFormat;
 use windows::{
     core::*,
     Win32::Graphics::Direct2D::Common::*,
     Win32::Graphics::Direct2D::*,
 };
 
+/// Window covered by the time axis, in seconds
+const TIME_AXIS_SECONDS: u32 = 60;
+
 /// Performance panel displaying system metrics graphs
 pub struct PerformancePanel {
     cpu_graph: LineGraph,
@@ -49,7 +53,7 @@ impl PerformancePanel {
         percent_axis.set_percent_labels();
 
         let mut time_axis = GraphAxis::horizontal();
-        time_axis.set_time_labels(60);
+        time_axis.set_time_labels(TIME_AXIS_SECONDS);
 
         Self {
             cpu_graph,
@@ -63,6 +67,14 @@ impl PerformancePanel {
         }
     }
 
+    /// Relabel the axes with `numbers` (`DisplayConfig::number_format`)
+    pub fn set_number_format(&mut self, numbers: NumberFormat) {
+        self.percent_axis.set_number_format(numbers);
+        self.percent_axis.set_percent_labels();
+        self.time_axis.set_number_format(numbers);
+        self.time_axis.set_time_labels(TIME_AXIS_SECONDS);
+    }
+
     /// Set layout mode
     pub fn set_layout_mode(&mut self, mode: LayoutMode) {
         self.layout_mode = mode;
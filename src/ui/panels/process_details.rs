@@ -10,6 +10,8 @@
 
 use std::fmt::Write;
 
+use crate::util::format::NumberFormat;
+
 /// T213-T219: Process details panel data
 #[derive(Debug, Clone, Default)]
 pub struct ProcessDetails {
@@ -119,12 +121,22 @@ impl IntegrityLevel {
 /// T213: Process details panel UI component
 pub struct ProcessDetailsPanel {
     details: Option<ProcessDetails>,
+    /// Separators and byte units of the formatted values
+    numbers: NumberFormat,
 }
 
 impl ProcessDetailsPanel {
     /// Create new process details panel
     pub fn new() -> Self {
-        Self { details: None }
+        Self {
+            details: None,
+            numbers: NumberFormat::new(),
+        }
+    }
+
+    /// Format values with `numbers` (`DisplayConfig::number_format`)
+    pub fn set_number_format(&mut self, numbers: NumberFormat) {
+        self.numbers = numbers;
     }
 
     /// T214: Set selected process details
@@ -147,6 +159,9 @@ impl ProcessDetailsPanel {
         let Some(details) = &self.details else {
             return vec![];
         };
+        let numbers = &self.numbers;
+        let bytes = |value: u64| numbers.bytes(value);
+        let count = |value: u32| numbers.integer(value as i64);
 
         let mut sections = Vec::new();
 
@@ -167,10 +182,10 @@ impl ProcessDetailsPanel {
         sections.push((
             "Memory".to_string(),
             vec![
-                ("Working Set".to_string(), format_bytes(details.working_set)),
-                ("Peak Working Set".to_string(), format_bytes(details.peak_working_set)),
-                ("Private Bytes".to_string(), format_bytes(details.private_bytes)),
-                ("Commit Charge".to_string(), format_bytes(details.commit_charge)),
+                ("Working Set".to_string(), bytes(details.working_set)),
+                ("Peak Working Set".to_string(), bytes(details.peak_working_set)),
+                ("Private Bytes".to_string(), bytes(details.private_bytes)),
+                ("Commit Charge".to_string(), bytes(details.commit_charge)),
             ],
         ));
 
@@ -178,10 +193,10 @@ impl ProcessDetailsPanel {
         sections.push((
             "Resources".to_string(),
             vec![
-                ("Threads".to_string(), details.thread_count.to_string()),
-                ("Handles".to_string(), details.handle_count.to_string()),
-                ("GDI Objects".to_string(), details.gdi_objects.to_string()),
-                ("USER Objects".to_string(), details.user_objects.to_string()),
+                ("Threads".to_string(), count(details.thread_count)),
+                ("Handles".to_string(), count(details.handle_count)),
+                ("GDI Objects".to_string(), count(details.gdi_objects)),
+                ("USER Objects".to_string(), count(details.user_objects)),
             ],
         ));
 
@@ -214,9 +229,9 @@ impl ProcessDetailsPanel {
         sections.push((
             "Performance".to_string(),
             vec![
-                ("CPU Usage".to_string(), format!("{:.1}%", details.cpu_usage)),
-                ("I/O Read".to_string(), format_bytes(details.io_read_bytes)),
-                ("I/O Write".to_string(), format_bytes(details.io_write_bytes)),
+                ("CPU Usage".to_string(), numbers.percent(details.cpu_usage)),
+                ("I/O Read".to_string(), bytes(details.io_read_bytes)),
+                ("I/O Write".to_string(), bytes(details.io_write_bytes)),
             ],
         ));
 
@@ -266,26 +281,10 @@ impl Default for ProcessDetailsPanel {
     }
 }
 
-/// Format bytes as human-readable string
-fn format_bytes(bytes: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = KB * 1024;
-    const GB: u64 = MB * 1024;
-
-    if bytes >= GB {
-        format!("{:.2} GB", bytes as f64 / GB as f64)
-    } else if bytes >= MB {
-        format!("{:.2} MB", bytes as f64 / MB as f64)
-    } else if bytes >= KB {
-        format!("{:.2} KB", bytes as f64 / KB as f64)
-    } else {
-        format!("{} bytes", bytes)
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::util::format::UnitSystem;
 
     fn make_test_details() -> ProcessDetails {
         ProcessDetails {
@@ -399,10 +398,24 @@ mod tests {
     }
 
     #[test]
-    fn test_format_bytes() {
-        assert_eq!(format_bytes(500), "500 bytes");
-        assert_eq!(format_bytes(2048), "2.00 KB");
-        assert_eq!(format_bytes(5_242_880), "5.00 MB");
-        assert_eq!(format_bytes(2_147_483_648), "2.00 GB");
+    fn test_formatted_values() {
+        let mut panel = ProcessDetailsPanel::new();
+        panel.set_details(Some(make_test_details()));
+        let value = |panel: &ProcessDetailsPanel, key: &str| -> String {
+            let sections = panel.format_sections();
+            let mut items = sections.into_iter().flat_map(|(_, items)| items);
+            items.find(|(k, _)| k == key).unwrap().1
+        };
+        assert_eq!(value(&panel, "Working Set"), "95.4 MB");
+        assert_eq!(value(&panel, "CPU Usage"), "12.5%");
+        assert_eq!(value(&panel, "I/O Write"), "488 KB");
+
+        let german = NumberFormat::new()
+            .with_separators(',', Some('.'))
+            .with_units(UnitSystem::Si);
+        panel.set_number_format(german);
+        assert_eq!(value(&panel, "Working Set"), "100 MB");
+        assert_eq!(value(&panel, "CPU Usage"), "12,5%");
+        assert_eq!(value(&panel, "I/O Read"), "1,0 MB");
     }
 }
@@ -8,12 +8,15 @@
 //! - Startup options (run at login, start minimized)
 //! - Column visibility toggles
 //! - Performance mode toggle
+//! - Active configuration profile
 
 use windows::Win32::Foundation::RECT;
 use std::collections::HashMap;
 
 use crate::app::theme::Theme;
-use crate::app::config::ConfigManager;
+use crate::app::config::{ConfigManager, MonitoringConfig};
+use crate::app::validation::{self, ValidationError, ValidationErrors};
+use crate::ui::controls::table::{ColumnLayout, TableColumn};
 
 /// Settings panel sections
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -23,6 +26,7 @@ pub enum SettingsSection {
     Columns,
     Startup,
     Performance,
+    Profiles,
 }
 
 /// Theme selector options (T416)
@@ -229,6 +233,11 @@ pub struct SettingsPanel {
     
     // Performance options (T422)
     pub performance_mode: bool,
+
+    /// Names of the configured profiles
+    pub profiles: Vec<String>,
+    /// Profile applied over these settings; None for the base configuration
+    pub active_profile: Option<String>,
 }
 
 impl SettingsPanel {
@@ -245,6 +254,8 @@ impl SettingsPanel {
             start_minimized: false,
             column_visibility: Self::default_column_visibility(),
             performance_mode: false,
+            profiles: Vec::new(),
+            active_profile: None,
         }
     }
 
@@ -266,22 +277,48 @@ impl SettingsPanel {
         self.performance_mode = app_config.startup.performance_mode;
         
         // Load column visibility
-        for (name, visible) in &app_config.columns.visibility {
-            self.column_visibility.insert(name.clone(), *visible);
+        for entry in &app_config.columns.entries {
+            if let Some(column) = TableColumn::from_name(&entry.key) {
+                self.column_visibility.insert(column.label.to_string(), entry.visible);
+            }
         }
+
+        // Load profiles
+        self.profiles = app_config.profiles.names().into_iter().map(String::from).collect();
+        self.active_profile = app_config.profiles.active.clone();
+    }
+
+    /// Check the current selection before saving
+    ///
+    /// Errors name the offending field (e.g. `monitoring.history_length_sec`
+    /// for 24 hours of history at 0.1 seconds) so it can be highlighted.
+    pub fn validate(&self) -> Result<(), ValidationErrors> {
+        validation::validate_monitoring(&self.monitoring_config())
     }
 
     /// Save settings to config manager
-    pub fn save_to_config(&self, config: &ConfigManager) {
+    ///
+    /// Nothing is saved if the settings are invalid.
+    pub fn save_to_config(&self, config: &ConfigManager) -> Result<(), ValidationErrors> {
+        self.validate()?;
+
+        // Switch profile first; an unknown or cyclic profile saves nothing
+        config
+            .switch_profile(self.active_profile.as_deref())
+            .map_err(|e| ValidationErrors {
+                errors: vec![ValidationError::new("profiles.active", e)],
+            })?;
+
         // Save theme
         config.set_theme(self.theme.to_theme());
         
         // Save monitoring
+        let monitoring = self.monitoring_config();
         config.set_monitoring(
-            self.refresh_rate.to_millis(),
-            self.history_length.to_seconds(),
-            self.graph_type.to_u32(),
-        );
+            monitoring.refresh_rate_ms,
+            monitoring.history_length_sec,
+            monitoring.graph_type,
+        )?;
         
         // Save startup
         config.set_startup_options(
@@ -292,7 +329,20 @@ impl SettingsPanel {
         
         // Save column visibility
         for (name, visible) in &self.column_visibility {
-            config.set_column_visibility(name, *visible);
+            config
+                .set_column_visibility(name, *visible)
+                .map_err(|e| ValidationErrors {
+                    errors: vec![ValidationError::new(format!("columns.{}", name), e)],
+                })?;
+        }
+        Ok(())
+    }
+
+    fn monitoring_config(&self) -> MonitoringConfig {
+        MonitoringConfig {
+            refresh_rate_ms: self.refresh_rate.to_millis(),
+            history_length_sec: self.history_length.to_seconds(),
+            graph_type: self.graph_type.to_u32(),
         }
     }
 
@@ -316,15 +366,24 @@ impl SettingsPanel {
         self.bounds
     }
 
-    /// Toggle column visibility (T421)
+    /// Toggle column visibility (T421); unknown columns are ignored
     pub fn toggle_column(&mut self, column_name: &str) {
-        let current = self.column_visibility.get(column_name).copied().unwrap_or(true);
-        self.column_visibility.insert(column_name.to_string(), !current);
+        if let Some(column) = TableColumn::from_name(column_name) {
+            let current = self.is_column_visible(column.label);
+            self.column_visibility.insert(column.label.to_string(), !current);
+        }
     }
 
-    /// Get column visibility
+    /// Select the active profile (None for the base configuration)
+    pub fn select_profile(&mut self, name: Option<&str>) {
+        self.active_profile = name.map(String::from);
+    }
+
+    /// Get column visibility, by key or label
     pub fn is_column_visible(&self, column_name: &str) -> bool {
-        self.column_visibility.get(column_name).copied().unwrap_or(true)
+        TableColumn::from_name(column_name)
+            .and_then(|column| self.column_visibility.get(column.label).copied())
+            .unwrap_or(false)
     }
 
     /// Get all available sections
@@ -335,6 +394,7 @@ impl SettingsPanel {
             SettingsSection::Columns,
             SettingsSection::Startup,
             SettingsSection::Performance,
+            SettingsSection::Profiles,
         ]
     }
 
@@ -346,28 +406,20 @@ impl SettingsPanel {
             SettingsSection::Columns => "Columns",
             SettingsSection::Startup => "Startup",
             SettingsSection::Performance => "Performance",
+            SettingsSection::Profiles => "Profiles",
         }
     }
 
-    /// Default column visibility
+    /// Default column visibility, by header label
     fn default_column_visibility() -> HashMap<String, bool> {
-        let mut visibility = HashMap::new();
-        
-        // Default visible columns
-        visibility.insert("Name".to_string(), true);
-        visibility.insert("PID".to_string(), true);
-        visibility.insert("CPU".to_string(), true);
-        visibility.insert("Memory".to_string(), true);
-        visibility.insert("Status".to_string(), true);
-        
-        // Default hidden columns
-        visibility.insert("Threads".to_string(), false);
-        visibility.insert("Handles".to_string(), false);
-        visibility.insert("Disk".to_string(), false);
-        visibility.insert("Network".to_string(), false);
-        visibility.insert("GPU".to_string(), false);
-        
-        visibility
+        ColumnLayout::default()
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                let column = TableColumn::from_name(&entry.key)?;
+                Some((column.label.to_string(), entry.visible))
+            })
+            .collect()
     }
 
     /// Get settings for a specific section
@@ -426,6 +478,23 @@ impl SettingsPanel {
                     enabled: self.performance_mode,
                 },
             ],
+            SettingsSection::Profiles => {
+                // One checked entry, like a radio group
+                let mut items = vec![SettingItem::Toggle {
+                    label: "Base configuration".to_string(),
+                    enabled: self.active_profile.is_none(),
+                }];
+                for name in &self.profiles {
+                    items.push(SettingItem::Toggle {
+                        label: name.clone(),
+                        enabled: self
+                            .active_profile
+                            .as_deref()
+                            .is_some_and(|active| active.eq_ignore_ascii_case(name)),
+                    });
+                }
+                items
+            },
         }
     }
 
@@ -463,6 +532,7 @@ pub enum SettingItem {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::app::config::Profile;
 
     #[test]
     fn test_settings_panel_creation() {
@@ -509,14 +579,19 @@ mod tests {
         // Default visibility
         assert!(panel.is_column_visible("Name"));
         assert!(panel.is_column_visible("CPU"));
+        assert!(!panel.is_column_visible("Leak Rate"));
         assert!(!panel.is_column_visible("Threads"));
         
         // Toggle
-        panel.toggle_column("Threads");
-        assert!(panel.is_column_visible("Threads"));
+        panel.toggle_column("Leak Rate");
+        assert!(panel.is_column_visible("leak_rate"));
         
         panel.toggle_column("CPU");
         assert!(!panel.is_column_visible("CPU"));
+
+        // Unknown columns are not offered
+        panel.toggle_column("Threads");
+        assert_eq!(panel.column_visibility.len(), TableColumn::ALL.len());
     }
 
     #[test]
@@ -540,7 +615,7 @@ mod tests {
         panel.run_at_login = true;
         
         // Save to config
-        panel.save_to_config(&config);
+        panel.save_to_config(&config).unwrap();
         
         // Load into new panel
         let mut panel2 = SettingsPanel::new();
@@ -551,6 +626,46 @@ mod tests {
         assert!(panel2.run_at_login);
     }
 
+    #[test]
+    fn test_invalid_settings_not_saved() {
+        let config = ConfigManager::new();
+        let mut panel = SettingsPanel::new();
+        panel.theme = ThemeOption::Dark;
+        panel.refresh_rate = RefreshRate::Fast100ms;
+        panel.history_length = HistoryLength::TwentyFourHours;
+
+        let errors = panel.save_to_config(&config).unwrap_err();
+        assert!(errors.for_field("monitoring.history_length_sec").is_some());
+        assert_eq!(config.get().theme.preference, Theme::System);
+        assert!(!config.is_dirty());
+
+        panel.refresh_rate = RefreshRate::Normal1s;
+        assert!(panel.validate().is_ok());
+    }
+
+    #[test]
+    fn test_profile_selection() {
+        let config = ConfigManager::new();
+        let mut app_config = config.get();
+        app_config.profiles.profiles.push(Profile::new("Presentation").with_refresh_rate_ms(2000));
+        config.apply_external(app_config);
+
+        let mut panel = SettingsPanel::new();
+        panel.load_from_config(&config);
+        assert_eq!(panel.profiles, vec!["Presentation".to_string()]);
+
+        panel.select_profile(Some("Missing"));
+        let errors = panel.save_to_config(&config).unwrap_err();
+        assert!(errors.for_field("profiles.active").is_some());
+        assert!(!config.is_dirty());
+
+        panel.select_profile(Some("Presentation"));
+        panel.save_to_config(&config).unwrap();
+        assert_eq!(config.effective().monitoring.refresh_rate_ms, 2000);
+        let items = panel.get_section_settings(SettingsSection::Profiles);
+        assert!(matches!(&items[1], SettingItem::Toggle { enabled: true, .. }));
+    }
+
     #[test]
     fn test_get_section_settings() {
         let panel = SettingsPanel::new();
@@ -568,7 +683,7 @@ mod tests {
     #[test]
     fn test_all_sections() {
         let sections = SettingsPanel::all_sections();
-        assert_eq!(sections.len(), 5);
+        assert_eq!(sections.len(), 6);
         assert_eq!(SettingsPanel::section_label(SettingsSection::Appearance), "Appearance");
     }
 }
@@ -6,10 +6,16 @@
 //! - Memory usage (used / total)
 //! - Update timestamp
 //! - Elevation status (Administrator badge)
+//!
+//! Labels and relative times come from the locale bundle, numbers and sizes
+//! from `util::format::NumberFormat`.
 
 use windows::Win32::Foundation::RECT;
 use std::time::Instant;
 
+use crate::ui::i18n::StringResources;
+use crate::util::format::NumberFormat;
+
 /// Status bar metrics and layout
 pub struct StatusBarMetrics {
     /// Status bar height
@@ -61,6 +67,10 @@ pub struct StatusBar {
     last_update: Instant,
     /// Is running as administrator
     is_elevated: bool,
+    /// Labels and relative times
+    strings: StringResources,
+    /// Separators and byte units
+    numbers: NumberFormat,
 }
 
 impl StatusBar {
@@ -75,9 +85,21 @@ impl StatusBar {
             memory_total: 0,
             last_update: Instant::now(),
             is_elevated: Self::check_elevation(),
+            strings: StringResources::default(),
+            numbers: NumberFormat::new(),
         }
     }
 
+    /// Use `strings` for labels and relative times
+    pub fn set_strings(&mut self, strings: StringResources) {
+        self.strings = strings;
+    }
+
+    /// Format counts and sizes with `numbers` (`DisplayConfig::number_format`)
+    pub fn set_number_format(&mut self, numbers: NumberFormat) {
+        self.numbers = numbers;
+    }
+
     /// Set status bar bounds
     pub fn set_bounds(&mut self, bounds: RECT) {
         self.bounds = bounds;
@@ -118,38 +140,40 @@ impl StatusBar {
 
     /// Get process count text
     pub fn process_count_text(&self) -> String {
-        format!("Processes: {}", self.process_count)
+        format!(
+            "{}: {}",
+            self.strings.get("statusbar.processes"),
+            self.numbers.integer(self.process_count as i64)
+        )
     }
 
     /// Get CPU usage text
     pub fn cpu_usage_text(&self) -> String {
-        format!("CPU: {:.0}%", self.cpu_usage)
+        format!(
+            "{}: {}%",
+            self.strings.get("statusbar.cpu"),
+            self.numbers.decimal(self.cpu_usage as f64, 0)
+        )
     }
 
     /// Get memory usage text
     pub fn memory_usage_text(&self) -> String {
-        let used_gb = self.memory_used as f64 / (1024.0 * 1024.0 * 1024.0);
-        let total_gb = self.memory_total as f64 / (1024.0 * 1024.0 * 1024.0);
-        format!("Memory: {:.1} / {:.1} GB", used_gb, total_gb)
+        format!(
+            "{}: {} / {}",
+            self.strings.get("statusbar.memory"),
+            self.numbers.bytes(self.memory_used),
+            self.numbers.bytes(self.memory_total)
+        )
     }
 
     /// Get update status text (T433)
     pub fn update_status_text(&self) -> String {
         let elapsed = self.last_update.elapsed().as_secs();
-        if elapsed == 0 {
-            "Updated just now".to_string()
-        } else if elapsed == 1 {
-            "Updated 1s ago".to_string()
-        } else if elapsed < 60 {
-            format!("Updated {}s ago", elapsed)
-        } else {
-            let minutes = elapsed / 60;
-            if minutes == 1 {
-                "Updated 1m ago".to_string()
-            } else {
-                format!("Updated {}m ago", minutes)
-            }
-        }
+        format!(
+            "{} {}",
+            self.strings.get("statusbar.updated"),
+            self.strings.relative_time(elapsed)
+        )
     }
 
     /// Get elevation status text (T434)
@@ -302,6 +326,7 @@ impl Default for StatusBar {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ui::i18n::Locale;
 
     #[test]
     fn test_status_bar_creation() {
@@ -353,6 +378,20 @@ mod tests {
         assert!(text.contains("Updated"));
     }
 
+    #[test]
+    fn test_localized_text() {
+        let mut status_bar = StatusBar::new();
+        let locale = Locale::new("de-DE");
+        status_bar.set_number_format(StringResources::new(locale.clone()).number_format());
+        status_bar.set_strings(StringResources::new(locale));
+        status_bar.set_process_count(1234);
+        status_bar.set_memory_usage(1536 * 1024 * 1024, 16 * 1024 * 1024 * 1024);
+
+        assert_eq!(status_bar.process_count_text(), "Prozesse: 1.234");
+        assert_eq!(status_bar.memory_usage_text(), "Arbeitsspeicher: 1,5 GB / 16,0 GB");
+        assert_eq!(status_bar.update_status_text(), "Aktualisiert gerade eben");
+    }
+
     #[test]
     fn test_elevation_status() {
         let status_bar = StatusBar::new();
@@ -6,12 +6,18 @@
 use windows::Win32::Foundation::{HWND, LPARAM, POINT};
 use windows::core::PCWSTR;
 use windows::Win32::UI::Shell::{
-    Shell_NotifyIconW, NIF_ICON, NIF_MESSAGE, NIF_TIP, NIM_ADD, NIM_DELETE,
-    NIM_MODIFY, NOTIFYICONDATAW,
+    Shell_NotifyIconW, NIF_ICON, NIF_INFO, NIF_MESSAGE, NIF_TIP, NIIF_ERROR, NIIF_INFO,
+    NIIF_WARNING, NIM_ADD, NIM_DELETE, NIM_MODIFY, NOTIFYICONDATAW,
 };
+
+use crate::app::alerts::{AlertEvent, AlertEventKind};
+use crate::app::commands::{CommandHandler, CommandRegistry};
+use crate::app::config::AlertSeverity;
+use crate::util::format::NumberFormat;
 use windows::Win32::UI::WindowsAndMessaging::{
     CreatePopupMenu, TrackPopupMenu, SetForegroundWindow,
     AppendMenuW, DestroyMenu, GetCursorPos, LoadIconW, HMENU,
+    InsertMenuW, DeleteMenu, MF_BYPOSITION, MF_CHECKED, MF_POPUP,
     MF_STRING, MF_SEPARATOR, TPM_LEFTALIGN, TPM_BOTTOMALIGN, TPM_RIGHTBUTTON,
     WM_APP, WM_LBUTTONDBLCLK, WM_RBUTTONUP, IDI_APPLICATION,
 };
@@ -21,6 +27,10 @@ pub struct SystemTray {
     hwnd: HWND,
     icon_data: NOTIFYICONDATAW,
     context_menu: HMENU,
+    /// Entries in the "Profile" submenu after "Base configuration"; None without submenu
+    profile_count: Option<usize>,
+    /// Formats the tooltip numbers
+    numbers: NumberFormat,
 }
 
 // Custom message IDs for tray menu
@@ -28,6 +38,10 @@ const WM_TRAYICON: u32 = WM_APP + 1;
 const ID_TRAY_SHOW: usize = 1001;
 const ID_TRAY_HIDE: usize = 1002;
 const ID_TRAY_EXIT: usize = 1003;
+/// "Base configuration"; profile `i` is `ID_TRAY_PROFILE_BASE + 1 + i`
+const ID_TRAY_PROFILE_BASE: usize = 1100;
+/// Position of the "Profile" submenu in the context menu (after Show, Hide)
+const PROFILE_MENU_POSITION: u32 = 2;
 
 impl SystemTray {
     /// Create new system tray icon (T458)
@@ -87,16 +101,20 @@ impl SystemTray {
                 hwnd,
                 icon_data,
                 context_menu: menu,
+                profile_count: None,
+                numbers: NumberFormat::new(),
             })
         }
     }
 
+    /// Format tooltip numbers with `numbers` (`DisplayConfig::number_format`)
+    pub fn set_number_format(&mut self, numbers: NumberFormat) {
+        self.numbers = numbers;
+    }
+
     /// Update tooltip with current stats (T461)
-    pub fn update_tooltip(&mut self, cpu: f32, memory_used_gb: f32, memory_total_gb: f32) {
-        let tooltip = format!(
-            "Task Manager\nCPU: {:.1}%\nMemory: {:.1}/{:.1} GB",
-            cpu, memory_used_gb, memory_total_gb
-        );
+    pub fn update_tooltip(&mut self, cpu: f32, memory_used: u64, memory_total: u64) {
+        let tooltip = tooltip_text(&self.numbers, cpu, memory_used, memory_total);
         
         let tooltip_wide: Vec<u16> = tooltip.encode_utf16().chain(std::iter::once(0)).collect();
         self.icon_data.szTip[..tooltip_wide.len().min(128)].copy_from_slice(&tooltip_wide[..tooltip_wide.len().min(128)]);
@@ -106,6 +124,101 @@ impl SystemTray {
         }
     }
 
+    /// Show a balloon notification for an alert event
+    ///
+    /// Only fired events are shown; cleared events are left to the log so the
+    /// user is not notified twice for one incident.
+    pub fn show_alert(&mut self, event: &AlertEvent) {
+        if event.kind != AlertEventKind::Fired {
+            return;
+        }
+
+        let title: Vec<u16> = event.rule.encode_utf16().chain(std::iter::once(0)).collect();
+        let text: Vec<u16> = event.message().encode_utf16().chain(std::iter::once(0)).collect();
+        let title_len = title.len().min(self.icon_data.szInfoTitle.len());
+        let text_len = text.len().min(self.icon_data.szInfo.len());
+
+        self.icon_data.szInfoTitle = [0; 64];
+        self.icon_data.szInfoTitle[..title_len].copy_from_slice(&title[..title_len]);
+        self.icon_data.szInfo = [0; 256];
+        self.icon_data.szInfo[..text_len].copy_from_slice(&text[..text_len]);
+        self.icon_data.dwInfoFlags = match event.severity {
+            AlertSeverity::Info => NIIF_INFO,
+            AlertSeverity::Warning => NIIF_WARNING,
+            AlertSeverity::Critical => NIIF_ERROR,
+        };
+
+        // NIF_INFO only for this call so later tooltip updates don't replay the balloon
+        let flags = self.icon_data.uFlags;
+        self.icon_data.uFlags = flags | NIF_INFO;
+        unsafe {
+            let _ = Shell_NotifyIconW(NIM_MODIFY, &self.icon_data);
+        }
+        self.icon_data.uFlags = flags;
+    }
+
+    /// Rebuild the "Profile" submenu with `names`, checking `active`
+    ///
+    /// Selecting an entry yields `TrayAction::SwitchProfile` with the index
+    /// into `names` (None for the base configuration).
+    pub fn set_profiles(&mut self, names: &[String], active: Option<&str>) -> Result<(), String> {
+        unsafe {
+            if self.profile_count.take().is_some() {
+                // Also destroys the old submenu
+                DeleteMenu(self.context_menu, PROFILE_MENU_POSITION, MF_BYPOSITION)
+                    .map_err(|e| format!("Failed to remove Profile menu: {:?}", e))?;
+            }
+            if names.is_empty() {
+                return Ok(());
+            }
+
+            let submenu = CreatePopupMenu()
+                .map_err(|e| format!("Failed to create menu: {:?}", e))?;
+            let flags = |is_active: bool| {
+                if is_active {
+                    MF_STRING | MF_CHECKED
+                } else {
+                    MF_STRING
+                }
+            };
+
+            let base_text: Vec<u16> = "Base configuration\0".encode_utf16().collect();
+            AppendMenuW(
+                submenu,
+                flags(active.is_none()),
+                ID_TRAY_PROFILE_BASE,
+                PCWSTR(base_text.as_ptr()),
+            )
+            .map_err(|e| format!("Failed to add profile: {:?}", e))?;
+            for (i, name) in names.iter().enumerate() {
+                let is_active = active.is_some_and(|active| active.eq_ignore_ascii_case(name));
+                let text: Vec<u16> = name.encode_utf16().chain(std::iter::once(0)).collect();
+                AppendMenuW(
+                    submenu,
+                    flags(is_active),
+                    ID_TRAY_PROFILE_BASE + 1 + i,
+                    PCWSTR(text.as_ptr()),
+                )
+                .map_err(|e| format!("Failed to add profile: {:?}", e))?;
+            }
+
+            let profile_text: Vec<u16> = "Profile\0".encode_utf16().collect();
+            InsertMenuW(
+                self.context_menu,
+                PROFILE_MENU_POSITION,
+                MF_BYPOSITION | MF_POPUP,
+                submenu.0 as usize,
+                PCWSTR(profile_text.as_ptr()),
+            )
+            .map_err(|e| {
+                let _ = DestroyMenu(submenu);
+                format!("Failed to add Profile menu: {:?}", e)
+            })?;
+            self.profile_count = Some(names.len());
+            Ok(())
+        }
+    }
+
     /// Handle tray icon messages (T459, T460, T462)
     pub fn handle_message(&self, lparam: LPARAM) -> Option<TrayAction> {
         let msg = lparam.0 as u32;
@@ -145,7 +258,7 @@ impl SystemTray {
                     } else if cmd.0 == ID_TRAY_EXIT as i32 {
                         Some(TrayAction::Exit)
                     } else {
-                        None
+                        profile_action(cmd.0, self.profile_count.unwrap_or(0))
                     }
                 }
             }
@@ -170,6 +283,16 @@ impl SystemTray {
     }
 }
 
+/// Action for a "Profile" submenu command, if `cmd` is one
+fn profile_action(cmd: i32, profile_count: usize) -> Option<TrayAction> {
+    let offset = usize::try_from(cmd).ok()?.checked_sub(ID_TRAY_PROFILE_BASE)?;
+    match offset {
+        0 => Some(TrayAction::SwitchProfile(None)),
+        n if n <= profile_count => Some(TrayAction::SwitchProfile(Some(n - 1))),
+        _ => None,
+    }
+}
+
 impl Drop for SystemTray {
     fn drop(&mut self) {
         let _ = self.remove();
@@ -187,11 +310,50 @@ pub enum TrayAction {
     ToggleWindow,
     /// Exit the application
     Exit,
+    /// Switch to the profile at this index of the `set_profiles` names; None for the base
+    SwitchProfile(Option<usize>),
+}
+
+impl TrayAction {
+    /// Id of the `app::commands` command this entry runs
+    fn command_id(&self) -> Option<&'static str> {
+        match self {
+            TrayAction::ShowWindow => Some("window.show"),
+            TrayAction::HideWindow => Some("window.hide"),
+            TrayAction::ToggleWindow => Some("window.toggle"),
+            TrayAction::Exit => Some("app.exit"),
+            TrayAction::SwitchProfile(_) => None,
+        }
+    }
+
+    /// Run this entry's command on `handler`
+    ///
+    /// Returns None for profile entries, which are dynamic and go to
+    /// `ConfigManager::switch_profile` instead.
+    pub fn dispatch(
+        &self,
+        registry: &CommandRegistry,
+        handler: &mut dyn CommandHandler,
+    ) -> Option<Result<(), String>> {
+        self.command_id().map(|id| registry.dispatch(id, handler))
+    }
+}
+
+/// Tooltip with CPU usage and memory in use out of the total (bytes)
+fn tooltip_text(numbers: &NumberFormat, cpu: f32, memory_used: u64, memory_total: u64) -> String {
+    format!(
+        "Task Manager\nCPU: {}\nMemory: {} / {}",
+        numbers.percent(cpu as f64),
+        numbers.bytes(memory_used),
+        numbers.bytes(memory_total)
+    )
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::app::commands::{Action, CommandContext};
+    use crate::test_support::CommandRecorder;
 
     #[test]
     fn test_tray_action_variants() {
@@ -201,8 +363,33 @@ mod tests {
             TrayAction::HideWindow,
             TrayAction::ToggleWindow,
             TrayAction::Exit,
+            TrayAction::SwitchProfile(None),
         ];
-        assert_eq!(actions.len(), 4);
+        assert_eq!(actions.len(), 5);
+
+        let registry = CommandRegistry::defaults();
+        let mut handler = CommandRecorder::new(CommandContext::default());
+        let show = TrayAction::ShowWindow.dispatch(&registry, &mut handler);
+        assert_eq!(show, Some(Ok(())));
+        // Hiding is disabled while the window is hidden
+        let hide = TrayAction::HideWindow.dispatch(&registry, &mut handler);
+        assert!(matches!(hide, Some(Err(_))));
+        handler.context.window_visible = true;
+        for action in &actions[1..4] {
+            assert_eq!(action.dispatch(&registry, &mut handler), Some(Ok(())));
+        }
+        assert_eq!(
+            handler.executed,
+            vec![Action::ShowWindow, Action::HideWindow, Action::ToggleWindow, Action::Exit]
+        );
+        assert_eq!(TrayAction::SwitchProfile(None).dispatch(&registry, &mut handler), None);
+    }
+
+    #[test]
+    fn test_tooltip_text() {
+        let gb = 1024 * 1024 * 1024;
+        let text = tooltip_text(&NumberFormat::new(), 12.34, 3 * gb, 16 * gb);
+        assert_eq!(text, "Task Manager\nCPU: 12.3%\nMemory: 3.0 GB / 16.0 GB");
     }
 
     #[test]
@@ -211,5 +398,16 @@ mod tests {
         assert_ne!(ID_TRAY_SHOW, ID_TRAY_HIDE);
         assert_ne!(ID_TRAY_SHOW, ID_TRAY_EXIT);
         assert_ne!(ID_TRAY_HIDE, ID_TRAY_EXIT);
+        assert!(ID_TRAY_PROFILE_BASE > ID_TRAY_EXIT);
+    }
+
+    #[test]
+    fn test_profile_action() {
+        let base = ID_TRAY_PROFILE_BASE as i32;
+        assert_eq!(profile_action(base, 2), Some(TrayAction::SwitchProfile(None)));
+        assert_eq!(profile_action(base + 2, 2), Some(TrayAction::SwitchProfile(Some(1))));
+        assert_eq!(profile_action(base + 3, 2), None);
+        assert_eq!(profile_action(ID_TRAY_EXIT as i32, 2), None);
+        assert_eq!(profile_action(0, 0), None);
     }
 }
@@ -0,0 +1,1563 @@
+//! TUI view state
+//!
+//! `TuiApp` owns everything the terminal frontend shows. It is driven by
+//! `UpdateMessage`s from the updater and decoded `KeyboardEvent`s, and draws
+//! itself into a `Screen`; it never touches the terminal directly, so the
+//! whole interface can be exercised with recorded snapshots.
+//!
+//! Shortcuts and the command palette (Ctrl+P) run commands from
+//! `app::commands`, limited to the actions a terminal can perform. Filter
+//! presets from the configuration are applied through the palette; a preset
+//! sets the filter, the sort keys and the visible columns at once.
+//!
+//! Columns follow the table's `ColumnLayout`: pinned columns stay at the left
+//! while Left/Right scroll the others. Computed columns from the
+//! configuration follow them and take part in sort cycling.
+//!
+//! Edits of the config file reach the app as `ConfigReload`s and replace the
+//! presets, columns, number format and key bindings in place. CPU spikes
+//! reported by `AnomalySink` are marked in red on the CPU meter.
+
+use std::collections::HashMap;
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use crate::app::anomaly::{AnomalyEvent, AnomalySeries};
+use crate::app::cli::{descendants, process_rows};
+use crate::app::commands::{Action, Command, CommandContext, CommandHandler, CommandRegistry};
+use crate::app::config::ConfigSection;
+use crate::app::leaks::apply_leak_rates;
+use crate::app::policies::ProcessActuator;
+use crate::app::reload::ConfigReload;
+use crate::app::updater::UpdateMessage;
+use crate::core::expression::ComputedColumns;
+use crate::core::filter::{
+    self, ProcessFilter, ProcessFilterSort, ProcessSorter, SortColumn, SortDirection,
+};
+use crate::core::leak::LeakDetector;
+use crate::ui::controls::filter_box::FilterPreset;
+use crate::ui::controls::graph::CircularBuffer;
+use crate::ui::controls::table::{self, ColumnAlignment, ColumnLayout, ProcessTable, TableColumn};
+use crate::ui::input::{KeyboardEvent, Shortcut};
+use crate::ui::keymap::{active_keymap, install_keymap, KeyResolution, KeymapResolver};
+use crate::util::format::NumberFormat;
+use crate::windows::monitor::ProcessSnapshot;
+use crate::windows::process::control::PriorityClass;
+
+use super::keys::{
+    VK_BACK, VK_DOWN, VK_END, VK_F1, VK_HOME, VK_LEFT, VK_NEXT, VK_PRIOR, VK_RIGHT, VK_UP,
+};
+use super::screen::{sparkline, Color, Screen, Style};
+
+/// Samples kept for the header sparklines
+const HISTORY_LEN: usize = 120;
+
+/// Meter rows, status row and column header above the list
+const HEADER_ROWS: u16 = 4;
+
+/// Matches shown at once in the command palette
+const PALETTE_ROWS: usize = 8;
+
+/// Order used when cycling the sort column
+const SORT_ORDER: [SortColumn; 5] = [
+    SortColumn::Cpu,
+    SortColumn::Memory,
+    SortColumn::Pid,
+    SortColumn::Name,
+    SortColumn::Handles,
+];
+
+/// Layout width in pixels per terminal cell
+const PIXELS_PER_CELL: i32 = 10;
+
+/// Width of a computed column in cells, unless its name needs more
+const CUSTOM_COLUMN_CELLS: usize = 10;
+
+/// Entry of the kill/priority menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuAction {
+    Terminate,
+    SetPriority(PriorityClass),
+    /// Terminate with all descendants, children first
+    TerminateTree,
+}
+
+impl MenuAction {
+    pub const ALL: [MenuAction; 7] = [
+        MenuAction::Terminate,
+        MenuAction::SetPriority(PriorityClass::Idle),
+        MenuAction::SetPriority(PriorityClass::BelowNormal),
+        MenuAction::SetPriority(PriorityClass::Normal),
+        MenuAction::SetPriority(PriorityClass::AboveNormal),
+        MenuAction::SetPriority(PriorityClass::High),
+        MenuAction::TerminateTree,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MenuAction::Terminate => "End process",
+            MenuAction::SetPriority(PriorityClass::Idle) => "Priority: Idle",
+            MenuAction::SetPriority(PriorityClass::BelowNormal) => "Priority: Below normal",
+            MenuAction::SetPriority(PriorityClass::Normal) => "Priority: Normal",
+            MenuAction::SetPriority(PriorityClass::AboveNormal) => "Priority: Above normal",
+            MenuAction::SetPriority(PriorityClass::High) => "Priority: High",
+            MenuAction::SetPriority(PriorityClass::Realtime) => "Priority: Realtime",
+            MenuAction::TerminateTree => "End process tree",
+        }
+    }
+}
+
+/// Input mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Normal,
+    /// Typing into the incremental filter
+    Filter,
+    /// Kill/priority menu open with the given entry highlighted
+    Menu(usize),
+    /// Command palette open with the given match highlighted
+    Palette(usize),
+}
+
+/// Terminal frontend state
+pub struct TuiApp {
+    actuator: Box<dyn ProcessActuator>,
+    table: ProcessTable,
+    mode: Mode,
+    filter_text: String,
+    tree: bool,
+    /// Chord in progress in normal mode
+    keys: KeymapResolver,
+    /// Commands reachable from shortcuts and the palette
+    commands: CommandRegistry,
+    palette_query: String,
+    /// Filter presets in menu order
+    presets: Vec<FilterPreset>,
+    /// Name and filter of the applied preset
+    preset: Option<(String, ProcessFilter)>,
+    /// Sort keys after the table's sort column, from the applied preset
+    tie_breakers: Vec<(SortColumn, SortDirection)>,
+    /// Columns the applied preset shows besides the name; all when empty
+    visible_columns: Vec<SortColumn>,
+    /// Computed columns drawn after the layout's columns
+    columns: ComputedColumns,
+    /// Source of the Leak Rate column, if leak detection runs
+    leaks: Option<Arc<Mutex<LeakDetector>>>,
+    previous: Option<ProcessSnapshot>,
+    /// All processes from the latest snapshot, with CPU %
+    processes: Vec<filter::ProcessInfo>,
+    /// Visible rows in display order
+    rows: Vec<table::ProcessInfo>,
+    /// Tree guides drawn before each row's name (empty in list mode)
+    prefixes: Vec<String>,
+    cpu_history: CircularBuffer,
+    memory_history: CircularBuffer,
+    /// Spikes from `AnomalySink`, if spike detection runs
+    anomalies: Option<Receiver<AnomalyEvent>>,
+    /// Snapshot times of reported system CPU spikes not yet drawn
+    cpu_spikes: Vec<Instant>,
+    cpu_total: f32,
+    memory_used: u64,
+    memory_total: u64,
+    /// Separators and byte units for meters and cells
+    numbers: NumberFormat,
+    /// Number of list rows in the last rendered frame
+    list_height: usize,
+    status: Option<String>,
+    quit: bool,
+}
+
+impl TuiApp {
+    pub fn new(actuator: Box<dyn ProcessActuator>) -> Self {
+        Self {
+            actuator,
+            table: ProcessTable::new(),
+            mode: Mode::Normal,
+            filter_text: String::new(),
+            tree: false,
+            keys: KeymapResolver::new(),
+            commands: terminal_commands(),
+            palette_query: String::new(),
+            presets: FilterPreset::defaults(),
+            preset: None,
+            tie_breakers: Vec::new(),
+            visible_columns: Vec::new(),
+            columns: ComputedColumns::default(),
+            leaks: None,
+            previous: None,
+            processes: Vec::new(),
+            rows: Vec::new(),
+            prefixes: Vec::new(),
+            cpu_history: CircularBuffer::new(HISTORY_LEN),
+            memory_history: CircularBuffer::new(HISTORY_LEN),
+            anomalies: None,
+            cpu_spikes: Vec::new(),
+            cpu_total: 0.0,
+            memory_used: 0,
+            memory_total: 0,
+            numbers: NumberFormat::new(),
+            list_height: 1,
+            status: None,
+            quit: false,
+        }
+    }
+
+    /// Draw the columns of `layout` (e.g. `AppConfig::columns`)
+    pub fn with_column_layout(mut self, layout: ColumnLayout) -> Self {
+        self.table.set_layout(layout);
+        self
+    }
+
+    /// Format numbers with `numbers` (e.g. `DisplayConfig::number_format`)
+    pub fn with_number_format(mut self, numbers: NumberFormat) -> Self {
+        self.numbers = numbers;
+        self.table.set_number_format(numbers);
+        self
+    }
+
+    /// Add computed columns (e.g. from `AppConfig::custom_columns`)
+    pub fn with_custom_columns(mut self, columns: ComputedColumns) -> Self {
+        self.columns = columns;
+        self.columns.apply(&mut self.processes);
+        self.refresh();
+        self
+    }
+
+    /// Fill the Leak Rate column from `LeakMonitor::detector`
+    pub fn with_leak_detector(mut self, detector: Arc<Mutex<LeakDetector>>) -> Self {
+        self.leaks = Some(detector);
+        self
+    }
+
+    /// Mark CPU spikes sent by `AnomalySink::with_sender` on the CPU meter
+    pub fn with_anomalies(mut self, events: Receiver<AnomalyEvent>) -> Self {
+        self.anomalies = Some(events);
+        self
+    }
+
+    /// Use `presets` (e.g. `AppConfig::filter_presets`) instead of the built-ins
+    pub fn with_filter_presets(mut self, presets: Vec<FilterPreset>) -> Self {
+        self.commands.set_filter_presets(&presets);
+        self.presets = presets;
+        self
+    }
+
+    pub fn should_quit(&self) -> bool {
+        self.quit
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    pub fn is_tree(&self) -> bool {
+        self.tree
+    }
+
+    /// PIDs of the visible rows in display order
+    pub fn visible_pids(&self) -> Vec<u32> {
+        self.rows.iter().map(|row| row.pid).collect()
+    }
+
+    /// Selected PIDs in display order
+    pub fn selected_pids(&self) -> Vec<u32> {
+        let selected = self.table.selected_pids();
+        self.rows
+            .iter()
+            .map(|row| row.pid)
+            .filter(|pid| selected.contains(pid))
+            .collect()
+    }
+
+    /// Last status line message (action results, updater errors)
+    pub fn status(&self) -> Option<&str> {
+        self.status.as_deref()
+    }
+
+    /// Apply a message from the updater channel
+    pub fn handle_update(&mut self, message: UpdateMessage) {
+        match message {
+            UpdateMessage::Snapshot(snapshot) => {
+                let metrics = &snapshot.system_metrics;
+                let cpus = match metrics.cpu_cores.len() {
+                    0 => num_cpus::get(),
+                    n => n,
+                };
+                self.processes = match &self.previous {
+                    Some(previous) => process_rows(previous, &snapshot, cpus),
+                    None => process_rows(&snapshot, &snapshot, cpus),
+                };
+                if let Some(detector) = &self.leaks {
+                    apply_leak_rates(&mut self.processes, detector);
+                }
+                self.columns.apply(&mut self.processes);
+
+                self.cpu_total = metrics.cpu_total;
+                self.memory_total = metrics.memory_total;
+                self.memory_used = metrics
+                    .memory_total
+                    .saturating_sub(metrics.memory_available);
+                let spike = self.take_cpu_spike(snapshot.timestamp);
+                self.cpu_history.push_flagged(metrics.cpu_total, spike);
+                self.memory_history
+                    .push(percent(self.memory_used, self.memory_total));
+
+                self.previous = Some(snapshot);
+                self.refresh();
+            }
+            UpdateMessage::Error(error) => self.status = Some(format!("Update failed: {}", error)),
+            UpdateMessage::Shutdown => self.status = Some("Updates stopped".to_string()),
+        }
+    }
+
+    /// Whether a system CPU spike was reported for the snapshot taken at
+    /// `timestamp`
+    ///
+    /// Sinks run before a snapshot is forwarded, so its events are already
+    /// queued; events for snapshots not handled yet are kept for later.
+    fn take_cpu_spike(&mut self, timestamp: Instant) -> bool {
+        if let Some(events) = &self.anomalies {
+            for event in events.try_iter() {
+                if event.series == AnomalySeries::SystemCpu {
+                    self.cpu_spikes.push(event.timestamp);
+                }
+            }
+        }
+        let spike = self.cpu_spikes.contains(&timestamp);
+        self.cpu_spikes.retain(|spike| *spike > timestamp);
+        spike
+    }
+
+    /// Take over the sections of a reloaded configuration this view uses
+    ///
+    /// The file was validated before the reload was sent, so invalid computed
+    /// columns or key bindings cannot occur here; they would be skipped.
+    pub fn apply_config(&mut self, reload: &ConfigReload) {
+        let config = &reload.config;
+        if reload.affects(ConfigSection::FilterPresets) {
+            self.commands.set_filter_presets(&config.filter_presets);
+            self.presets = config.filter_presets.clone();
+        }
+        if reload.affects(ConfigSection::Columns) {
+            self.table.set_layout(config.columns.clone());
+        }
+        if reload.affects(ConfigSection::Display) {
+            self.numbers = config.display.number_format();
+            self.table.set_number_format(self.numbers);
+        }
+        if reload.affects(ConfigSection::CustomColumns) {
+            if let Ok(columns) = ComputedColumns::new(&config.custom_columns) {
+                self.columns = columns;
+                self.columns.apply(&mut self.processes);
+            }
+        }
+        if reload.affects(ConfigSection::Keymap) {
+            let _ = install_keymap(&config.keymap);
+            self.keys = KeymapResolver::new();
+        }
+        self.refresh();
+    }
+
+    /// Rebuild the visible rows after data, filter, sort or tree changes
+    fn refresh(&mut self) {
+        let mut filter = match &self.preset {
+            Some((_, filter)) => filter.clone(),
+            None => ProcessFilter::new(),
+        };
+        if !self.filter_text.is_empty() {
+            filter = filter.with_name(self.filter_text.clone());
+        }
+        let (column, direction) = self.table.sort_state();
+        let mut sorter = ProcessSorter::new(column, direction);
+        for &(column, direction) in &self.tie_breakers {
+            sorter = sorter.then_by(column, direction);
+        }
+        let engine = ProcessFilterSort::new(filter, sorter);
+        let matched = engine.apply(&self.processes);
+
+        let order: Vec<(usize, String)> = if self.tree {
+            tree_order(&matched)
+        } else {
+            (0..matched.len()).map(|i| (i, String::new())).collect()
+        };
+        self.rows = order.iter().map(|(i, _)| table_row(matched[*i])).collect();
+        self.prefixes = order.into_iter().map(|(_, prefix)| prefix).collect();
+
+        let selected = self.table.selected_pids();
+        if !self.rows.iter().any(|row| selected.contains(&row.pid)) {
+            self.select_index(0);
+        }
+        self.keep_cursor_visible();
+    }
+
+    /// Handle one decoded key
+    pub fn handle_key(&mut self, event: &KeyboardEvent) {
+        match self.mode {
+            Mode::Normal => self.normal_key(event),
+            Mode::Filter => self.filter_key(event),
+            Mode::Menu(index) => self.menu_key(index, event),
+            Mode::Palette(index) => self.palette_key(index, event),
+        }
+        self.keep_cursor_visible();
+    }
+
+    fn normal_key(&mut self, event: &KeyboardEvent) {
+        match self.keys.feed(&active_keymap(), event) {
+            KeyResolution::Command(Shortcut::CommandPalette) => {
+                self.palette_query.clear();
+                self.mode = Mode::Palette(0);
+                return;
+            }
+            KeyResolution::Command(Shortcut::Escape) => {
+                self.status = None;
+                return;
+            }
+            KeyResolution::Command(shortcut) => {
+                let command = self.commands.for_shortcut(shortcut);
+                if let Some(id) = command.map(|command| command.id.clone()) {
+                    self.run_command(&id);
+                    return;
+                }
+            }
+            // Wait for the rest of the chord
+            KeyResolution::Pending => return,
+            KeyResolution::Unbound => {}
+        }
+
+        match *event {
+            KeyboardEvent::KeyDown {
+                vkey, modifiers, ..
+            } => {
+                if !self.navigate(vkey, modifiers.ctrl) {
+                    match vkey {
+                        VK_LEFT => self.table.scroll_columns(-1),
+                        VK_RIGHT => self.table.scroll_columns(1),
+                        // F6 like htop: next sort column
+                        v if v == VK_F1 + 5 => self.cycle_sort(1),
+                        // F9 like htop: kill menu
+                        v if v == VK_F1 + 8 => self.run_command("process.end"),
+                        _ => {}
+                    }
+                }
+            }
+            KeyboardEvent::Char { character, .. } => match character {
+                'q' => self.run_command("app.exit"),
+                '/' => self.run_command("view.find"),
+                't' => self.run_command("view.toggle_tree"),
+                '>' | '.' => self.cycle_sort(1),
+                '<' | ',' => self.cycle_sort(-1),
+                'r' => {
+                    let (column, _) = self.table.sort_state();
+                    self.table.on_header_click(column);
+                    self.refresh();
+                }
+                'k' => self.run_command("process.end"),
+                _ => {}
+            },
+            KeyboardEvent::KeyUp { .. } => {}
+        }
+    }
+
+    fn filter_key(&mut self, event: &KeyboardEvent) {
+        match Shortcut::from_keyboard_event(event) {
+            Some(Shortcut::Escape) => {
+                self.filter_text.clear();
+                self.mode = Mode::Normal;
+                self.refresh();
+                return;
+            }
+            Some(Shortcut::Enter) => {
+                self.mode = Mode::Normal;
+                return;
+            }
+            _ => {}
+        }
+
+        match *event {
+            KeyboardEvent::Char { character, .. } => {
+                self.filter_text.push(character);
+                self.refresh();
+            }
+            KeyboardEvent::KeyDown { vkey: VK_BACK, .. } => {
+                self.filter_text.pop();
+                self.refresh();
+            }
+            KeyboardEvent::KeyDown {
+                vkey, modifiers, ..
+            } => {
+                self.navigate(vkey, modifiers.ctrl);
+            }
+            KeyboardEvent::KeyUp { .. } => {}
+        }
+    }
+
+    fn menu_key(&mut self, index: usize, event: &KeyboardEvent) {
+        match Shortcut::from_keyboard_event(event) {
+            Some(Shortcut::Escape) => {
+                self.mode = Mode::Normal;
+                return;
+            }
+            Some(Shortcut::Enter) => {
+                self.mode = Mode::Normal;
+                self.run_action(MenuAction::ALL[index]);
+                return;
+            }
+            _ => {}
+        }
+
+        if let KeyboardEvent::KeyDown { vkey, .. } = *event {
+            let last = MenuAction::ALL.len() - 1;
+            match vkey {
+                VK_UP => self.mode = Mode::Menu(index.saturating_sub(1)),
+                VK_DOWN => self.mode = Mode::Menu((index + 1).min(last)),
+                _ => {}
+            }
+        }
+    }
+
+    fn palette_key(&mut self, index: usize, event: &KeyboardEvent) {
+        match Shortcut::from_keyboard_event(event) {
+            Some(Shortcut::Escape) => {
+                self.mode = Mode::Normal;
+                return;
+            }
+            Some(Shortcut::Enter) => {
+                self.mode = Mode::Normal;
+                let chosen = self.palette_matches().get(index).map(|c| c.id.clone());
+                if let Some(id) = chosen {
+                    self.run_command(&id);
+                }
+                return;
+            }
+            _ => {}
+        }
+
+        match *event {
+            KeyboardEvent::Char { character, .. } => {
+                self.palette_query.push(character);
+                self.mode = Mode::Palette(0);
+            }
+            KeyboardEvent::KeyDown { vkey: VK_BACK, .. } => {
+                self.palette_query.pop();
+                self.mode = Mode::Palette(0);
+            }
+            KeyboardEvent::KeyDown { vkey: VK_UP, .. } => {
+                self.mode = Mode::Palette(index.saturating_sub(1));
+            }
+            KeyboardEvent::KeyDown { vkey: VK_DOWN, .. } => {
+                let last = self.palette_matches().len().saturating_sub(1);
+                self.mode = Mode::Palette((index + 1).min(last));
+            }
+            _ => {}
+        }
+    }
+
+    /// Enabled commands matching the palette query, best first
+    fn palette_matches(&self) -> Vec<&Command> {
+        let context = self.context();
+        self.commands
+            .search(&self.palette_query)
+            .into_iter()
+            .filter(|command| command.is_enabled(&context))
+            .collect()
+    }
+
+    /// Run a registered command, reporting failures in the status line
+    fn run_command(&mut self, id: &str) {
+        let result = self
+            .commands
+            .resolve(id, &self.context())
+            .and_then(|action| self.execute(action));
+        if let Err(error) = result {
+            self.status = Some(error);
+        }
+    }
+
+    /// Cursor movement; returns false if `vkey` is not a navigation key
+    fn navigate(&mut self, vkey: u16, ctrl: bool) -> bool {
+        let cursor = self.cursor_index().unwrap_or(0);
+        let page = self.list_height.max(1);
+        match vkey {
+            VK_UP | VK_DOWN => self.table.on_key_down(vkey as u32, ctrl, &self.rows),
+            VK_PRIOR => self.select_index(cursor.saturating_sub(page)),
+            VK_NEXT => self.select_index((cursor + page).min(self.rows.len().saturating_sub(1))),
+            VK_HOME => self.select_index(0),
+            VK_END => self.select_index(self.rows.len().saturating_sub(1)),
+            _ => return false,
+        }
+        true
+    }
+
+    fn select_index(&mut self, index: usize) {
+        if let Some(pid) = self.rows.get(index).map(|row| row.pid) {
+            self.table.on_row_click(pid, false, false, &self.rows);
+        }
+    }
+
+    /// Index of the first selected row
+    fn cursor_index(&self) -> Option<usize> {
+        let selected = self.table.selected_pids();
+        self.rows.iter().position(|row| selected.contains(&row.pid))
+    }
+
+    fn keep_cursor_visible(&mut self) {
+        let height = self.list_height.max(1);
+        let max_offset = self.rows.len().saturating_sub(height);
+        let mut offset = self.table.scroll_offset().min(max_offset);
+        if let Some(cursor) = self.cursor_index() {
+            if cursor < offset {
+                offset = cursor;
+            } else if cursor >= offset + height {
+                offset = cursor + 1 - height;
+            }
+        }
+        self.table.set_scroll_offset(offset);
+    }
+
+    /// Move the sort column `step` places through `SORT_ORDER`, then the computed columns
+    fn cycle_sort(&mut self, step: isize) {
+        let order: Vec<SortColumn> = SORT_ORDER
+            .into_iter()
+            .chain((0..self.columns.len()).map(SortColumn::Custom))
+            .collect();
+        let (column, _) = self.table.sort_state();
+        let position = order.iter().position(|c| *c == column).unwrap_or(0);
+        let next = (position as isize + step).rem_euclid(order.len() as isize);
+        self.table.on_header_click(order[next as usize]);
+        self.refresh();
+    }
+
+    /// Apply the filter, sort keys and columns of preset `index`
+    fn apply_preset(&mut self, index: usize) -> Result<(), String> {
+        let preset = self
+            .presets
+            .get(index)
+            .ok_or_else(|| format!("No filter preset {}", index + 1))?;
+        let invalid = |e: String| format!("Preset '{}': {}", preset.name, e);
+        let filter = preset.filter().map_err(invalid)?;
+        let keys = preset.sort_keys().map_err(invalid)?;
+        let columns = preset.visible_columns().map_err(invalid)?;
+
+        if let Some(&(column, direction)) = keys.first() {
+            self.table.set_sort(column, direction);
+        }
+        self.tie_breakers = keys.into_iter().skip(1).collect();
+        self.visible_columns = columns;
+        self.preset = Some((preset.name.clone(), filter));
+        self.refresh();
+        Ok(())
+    }
+
+    /// Columns to draw, with their widths in cells
+    ///
+    /// Every column keeps room for its label and sort arrow. The applied
+    /// preset can hide columns, but not the name.
+    fn shown_columns(&self) -> Vec<(TableColumn, u16)> {
+        self.table
+            .displayed_columns()
+            .into_iter()
+            .filter(|(column, _)| {
+                column.id == SortColumn::Name
+                    || self.visible_columns.is_empty()
+                    || self.visible_columns.contains(&column.id)
+            })
+            .map(|(column, entry)| {
+                let label = column.label.chars().count() as i32 + 3;
+                let cells = (entry.width / PIXELS_PER_CELL).max(label);
+                (column, cells.min(u16::MAX as i32) as u16)
+            })
+            .collect()
+    }
+
+    /// Computed columns to draw, with their widths in cells
+    ///
+    /// A preset that lists columns hides them.
+    fn shown_custom_columns(&self) -> Vec<(usize, u16)> {
+        if !self.visible_columns.is_empty() {
+            return Vec::new();
+        }
+        self.columns
+            .definitions()
+            .enumerate()
+            .map(|(index, column)| {
+                let cells = (column.name.chars().count() + 3).max(CUSTOM_COLUMN_CELLS);
+                (index, cells.min(u16::MAX as usize) as u16)
+            })
+            .collect()
+    }
+
+    /// Header label of `column`, including computed columns
+    fn column_label(&self, column: SortColumn) -> String {
+        match column {
+            SortColumn::Custom(index) => self
+                .columns
+                .definitions()
+                .nth(index)
+                .map(|column| column.name.clone())
+                .unwrap_or_default(),
+            column => TableColumn::for_id(column).label.to_string(),
+        }
+    }
+
+    /// Header text of computed column `index`, with the sort arrow
+    fn custom_header_text(&self, index: usize) -> String {
+        let label = self.column_label(SortColumn::Custom(index));
+        match self.table.sort_state() {
+            (SortColumn::Custom(sorted), SortDirection::Ascending) if sorted == index => {
+                format!("{} ▲", label)
+            }
+            (SortColumn::Custom(sorted), SortDirection::Descending) if sorted == index => {
+                format!("{} ▼", label)
+            }
+            _ => label,
+        }
+    }
+
+    fn toggle_tree(&mut self) {
+        self.tree = !self.tree;
+        self.refresh();
+    }
+
+    /// Open the kill/priority menu with `action` highlighted
+    fn open_menu(&mut self, action: MenuAction) {
+        if self.selected_pids().is_empty() {
+            self.status = Some("No process selected".to_string());
+        } else {
+            let index = MenuAction::ALL.iter().position(|a| *a == action);
+            self.mode = Mode::Menu(index.unwrap_or(0));
+        }
+    }
+
+    /// Apply a menu action to every selected process
+    fn run_action(&mut self, action: MenuAction) {
+        let mut targets = self.selected_pids();
+        if action == MenuAction::TerminateTree {
+            targets = self.with_descendants(&targets);
+        }
+        let mut succeeded = 0;
+        let mut first_error = None;
+        for pid in &targets {
+            let result = match action {
+                MenuAction::Terminate | MenuAction::TerminateTree => self.actuator.terminate(*pid),
+                MenuAction::SetPriority(class) => self.actuator.set_priority(*pid, class),
+            };
+            match result {
+                Ok(()) => succeeded += 1,
+                Err(error) => {
+                    first_error.get_or_insert_with(|| {
+                        format!("PID {}: {}", pid, error.short_description())
+                    });
+                }
+            }
+        }
+
+        let noun = if succeeded == 1 {
+            "process"
+        } else {
+            "processes"
+        };
+        let done = match action {
+            MenuAction::Terminate | MenuAction::TerminateTree => {
+                format!("Ended {} {}", succeeded, noun)
+            }
+            MenuAction::SetPriority(_) => format!("{} for {} {}", action.label(), succeeded, noun),
+        };
+        self.status = Some(match first_error {
+            Some(error) => format!("{} ({} failed: {})", done, targets.len() - succeeded, error),
+            None => done,
+        });
+    }
+
+    /// `pids` preceded by all their descendants, children before parents
+    fn with_descendants(&self, pids: &[u32]) -> Vec<u32> {
+        let mut ordered: Vec<u32> = Vec::new();
+        for pid in pids {
+            let tree = match &self.previous {
+                Some(snapshot) => descendants(snapshot, *pid),
+                None => Vec::new(),
+            };
+            for victim in tree.into_iter().chain([*pid]) {
+                if !ordered.contains(&victim) {
+                    ordered.push(victim);
+                }
+            }
+        }
+        ordered
+    }
+
+    /// Draw the whole interface
+    pub fn render(&mut self, screen: &mut Screen) {
+        let (width, height) = (screen.width(), screen.height());
+        if width < 40 || height < HEADER_ROWS + 2 {
+            screen.put_str(0, 0, "Terminal too small", Style::PLAIN);
+            return;
+        }
+        self.list_height = (height - HEADER_ROWS - 1) as usize;
+        self.keep_cursor_visible();
+
+        self.draw_meters(screen);
+        self.draw_summary(screen);
+        self.draw_table(screen);
+        self.draw_footer(screen);
+        match self.mode {
+            Mode::Menu(index) => self.draw_menu(screen, index),
+            Mode::Palette(index) => self.draw_palette(screen, index),
+            _ => {}
+        }
+    }
+
+    fn draw_meters(&self, screen: &mut Screen) {
+        let width = screen.width() as usize;
+        let meters = [
+            (
+                "CPU ",
+                self.cpu_history.to_vec(),
+                self.cpu_history.flagged_indices(),
+                format!(" {:>5}%", self.numbers.decimal(self.cpu_total as f64, 1)),
+                Color::Green,
+            ),
+            (
+                "Mem ",
+                self.memory_history.to_vec(),
+                Vec::new(),
+                format!(
+                    " {} / {}",
+                    self.numbers.bytes(self.memory_used),
+                    self.numbers.bytes(self.memory_total)
+                ),
+                Color::Cyan,
+            ),
+        ];
+
+        for (y, (label, history, flagged, value, color)) in meters.into_iter().enumerate() {
+            let y = y as u16;
+            let spark_width = width.saturating_sub(label.len() + value.chars().count());
+            let mut x = screen.put_str(0, y, label, Style::PLAIN.bold());
+            let line = sparkline(&history, 100.0, spark_width);
+            let drawn = screen.put_str(x, y, &line, Style::fg(color));
+            // Redraw flagged samples; the sparkline ends with the newest value
+            let bars: Vec<char> = line.chars().collect();
+            for index in flagged {
+                if let Some(column) = (bars.len() + index).checked_sub(history.len()) {
+                    let bar = bars[column].to_string();
+                    screen.put_str(x + column as u16, y, &bar, Style::fg(Color::Red).bold());
+                }
+            }
+            x += drawn;
+            screen.put_str(x, y, &value, Style::PLAIN.bold());
+        }
+    }
+
+    fn draw_summary(&self, screen: &mut Screen) {
+        let (column, direction) = self.table.sort_state();
+        let arrow = match direction {
+            SortDirection::Ascending => "▲",
+            SortDirection::Descending => "▼",
+        };
+        let label = self.column_label(column);
+        let mut summary = format!(
+            "Tasks: {}  Shown: {}  Sort: {} {}  Tree: {}",
+            self.numbers.integer(self.processes.len() as i64),
+            self.numbers.integer(self.rows.len() as i64),
+            label,
+            arrow,
+            if self.tree { "on" } else { "off" }
+        );
+        if let Some((name, _)) = &self.preset {
+            summary.push_str(&format!("  Preset: {}", name));
+        }
+        if !self.filter_text.is_empty() {
+            summary.push_str(&format!("  Filter: {}", self.filter_text));
+        }
+        screen.put_str(0, 2, &summary, Style::PLAIN);
+    }
+
+    fn draw_table(&self, screen: &mut Screen) {
+        let layout = self.table.layout();
+        let mut columns = self.shown_columns();
+        let custom = self.shown_custom_columns();
+        // A left-aligned last column (e.g. the name) takes the rest of the line
+        let used: u16 = columns.iter().map(|(_, width)| width).sum();
+        if let Some((column, width)) = columns.last_mut().filter(|_| custom.is_empty()) {
+            if layout.alignment(column) == ColumnAlignment::Left {
+                *width += screen.width().saturating_sub(used);
+            }
+        }
+
+        let header = HEADER_ROWS - 1;
+        screen.fill_row(header, Style::reversed());
+        let mut x = 0;
+        for (column, width) in &columns {
+            let text = self.table.format_header_text(column);
+            let text = table::fit(&text, *width as usize - 1, layout.alignment(column));
+            x += screen.put_str(x, header, &text, Style::reversed()) + 1;
+        }
+        for (index, width) in &custom {
+            let text = self.custom_header_text(*index);
+            let text = table::fit(&text, *width as usize - 1, ColumnAlignment::Right);
+            x += screen.put_str(x, header, &text, Style::reversed()) + 1;
+        }
+
+        let selected = self.table.selected_pids();
+        let offset = self.table.scroll_offset();
+        for (line, index) in (offset..self.rows.len()).take(self.list_height).enumerate() {
+            let y = HEADER_ROWS + line as u16;
+            let row = &self.rows[index];
+            let style = if selected.contains(&row.pid) {
+                Style::fg(Color::Black).on(Color::Cyan)
+            } else {
+                Style::PLAIN
+            };
+            screen.fill_row(y, style);
+
+            let mut x = 0;
+            for (column, width) in &columns {
+                let text = match column.id {
+                    SortColumn::Name => format!("{}{}", self.prefixes[index], row.name),
+                    id => self.table.format_cell_text(id, row),
+                };
+                let text = table::fit(&text, *width as usize - 1, layout.alignment(column));
+                x += screen.put_str(x, y, &text, style) + 1;
+            }
+            for (index, width) in &custom {
+                let text = self.table.format_cell_text(SortColumn::Custom(*index), row);
+                let text = table::fit(&text, *width as usize - 1, ColumnAlignment::Right);
+                x += screen.put_str(x, y, &text, style) + 1;
+            }
+        }
+    }
+
+    fn draw_footer(&self, screen: &mut Screen) {
+        let y = screen.height() - 1;
+        let (text, style) = match (&self.mode, &self.status) {
+            (Mode::Filter, _) => (
+                format!("Filter: {}_   Enter done  Esc clear", self.filter_text),
+                Style::fg(Color::Yellow),
+            ),
+            (Mode::Palette(_), _) => (
+                "Type to search   Up/Down select  Enter run  Esc close".to_string(),
+                Style::fg(Color::Yellow),
+            ),
+            (_, Some(status)) => (status.clone(), Style::fg(Color::Yellow).bold()),
+            _ => (
+                "q Quit  / Filter  t Tree  </> Sort  r Reverse  Del Kill  ^P Commands".to_string(),
+                Style::PLAIN,
+            ),
+        };
+        screen.put_str(0, y, &text, style);
+    }
+
+    fn draw_menu(&self, screen: &mut Screen, highlighted: usize) {
+        let targets = self.selected_pids();
+        let title = match targets.as_slice() {
+            [pid] => {
+                let name = self
+                    .rows
+                    .iter()
+                    .find(|r| r.pid == *pid)
+                    .map_or("", |r| r.name.as_str());
+                format!(" {} ({}) ", name, pid)
+            }
+            _ => format!(" {} processes ", targets.len()),
+        };
+
+        let inner = MenuAction::ALL
+            .iter()
+            .map(|a| a.label().chars().count())
+            .chain([title.chars().count()])
+            .max()
+            .unwrap_or(0) as u16
+            + 2;
+        let height = MenuAction::ALL.len() as u16 + 2;
+        let left = screen.width().saturating_sub(inner + 2) / 2;
+        let top = screen.height().saturating_sub(height) / 2;
+        let frame = Style::fg(Color::White).on(Color::Blue);
+
+        let border = "─".repeat(inner as usize);
+        screen.put_str(left, top, &format!("┌{}┐", border), frame);
+        screen.put_str(left + 2, top, &title, frame.bold());
+        for (i, action) in MenuAction::ALL.iter().enumerate() {
+            let y = top + 1 + i as u16;
+            let style = if i == highlighted {
+                Style::fg(Color::Black).on(Color::Cyan)
+            } else {
+                frame
+            };
+            screen.put_str(left, y, "│", frame);
+            screen.fill(left + 1, y, inner, style);
+            screen.put_str(left + 2, y, action.label(), style);
+            screen.put_str(left + 1 + inner, y, "│", frame);
+        }
+        screen.put_str(left, top + height - 1, &format!("└{}┘", border), frame);
+    }
+
+    fn draw_palette(&self, screen: &mut Screen, highlighted: usize) {
+        let matches = self.palette_matches();
+        let inner = screen.width().saturating_sub(4).min(60);
+        let rows = PALETTE_ROWS.min(screen.height().saturating_sub(5) as usize);
+        let left = screen.width().saturating_sub(inner + 2) / 2;
+        let top = 1;
+        let frame = Style::fg(Color::White).on(Color::Blue);
+
+        let border = "─".repeat(inner as usize);
+        screen.put_str(left, top, &format!("┌{}┐", border), frame);
+        screen.put_str(left + 2, top, " Commands ", frame.bold());
+
+        let query = format!("> {}_", self.palette_query);
+        let mut lines = vec![(query, String::new(), frame.bold())];
+        if matches.is_empty() {
+            lines.push(("No matching commands".to_string(), String::new(), frame));
+        }
+        // Scroll so the highlighted match stays visible
+        let offset = (highlighted + 1).saturating_sub(rows);
+        for (i, command) in matches.iter().enumerate().skip(offset).take(rows) {
+            let style = if i == highlighted {
+                Style::fg(Color::Black).on(Color::Cyan)
+            } else {
+                frame
+            };
+            let keys = command.shortcut.map(|s| s.to_string()).unwrap_or_default();
+            lines.push((command.title.clone(), keys, style));
+        }
+
+        for (i, (text, keys, style)) in lines.iter().enumerate() {
+            let y = top + 1 + i as u16;
+            screen.put_str(left, y, "│", frame);
+            screen.fill(left + 1, y, inner, *style);
+            screen.put_str(left + 2, y, text, *style);
+            let keys_width = keys.chars().count() as u16;
+            screen.put_str(left + inner - keys_width, y, keys, *style);
+            screen.put_str(left + 1 + inner, y, "│", frame);
+        }
+        let bottom = top + 1 + lines.len() as u16;
+        screen.put_str(left, bottom, &format!("└{}┘", border), frame);
+    }
+}
+
+impl CommandHandler for TuiApp {
+    fn context(&self) -> CommandContext {
+        CommandContext {
+            selected: self.selected_pids().len(),
+            elevated: false,
+            window_visible: true,
+        }
+    }
+
+    fn execute(&mut self, action: Action) -> Result<(), String> {
+        match action {
+            Action::Find => self.mode = Mode::Filter,
+            Action::ToggleTreeView => self.toggle_tree(),
+            Action::EndProcess => self.open_menu(MenuAction::Terminate),
+            Action::EndProcessTree => self.open_menu(MenuAction::TerminateTree),
+            Action::SetPriority(class) => self.run_action(MenuAction::SetPriority(class)),
+            Action::ApplyFilterPreset(index) => self.apply_preset(index)?,
+            Action::Exit => self.quit = true,
+            _ => return Err("Not available in the terminal".to_string()),
+        }
+        Ok(())
+    }
+}
+
+/// Registry limited to the actions `TuiApp::execute` supports
+fn terminal_commands() -> CommandRegistry {
+    let mut commands = CommandRegistry::defaults();
+    commands.retain(|action| {
+        matches!(
+            action,
+            Action::Find
+                | Action::ToggleTreeView
+                | Action::EndProcess
+                | Action::EndProcessTree
+                | Action::SetPriority(_)
+                | Action::ApplyFilterPreset(_)
+                | Action::Exit
+        )
+    });
+    commands
+}
+
+/// Depth-first tree order of `rows`, keeping their sort order among siblings
+///
+/// Returns (index into `rows`, tree guide prefix). Processes whose parent is
+/// not in `rows` are roots; PID reuse can create parent cycles, so every
+/// process is visited once.
+fn tree_order(rows: &[&filter::ProcessInfo]) -> Vec<(usize, String)> {
+    fn visit(
+        index: usize,
+        guide: &str,
+        prefix: String,
+        children: &HashMap<u32, Vec<usize>>,
+        rows: &[&filter::ProcessInfo],
+        visited: &mut [bool],
+        order: &mut Vec<(usize, String)>,
+    ) {
+        visited[index] = true;
+        order.push((index, prefix));
+        let kids: Vec<usize> = children
+            .get(&rows[index].pid)
+            .map(|kids| kids.iter().copied().filter(|&k| !visited[k]).collect())
+            .unwrap_or_default();
+        for (n, &kid) in kids.iter().enumerate() {
+            if visited[kid] {
+                continue;
+            }
+            let last = n + 1 == kids.len();
+            let branch = if last { "└─ " } else { "├─ " };
+            let next_guide = format!("{}{}", guide, if last { "   " } else { "│  " });
+            let prefix = format!("{}{}", guide, branch);
+            visit(kid, &next_guide, prefix, children, rows, visited, order);
+        }
+    }
+
+    let present: HashMap<u32, usize> = rows.iter().enumerate().map(|(i, p)| (p.pid, i)).collect();
+    let mut children: HashMap<u32, Vec<usize>> = HashMap::new();
+    let mut roots = Vec::new();
+    for (i, process) in rows.iter().enumerate() {
+        if process.parent_pid != process.pid && present.contains_key(&process.parent_pid) {
+            children.entry(process.parent_pid).or_default().push(i);
+        } else {
+            roots.push(i);
+        }
+    }
+
+    let mut visited = vec![false; rows.len()];
+    let mut order = Vec::with_capacity(rows.len());
+    // Roots first, then anything only reachable through a cycle
+    for start in roots.into_iter().chain(0..rows.len()) {
+        if !visited[start] {
+            visit(
+                start,
+                "",
+                String::new(),
+                &children,
+                rows,
+                &mut visited,
+                &mut order,
+            );
+        }
+    }
+    order
+}
+
+fn table_row(process: &filter::ProcessInfo) -> table::ProcessInfo {
+    table::ProcessInfo {
+        pid: process.pid,
+        parent_pid: process.parent_pid,
+        name: process.name.clone(),
+        cpu_usage: process.cpu_usage,
+        memory_private: process.memory_private,
+        memory_working_set: process.memory_working_set,
+        handle_count: process.handle_count,
+        leak_rate: process.leak_rate,
+        custom: process.custom.clone(),
+    }
+}
+
+fn percent(part: u64, total: u64) -> f32 {
+    if total == 0 {
+        0.0
+    } else {
+        (part as f64 / total as f64 * 100.0) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::config::AppConfig;
+    use crate::app::updater::Updater;
+    use crate::core::expression::{ColumnFormat, CustomColumn};
+    use crate::core::metrics::Anomaly;
+    use crate::test_support::{ProcessBuilder, SnapshotBuilder};
+    use crate::ui::tui::keys::decode;
+    use crate::windows::process::control::ProcessError;
+    use std::sync::mpsc::channel;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    /// Actuator recording calls; PID 666 is protected
+    struct RecordingActuator(Arc<Mutex<Vec<String>>>);
+
+    impl ProcessActuator for RecordingActuator {
+        fn set_priority(&mut self, pid: u32, class: PriorityClass) -> Result<(), ProcessError> {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("priority {} {:?}", pid, class));
+            Ok(())
+        }
+
+        fn set_affinity(&mut self, _pid: u32, _mask: usize) -> Result<(), ProcessError> {
+            Ok(())
+        }
+
+        fn terminate(&mut self, pid: u32) -> Result<(), ProcessError> {
+            if pid == 666 {
+                return Err(ProcessError::AccessDenied("protected".to_string()));
+            }
+            self.0.lock().unwrap().push(format!("terminate {}", pid));
+            Ok(())
+        }
+    }
+
+    /// Two one-second snapshots on one core, delivered by the replay backend
+    fn replayed_updates() -> Vec<UpdateMessage> {
+        let start = Instant::now();
+        let snapshots = (0..2u64)
+            .map(|i| {
+                SnapshotBuilder::new()
+                    .with_timestamp(start + Duration::from_secs(i))
+                    .with_processes(vec![
+                        ProcessBuilder::new(4, "System")
+                            .with_memory(4 << 20)
+                            .build(),
+                        ProcessBuilder::new(100, "services.exe")
+                            .with_parent(4)
+                            .with_cpu_time(i * 1_000_000)
+                            .with_memory(100 << 20)
+                            .build(),
+                        ProcessBuilder::new(200, "svchost.exe")
+                            .with_parent(100)
+                            .with_cpu_time(i * 3_000_000)
+                            .with_memory(200 << 20)
+                            .build(),
+                        ProcessBuilder::new(300, "explorer.exe")
+                            .with_memory(300 << 20)
+                            .build(),
+                        ProcessBuilder::new(666, "shell.exe")
+                            .with_parent(300)
+                            .with_memory(666 << 20)
+                            .build(),
+                    ])
+                    .with_cpu(40.0 * i as f32, vec![0.0])
+                    .with_memory(8 << 30, 6 << 30)
+                    .build()
+            })
+            .collect();
+
+        let (mut updater, rx) = Updater::start_replay(1, snapshots, Vec::new());
+        let messages: Vec<UpdateMessage> = rx
+            .iter()
+            .take_while(|m| !matches!(m, UpdateMessage::Shutdown))
+            .collect();
+        updater.shutdown();
+        messages
+    }
+
+    fn make_app() -> (TuiApp, Arc<Mutex<Vec<String>>>) {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut app = TuiApp::new(Box::new(RecordingActuator(Arc::clone(&calls))));
+        for message in replayed_updates() {
+            app.handle_update(message);
+        }
+        (app, calls)
+    }
+
+    fn press(app: &mut TuiApp, input: &[u8]) {
+        for event in decode(input) {
+            app.handle_key(&event);
+        }
+    }
+
+    fn draw(app: &mut TuiApp) -> Screen {
+        let mut screen = Screen::new(80, 12);
+        app.render(&mut screen);
+        screen
+    }
+
+    #[test]
+    fn test_list_sorted_by_cpu_and_rendered() {
+        let (mut app, _) = make_app();
+        assert_eq!(app.visible_pids()[..2], [200, 100]);
+        // Selection follows the PID picked on the first snapshot
+        assert_eq!(app.selected_pids(), vec![4]);
+        press(&mut app, b"\x1b[H");
+        assert_eq!(app.selected_pids(), vec![200]);
+
+        let screen = draw(&mut app);
+        assert!(screen.row_text(0).starts_with("CPU "));
+        assert!(screen.row_text(0).ends_with(" 40.0%"));
+        assert!(screen.row_text(1).ends_with("2.0 GB / 8.0 GB"));
+        assert!(screen
+            .row_text(2)
+            .starts_with("Tasks: 5  Shown: 5  Sort: CPU % ▼  Tree: off"));
+        assert!(screen.row_text(3).contains("CPU % ▼"));
+        assert!(screen.row_text(4).contains("30.0"));
+        assert!(screen.row_text(4).starts_with("svchost.exe "));
+        assert_eq!(screen.style_at(0, 4).bg, Some(Color::Cyan));
+    }
+
+    #[test]
+    fn test_tree_toggle_and_navigation() {
+        let (mut app, _) = make_app();
+        press(&mut app, b">t");
+        assert!(app.is_tree());
+        // Memory sort descending, children under parents
+        assert_eq!(app.visible_pids(), vec![300, 666, 4, 100, 200]);
+
+        let screen = draw(&mut app);
+        assert!(screen.row_text(5).starts_with("└─ shell.exe "));
+        assert!(screen.row_text(7).starts_with("└─ services.exe "));
+        assert!(screen.row_text(8).starts_with("   └─ svchost.exe "));
+
+        press(&mut app, b"\x1b[F\x1b[A");
+        assert_eq!(app.selected_pids(), vec![100]);
+    }
+
+    #[test]
+    fn test_incremental_filter() {
+        let (mut app, _) = make_app();
+        press(&mut app, b"/svc");
+        assert_eq!(app.mode(), Mode::Filter);
+        assert_eq!(app.visible_pids(), vec![200]);
+
+        press(&mut app, b"\x7f\x7f\x7ferv\r");
+        assert_eq!(app.mode(), Mode::Normal);
+        assert_eq!(app.visible_pids(), vec![100]);
+        assert!(draw(&mut app).row_text(2).ends_with("Filter: erv"));
+
+        press(&mut app, b"\x06\x1b");
+        assert_eq!(app.visible_pids().len(), 5);
+    }
+
+    #[test]
+    fn test_kill_and_priority_menu() {
+        let (mut app, calls) = make_app();
+        press(&mut app, b"\x1b[H\x1b[3~\x1b[B\x1b[B\x1b[B\x1b[B\x1b[B\r");
+        assert_eq!(calls.lock().unwrap().as_slice(), ["priority 200 High"]);
+        assert_eq!(app.status(), Some("Priority: High for 1 process"));
+
+        press(&mut app, b"\x1b[1;5Bk");
+        assert_eq!(app.mode(), Mode::Menu(0));
+        assert!(draw(&mut app).row_text(1).contains(" 2 processes "));
+        press(&mut app, b"\r");
+        assert_eq!(calls.lock().unwrap().len(), 3);
+
+        press(&mut app, b"/shell\r\x1b[3~\r");
+        assert!(app
+            .status()
+            .unwrap()
+            .starts_with("Ended 0 processes (1 failed: PID 666: "));
+    }
+
+    #[test]
+    fn test_kill_tree_and_toggle_tree_shortcuts() {
+        let (mut app, calls) = make_app();
+        // Ctrl+T
+        press(&mut app, b"\x14");
+        assert!(app.is_tree());
+
+        // services.exe, then Shift+Delete opens the menu on "End process tree"
+        press(&mut app, b"/services\r\x1b[3;2~");
+        assert_eq!(app.mode(), Mode::Menu(MenuAction::ALL.len() - 1));
+        press(&mut app, b"\r");
+        assert_eq!(
+            calls.lock().unwrap().as_slice(),
+            ["terminate 200", "terminate 100"]
+        );
+        assert_eq!(app.status(), Some("Ended 2 processes"));
+    }
+
+    #[test]
+    fn test_command_palette() {
+        let (mut app, calls) = make_app();
+        // Ctrl+P on svchost.exe, then fuzzy search
+        press(&mut app, b"\x1b[H\x10");
+        assert_eq!(app.mode(), Mode::Palette(0));
+        press(&mut app, b"prio hi");
+        let screen = draw(&mut app);
+        assert!(screen.row_text(2).contains("> prio hi_"));
+        assert!(screen.row_text(3).contains("Set priority: High"));
+        press(&mut app, b"\r");
+        assert_eq!(app.mode(), Mode::Normal);
+        assert_eq!(calls.lock().unwrap().as_slice(), ["priority 200 High"]);
+
+        // Commands the terminal cannot run are not registered
+        press(&mut app, b"\x10theme dark");
+        assert!(draw(&mut app).row_text(3).contains("No matching commands"));
+        press(&mut app, b"\x1b");
+        assert_eq!(app.mode(), Mode::Normal);
+
+        // Equal scores keep registration order: toggle tree, then end tree
+        press(&mut app, b"\x10tree\r");
+        assert!(app.is_tree());
+        press(&mut app, b"\x10tree\x1b[B");
+        let row = draw(&mut app).row_text(4);
+        assert!(row.contains("│ End process tree "));
+        assert!(row.ends_with("Shift+Delete │"));
+        press(&mut app, b"\r");
+        assert_eq!(app.mode(), Mode::Menu(MenuAction::ALL.len() - 1));
+    }
+
+    #[test]
+    fn test_column_layout_and_scrolling() {
+        let (app, _) = make_app();
+        let mut layout = ColumnLayout::default();
+        layout.set_pinned("name", false).unwrap();
+        layout.move_column("name", 10).unwrap();
+        layout.set_visible("handles", false).unwrap();
+        layout.set_alignment("pid", ColumnAlignment::Left).unwrap();
+        layout.set_pinned("pid", true).unwrap();
+        let mut app = app.with_column_layout(layout);
+        press(&mut app, b"\x1b[H");
+
+        // htop style: name last, taking the rest of the line
+        let screen = draw(&mut app);
+        assert!(screen.row_text(3).starts_with("PID     "));
+        assert!(screen.row_text(3).ends_with("Name"));
+        assert!(screen.row_text(4).starts_with("200 "));
+        assert!(screen.row_text(4).ends_with(" svchost.exe"));
+
+        // Right scrolls CPU out of view; the pinned PID stays
+        press(&mut app, b"\x1b[C");
+        let header = draw(&mut app).row_text(3);
+        assert!(header.starts_with("PID"));
+        assert!(!header.contains("CPU"));
+        assert!(header.contains("Memory"));
+        press(&mut app, b"\x1b[D\x1b[D");
+        assert!(draw(&mut app).row_text(3).contains("CPU % ▼"));
+    }
+
+    #[test]
+    fn test_custom_columns() {
+        let (app, _) = make_app();
+        let columns = ComputedColumns::new(&[
+            CustomColumn::new(
+                "per_thread",
+                "memory_private / threads",
+                ColumnFormat::Bytes,
+            ),
+            CustomColumn::new("cpu_share", "cpu / total(cpu) * 100", ColumnFormat::Percent),
+        ])
+        .unwrap();
+        let mut app = app.with_custom_columns(columns);
+        let wide = |app: &mut TuiApp| {
+            let mut screen = Screen::new(120, 12);
+            app.render(&mut screen);
+            screen
+        };
+
+        let screen = wide(&mut app);
+        assert!(screen
+            .row_text(3)
+            .trim_end()
+            .ends_with("per_thread   cpu_share"));
+        assert!(screen
+            .row_text(4)
+            .trim_end()
+            .ends_with("200 MB       75.0%"));
+
+        // Sort cycling reaches the computed columns after the built-ins
+        press(&mut app, b"<");
+        assert_eq!(app.visible_pids()[..2], [200, 100]);
+        let screen = wide(&mut app);
+        assert!(screen.row_text(2).contains("Sort: cpu_share ▼"));
+        assert!(screen.row_text(3).contains("cpu_share ▼"));
+        press(&mut app, b"<");
+        assert_eq!(app.visible_pids()[..2], [666, 300]);
+        press(&mut app, b">>");
+        assert!(wide(&mut app).row_text(2).contains("Sort: CPU % ▼"));
+    }
+
+    #[test]
+    fn test_cpu_spikes_flagged() {
+        let (tx, rx) = channel();
+        let mut app = TuiApp::new(Box::new(RecordingActuator(Arc::default()))).with_anomalies(rx);
+        let start = Instant::now();
+        let spike = |at, series| AnomalyEvent {
+            series,
+            process_name: None,
+            anomaly: Anomaly {
+                value: 90.0,
+                expected: 10.0,
+                score: 8.0,
+            },
+            timestamp: at,
+            local_time: "14:03".to_string(),
+        };
+        // Both events are queued before the first snapshot is handled
+        let second = start + Duration::from_secs(1);
+        tx.send(spike(start, AnomalySeries::ProcessCpu(100)))
+            .unwrap();
+        tx.send(spike(second, AnomalySeries::SystemCpu)).unwrap();
+        for (secs, cpu) in [(0, 10.0), (1, 90.0), (2, 10.0)] {
+            let snapshot = SnapshotBuilder::new()
+                .with_timestamp(start + Duration::from_secs(secs))
+                .with_cpu(cpu, vec![cpu])
+                .with_memory(8 << 30, 6 << 30)
+                .build();
+            app.handle_update(UpdateMessage::Snapshot(snapshot));
+        }
+
+        assert_eq!(app.cpu_history.flagged_indices(), vec![1]);
+        let screen = draw(&mut app);
+        let spark_end = 80 - format!(" {:>5}%", "10.0").len() as u16;
+        assert_eq!(screen.style_at(spark_end - 2, 0).fg, Some(Color::Red));
+        assert_eq!(screen.style_at(spark_end - 1, 0).fg, Some(Color::Green));
+    }
+
+    #[test]
+    fn test_config_reload() {
+        let (mut app, _) = make_app();
+        let config = AppConfig {
+            custom_columns: vec![CustomColumn::new(
+                "per_thread",
+                "memory_private / threads",
+                ColumnFormat::Bytes,
+            )],
+            filter_presets: vec![FilterPreset::new("Busy").with_filter("cpu>5")],
+            ..Default::default()
+        };
+        let mut reload = ConfigReload {
+            config: Arc::new(config),
+            changed: vec![ConfigSection::CustomColumns],
+        };
+
+        app.apply_config(&reload);
+        let mut screen = Screen::new(120, 12);
+        app.render(&mut screen);
+        assert!(screen.row_text(3).trim_end().ends_with("per_thread"));
+        // Presets did not change
+        press(&mut app, b"\x10busy");
+        assert!(draw(&mut app).row_text(3).contains("No matching commands"));
+        press(&mut app, b"\x1b");
+
+        reload.changed = vec![ConfigSection::FilterPresets];
+        app.apply_config(&reload);
+        press(&mut app, b"\x10busy\r");
+        assert_eq!(app.visible_pids(), vec![200, 100]);
+    }
+
+    #[test]
+    fn test_filter_presets() {
+        let (app, _) = make_app();
+        let mut app = app.with_filter_presets(vec![
+            FilterPreset::new("Everything"),
+            FilterPreset::new("Busy")
+                .with_filter("cpu>5")
+                .with_sort("name", false)
+                .with_columns(&["cpu"]),
+            FilterPreset::new("Broken").with_filter("cpu>>5"),
+        ]);
+
+        press(&mut app, b"\x10busy\r");
+        assert_eq!(app.visible_pids(), vec![100, 200]);
+        let screen = draw(&mut app);
+        assert!(screen.row_text(2).contains("Sort: Name ▲"));
+        assert!(screen.row_text(2).contains("Preset: Busy"));
+        assert!(!screen.row_text(3).contains("PID"));
+        assert!(screen.row_text(3).contains("CPU %"));
+
+        // Built-in presets were replaced
+        press(&mut app, b"\x10high cpu");
+        assert!(draw(&mut app).row_text(3).contains("No matching commands"));
+        press(&mut app, b"\x1b\x10broken\r");
+        assert!(app.status().unwrap().starts_with("Preset 'Broken': "));
+        assert_eq!(app.visible_pids(), vec![100, 200]);
+
+        press(&mut app, b"\x10everything\r");
+        assert_eq!(app.visible_pids().len(), 5);
+        assert!(draw(&mut app).row_text(3).contains("PID"));
+    }
+}
@@ -0,0 +1,190 @@
+//! VT input decoding
+//!
+//! Terminal input arrives as bytes (UTF-8 text plus escape sequences). It is
+//! translated into the same `KeyboardEvent`s the window receives, with
+//! Windows virtual-key codes, so `Shortcut` and `ProcessTable` key handling
+//! work unchanged.
+
+use crate::ui::input::{KeyModifiers, KeyboardEvent};
+
+pub const VK_BACK: u16 = 0x08;
+pub const VK_TAB: u16 = 0x09;
+pub const VK_RETURN: u16 = 0x0D;
+pub const VK_ESCAPE: u16 = 0x1B;
+pub const VK_PRIOR: u16 = 0x21;
+pub const VK_NEXT: u16 = 0x22;
+pub const VK_END: u16 = 0x23;
+pub const VK_HOME: u16 = 0x24;
+pub const VK_LEFT: u16 = 0x25;
+pub const VK_UP: u16 = 0x26;
+pub const VK_RIGHT: u16 = 0x27;
+pub const VK_DOWN: u16 = 0x28;
+pub const VK_DELETE: u16 = 0x2E;
+pub const VK_F1: u16 = 0x70;
+
+/// Decode one read of terminal input
+///
+/// Each read is expected to hold whole sequences (terminals write a key's
+/// sequence at once), so a trailing lone ESC is the Escape key.
+pub fn decode(input: &[u8]) -> Vec<KeyboardEvent> {
+    let text = String::from_utf8_lossy(input);
+    let chars: Vec<char> = text.chars().collect();
+    let mut events = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let ch = chars[i];
+        i += 1;
+        match ch {
+            '\x1b' if matches!(chars.get(i), Some('[') | Some('O')) => {
+                let introducer = chars[i];
+                let start = i + 1;
+                // Parameters and intermediates, then one final byte
+                let end = chars[start..]
+                    .iter()
+                    .position(|c| ('@'..='~').contains(c))
+                    .map(|offset| start + offset);
+                let Some(end) = end else {
+                    break;
+                };
+                let params: String = chars[start..end].iter().collect();
+                if let Some(event) = decode_sequence(introducer, &params, chars[end]) {
+                    events.push(event);
+                }
+                i = end + 1;
+            }
+            '\x1b' => events.push(key(VK_ESCAPE, KeyModifiers::default())),
+            '\r' | '\n' => events.push(key(VK_RETURN, KeyModifiers::default())),
+            '\t' => events.push(key(VK_TAB, KeyModifiers::default())),
+            '\x7f' | '\x08' => events.push(key(VK_BACK, KeyModifiers::default())),
+            // Ctrl+A..Ctrl+Z arrive as 0x01..0x1A
+            '\x01'..='\x1a' => {
+                let letter = b'A' + (ch as u8 - 1);
+                let modifiers = KeyModifiers {
+                    ctrl: true,
+                    ..KeyModifiers::default()
+                };
+                events.push(key(letter as u16, modifiers));
+            }
+            ch if ch.is_control() => {}
+            character => events.push(KeyboardEvent::Char {
+                character,
+                repeat_count: 1,
+            }),
+        }
+    }
+    events
+}
+
+/// Decode `ESC [ params final` or `ESC O final`
+fn decode_sequence(introducer: char, params: &str, final_char: char) -> Option<KeyboardEvent> {
+    let mut parts = params.split(';');
+    let first: Option<u16> = parts.next().and_then(|p| p.parse().ok());
+    // xterm modifier parameter: 1 + (shift | alt << 1 | ctrl << 2)
+    let modifier_bits = parts
+        .next()
+        .and_then(|p| p.parse::<u16>().ok())
+        .map(|m| m.saturating_sub(1))
+        .unwrap_or(0);
+    let modifiers = KeyModifiers {
+        shift: modifier_bits & 1 != 0,
+        alt: modifier_bits & 2 != 0,
+        ctrl: modifier_bits & 4 != 0,
+    };
+
+    let vkey = match (introducer, final_char) {
+        (_, 'A') => VK_UP,
+        (_, 'B') => VK_DOWN,
+        (_, 'C') => VK_RIGHT,
+        (_, 'D') => VK_LEFT,
+        (_, 'H') => VK_HOME,
+        (_, 'F') => VK_END,
+        ('O', 'P'..='S') => VK_F1 + (final_char as u16 - 'P' as u16),
+        ('[', 'Z') => {
+            let modifiers = KeyModifiers {
+                shift: true,
+                ..modifiers
+            };
+            return Some(key(VK_TAB, modifiers));
+        }
+        ('[', '~') => match first? {
+            1 | 7 => VK_HOME,
+            3 => VK_DELETE,
+            4 | 8 => VK_END,
+            5 => VK_PRIOR,
+            6 => VK_NEXT,
+            11..=15 => VK_F1 + (first? - 11),
+            17..=21 => VK_F1 + 5 + (first? - 17),
+            23 | 24 => VK_F1 + 10 + (first? - 23),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(key(vkey, modifiers))
+}
+
+fn key(vkey: u16, modifiers: KeyModifiers) -> KeyboardEvent {
+    KeyboardEvent::KeyDown {
+        vkey,
+        repeat_count: 1,
+        modifiers,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::input::Shortcut;
+
+    fn vkeys(input: &[u8]) -> Vec<(u16, bool)> {
+        decode(input)
+            .into_iter()
+            .filter_map(|event| match event {
+                KeyboardEvent::KeyDown {
+                    vkey, modifiers, ..
+                } => Some((vkey, modifiers.ctrl)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_decode_sequences() {
+        assert_eq!(
+            vkeys(b"\x1b[A\x1b[1;5B\x1b[3~\x1b[6~\x1bOP\x1b[17~"),
+            vec![
+                (VK_UP, false),
+                (VK_DOWN, true),
+                (VK_DELETE, false),
+                (VK_NEXT, false),
+                (VK_F1, false),
+                (VK_F1 + 5, false),
+            ]
+        );
+        assert_eq!(vkeys(b"\x1b"), vec![(VK_ESCAPE, false)]);
+    }
+
+    #[test]
+    fn test_decode_text_and_shortcuts() {
+        let events = decode("q\u{e9}\r".as_bytes());
+        assert!(matches!(
+            events[0],
+            KeyboardEvent::Char { character: 'q', .. }
+        ));
+        assert!(matches!(
+            events[1],
+            KeyboardEvent::Char {
+                character: 'é', ..
+            }
+        ));
+
+        let shortcuts: Vec<_> = decode(b"\x06\x1b[3~\r")
+            .iter()
+            .filter_map(Shortcut::from_keyboard_event)
+            .collect();
+        assert_eq!(
+            shortcuts,
+            vec![Shortcut::Find, Shortcut::Delete, Shortcut::Enter]
+        );
+    }
+}
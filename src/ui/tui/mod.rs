@@ -0,0 +1,209 @@
+//! Terminal user interface
+//!
+//! Full-screen, htop-style frontend for SSH-only machines. It consumes the
+//! same `Updater` channel as the window and reuses the core filter/sort
+//! engine, the `ProcessTable` selection model and the `Shortcut` mapping:
+//! - `keys` decodes VT input into `KeyboardEvent`s
+//! - `screen` is a cell buffer rendered as ANSI escape sequences
+//! - `app` holds the view state and handles keys and updates
+//! - `terminal` switches the Windows console into raw VT mode
+//!
+//! Edits of the config file are picked up by a `ConfigWatcher` while the
+//! TUI runs and handed to both the app and the updater.
+
+pub mod app;
+pub mod keys;
+pub mod screen;
+pub mod terminal;
+
+pub use app::TuiApp;
+pub use terminal::{ConsoleTerminal, Terminal};
+
+use std::io;
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::app::config::ConfigManager;
+use crate::app::leaks::LeakMonitor;
+use crate::app::policies::ProcessActuator;
+use crate::app::reload::{ConfigReload, ConfigWatcher};
+use crate::app::services::Services;
+use crate::app::updater::{ProcessSource, UpdateMessage, Updater};
+use crate::core::expression::ComputedColumns;
+use screen::Screen;
+
+/// How long to wait for a key before redrawing
+const INPUT_POLL: Duration = Duration::from_millis(100);
+
+/// Run the TUI on the attached console until the user quits
+///
+/// `source` and `actuator` are the local system or a remote agent;
+/// `services` contributes the sinks of the configured exporters and the
+/// custom collectors; `manager` supplies the filter presets, the column
+/// layout, the computed columns, the number format and the leak detector
+/// settings, and is watched for edits of its config file.
+pub fn run(
+    refresh_rate_ms: u64,
+    source: Box<dyn ProcessSource>,
+    actuator: Box<dyn ProcessActuator>,
+    services: Services,
+    manager: Arc<ConfigManager>,
+) -> io::Result<()> {
+    let config = manager.get();
+    let columns = ComputedColumns::new(&config.custom_columns)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let mut terminal = ConsoleTerminal::open()?;
+    // Spikes found by the anomaly sink are marked on the CPU meter
+    let (anomaly_tx, anomalies) = channel();
+    let mut services = services.with_anomaly_sender(anomaly_tx);
+    let mut app = TuiApp::new(actuator)
+        .with_anomalies(anomalies)
+        .with_filter_presets(config.filter_presets.clone())
+        .with_column_layout(config.columns.clone())
+        .with_number_format(config.display.number_format())
+        .with_custom_columns(columns);
+    let mut sinks = services.take_sinks();
+    if config.leak_detection.enabled {
+        let leaks = LeakMonitor::from_config(&config.leak_detection);
+        app = app.with_leak_detector(leaks.detector());
+        sinks.push(Box::new(leaks));
+    }
+    let collectors = services.take_collectors();
+    let (mut updater, updates) =
+        Updater::start_with_collectors(refresh_rate_ms, source, collectors, sinks);
+    let (mut watcher, reloads) = match ConfigWatcher::start_for(manager) {
+        Some((watcher, reloads)) => (Some(watcher), reloads),
+        // Registry-backed configuration is never reloaded
+        None => (None, channel().1),
+    };
+
+    let result = run_loop(&mut app, &mut terminal, &updates, &reloads, &updater);
+    if let Some(watcher) = &mut watcher {
+        watcher.shutdown();
+    }
+    updater.shutdown();
+    result
+}
+
+/// Event loop: apply updates and config reloads, redraw changed rows,
+/// handle input
+pub fn run_loop(
+    app: &mut TuiApp,
+    terminal: &mut dyn Terminal,
+    updates: &Receiver<UpdateMessage>,
+    reloads: &Receiver<ConfigReload>,
+    updater: &Updater,
+) -> io::Result<()> {
+    let mut previous: Option<Screen> = None;
+
+    while !app.should_quit() {
+        while let Ok(reload) = reloads.try_recv() {
+            reload.apply_to(updater);
+            app.apply_config(&reload);
+        }
+        while let Ok(message) = updates.try_recv() {
+            app.handle_update(message);
+        }
+
+        let (width, height) = terminal.size()?;
+        let mut screen = Screen::new(width, height);
+        app.render(&mut screen);
+        terminal.write(&screen.render(previous.as_ref()))?;
+        previous = Some(screen);
+
+        let input = terminal.read_input(INPUT_POLL)?;
+        for event in keys::decode(&input) {
+            app.handle_key(&event);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::config::{AppConfig, ConfigSection};
+    use crate::core::metrics::SystemMetrics;
+    use crate::windows::monitor::ProcessSnapshot;
+    use crate::windows::process::control::{PriorityClass, ProcessError};
+    use std::collections::VecDeque;
+    use std::sync::mpsc::channel;
+    use std::time::Instant;
+
+    struct NoopActuator;
+
+    impl ProcessActuator for NoopActuator {
+        fn set_priority(&mut self, _pid: u32, _class: PriorityClass) -> Result<(), ProcessError> {
+            Ok(())
+        }
+
+        fn set_affinity(&mut self, _pid: u32, _mask: usize) -> Result<(), ProcessError> {
+            Ok(())
+        }
+
+        fn terminate(&mut self, _pid: u32) -> Result<(), ProcessError> {
+            Ok(())
+        }
+    }
+
+    /// Terminal replaying canned input and capturing output
+    struct ScriptedTerminal {
+        input: VecDeque<&'static [u8]>,
+        output: Vec<String>,
+    }
+
+    impl Terminal for ScriptedTerminal {
+        fn size(&self) -> io::Result<(u16, u16)> {
+            Ok((60, 10))
+        }
+
+        fn write(&mut self, output: &str) -> io::Result<()> {
+            self.output.push(output.to_string());
+            Ok(())
+        }
+
+        fn read_input(&mut self, _timeout: Duration) -> io::Result<Vec<u8>> {
+            Ok(self.input.pop_front().unwrap_or(b"q").to_vec())
+        }
+    }
+
+    #[test]
+    fn test_run_loop_redraws_changes_and_quits() {
+        let (tx, rx) = channel();
+        tx.send(UpdateMessage::Snapshot(ProcessSnapshot {
+            timestamp: Instant::now(),
+            processes: Vec::new(),
+            system_metrics: SystemMetrics::new(),
+        }))
+        .unwrap();
+
+        let (reload_tx, reloads) = channel();
+        let mut config = AppConfig::default();
+        config.columns.set_visible("PID", false).unwrap();
+        reload_tx
+            .send(ConfigReload {
+                config: Arc::new(config),
+                changed: vec![ConfigSection::Columns],
+            })
+            .unwrap();
+        let (mut updater, _) = Updater::start_replay(1000, Vec::new(), Vec::new());
+
+        let mut terminal = ScriptedTerminal {
+            input: VecDeque::from([&b""[..], b"t"]),
+            output: Vec::new(),
+        };
+        let mut app = TuiApp::new(Box::new(NoopActuator));
+        run_loop(&mut app, &mut terminal, &rx, &reloads, &updater).unwrap();
+        updater.shutdown();
+
+        assert!(app.should_quit());
+        assert_eq!(terminal.output.len(), 3);
+        assert!(terminal.output[0].contains("\x1b[2J"));
+        // The reloaded layout hides the PID column
+        assert!(!terminal.output[0].contains("PID"));
+        // Nothing changed between the first two frames
+        assert_eq!(terminal.output[1], "");
+        assert!(terminal.output[2].contains("Tree: on"));
+    }
+}
@@ -0,0 +1,270 @@
+//! Character cell buffer rendered with ANSI escape sequences
+//!
+//! The TUI draws each frame into a `Screen`, then `render` emits only the
+//! rows that changed since the previous frame to keep SSH traffic low.
+
+use std::fmt::Write;
+
+/// ANSI terminal colors (SGR 30-37 / 40-47)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+}
+
+impl Color {
+    fn index(self) -> u8 {
+        self as u8
+    }
+}
+
+/// Cell style
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Style {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    pub bold: bool,
+    pub reverse: bool,
+}
+
+impl Style {
+    pub const PLAIN: Self = Self {
+        fg: None,
+        bg: None,
+        bold: false,
+        reverse: false,
+    };
+
+    pub fn fg(color: Color) -> Self {
+        Self {
+            fg: Some(color),
+            ..Self::PLAIN
+        }
+    }
+
+    pub fn reversed() -> Self {
+        Self {
+            reverse: true,
+            ..Self::PLAIN
+        }
+    }
+
+    pub fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    pub fn on(mut self, color: Color) -> Self {
+        self.bg = Some(color);
+        self
+    }
+
+    /// SGR sequence selecting this style from the default one
+    fn sgr(&self) -> String {
+        let mut codes = vec!["0".to_string()];
+        if self.bold {
+            codes.push("1".to_string());
+        }
+        if self.reverse {
+            codes.push("7".to_string());
+        }
+        if let Some(fg) = self.fg {
+            codes.push((30 + fg.index()).to_string());
+        }
+        if let Some(bg) = self.bg {
+            codes.push((40 + bg.index()).to_string());
+        }
+        format!("\x1b[{}m", codes.join(";"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Cell {
+    ch: char,
+    style: Style,
+}
+
+const BLANK: Cell = Cell {
+    ch: ' ',
+    style: Style::PLAIN,
+};
+
+/// Fixed-size grid of styled characters
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Screen {
+    width: u16,
+    height: u16,
+    cells: Vec<Cell>,
+}
+
+impl Screen {
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![BLANK; width as usize * height as usize],
+        }
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// Write text at (x, y), clipped to the screen; returns columns written
+    pub fn put_str(&mut self, x: u16, y: u16, text: &str, style: Style) -> u16 {
+        if y >= self.height {
+            return 0;
+        }
+        let mut column = x;
+        for ch in text.chars() {
+            if column >= self.width {
+                break;
+            }
+            let ch = if ch.is_control() { ' ' } else { ch };
+            let index = y as usize * self.width as usize + column as usize;
+            self.cells[index] = Cell { ch, style };
+            column += 1;
+        }
+        column - x
+    }
+
+    /// Fill a whole row with blanks in `style`
+    pub fn fill_row(&mut self, y: u16, style: Style) {
+        self.fill(0, y, self.width, style);
+    }
+
+    /// Fill `width` cells starting at (x, y) with blanks in `style`
+    pub fn fill(&mut self, x: u16, y: u16, width: u16, style: Style) {
+        if y >= self.height {
+            return;
+        }
+        let end = x.saturating_add(width).min(self.width);
+        for column in x.min(end)..end {
+            self.cells[y as usize * self.width as usize + column as usize] =
+                Cell { ch: ' ', style };
+        }
+    }
+
+    /// Text of a row without styling or trailing blanks
+    pub fn row_text(&self, y: u16) -> String {
+        self.row(y)
+            .iter()
+            .map(|cell| cell.ch)
+            .collect::<String>()
+            .trim_end()
+            .to_string()
+    }
+
+    /// Style of the cell at (x, y)
+    pub fn style_at(&self, x: u16, y: u16) -> Style {
+        self.row(y)
+            .get(x as usize)
+            .map(|cell| cell.style)
+            .unwrap_or_default()
+    }
+
+    fn row(&self, y: u16) -> &[Cell] {
+        if y >= self.height {
+            return &[];
+        }
+        let start = y as usize * self.width as usize;
+        &self.cells[start..start + self.width as usize]
+    }
+
+    /// Escape sequences drawing this frame over `previous`
+    ///
+    /// Without a previous frame of the same size the screen is cleared and
+    /// redrawn; otherwise only rows that differ are rewritten.
+    pub fn render(&self, previous: Option<&Screen>) -> String {
+        let previous = previous.filter(|p| p.width == self.width && p.height == self.height);
+        let mut out = String::new();
+        if previous.is_none() {
+            out.push_str("\x1b[0m\x1b[2J");
+        }
+
+        for y in 0..self.height {
+            let row = self.row(y);
+            if previous.is_some_and(|p| p.row(y) == row) {
+                continue;
+            }
+            let _ = write!(out, "\x1b[{};1H", y + 1);
+            let mut current = None;
+            for cell in row {
+                if current != Some(cell.style) {
+                    out.push_str(&cell.style.sgr());
+                    current = Some(cell.style);
+                }
+                out.push(cell.ch);
+            }
+        }
+        if !out.is_empty() {
+            out.push_str("\x1b[0m");
+        }
+        out
+    }
+}
+
+/// Block-character sparkline of the last `width` values scaled to `max`
+///
+/// Shorter histories are right-aligned so the newest sample is always at
+/// the right edge.
+pub fn sparkline(values: &[f32], max: f32, width: usize) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+    let recent = &values[values.len().saturating_sub(width)..];
+    let mut line = " ".repeat(width - recent.len());
+    for &value in recent {
+        let level = if max > 0.0 {
+            (value / max).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        line.push(BARS[(level * (BARS.len() - 1) as f32).round() as usize]);
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_put_str_clips() {
+        let mut screen = Screen::new(5, 2);
+        assert_eq!(screen.put_str(2, 0, "hello", Style::PLAIN), 3);
+        assert_eq!(screen.put_str(0, 5, "hidden", Style::PLAIN), 0);
+        assert_eq!(screen.row_text(0), "  hel");
+        assert_eq!(screen.row_text(1), "");
+    }
+
+    #[test]
+    fn test_render_only_changed_rows() {
+        let mut first = Screen::new(4, 3);
+        first.put_str(0, 0, "ab", Style::PLAIN);
+        let full = first.render(None);
+        assert!(full.starts_with("\x1b[0m\x1b[2J"));
+        assert!(full.contains("\x1b[3;1H"));
+
+        let mut second = first.clone();
+        second.put_str(0, 1, "x", Style::fg(Color::Red).bold());
+        let diff = second.render(Some(&first));
+        assert_eq!(diff, "\x1b[2;1H\x1b[0;1;31mx\x1b[0m   \x1b[0m");
+        assert_eq!(second.render(Some(&second)), "");
+    }
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[0.0, 50.0, 100.0], 100.0, 5), "  ▁▅█");
+        assert_eq!(sparkline(&[100.0, 0.0, 200.0], 100.0, 2), "▁█");
+    }
+}
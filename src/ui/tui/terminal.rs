@@ -0,0 +1,140 @@
+//! Terminal I/O for the TUI
+//!
+//! `Terminal` abstracts the console so the event loop can run against a
+//! scripted terminal in tests. `ConsoleTerminal` puts the Windows console
+//! into raw VT mode (which also works over OpenSSH's ConPTY) and restores
+//! it on drop.
+
+use std::io::{self, Write};
+use std::time::Duration;
+
+use windows::Win32::Foundation::{HANDLE, WAIT_OBJECT_0};
+use windows::Win32::System::Console::{
+    GetConsoleMode, GetConsoleScreenBufferInfo, GetNumberOfConsoleInputEvents, GetStdHandle,
+    ReadConsoleInputW, SetConsoleMode, CONSOLE_MODE, CONSOLE_SCREEN_BUFFER_INFO,
+    ENABLE_ECHO_INPUT, ENABLE_LINE_INPUT, ENABLE_PROCESSED_INPUT, ENABLE_VIRTUAL_TERMINAL_INPUT,
+    ENABLE_VIRTUAL_TERMINAL_PROCESSING, INPUT_RECORD, KEY_EVENT, STD_INPUT_HANDLE,
+    STD_OUTPUT_HANDLE,
+};
+use windows::Win32::System::Threading::WaitForSingleObject;
+
+/// Full-screen terminal used by the TUI event loop
+pub trait Terminal {
+    /// Current size in columns and rows
+    fn size(&self) -> io::Result<(u16, u16)>;
+    /// Write raw output (text and escape sequences)
+    fn write(&mut self, output: &str) -> io::Result<()>;
+    /// Wait up to `timeout` for input; empty if none arrived
+    fn read_input(&mut self, timeout: Duration) -> io::Result<Vec<u8>>;
+}
+
+/// Windows console in raw VT mode on the alternate screen
+pub struct ConsoleTerminal {
+    input: HANDLE,
+    output: HANDLE,
+    saved_input_mode: CONSOLE_MODE,
+    saved_output_mode: CONSOLE_MODE,
+}
+
+impl ConsoleTerminal {
+    /// Switch the attached console to raw mode and the alternate screen
+    pub fn open() -> io::Result<Self> {
+        unsafe {
+            let input = GetStdHandle(STD_INPUT_HANDLE).map_err(io::Error::other)?;
+            let output = GetStdHandle(STD_OUTPUT_HANDLE).map_err(io::Error::other)?;
+
+            let mut saved_input_mode = CONSOLE_MODE::default();
+            let mut saved_output_mode = CONSOLE_MODE::default();
+            GetConsoleMode(input, &mut saved_input_mode).map_err(io::Error::other)?;
+            GetConsoleMode(output, &mut saved_output_mode).map_err(io::Error::other)?;
+
+            // No line buffering, echo or Ctrl+C processing; keys arrive as VT sequences
+            let raw_input = CONSOLE_MODE(
+                (saved_input_mode.0
+                    & !(ENABLE_LINE_INPUT.0 | ENABLE_ECHO_INPUT.0 | ENABLE_PROCESSED_INPUT.0))
+                    | ENABLE_VIRTUAL_TERMINAL_INPUT.0,
+            );
+            SetConsoleMode(input, raw_input).map_err(io::Error::other)?;
+            let vt_output =
+                CONSOLE_MODE(saved_output_mode.0 | ENABLE_VIRTUAL_TERMINAL_PROCESSING.0);
+            if let Err(e) = SetConsoleMode(output, vt_output) {
+                let _ = SetConsoleMode(input, saved_input_mode);
+                return Err(io::Error::other(e));
+            }
+
+            let mut terminal = Self {
+                input,
+                output,
+                saved_input_mode,
+                saved_output_mode,
+            };
+            // Alternate screen, hidden cursor
+            terminal.write("\x1b[?1049h\x1b[?25l")?;
+            Ok(terminal)
+        }
+    }
+}
+
+impl Terminal for ConsoleTerminal {
+    fn size(&self) -> io::Result<(u16, u16)> {
+        let mut info = CONSOLE_SCREEN_BUFFER_INFO::default();
+        unsafe { GetConsoleScreenBufferInfo(self.output, &mut info) }.map_err(io::Error::other)?;
+        let window = info.srWindow;
+        let width = (window.Right - window.Left + 1).max(0) as u16;
+        let height = (window.Bottom - window.Top + 1).max(0) as u16;
+        Ok((width, height))
+    }
+
+    fn write(&mut self, output: &str) -> io::Result<()> {
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(output.as_bytes())?;
+        stdout.flush()
+    }
+
+    fn read_input(&mut self, timeout: Duration) -> io::Result<Vec<u8>> {
+        let millis = timeout.as_millis().min(u32::MAX as u128) as u32;
+        if unsafe { WaitForSingleObject(self.input, millis) } != WAIT_OBJECT_0 {
+            return Ok(Vec::new());
+        }
+
+        // The handle is also signaled by focus, mouse and resize events, which
+        // ReadConsoleW would block past; read records and keep only key presses
+        let mut pending = 0u32;
+        unsafe { GetNumberOfConsoleInputEvents(self.input, &mut pending) }
+            .map_err(io::Error::other)?;
+        if pending == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut records = [INPUT_RECORD::default(); 128];
+        let count = (pending as usize).min(records.len());
+        let mut read = 0u32;
+        unsafe { ReadConsoleInputW(self.input, &mut records[..count], &mut read) }
+            .map_err(io::Error::other)?;
+
+        let mut units = Vec::new();
+        for record in &records[..read as usize] {
+            if record.EventType != KEY_EVENT as u16 {
+                continue;
+            }
+            let key = unsafe { record.Event.KeyEvent };
+            let unit = unsafe { key.uChar.UnicodeChar };
+            if key.bKeyDown.as_bool() && unit != 0 {
+                for _ in 0..key.wRepeatCount.max(1) {
+                    units.push(unit);
+                }
+            }
+        }
+        Ok(String::from_utf16_lossy(&units).into_bytes())
+    }
+}
+
+impl Drop for ConsoleTerminal {
+    fn drop(&mut self) {
+        let _ = self.write("\x1b[0m\x1b[?25h\x1b[?1049l");
+        unsafe {
+            let _ = SetConsoleMode(self.input, self.saved_input_mode);
+            let _ = SetConsoleMode(self.output, self.saved_output_mode);
+        }
+    }
+}
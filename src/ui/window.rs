@@ -8,7 +8,9 @@ use windows::Win32::Graphics::Gdi::{BeginPaint, EndPaint, HBRUSH, PAINTSTRUCT, I
 use windows::Win32::System::LibraryLoader::GetModuleHandleW;
 use windows::Win32::UI::WindowsAndMessaging::*;
 
+use crate::app::config::ConfigSection;
 use crate::app::state::AppState;
+use crate::ui::input::{parse_key_down, Shortcut};
 use crate::util::strings::to_wide_string;
 
 /// Window class name
@@ -91,13 +93,16 @@ impl Window {
         self.state = Some(state_rc);
         
         // Initial data collection
+        let mut refresh_rate_ms = 1000;
         if let Some(state) = &self.state {
-            state.borrow_mut().update().ok();
+            let mut state = state.borrow_mut();
+            state.update().ok();
+            refresh_rate_ms = state.refresh_rate_ms();
         }
         
-        // Set up timer for periodic updates (1 second)
+        // Set up timer for periodic updates (configured refresh rate)
         unsafe {
-            SetTimer(Some(self.hwnd), 1, 1000, None);
+            SetTimer(Some(self.hwnd), 1, refresh_rate_ms, None);
         }
         
         // Force initial paint
@@ -214,6 +219,11 @@ impl Window {
                     if let Some(state) = Self::get_state(hwnd) {
                         if let Ok(mut state_mut) = state.try_borrow_mut() {
                             state_mut.update().ok();
+
+                            // Config file edited: restart the timer at the new rate
+                            if state_mut.poll_config().contains(&ConfigSection::Monitoring) {
+                                SetTimer(Some(hwnd), 1, state_mut.refresh_rate_ms(), None);
+                            }
                         }
                     }
                     
@@ -223,8 +233,9 @@ impl Window {
                 LRESULT(0)
             }
             WM_KEYDOWN => {
-                // F5 to manually refresh (VK_F5 = 0x74)
-                if wparam.0 == 0x74 {
+                // Manual refresh (F5 unless remapped)
+                let event = parse_key_down(wparam, lparam);
+                if Shortcut::from_keyboard_event(&event) == Some(Shortcut::Refresh) {
                     unsafe {
                         if let Some(state) = Self::get_state(hwnd) {
                             if let Ok(mut state_mut) = state.try_borrow_mut() {
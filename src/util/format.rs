@@ -0,0 +1,264 @@
+//! Number, byte, rate and duration formatting
+//!
+//! Every panel and export formats values through a `NumberFormat`, so the
+//! same value reads the same everywhere. The decimal and grouping separators
+//! come from the locale bundle (`ui::i18n::StringResources::number_format`)
+//! and the byte unit system from `AppConfig::display`.
+//!
+//! Scaled values use adaptive precision: one decimal below 100, none above
+//! (`1.5 GB`, `12.3 MB`, `512 MB`). Durations are compact (`1h 02m`, `42s`).
+
+use serde::{Deserialize, Serialize};
+
+/// Byte size units
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UnitSystem {
+    /// Powers of 1024 labelled `KB`, `MB`, as Windows shows them
+    #[default]
+    Binary,
+    /// Powers of 1024 with IEC labels (`KiB`, `MiB`)
+    Iec,
+    /// Powers of 1000 with SI labels (`kB`, `MB`)
+    Si,
+}
+
+impl UnitSystem {
+    fn base(self) -> f64 {
+        match self {
+            UnitSystem::Binary | UnitSystem::Iec => 1024.0,
+            UnitSystem::Si => 1000.0,
+        }
+    }
+
+    /// Labels from bytes upwards
+    fn labels(self) -> [&'static str; 6] {
+        match self {
+            UnitSystem::Binary => ["B", "KB", "MB", "GB", "TB", "PB"],
+            UnitSystem::Iec => ["B", "KiB", "MiB", "GiB", "TiB", "PiB"],
+            UnitSystem::Si => ["B", "kB", "MB", "GB", "TB", "PB"],
+        }
+    }
+}
+
+/// Locale separators and unit system for formatting values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    decimal: char,
+    /// Thousands separator; `None` leaves digits ungrouped
+    grouping: Option<char>,
+    units: UnitSystem,
+}
+
+impl NumberFormat {
+    /// English separators (`1,234.5`) and Windows byte units
+    pub fn new() -> Self {
+        Self {
+            decimal: '.',
+            grouping: Some(','),
+            units: UnitSystem::Binary,
+        }
+    }
+
+    pub fn with_separators(mut self, decimal: char, grouping: Option<char>) -> Self {
+        self.decimal = decimal;
+        self.grouping = grouping;
+        self
+    }
+
+    pub fn with_units(mut self, units: UnitSystem) -> Self {
+        self.units = units;
+        self
+    }
+
+    pub fn units(&self) -> UnitSystem {
+        self.units
+    }
+
+    /// Whole number with grouping (`1,234,567`)
+    pub fn integer(&self, value: i64) -> String {
+        let digits = self.group(&value.unsigned_abs().to_string());
+        if value < 0 {
+            format!("-{}", digits)
+        } else {
+            digits
+        }
+    }
+
+    /// `value` with exactly `decimals` decimals
+    pub fn decimal(&self, value: f64, decimals: usize) -> String {
+        if !value.is_finite() {
+            return value.to_string();
+        }
+        let text = format!("{:.*}", decimals, value.abs());
+        let (whole, fraction) = match text.split_once('.') {
+            Some((whole, fraction)) => (whole, Some(fraction)),
+            None => (text.as_str(), None),
+        };
+        let mut result = String::new();
+        // No sign when the value rounds to zero ("-0.0")
+        if value < 0.0 && text.chars().any(|c| c.is_ascii_digit() && c != '0') {
+            result.push('-');
+        }
+        result.push_str(&self.group(whole));
+        if let Some(fraction) = fraction {
+            result.push(self.decimal);
+            result.push_str(fraction);
+        }
+        result
+    }
+
+    /// Whole numbers as-is, others with adaptive precision
+    pub fn number(&self, value: f64) -> String {
+        if value.fract() == 0.0 && value.abs() < 1e15 {
+            self.integer(value as i64)
+        } else {
+            self.decimal(value, adaptive_decimals(value))
+        }
+    }
+
+    /// Percentage with one decimal (`12.5%`)
+    pub fn percent(&self, value: f64) -> String {
+        format!("{}%", self.decimal(value, 1))
+    }
+
+    /// Byte count scaled to the largest fitting unit (`1.5 GB`)
+    pub fn bytes(&self, bytes: u64) -> String {
+        self.bytes_f64(bytes as f64)
+    }
+
+    /// Like `bytes`, for computed and possibly negative values
+    pub fn bytes_f64(&self, bytes: f64) -> String {
+        let base = self.units.base();
+        let labels = self.units.labels();
+        if bytes.is_nan() || bytes.abs() < base {
+            return format!("{} {}", self.decimal(bytes, 0), labels[0]);
+        }
+        let mut value = bytes / base;
+        let mut unit = 1;
+        while value.abs() >= base && unit < labels.len() - 1 {
+            value /= base;
+            unit += 1;
+        }
+        format!(
+            "{} {}",
+            self.decimal(value, adaptive_decimals(value)),
+            labels[unit]
+        )
+    }
+
+    /// Bytes per second (`12.3 MB/s`)
+    pub fn rate(&self, bytes_per_sec: f64) -> String {
+        format!("{}/s", self.bytes_f64(bytes_per_sec))
+    }
+
+    /// Compact duration: `2d 03h`, `1h 02m`, `3m 05s`, `42s`, `5m`
+    ///
+    /// Shows the two largest units and drops a zero second one.
+    pub fn duration(&self, secs: f64) -> String {
+        let total = secs.max(0.0).round() as u64;
+        let (days, hours) = (total / 86_400, (total % 86_400) / 3600);
+        let (minutes, seconds) = ((total % 3600) / 60, total % 60);
+        let (major, major_unit, minor, minor_unit) = if days > 0 {
+            (days, "d", hours, "h")
+        } else if hours > 0 {
+            (hours, "h", minutes, "m")
+        } else if minutes > 0 {
+            (minutes, "m", seconds, "s")
+        } else {
+            return format!("{}s", seconds);
+        };
+        let major = self.integer(major as i64);
+        if minor == 0 {
+            format!("{}{}", major, major_unit)
+        } else {
+            format!("{}{} {:02}{}", major, major_unit, minor, minor_unit)
+        }
+    }
+
+    /// Insert the grouping separator into a run of digits
+    fn group(&self, digits: &str) -> String {
+        let Some(separator) = self.grouping else {
+            return digits.to_string();
+        };
+        // Digits before the first separator, counted modulo 3
+        let lead = digits.len() % 3;
+        let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && i % 3 == lead {
+                grouped.push(separator);
+            }
+            grouped.push(digit);
+        }
+        grouped
+    }
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One decimal below 100, none above
+fn adaptive_decimals(value: f64) -> usize {
+    if value.abs() < 100.0 {
+        1
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_numbers_and_separators() {
+        let english = NumberFormat::new();
+        assert_eq!(english.integer(1_234_567), "1,234,567");
+        assert_eq!(english.integer(-999), "-999");
+        assert_eq!(english.decimal(-0.04, 1), "0.0");
+        assert_eq!(english.number(42.0), "42");
+        assert_eq!(english.number(3.27), "3.3");
+        assert_eq!(english.number(1234.6), "1,235");
+        assert_eq!(english.percent(12.345), "12.3%");
+
+        let german = NumberFormat::new().with_separators(',', Some('.'));
+        assert_eq!(german.decimal(1234.5, 2), "1.234,50");
+        assert_eq!(german.percent(7.26), "7,3%");
+        let ungrouped = NumberFormat::new().with_separators('.', None);
+        assert_eq!(ungrouped.integer(1_000_000), "1000000");
+    }
+
+    #[test]
+    fn test_bytes_and_rates() {
+        let binary = NumberFormat::new();
+        assert_eq!(binary.bytes(500), "500 B");
+        assert_eq!(binary.bytes(2048), "2.0 KB");
+        assert_eq!(binary.bytes(200 * 1024 * 1024), "200 MB");
+        assert_eq!(binary.bytes(3 * 1024 * 1024 * 1024 / 2), "1.5 GB");
+        assert_eq!(binary.bytes_f64(-2048.0), "-2.0 KB");
+        assert_eq!(binary.rate(1_572_864.0), "1.5 MB/s");
+
+        let iec = NumberFormat::new().with_units(UnitSystem::Iec);
+        assert_eq!(iec.bytes(2048), "2.0 KiB");
+        let si = NumberFormat::new().with_units(UnitSystem::Si);
+        assert_eq!(si.bytes(2048), "2.0 kB");
+        assert_eq!(si.bytes(1_500_000_000), "1.5 GB");
+        let french = NumberFormat::new().with_separators(',', Some('\u{202f}'));
+        assert_eq!(french.bytes(1_572_864), "1,5 MB");
+    }
+
+    #[test]
+    fn test_durations() {
+        let format = NumberFormat::new();
+        assert_eq!(format.duration(42.0), "42s");
+        assert_eq!(format.duration(185.0), "3m 05s");
+        assert_eq!(format.duration(300.0), "5m");
+        assert_eq!(format.duration(3720.0), "1h 02m");
+        assert_eq!(format.duration(3600.0), "1h");
+        assert_eq!(format.duration(2.0 * 86_400.0 + 3.0 * 3600.0), "2d 03h");
+        assert_eq!(format.duration(-5.0), "0s");
+    }
+}
@@ -1,6 +1,7 @@
 //! Utility functions
 
 pub mod arenas;
+pub mod format;
 pub mod logging;
 pub mod profiling;
 pub mod strings;
@@ -39,6 +39,18 @@ pub struct ProcessSnapshot {
     pub system_metrics: SystemMetrics,
 }
 
+impl ProcessSnapshot {
+    /// Up to `limit` processes, largest working set first (ties by PID)
+    ///
+    /// Used by exporters to cap per-process series on busy machines.
+    pub fn top_by_working_set(&self, limit: usize) -> Vec<&nt_query::ProcessInfo> {
+        let mut ranked: Vec<_> = self.processes.iter().collect();
+        ranked.sort_by_key(|p| (std::cmp::Reverse(p.memory_working_set), p.pid));
+        ranked.truncate(limit);
+        ranked
+    }
+}
+
 /// System monitor coordinating all data collectors
 ///
 /// # Performance Target (T323)
@@ -59,6 +59,14 @@ pub struct ProcessInfo {
     pub memory_pagefile: u64,
     /// Private page count
     pub memory_private: u64,
+    /// Terminal Services session; 0 for services and system processes
+    pub session_id: u32,
+    /// Creation time (100ns units since 1601); 0 if unknown
+    pub create_time: u64,
+    /// Bytes read by I/O operations since the process started
+    pub io_read_bytes: u64,
+    /// Bytes written by I/O operations since the process started
+    pub io_write_bytes: u64,
 }
 
 /// SYSTEM_PROCESS_INFORMATION structure from ntdll.dll
@@ -94,7 +102,13 @@ struct SYSTEM_PROCESS_INFORMATION {
     pagefile_usage: usize,
     peak_pagefile_usage: usize,
     private_page_count: usize,
-    // Additional fields omitted for brevity - we extract what we need above
+    read_operation_count: i64,
+    write_operation_count: i64,
+    other_operation_count: i64,
+    read_transfer_count: i64,
+    write_transfer_count: i64,
+    other_transfer_count: i64,
+    // Thread array follows; not needed here
 }
 
 /// Process enumerator with pre-allocated buffer
@@ -307,6 +321,10 @@ impl ProcessEnumerator {
             memory_working_set,
             memory_pagefile,
             memory_private,
+            session_id: info.session_id,
+            create_time: info.create_time.max(0) as u64,
+            io_read_bytes: info.read_transfer_count.max(0) as u64,
+            io_write_bytes: info.write_transfer_count.max(0) as u64,
         })
     }
 
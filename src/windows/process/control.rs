@@ -101,7 +101,7 @@ impl Drop for ProcessHandle {
 }
 
 /// T159: Process priority class
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum PriorityClass {
     /// Lowest priority - only runs when system is idle
     Idle,